@@ -0,0 +1,353 @@
+use crate::dependency::Dependency;
+use crate::version::parse_version;
+use crate::version::VersionConstraint;
+use crate::FlError;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use semver::Version;
+use serde::Serialize;
+
+/// Component of a version incremented by the `bump` command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BumpPart {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl BumpPart {
+    pub fn parse(value: &str) -> Option<BumpPart> {
+        match value {
+            "major" => Some(BumpPart::Major),
+            "minor" => Some(BumpPart::Minor),
+            "patch" => Some(BumpPart::Patch),
+            _ => None,
+        }
+    }
+
+    /// Increment the given `version` (dropping pre-release and build
+    /// metadata).
+    pub fn apply(&self, version: &Version) -> Version {
+        match self {
+            BumpPart::Major => Version::new(version.major + 1, 0, 0),
+            BumpPart::Minor => Version::new(version.major, version.minor + 1, 0),
+            BumpPart::Patch => Version::new(version.major, version.minor, version.patch + 1),
+        }
+    }
+}
+
+/// Dependency constraint not matching the bumped version.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct OutdatedConstraint {
+    /// Name of the depending package
+    pub package: String,
+    /// Path of the depending package's pubspec
+    pub path: String,
+    pub constraint: String,
+    /// Constraint is part of the dev_dependencies
+    pub dev: bool,
+}
+
+/// Result of the `bump` command.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BumpReport {
+    pub package: String,
+    pub version: String,
+    pub new_version: String,
+    pub outdated: Vec<OutdatedConstraint>,
+    /// Whether the pubspecs were rewritten (see `--write`)
+    pub written: bool,
+}
+
+/// Determine all constraints of workspace packages on `package` that no
+/// longer match its version after bumping the given `part`.
+pub fn bump(package: &str, part: BumpPart, pubspecs: &[Pubspec]) -> Result<BumpReport, FlError> {
+    let bumped = pubspecs
+        .iter()
+        .find(|pubspec| pubspec.name == package)
+        .ok_or_else(|| FlError::UnknownPackage(package.to_owned()))?;
+
+    let version_str = bumped
+        .version
+        .as_deref()
+        .ok_or_else(|| FlError::InvalidVersion(package.to_owned(), "missing".to_owned()))?;
+    let version = parse_version(version_str)
+        .ok_or_else(|| FlError::InvalidVersion(package.to_owned(), format!("'{}'", version_str)))?;
+    let new_version = part.apply(&version);
+
+    let outdated = pubspecs
+        .iter()
+        .flat_map(|pubspec| {
+            let deps = pubspec.dependencies.iter().map(|dep| (dep, false));
+            let dev_deps = pubspec.dev_dependencies.iter().map(|dep| (dep, true));
+            let new_version = &new_version;
+
+            deps.chain(dev_deps).flat_map(move |(dep, dev)| match dep {
+                Dependency::PubDev { name, version, .. } if name == package => {
+                    let matches = VersionConstraint::parse(version)
+                        .is_some_and(|constraint| constraint.allows(new_version));
+
+                    (!matches).then(|| OutdatedConstraint {
+                        package: pubspec.name.clone(),
+                        path: pubspec.path.clone(),
+                        constraint: version.clone(),
+                        dev,
+                    })
+                }
+                _ => None,
+            })
+        })
+        .collect();
+
+    Ok(BumpReport {
+        package: package.to_owned(),
+        version: version_str.to_owned(),
+        new_version: new_version.to_string(),
+        outdated,
+        written: false,
+    })
+}
+
+/// Replace the constraint of the dependency `name` within the
+/// `dependencies` and `dev_dependencies` of the pubspec `content`. The
+/// constraint of dependencies given as mapping (e.g. carrying a `flcheck`
+/// section) is their `version`.
+pub fn rewrite_constraint(content: &str, name: &str, constraint: &str) -> String {
+    let mut section = String::new();
+    // indentation of the dependency `name` given as block mapping
+    let mut mapping: Option<usize> = None;
+
+    rewrite_lines(content, |line| {
+        let top_level = !line.starts_with(char::is_whitespace) && !line.starts_with('#');
+        if top_level && !line.trim().is_empty() {
+            section = line.split(':').next().unwrap_or("").to_owned();
+            mapping = None;
+            return None;
+        }
+
+        let trimmed = line.trim_start();
+        if (section != "dependencies" && section != "dev_dependencies")
+            || trimmed.is_empty()
+            || trimmed.starts_with('#')
+        {
+            return None;
+        }
+
+        let indent = line.len() - trimmed.len();
+        if let Some(parent) = mapping {
+            if indent > parent {
+                return replace_value(line, "version", constraint);
+            }
+            mapping = None;
+        }
+
+        let value = trimmed.strip_prefix(name)?.strip_prefix(':')?.trim_start();
+        if value.is_empty() || value.starts_with('#') {
+            mapping = Some(indent);
+            return None;
+        }
+        if value.starts_with('{') {
+            return replace_flow_value(line, "version", constraint);
+        }
+
+        replace_value(line, name, constraint)
+    })
+}
+
+/// Replace the (top-level) `version` of the pubspec `content`.
+pub fn rewrite_version(content: &str, version: &str) -> String {
+    rewrite_lines(content, |line| {
+        if line.starts_with(char::is_whitespace) {
+            None
+        } else {
+            replace_value(line, "version", version)
+        }
+    })
+}
+
+/// Apply `rewrite` to every line of `content` (retaining line endings).
+fn rewrite_lines<F: FnMut(&str) -> Option<String>>(content: &str, mut rewrite: F) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let text = line.trim_end_matches(['\r', '\n']);
+            match rewrite(text) {
+                Some(rewritten) => format!("{}{}", rewritten, &line[text.len()..]),
+                None => line.to_owned(),
+            }
+        })
+        .collect()
+}
+
+/// Replace the scalar value of `key` in the given YAML `line` (keeping
+/// indentation, quotes and trailing comments).
+pub fn replace_value(line: &str, key: &str, value: &str) -> Option<String> {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let current = line
+        .trim_start()
+        .strip_prefix(key)?
+        .strip_prefix(':')?
+        .trim();
+
+    let (quote, rest) = match current.chars().next() {
+        Some(quote @ ('\'' | '"')) => {
+            let end = current[1..].find(quote)? + 2;
+            (quote.to_string(), &current[end..])
+        }
+        Some('#' | '{' | '[') | None => return None,
+        Some(_) => {
+            let end = current.find(" #").unwrap_or(current.len());
+            (String::new(), &current[end..])
+        }
+    };
+
+    Some(format!(
+        "{}{}: {}{}{}{}",
+        indent, key, quote, value, quote, rest
+    ))
+}
+
+/// Replace the scalar value of `key` in the flow mapping of the given YAML
+/// `line`, e.g. `foo: {version: ^1.0.0, flcheck: {internal: true}}`.
+fn replace_flow_value(line: &str, key: &str, value: &str) -> Option<String> {
+    let open = line.find('{')?;
+    let start = line[open..].match_indices(key).find_map(|(idx, _)| {
+        let before = line[open..open + idx].trim_end();
+        let after = open + idx + key.len();
+        ((before.ends_with('{') || before.ends_with(',')) && line[after..].starts_with(':'))
+            .then_some(after + 1)
+    })?;
+
+    let current = line[start..].trim_start();
+    let start = line.len() - current.len();
+    let (quote, end) = match current.chars().next() {
+        Some(quote @ ('\'' | '"')) => (quote.to_string(), current[1..].find(quote)? + 2),
+        _ => {
+            let end = current.find([',', '}'])?;
+            (String::new(), current[..end].trim_end().len())
+        }
+    };
+
+    Some(format!(
+        "{}{}{}{}{}",
+        &line[..start],
+        quote,
+        value,
+        quote,
+        &line[start + end..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bump::bump;
+    use crate::bump::rewrite_constraint;
+    use crate::bump::rewrite_version;
+    use crate::bump::BumpPart;
+    use crate::bump::OutdatedConstraint;
+    use crate::Pubspec;
+
+    #[test]
+    fn outdated_constraints() {
+        let all = vec![
+            Pubspec::test("app")
+                .with_deps(vec![Pubspec::hosted("shared_ui", "^1.4.0")])
+                .with_dev_deps(vec![Pubspec::hosted("shared_ui", "any")]),
+            Pubspec::test("other").with_deps(vec![Pubspec::hosted("shared_ui", ">=1.0.0 <3.0.0")]),
+            Pubspec::test("shared_ui").with_version("1.4.0"),
+        ];
+
+        let report = bump("shared_ui", BumpPart::Major, &all).unwrap();
+
+        assert_eq!(report.new_version, "2.0.0");
+        assert_eq!(
+            report.outdated,
+            vec![OutdatedConstraint {
+                package: "app".to_owned(),
+                path: "/tmp/app/pubspec.yaml".to_owned(),
+                constraint: "^1.4.0".to_owned(),
+                dev: false,
+            }]
+        );
+
+        let report = bump("shared_ui", BumpPart::Minor, &all).unwrap();
+        assert_eq!(report.new_version, "1.5.0");
+        assert!(report.outdated.is_empty());
+    }
+
+    #[test]
+    fn missing_version() {
+        let all = vec![Pubspec::test("shared_ui")];
+
+        assert_eq!(
+            bump("shared_ui", BumpPart::Patch, &all)
+                .unwrap_err()
+                .to_string(),
+            "invalid version of package 'shared_ui': missing"
+        );
+        assert!(bump("unknown", BumpPart::Patch, &all).is_err());
+    }
+
+    #[test]
+    fn rewrite_pubspec() {
+        let content = "name: app\n\
+                       version: 1.0.0 # release\n\
+                       dependencies:\n  \
+                         shared_ui: '^1.4.0'\n  \
+                         http: ^1.0.0\n\
+                       dependency_overrides:\n  \
+                         shared_ui: ^1.4.0\n\
+                       dev_dependencies:\n  \
+                         shared_ui: ^1.4.0 # tests\n";
+
+        let rewritten =
+            rewrite_version(&rewrite_constraint(content, "shared_ui", "^2.0.0"), "1.1.0");
+
+        assert_eq!(
+            rewritten,
+            "name: app\n\
+             version: 1.1.0 # release\n\
+             dependencies:\n  \
+               shared_ui: '^2.0.0'\n  \
+               http: ^1.0.0\n\
+             dependency_overrides:\n  \
+               shared_ui: ^1.4.0\n\
+             dev_dependencies:\n  \
+               shared_ui: ^2.0.0 # tests\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_mapping_constraints() {
+        let content = "name: app\n\
+                       dependencies:\n  \
+                         shared_ui:\n    \
+                           version: ^1.4.0 # pinned\n    \
+                           flcheck:\n      \
+                             reason: shared\n  \
+                         shared_core: {version: '^1.4.0', flcheck: {internal: true}}\n  \
+                         http: {flcheck: {reason: api}, version: ^1.0.0}\n\
+                       dev_dependencies:\n  \
+                         shared_ui: {flcheck: {reason: tests}, version: ^1.4.0}\n";
+
+        let rewritten = ["shared_ui", "shared_core"]
+            .iter()
+            .fold(content.to_owned(), |content, name| {
+                rewrite_constraint(&content, name, "^2.0.0")
+            });
+
+        assert_eq!(
+            rewritten,
+            "name: app\n\
+             dependencies:\n  \
+               shared_ui:\n    \
+                 version: ^2.0.0 # pinned\n    \
+                 flcheck:\n      \
+                   reason: shared\n  \
+               shared_core: {version: '^2.0.0', flcheck: {internal: true}}\n  \
+               http: {flcheck: {reason: api}, version: ^1.0.0}\n\
+             dev_dependencies:\n  \
+               shared_ui: {flcheck: {reason: tests}, version: ^2.0.0}\n"
+        );
+    }
+}
@@ -0,0 +1,412 @@
+use crate::error::FlError;
+use crate::error::PackageValidation;
+use crate::util::content_hash;
+use crate::Pubspec;
+use log::debug;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+/// Directory (next to the configuration file) the cache is stored in.
+pub const CACHE_DIR: &str = ".flcheck-cache";
+
+/// File of the parsed pubspecs inside the cache directory.
+const PUBSPECS_FILE: &str = "pubspecs.json";
+
+/// File of the findings of the last (complete) validation run inside the
+/// cache directory.
+const FINDINGS_FILE: &str = "findings.json";
+
+/// Cache of parsed pubspecs between runs. Entries are valid as long as the
+/// modification time or the content hash of their pubspec file is unchanged
+/// and the cache was written by the same flcheck version.
+pub struct PubspecCache {
+    dir: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+    /// Entries of the pubspecs loaded in this run
+    used: HashMap<String, CacheEntry>,
+    changed: bool,
+}
+
+/// Pubspecs loaded via the cache in this run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheState {
+    /// Cache directory (see `cached_findings`)
+    pub dir: PathBuf,
+    /// Content hashes of the loaded pubspecs by path
+    pub hashes: HashMap<String, u64>,
+}
+
+/// Findings of the last complete validation run along with the pubspecs
+/// changed since.
+#[derive(Debug)]
+pub struct CachedFindings {
+    pub findings: Vec<PackageValidation>,
+    /// Paths of the pubspecs that were added or modified
+    pub changed: Vec<String>,
+    /// Names of the packages whose pubspecs do not exist anymore
+    pub removed: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<E> {
+    version: String,
+    entries: E,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FindingsFile<F> {
+    version: String,
+    /// Fingerprint of the configuration the findings were determined with
+    fingerprint: u64,
+    /// Validated packages by the path of their pubspec
+    packages: HashMap<String, ValidatedPackage>,
+    findings: F,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ValidatedPackage {
+    name: String,
+    /// Content hash of the pubspec at the time of validation
+    hash: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// Modification time in nanoseconds since the epoch
+    mtime: u64,
+    /// FNV-1a hash of the file content
+    hash: u64,
+    pubspec: Pubspec,
+}
+
+impl PubspecCache {
+    /// Create an empty cache that is written to the given directory.
+    pub fn empty(dir: &Path) -> PubspecCache {
+        PubspecCache {
+            dir: dir.to_path_buf(),
+            entries: HashMap::new(),
+            used: HashMap::new(),
+            changed: false,
+        }
+    }
+
+    /// Open the cache in the given directory. A missing or unreadable cache
+    /// as well as one of another flcheck version starts out empty.
+    pub fn open(dir: &Path) -> PubspecCache {
+        let file = dir.join(PUBSPECS_FILE);
+        let entries = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|content| {
+                serde_json::from_str::<CacheFile<HashMap<String, CacheEntry>>>(&content).ok()
+            })
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION"))
+            .map(|cache| cache.entries)
+            .unwrap_or_default();
+
+        debug!(
+            "loaded {} cached pubspec(s) from {}",
+            entries.len(),
+            file.display()
+        );
+
+        PubspecCache {
+            entries,
+            ..PubspecCache::empty(dir)
+        }
+    }
+
+    /// Load the pubspec at `path` from the cache or parse it (and cache it)
+    /// if changed since.
+    pub fn load(&mut self, path: &str) -> Result<Pubspec, FlError> {
+        let mtime = modification_time(path);
+
+        if let Some(entry) = self.entries.remove(path) {
+            if mtime.is_some_and(|mtime| mtime == entry.mtime) {
+                return Ok(self.reuse(path, entry));
+            }
+
+            // the file may have been touched without changing its content
+            let content = std::fs::read_to_string(path)?;
+            if content_hash(&content) == entry.hash {
+                self.changed = true;
+                return Ok(self.reuse(
+                    path,
+                    CacheEntry {
+                        mtime: mtime.unwrap_or_default(),
+                        ..entry
+                    },
+                ));
+            }
+            return self.parse(path, mtime, &content);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        self.parse(path, mtime, &content)
+    }
+
+    fn reuse(&mut self, path: &str, entry: CacheEntry) -> Pubspec {
+        let pubspec = entry.pubspec.clone();
+        self.used.insert(path.to_owned(), entry);
+        pubspec
+    }
+
+    fn parse(&mut self, path: &str, mtime: Option<u64>, content: &str) -> Result<Pubspec, FlError> {
+        let pubspec = Pubspec::parse(path, content)?;
+        debug!("caching parsed {}", path);
+
+        // files without modification time are revalidated via their hash
+        self.used.insert(
+            path.to_owned(),
+            CacheEntry {
+                mtime: mtime.unwrap_or_default(),
+                hash: content_hash(content),
+                pubspec: pubspec.clone(),
+            },
+        );
+        self.changed = true;
+
+        Ok(pubspec)
+    }
+
+    /// State of the pubspecs loaded so far.
+    pub fn state(&self) -> CacheState {
+        CacheState {
+            dir: self.dir.clone(),
+            hashes: self
+                .used
+                .iter()
+                .map(|(path, entry)| (path.clone(), entry.hash))
+                .collect(),
+        }
+    }
+
+    /// Write the entries of all pubspecs loaded in this run (dropping the
+    /// ones of pubspecs that were not loaded) if anything changed.
+    pub fn save(&self) -> Result<(), FlError> {
+        if !self.is_changed() {
+            return Ok(());
+        }
+
+        write_json(
+            &self.dir,
+            PUBSPECS_FILE,
+            &CacheFile {
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+                entries: &self.used,
+            },
+        )
+    }
+
+    /// Whether any pubspec was added, changed or removed compared to the
+    /// cached ones.
+    pub fn is_changed(&self) -> bool {
+        // remaining entries belong to pubspecs that were not loaded
+        self.changed || !self.entries.is_empty()
+    }
+
+    /// Start the next run (e.g. of `serve`) based on the pubspecs loaded in
+    /// this run.
+    pub fn next_run(self) -> PubspecCache {
+        PubspecCache {
+            entries: self.used,
+            ..PubspecCache::empty(&self.dir)
+        }
+    }
+}
+
+/// Load the findings of the last complete validation run (unless they
+/// were determined with a configuration of another `fingerprint` or by
+/// another flcheck version) and determine the pubspecs changed since by
+/// comparing their content hashes with the current `state`.
+pub fn cached_findings(state: &CacheState, fingerprint: u64) -> Option<CachedFindings> {
+    let cached = std::fs::read_to_string(state.dir.join(FINDINGS_FILE))
+        .ok()
+        .and_then(|content| {
+            serde_json::from_str::<FindingsFile<Vec<PackageValidation>>>(&content).ok()
+        })
+        .filter(|cached| {
+            cached.version == env!("CARGO_PKG_VERSION") && cached.fingerprint == fingerprint
+        })?;
+
+    let changed = state
+        .hashes
+        .iter()
+        .filter(|(path, hash)| {
+            cached
+                .packages
+                .get(*path)
+                .is_none_or(|package| package.hash != **hash)
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+    let removed = cached
+        .packages
+        .iter()
+        .filter(|(path, _)| !state.hashes.contains_key(*path))
+        .map(|(_, package)| package.name.clone())
+        .collect();
+
+    Some(CachedFindings {
+        findings: cached.findings,
+        changed,
+        removed,
+    })
+}
+
+/// Write the `findings` of a complete validation run of the given
+/// `pubspecs` to the cache (see `cached_findings`).
+pub fn write_findings(
+    state: &CacheState,
+    fingerprint: u64,
+    pubspecs: &[Pubspec],
+    findings: &[PackageValidation],
+) -> Result<(), FlError> {
+    let packages = pubspecs
+        .iter()
+        .flat_map(|pubspec| {
+            state.hashes.get(&pubspec.path).map(|hash| {
+                let package = ValidatedPackage {
+                    name: pubspec.name.clone(),
+                    hash: *hash,
+                };
+                (pubspec.path.clone(), package)
+            })
+        })
+        .collect();
+
+    write_json(
+        &state.dir,
+        FINDINGS_FILE,
+        &FindingsFile {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            fingerprint,
+            packages,
+            findings,
+        },
+    )
+}
+
+fn write_json<T: Serialize>(dir: &Path, file: &str, value: &T) -> Result<(), FlError> {
+    std::fs::create_dir_all(dir)?;
+
+    let json = serde_json::to_string(value).map_err(std::io::Error::from)?;
+    std::fs::write(dir.join(file), json)?;
+
+    Ok(())
+}
+
+fn modification_time(path: &str) -> Option<u64> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_nanos() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::cached_findings;
+    use crate::cache::write_findings;
+    use crate::cache::CacheState;
+    use crate::cache::PubspecCache;
+    use crate::Pubspec;
+    use std::fs;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    #[test]
+    fn cached_pubspecs() {
+        let dir = std::env::temp_dir().join(format!("flcheck-cache-{}", std::process::id()));
+        let pkg_dir = dir.join("pkg_core");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        let pubspec = pkg_dir.join("pubspec.yaml");
+        let path = pubspec.to_str().unwrap();
+        let cache_dir = dir.join(".flcheck-cache");
+
+        fs::write(&pubspec, "name: pkg_core\nversion: 1.0.0\n").unwrap();
+        let mut cache = PubspecCache::open(&cache_dir);
+        assert_eq!(cache.load(path).unwrap().version.as_deref(), Some("1.0.0"));
+        cache.save().unwrap();
+
+        // unchanged pubspecs are served from the cache
+        let mut cache = PubspecCache::open(&cache_dir);
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.load(path).unwrap().name, "pkg_core");
+        assert!(!cache.changed);
+
+        // changed content is parsed again (regardless of the granularity of
+        // modification times)
+        fs::write(&pubspec, "name: pkg_core\nversion: 1.1.0\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&pubspec)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(
+            PubspecCache::open(&cache_dir)
+                .load(path)
+                .unwrap()
+                .version
+                .as_deref(),
+            Some("1.1.0")
+        );
+
+        // caches of other versions are discarded
+        fs::write(
+            cache_dir.join("pubspecs.json"),
+            r#"{"version":"0.0.1","entries":{}}"#,
+        )
+        .unwrap();
+        cache = PubspecCache::open(&cache_dir);
+        assert!(cache.entries.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn findings_changed_since() {
+        let dir = std::env::temp_dir().join(format!("flcheck-findings-{}", std::process::id()));
+        let state = |hashes: &[(&str, u64)]| CacheState {
+            dir: dir.clone(),
+            hashes: hashes
+                .iter()
+                .map(|(path, hash)| (path.to_string(), *hash))
+                .collect(),
+        };
+        let pubspecs = ["pkg_core", "pkg_ui"]
+            .iter()
+            .map(|name| {
+                let path = format!("/ws/{}/pubspec.yaml", name);
+                Pubspec::parse(&path, &format!("name: {}", name)).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let before = state(&[
+            ("/ws/pkg_core/pubspec.yaml", 1),
+            ("/ws/pkg_ui/pubspec.yaml", 2),
+        ]);
+        write_findings(&before, 42, &pubspecs, &[]).unwrap();
+        assert!(cached_findings(&before, 7).is_none());
+
+        let after = state(&[
+            ("/ws/pkg_core/pubspec.yaml", 3),
+            ("/ws/app/pubspec.yaml", 4),
+        ]);
+        let mut cached = cached_findings(&after, 42).unwrap();
+        cached.changed.sort();
+
+        assert_eq!(
+            cached.changed,
+            vec!["/ws/app/pubspec.yaml", "/ws/pkg_core/pubspec.yaml"]
+        );
+        assert_eq!(cached.removed, vec!["pkg_ui"]);
+        assert!(cached.findings.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
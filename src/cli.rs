@@ -1,101 +1,872 @@
-extern crate getopts;
-
 use std::env;
+use std::ffi::OsStr;
+use std::ffi::OsString;
+
+use clap::builder::PossibleValue;
+use clap::builder::StringValueParser;
+use clap::builder::TypedValueParser;
+use clap::error::ErrorKind;
+use clap::Arg;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command;
 
-use getopts::Options;
+use crate::config::HttpSettings;
+use crate::error::exit_codes;
+use crate::error::ValidationLevel;
+use crate::error::ValidationType;
+use crate::error::EXIT_CONFIG;
+use crate::error::EXIT_SUCCESS;
+use crate::export::ExportFormat;
+use crate::findings::PathStyle;
+use crate::graph::GraphFormat;
+use crate::render::use_color;
+use crate::render::OutputFormat;
+use crate::sbom::SbomFormat;
+use crate::util::canonicalize;
+use crate::Config;
+use regex::Regex;
+use std::time::Duration;
+
+/// Shells completion scripts can be generated for.
+pub const SHELLS: [&str; 5] = ["bash", "zsh", "fish", "powershell", "elvish"];
 
 pub enum OptCommand {
     Validate,
     Dump,
     Check,
     Graph,
+    Stats,
+    Tree,
+    PublishOrder,
+    List,
+    Overrides,
+    Bump,
+    InstallHook,
+    Rules,
+    Explain,
+    ConfigCheck,
+    Init,
+    Completions,
+    Schema,
+    Diff,
+    Snapshot,
+    Export,
+    Sbom,
+    Docs,
+    Multi,
+    Serve,
     ExampleConfig,
 }
 
-#[derive(PartialEq)]
-pub enum OutputFormat {
-    Plain,
-    Json,
+/// Grouping of the validation findings (see `validate --group-by`).
+#[derive(Debug, PartialEq)]
+pub enum GroupBy {
+    Package,
+    Owner,
 }
 
 pub struct Opts {
     pub command: OptCommand,
-    pub config_file: String,
-    pub root_dir: String,
+    /// Positional arguments following the command
+    pub args: Vec<String>,
+    pub config_file: Option<String>,
+    pub root_dirs: Vec<String>,
     pub output: OutputFormat,
+    pub allow_unknown: bool,
+    /// Include dev dependencies (see `--dev`/`--no-dev`)
+    pub dev: bool,
+    /// Group the check report by external package
+    pub by_package: bool,
+    /// Probe the repositories of git dependencies (check)
+    pub probe_git: bool,
+    /// Verify the paths of git dependencies contain a pubspec (check)
+    pub probe_git_paths: bool,
+    /// Print the configuration changes allowing all dependencies
+    pub suggest_config: bool,
+    /// Fail validation on skipped or unnamed packages
+    pub strict: bool,
+    /// Validate packages affected by changes since the last run only
+    pub changed_only: bool,
+    /// Analyze the `package:` imports of the dart files as well
+    pub imports: bool,
+    /// File the summary of every validation run is appended to
+    pub append_metrics: Option<String>,
+    /// File the Prometheus metrics of the validation run are written to
+    pub metrics_file: Option<String>,
+    pub timings: bool,
+    /// Log discovery, parsing and request details
+    pub verbose: bool,
+    /// Print errors only (no warnings or progress)
+    pub quiet: bool,
+    pub fail_level: ValidationLevel,
+    pub max_warnings: Option<u32>,
+    pub group_by: GroupBy,
+    /// Paths of the findings in checkstyle/rdjson output
+    pub path_style: PathStyle,
+    pub color: bool,
+    pub all: bool,
+    /// Packages to validate exclusively (all if empty)
+    pub packages: Vec<String>,
+    /// Packages to skip during validation
+    pub exclude_packages: Vec<String>,
+    /// Pubspec files whose packages (and their dependents) are validated only
+    pub affected_by: Vec<String>,
+    /// Validate the packages whose pubspec files are listed on stdin only
+    pub stdin: bool,
+    /// Descend into symbolically linked directories during discovery
+    pub follow_symlinks: bool,
+    /// Parse all pubspecs instead of using the pubspec cache
+    pub no_cache: bool,
+    /// Configuration values overridden via `--set KEY=VALUE`
+    pub overrides: Vec<(String, String)>,
+    pub force: bool,
+    /// Rewrite the affected pubspecs (bump)
+    pub write: bool,
+    /// File the graph is written to (graph)
+    pub graph_file: Option<String>,
+    /// Include external dependencies in the graph (graph)
+    pub externals: bool,
+    /// Include matching external dependencies only (graph)
+    pub external_filter: Option<Regex>,
+    /// Highlight validation findings (graph)
+    pub annotate: bool,
+    /// Remove edges implied by transitivity (graph)
+    pub reduce: bool,
+    /// Git revision external dependencies are compared to (check)
+    pub since: Option<String>,
+    /// Validation report of the previous run (diff)
+    pub before: Option<String>,
+    /// Validation report of the current run (diff)
+    pub after: Option<String>,
+    /// Timeout of network requests
+    pub timeout: Option<Duration>,
+    /// Proxy network requests are sent through
+    pub proxy: Option<String>,
+    /// PEM file of additional root certificates
+    pub ca_bundle: Option<String>,
+    /// Skip verification of TLS certificates
+    pub insecure: bool,
+    /// Package type of the listed packages (list)
+    pub package_type: Option<String>,
+    /// List public packages only (list)
+    pub public_only: bool,
+    /// List packages directly depending on the given package only (list)
+    pub depends_on: Option<String>,
+    /// List packages directly or transitively depending on the given
+    /// package only (list)
+    pub transitively_depends_on: Option<String>,
+    /// List packages no other package depends on only (list)
+    pub no_dependents: bool,
+    /// Format of the workspace export (export)
+    pub export_format: Option<ExportFormat>,
+    /// Format of the software bill of materials (sbom)
+    pub sbom_format: Option<SbomFormat>,
+    /// File listing the repositories (multi)
+    pub repos: Option<String>,
+    /// Output directory of the generated documentation (docs)
+    pub docs_dir: Option<String>,
+}
+
+impl Opts {
+    /// Determine the root directories packages are discovered in: root
+    /// directories given on the command line take precedence over the
+    /// ones configured in the config file.
+    pub fn root_dirs(&self, config: &Config) -> Vec<String> {
+        if !self.root_dirs.is_empty() {
+            self.root_dirs.clone()
+        } else if !config.roots.is_empty() {
+            config.roots.clone()
+        } else {
+            vec![canonicalize(".").unwrap_or(".".to_owned())]
+        }
+    }
+
+    /// Determine the settings of network requests: options given on the
+    /// command line take precedence over the `http` configuration.
+    pub fn http_settings(&self, config: &Config) -> HttpSettings {
+        self.http_options().or(&config.http)
+    }
+
+    /// Settings of network requests given on the command line only (e.g.
+    /// while loading the configuration).
+    pub fn http_options(&self) -> HttpSettings {
+        HttpSettings {
+            proxy: self.proxy.clone(),
+            ca_bundle: self.ca_bundle.clone(),
+            timeout: self.timeout,
+            user_agent: None,
+            insecure: self.insecure,
+        }
+    }
+}
+
+/// Value parser accepting any value while offering the given ones in
+/// shell completions.
+#[derive(Clone)]
+struct SuggestedValues(Vec<&'static str>);
+
+impl TypedValueParser for SuggestedValues {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            self.0.iter().map(|value| PossibleValue::new(*value)),
+        ))
+    }
+}
+
+fn flag(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .help(help)
+        .action(ArgAction::SetTrue)
+}
+
+fn option(name: &'static str, value_name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).value_name(value_name).help(help)
+}
+
+/// Options of commands issuing network requests.
+fn network_args() -> [Arg; 4] {
+    [
+        option("timeout", "SECONDS", "timeout of every network request")
+            .value_parser(clap::value_parser!(f64)),
+        option(
+            "proxy",
+            "URL",
+            "send all network requests through the given proxy",
+        ),
+        option(
+            "ca-bundle",
+            "FILE",
+            "trust the root certificates of the given PEM file",
+        ),
+        flag(
+            "insecure",
+            "skip verification of TLS certificates (use with care)",
+        ),
+    ]
 }
 
-fn usage(opts: &Options, exec: &str) {
-    let brief = format!(
-        r#"Usage: {} COMMAND [OPTIONS]
+/// Options of commands failing depending on the validation findings.
+fn fail_args() -> [Arg; 2] {
+    [
+        option(
+            "fail-level",
+            "LEVEL",
+            "minimum level of findings failing validation (error, warn, none; default: error)",
+        )
+        .value_parser(["error", "warn", "none"])
+        .hide_possible_values(true),
+        option(
+            "max-warnings",
+            "N",
+            "maximum number of warnings before failing validation",
+        )
+        .value_parser(clap::value_parser!(u32)),
+    ]
+}
+
+/// Output format option supporting the given formats.
+fn output(formats: &[&'static str]) -> Arg {
+    Arg::new("output")
+        .short('o')
+        .long("output")
+        .value_name("FORMAT")
+        .help(format!(
+            "output format ({}; default: plain)",
+            formats.join(", ")
+        ))
+        .value_parser(formats.to_vec())
+        .hide_possible_values(true)
+}
 
-Commands:
-    validate - pubspec dependency validation
-    graph    - generate a dot dependency graph
-    check    - check all external dependencies' versions
-    dump     - dump package dependencies
-    example  - print example configuration"#,
-        exec
-    );
-    print!("{}", opts.usage(&brief));
+fn path_style() -> Arg {
+    option(
+        "path-style",
+        "STYLE",
+        "paths of the findings in checkstyle and rdjson output (absolute, relative; default: absolute)",
+    )
+    .value_parser(["absolute", "relative"])
+    .hide_possible_values(true)
+}
+
+/// Description of all commands along with their positional arguments
+/// and options.
+fn commands() -> Vec<Command> {
+    let validation_codes = ValidationType::values()
+        .map(|validation_type| validation_type.as_str())
+        .collect();
+    let plain = ["plain", "json"];
+    let report = ["plain", "json", "yaml", "toml"];
+    let findings = ["plain", "json", "yaml", "toml", "checkstyle", "rdjson"];
+
+    vec![
+        Command::new("validate")
+            .about("pubspec dependency validation")
+            .arg(
+                Arg::new("path")
+                    .value_name("PATH")
+                    .help("validate the package in the given directory only"),
+            )
+            .args([
+                output(&findings),
+                flag(
+                    "suggest-config",
+                    "print the includes allowing all not allowed dependencies",
+                ),
+                flag(
+                    "strict",
+                    "fail if any pubspec was skipped during discovery or has no name",
+                ),
+                flag(
+                    "changed-only",
+                    "validate packages affected by pubspecs changed since the last run only",
+                )
+                .conflicts_with_all(["package", "exclude", "affected-by", "path", "stdin"]),
+                flag(
+                    "imports",
+                    "report imports of undeclared packages and dependencies never imported by dart files",
+                )
+                .conflicts_with("changed-only"),
+                option(
+                    "package",
+                    "NAME",
+                    "validate the given package only (may be given multiple times)",
+                )
+                .action(ArgAction::Append),
+                option(
+                    "exclude",
+                    "NAME",
+                    "skip validation of the given package (may be given multiple times)",
+                )
+                .action(ArgAction::Append),
+                option(
+                    "affected-by",
+                    "FILE",
+                    "validate packages affected by changes of the given pubspec file only (may be given multiple times)",
+                )
+                .action(ArgAction::Append),
+                flag(
+                    "stdin",
+                    "validate the packages of the pubspec files listed on stdin (one per line) only",
+                ),
+                option(
+                    "group-by",
+                    "GROUP",
+                    "group the findings by package or owner (package, owner; default: package)",
+                )
+                .value_parser(["package", "owner"])
+                .hide_possible_values(true),
+                path_style(),
+                option(
+                    "append-metrics",
+                    "FILE",
+                    "append a summary of the findings to the given file (JSON lines)",
+                ),
+                option(
+                    "metrics-file",
+                    "FILE",
+                    "write the findings counts and workspace statistics as Prometheus metrics",
+                ),
+            ])
+            .args(fail_args()),
+        Command::new("graph")
+            .about("generate a dot dependency graph")
+            .args([
+                output(&plain),
+                option(
+                    "file",
+                    "FILE",
+                    "write graph to file (.dot, .svg, .png; the latter require graphviz)",
+                )
+                .short('f'),
+                flag("externals", "include external (pub.dev) dependencies"),
+                option(
+                    "external-filter",
+                    "REGEX",
+                    "include matching external dependencies only, implies --externals",
+                ),
+                flag("annotate", "highlight cyclic and not allowed dependencies"),
+                flag("reduce", "remove dependencies implied by transitive ones"),
+            ]),
+        Command::new("check")
+            .about("check all external dependencies' versions")
+            .args([
+                output(&plain),
+                flag(
+                    "allow-unknown",
+                    "list packages not found on pub.dev instead of failing",
+                ),
+                flag("timings", "print timings of network requests"),
+                flag("dev", "include dev dependencies (default)"),
+                flag("no-dev", "exclude dev dependencies").conflicts_with("dev"),
+                flag(
+                    "by-package",
+                    "list the packages using each external dependency",
+                ),
+                flag(
+                    "probe-git",
+                    "verify the repositories and refs of git dependencies exist (via git ls-remote)",
+                )
+                .conflicts_with("by-package"),
+                flag(
+                    "probe-git-paths",
+                    "verify the paths of git dependencies contain a pubspec.yaml (fetches the repositories), implies --probe-git",
+                )
+                .conflicts_with("by-package"),
+                option(
+                    "since",
+                    "REF",
+                    "apply the quality gate to dependencies added since the given git revision only (default: HEAD)",
+                ),
+            ])
+            .args(network_args()),
+        Command::new("dump")
+            .about("dump package dependencies")
+            .arg(output(&report)),
+        Command::new("stats")
+            .about("print workspace statistics")
+            .arg(output(&plain)),
+        Command::new("tree")
+            .about("print dependency tree of all or the given package")
+            .arg(Arg::new("package").value_name("PACKAGE"))
+            .args([
+                output(&plain),
+                flag("all", "include external dependencies"),
+            ]),
+        Command::new("publish-order")
+            .about("print the order packages can be published in")
+            .arg(output(&plain)),
+        Command::new("list")
+            .about("list the packages of the workspace")
+            .args([
+                output(&report),
+                option("type", "TYPE", "list packages of the given package type only"),
+                flag("public-only", "list public packages only"),
+                option(
+                    "depends-on",
+                    "PACKAGE",
+                    "list packages directly depending on the given package only",
+                ),
+                option(
+                    "transitively-depends-on",
+                    "PACKAGE",
+                    "list packages directly or transitively depending on the given package only",
+                ),
+                flag(
+                    "no-dependents",
+                    "list packages no other package depends on only",
+                ),
+            ]),
+        Command::new("overrides")
+            .about("list the active dependency overrides of all packages")
+            .arg(output(&report)),
+        Command::new("bump")
+            .about("list constraints affected by bumping the given package's version")
+            .arg(Arg::new("package").value_name("PACKAGE").required(true))
+            .arg(
+                Arg::new("part")
+                    .value_name("PART")
+                    .value_parser(["major", "minor", "patch"]),
+            )
+            .args([
+                output(&plain),
+                flag("write", "update the affected pubspecs"),
+            ]),
+        Command::new("rules")
+            .about("list all validation rules")
+            .arg(output(&plain)),
+        Command::new("explain")
+            .about("explain the given validation rule")
+            .arg(
+                Arg::new("code")
+                    .value_name("CODE")
+                    .required(true)
+                    .value_parser(SuggestedValues(validation_codes))
+                    .hide_possible_values(true),
+            )
+            .arg(output(&plain)),
+        Command::new("config-check")
+            .about("check the configuration for problems")
+            .arg(output(&plain)),
+        Command::new("init")
+            .about("generate a configuration based on the workspace")
+            .arg(flag("force", "overwrite an existing configuration")),
+        Command::new("install-hook")
+            .about("install a git pre-commit (default) or pre-push hook")
+            .arg(
+                Arg::new("hook")
+                    .value_name("HOOK")
+                    .value_parser(SuggestedValues(vec!["pre-commit", "pre-push"])),
+            )
+            .arg(flag("force", "overwrite an existing hook")),
+        Command::new("completions")
+            .about("print the completion script of the given shell")
+            .arg(
+                Arg::new("shell")
+                    .value_name("SHELL")
+                    .required(true)
+                    .value_parser(SHELLS),
+            ),
+        Command::new("schema").about("print the JSON schema of the machine readable output"),
+        Command::new("snapshot")
+            .about("write the internal dependency graph to a snapshot file or verify the workspace matches it")
+            .arg(
+                Arg::new("action")
+                    .value_name("ACTION")
+                    .required(true)
+                    .value_parser(["write", "verify"]),
+            )
+            .arg(
+                Arg::new("file")
+                    .value_name("FILE")
+                    .help("snapshot file (default: workspace.lock.json)"),
+            )
+            .arg(output(&plain)),
+        Command::new("diff")
+            .about("compare the findings of two validation runs (JSON output of validate)")
+            .args([
+                output(&plain),
+                option("before", "FILE", "validation report of the previous run").required(true),
+                option("after", "FILE", "validation report of the current run").required(true),
+            ]),
+        Command::new("export")
+            .about("export the internal dependencies for external build systems")
+            .arg(
+                option(
+                    "format",
+                    "FORMAT",
+                    "build system format (bazel, buck, nx, melos)",
+                )
+                .required(true)
+                .value_parser(["bazel", "buck", "nx", "melos"])
+                    .hide_possible_values(true),
+            ),
+        Command::new("sbom")
+            .about("write a software bill of materials of the external dependencies")
+            .arg(
+                option("format", "FORMAT", "SBOM format (cyclonedx, spdx)")
+                    .required(true)
+                    .value_parser(["cyclonedx", "spdx"])
+                    .hide_possible_values(true),
+            ),
+        Command::new("docs")
+            .about("generate markdown documentation of the workspace and its packages")
+            .arg(option(
+                "out",
+                "DIR",
+                "output directory (default: docs)",
+            )),
+        Command::new("multi")
+            .about("validate the packages of several repositories")
+            .args([
+                output(&findings),
+                option(
+                    "repos",
+                    "FILE",
+                    "file listing the repositories (and their configuration)",
+                )
+                .required(true),
+                path_style(),
+            ])
+            .args(fail_args()),
+        Command::new("serve")
+            .about("answer validation and dependency queries (JSON-RPC on stdin/stdout)"),
+        Command::new("example").about("print example configuration"),
+    ]
+}
+
+/// Options shared by all commands that may be given before or after the
+/// command.
+fn global_options() -> Vec<Arg> {
+    vec![
+        option(
+            "config",
+            "FILE",
+            "config file (default: FLCHECK_CONFIG or flcheck.yaml in the current or any parent directory)",
+        )
+        .short('c'),
+        option(
+            "dir",
+            "DIR",
+            "apps directory (may be given multiple times, default: FLCHECK_ROOT)",
+        )
+        .short('d')
+        .action(ArgAction::Append),
+        flag(
+            "follow-symlinks",
+            "descend into symbolically linked directories during package discovery",
+        ),
+        flag(
+            "no-cache",
+            "parse all pubspecs instead of reusing the ones cached in .flcheck-cache",
+        ),
+        option(
+            "set",
+            "KEY=VALUE",
+            "override configuration value (e.g. validations.validation:dependency:cyclic=warn)",
+        )
+        .action(ArgAction::Append),
+        flag("no-color", "disable colorized output (see also NO_COLOR)"),
+        flag(
+            "verbose",
+            "log discovery, parsing and request details (see also FLCHECK_LOG)",
+        )
+        .short('v'),
+        flag("quiet", "print errors only, no warnings or progress")
+            .short('q')
+            .conflicts_with("verbose"),
+    ]
+    .into_iter()
+    .map(|arg| arg.global(true).help_heading("Global options"))
+    .collect()
+}
+
+/// Command line interface of flcheck (also used to generate the shell
+/// completion scripts).
+pub fn command() -> Command {
+    Command::new("flcheck")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .arg_required_else_help(true)
+        .subcommands(commands())
+        .args(global_options())
+        .arg(
+            Arg::new("explain-exit-codes")
+                .long("explain-exit-codes")
+                .help("print all exit codes (JSON)")
+                .action(ArgAction::SetTrue),
+        )
 }
 
 /// Extract command line options.
 /// Exits with non-zero exit code on invalid arguments.
 pub fn get_opts() -> Opts {
-    let args: Vec<String> = env::args().collect();
-
-    let mut opts = Options::new();
-    opts.optopt("c", "config", "config file (default: flcheck.yaml)", "FILE");
-    opts.optopt("d", "dir", "apps directory", "DIR");
-    opts.optopt("o", "output", "output format (plain, json)", "FORMAT");
-    opts.optflag("h", "help", "show help");
-
-    let matches = match opts.parse(&args[1..]) {
-        Ok(parsed) => parsed,
-        Err(f) => {
-            eprintln!("{}", f.to_string());
-            std::process::exit(1)
-        }
-    };
+    let mut cmd = command();
+    let matches = cmd
+        .try_get_matches_from_mut(env::args_os())
+        .unwrap_or_else(|err| exit(err));
 
-    // print help/usage
-    if matches.opt_present("h") {
-        usage(&opts, &args[0]);
-        std::process::exit(0);
+    if matches.get_flag("explain-exit-codes") {
+        explain_exit_codes();
+        std::process::exit(EXIT_SUCCESS);
     }
 
-    let config_file = matches.opt_str("c").unwrap_or("flcheck.yaml".to_owned());
-    let root_dir = matches.opt_str("d").unwrap_or(".".to_owned());
-    let output_format = matches.opt_str("o").unwrap_or("plain".to_owned());
+    from_matches(&mut cmd, &matches).unwrap_or_else(|err| exit(err))
+}
 
-    let fail = |err: &str| -> ! {
-        eprintln!("E: {}", err);
-        eprintln!();
-        usage(&opts, &args[0]);
-        std::process::exit(1)
+/// Parse the given command line arguments (including the executable).
+pub fn parse_opts<I, T>(args: I) -> Result<Opts, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut cmd = command();
+    let matches = cmd.try_get_matches_from_mut(args)?;
+
+    from_matches(&mut cmd, &matches)
+}
+
+/// Print the given error (or help) and exit.
+fn exit(err: clap::Error) -> ! {
+    let _ = err.print();
+    std::process::exit(if err.use_stderr() {
+        EXIT_CONFIG
+    } else {
+        EXIT_SUCCESS
+    })
+}
+
+fn from_matches(cmd: &mut Command, matches: &ArgMatches) -> Result<Opts, clap::Error> {
+    let Some((name, matches)) = matches.subcommand() else {
+        return Err(cmd.error(ErrorKind::MissingSubcommand, "missing command"));
     };
+    let command = OptCommand::from(name)
+        .ok_or_else(|| cmd.error(ErrorKind::InvalidSubcommand, "unknown command"))?;
 
-    let cmd = match matches.free.len() {
-        1 => OptCommand::from(&matches.free[0]),
-        0 => fail("missing command"),
-        _ => fail("multiple commands are not supported"),
+    // options are looked up leniently as most of them are specific to
+    // some commands only
+    let strings = |id: &str| -> Vec<String> {
+        matches
+            .try_get_many::<String>(id)
+            .ok()
+            .flatten()
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default()
     };
+    let string = |id: &str| matches.try_get_one::<String>(id).ok().flatten().cloned();
+    let flag = |id: &str| {
+        matches
+            .try_get_one::<bool>(id)
+            .ok()
+            .flatten()
+            .is_some_and(|value| *value)
+    };
+
+    let args = cmd
+        .find_subcommand(name)
+        .map(|subcommand| {
+            subcommand
+                .get_positionals()
+                .flat_map(|arg| strings(arg.get_id().as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
 
-    let output = match parse_format(&output_format) {
-        Ok(fmt) => fmt,
-        Err(error) => fail(error),
+    // environment variables apply unless given on the command line
+    let config_file = string("config").or_else(|| {
+        env::var("FLCHECK_CONFIG")
+            .ok()
+            .filter(|file| !file.is_empty())
+    });
+    let dirs = strings("dir");
+    let root_dirs = if !dirs.is_empty() {
+        dirs
+    } else {
+        env::var_os("FLCHECK_ROOT")
+            .map(|roots| {
+                env::split_paths(&roots)
+                    .flat_map(|root| root.to_str().map(|dir| dir.to_owned()))
+                    .filter(|dir| !dir.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
     };
 
-    if let Some(command) = cmd {
-        Opts {
-            command,
-            config_file,
-            root_dir: canonicalize(&root_dir).unwrap_or(root_dir),
-            output,
+    let mut fail = |err: &str| cmd.error(ErrorKind::InvalidValue, err);
+
+    let output =
+        parse_format(&string("output").unwrap_or("plain".to_owned())).map_err(&mut fail)?;
+
+    let fail_level = match string("fail-level") {
+        Some(level) => ValidationLevel::parse(&level)
+            .ok_or_else(|| fail("invalid fail level (valid: error, warn, none)"))?,
+        None => ValidationLevel::Error,
+    };
+
+    let group_by = match string("group-by").as_deref() {
+        Some("owner") => GroupBy::Owner,
+        _ => GroupBy::Package,
+    };
+
+    let overrides = strings("set")
+        .iter()
+        .map(|assignment| match assignment.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_owned(), value.to_owned()))
+            }
+            _ => Err(fail("invalid configuration override (expected KEY=VALUE)")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let graph_file = string("file");
+    if graph_file
+        .as_ref()
+        .is_some_and(|file| GraphFormat::from_file(file).is_none())
+    {
+        return Err(fail("unsupported graph file (valid: .dot, .svg, .png)"));
+    }
+
+    let external_filter = string("external-filter")
+        .map(|filter| {
+            Regex::new(&filter).map_err(|_| fail("invalid external filter (expected a regex)"))
+        })
+        .transpose()?;
+
+    let timeout = match matches.try_get_one::<f64>("timeout").ok().flatten() {
+        Some(seconds) if *seconds > 0.0 && seconds.is_finite() => {
+            Some(Duration::from_secs_f64(*seconds))
         }
-    } else {
-        fail("unknown command");
+        Some(_) => {
+            return Err(fail(
+                "invalid timeout (expected a positive number of seconds)",
+            ))
+        }
+        None => None,
+    };
+
+    Ok(Opts {
+        command,
+        args,
+        config_file,
+        root_dirs: root_dirs
+            .into_iter()
+            .map(|dir| canonicalize(&dir).unwrap_or(dir))
+            .collect(),
+        output,
+        allow_unknown: flag("allow-unknown"),
+        dev: !flag("no-dev"),
+        by_package: flag("by-package"),
+        probe_git: flag("probe-git"),
+        probe_git_paths: flag("probe-git-paths"),
+        suggest_config: flag("suggest-config"),
+        strict: flag("strict"),
+        changed_only: flag("changed-only"),
+        imports: flag("imports"),
+        append_metrics: string("append-metrics"),
+        metrics_file: string("metrics-file"),
+        timings: flag("timings"),
+        verbose: flag("verbose"),
+        quiet: flag("quiet"),
+        fail_level,
+        max_warnings: matches
+            .try_get_one::<u32>("max-warnings")
+            .ok()
+            .flatten()
+            .copied(),
+        group_by,
+        path_style: string("path-style")
+            .and_then(|style| PathStyle::parse(&style))
+            .unwrap_or_default(),
+        color: use_color(flag("no-color")),
+        all: flag("all"),
+        packages: strings("package"),
+        exclude_packages: strings("exclude"),
+        affected_by: strings("affected-by"),
+        stdin: flag("stdin"),
+        follow_symlinks: flag("follow-symlinks"),
+        no_cache: flag("no-cache"),
+        overrides,
+        force: flag("force"),
+        write: flag("write"),
+        graph_file,
+        externals: flag("externals") || external_filter.is_some(),
+        external_filter,
+        annotate: flag("annotate"),
+        reduce: flag("reduce"),
+        since: string("since"),
+        before: string("before"),
+        after: string("after"),
+        timeout,
+        proxy: string("proxy"),
+        ca_bundle: string("ca-bundle"),
+        insecure: flag("insecure"),
+        package_type: string("type"),
+        public_only: flag("public-only"),
+        depends_on: string("depends-on"),
+        transitively_depends_on: string("transitively-depends-on"),
+        no_dependents: flag("no-dependents"),
+        export_format: string("format").and_then(|format| ExportFormat::parse(&format)),
+        sbom_format: string("format").and_then(|format| SbomFormat::parse(&format)),
+        repos: string("repos"),
+        docs_dir: string("out"),
+    })
+}
+
+fn explain_exit_codes() {
+    if let Ok(json) = serde_json::to_string(&exit_codes()) {
+        println!("{}", json);
     }
 }
 
@@ -103,17 +874,14 @@ fn parse_format(value: &str) -> Result<OutputFormat, &str> {
     match value {
         "plain" => Ok(OutputFormat::Plain),
         "json" => Ok(OutputFormat::Json),
-        _ => Err("invalid output format (valid: json, plain)"),
+        "yaml" => Ok(OutputFormat::Yaml),
+        "toml" => Ok(OutputFormat::Toml),
+        "checkstyle" => Ok(OutputFormat::Checkstyle),
+        "rdjson" => Ok(OutputFormat::Rdjson),
+        _ => Err("invalid output format (valid: plain, json, yaml, toml, checkstyle, rdjson)"),
     }
 }
 
-fn canonicalize(path: &String) -> Option<String> {
-    let canonicalized = std::fs::canonicalize(path).ok()?;
-    let canonical_str = canonicalized.to_str()?;
-
-    Some(canonical_str.to_owned())
-}
-
 impl OptCommand {
     pub fn from(value: &str) -> Option<OptCommand> {
         match value {
@@ -121,8 +889,231 @@ impl OptCommand {
             "dump" => Some(OptCommand::Dump),
             "check" => Some(OptCommand::Check),
             "graph" => Some(OptCommand::Graph),
+            "stats" => Some(OptCommand::Stats),
+            "tree" => Some(OptCommand::Tree),
+            "publish-order" => Some(OptCommand::PublishOrder),
+            "list" => Some(OptCommand::List),
+            "overrides" => Some(OptCommand::Overrides),
+            "bump" => Some(OptCommand::Bump),
+            "install-hook" => Some(OptCommand::InstallHook),
+            "rules" => Some(OptCommand::Rules),
+            "explain" => Some(OptCommand::Explain),
+            "config-check" => Some(OptCommand::ConfigCheck),
+            "init" => Some(OptCommand::Init),
+            "completions" => Some(OptCommand::Completions),
+            "schema" => Some(OptCommand::Schema),
+            "diff" => Some(OptCommand::Diff),
+            "snapshot" => Some(OptCommand::Snapshot),
+            "export" => Some(OptCommand::Export),
+            "sbom" => Some(OptCommand::Sbom),
+            "docs" => Some(OptCommand::Docs),
+            "multi" => Some(OptCommand::Multi),
+            "serve" => Some(OptCommand::Serve),
             "example" => Some(OptCommand::ExampleConfig),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::command;
+    use crate::cli::parse_opts;
+    use crate::cli::GroupBy;
+    use crate::cli::OptCommand;
+    use crate::error::ValidationLevel;
+    use crate::findings::PathStyle;
+    use crate::util::canonicalize;
+    use crate::Config;
+    use clap::error::ErrorKind;
+    use clap_complete::Shell;
+    use std::time::Duration;
+
+    #[test]
+    fn parse_command_and_options() {
+        let opts = parse_opts([
+            "flcheck",
+            "validate",
+            "--package",
+            "app_foo",
+            "--package",
+            "app_bar",
+            "--fail-level",
+            "warn",
+            "--max-warnings",
+            "3",
+            "--group-by",
+            "owner",
+            "--set",
+            "validations.validation:dependency:cyclic=warn",
+        ])
+        .unwrap();
+
+        assert!(matches!(opts.command, OptCommand::Validate));
+        assert_eq!(opts.packages, vec!["app_foo", "app_bar"]);
+        assert_eq!(opts.fail_level, ValidationLevel::Warning);
+        assert_eq!(opts.max_warnings, Some(3));
+        assert_eq!(opts.group_by, GroupBy::Owner);
+        assert_eq!(opts.path_style, PathStyle::Absolute);
+        assert_eq!(
+            opts.overrides,
+            vec![(
+                "validations.validation:dependency:cyclic".to_owned(),
+                "warn".to_owned()
+            )]
+        );
+
+        // global options may precede the command as well
+        let opts = parse_opts(["flcheck", "-q", "bump", "pkg_core", "minor", "--write"]).unwrap();
+
+        assert!(matches!(opts.command, OptCommand::Bump));
+        assert_eq!(opts.args, vec!["pkg_core", "minor"]);
+        assert!(opts.quiet);
+        assert!(opts.write);
+
+        let opts = parse_opts([
+            "flcheck",
+            "validate",
+            "apps/app_foo",
+            "--strict",
+            "--path-style",
+            "relative",
+        ])
+        .unwrap();
+
+        assert_eq!(opts.args, vec!["apps/app_foo"]);
+        assert!(opts.strict);
+        assert_eq!(opts.path_style, PathStyle::Relative);
+
+        let opts = parse_opts(["flcheck", "check", "--no-dev"]).unwrap();
+
+        assert!(!opts.dev);
+    }
+
+    #[test]
+    fn command_specific_options() {
+        let kind = |args: &[&str]| parse_opts(args).err().map(|err| err.kind());
+
+        // global options are accepted before and after the command
+        for args in [
+            ["flcheck", "-c", "flcheck.yaml", "--no-color", "stats"],
+            ["flcheck", "stats", "-c", "flcheck.yaml", "--no-color"],
+        ] {
+            let opts = parse_opts(args).unwrap();
+            assert!(matches!(opts.command, OptCommand::Stats));
+            assert_eq!(opts.config_file.as_deref(), Some("flcheck.yaml"));
+            assert!(!opts.color);
+        }
+
+        // options of a command are rejected by all others
+        assert!(parse_opts(["flcheck", "validate", "--fail-level", "warn"]).is_ok());
+        assert_eq!(
+            kind(&["flcheck", "check", "--fail-level", "warn"]),
+            Some(ErrorKind::UnknownArgument)
+        );
+        assert!(parse_opts(["flcheck", "check", "--allow-unknown"]).is_ok());
+        assert_eq!(
+            kind(&["flcheck", "validate", "--allow-unknown"]),
+            Some(ErrorKind::UnknownArgument)
+        );
+        assert!(parse_opts(["flcheck", "list", "--public-only"]).is_ok());
+        assert_eq!(
+            kind(&["flcheck", "tree", "--public-only"]),
+            Some(ErrorKind::UnknownArgument)
+        );
+    }
+
+    #[test]
+    fn root_dirs() {
+        let mut config = Config::default();
+        let opts = parse_opts(["flcheck", "validate"]).unwrap();
+
+        // packages are discovered in the (absolute) current directory by default
+        assert_eq!(opts.root_dirs(&config), vec![canonicalize(".").unwrap()]);
+
+        config.roots = vec!["/ws/apps".to_owned()];
+        assert_eq!(opts.root_dirs(&config), vec!["/ws/apps"]);
+
+        let opts = parse_opts(["flcheck", "-d", "/ws/packages", "validate"]).unwrap();
+        assert_eq!(opts.root_dirs(&config), vec!["/ws/packages"]);
+    }
+
+    #[test]
+    fn http_settings() {
+        let mut config = Config::default();
+        config.http.proxy = Some("http://proxy:3128".to_owned());
+        config.http.timeout = Some(Duration::from_secs(30));
+        config.http.user_agent = Some("acme".to_owned());
+
+        let opts = parse_opts(["flcheck", "check", "--timeout", "2.5", "--insecure"]).unwrap();
+        let http = opts.http_settings(&config);
+
+        assert_eq!(http.proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(http.timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(http.user_agent.as_deref(), Some("acme"));
+        assert!(http.insecure);
+
+        let opts = parse_opts(["flcheck", "check", "--proxy", "http://other:8080"]).unwrap();
+
+        assert_eq!(
+            opts.http_settings(&config).proxy.as_deref(),
+            Some("http://other:8080")
+        );
+        assert!(parse_opts(["flcheck", "check", "--timeout", "0"]).is_err());
+        assert!(parse_opts(["flcheck", "validate", "--insecure"]).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let kind = |args: &[&str]| parse_opts(args).err().map(|err| err.kind());
+
+        assert_eq!(
+            kind(&["flcheck", "-c", "flcheck.yaml"]),
+            Some(ErrorKind::MissingSubcommand)
+        );
+        assert_eq!(
+            kind(&["flcheck", "bump"]),
+            Some(ErrorKind::MissingRequiredArgument)
+        );
+        assert_eq!(
+            kind(&["flcheck", "tree", "app_foo", "app_bar"]),
+            Some(ErrorKind::UnknownArgument)
+        );
+        // options of other commands are rejected
+        assert_eq!(
+            kind(&["flcheck", "validate", "--all"]),
+            Some(ErrorKind::UnknownArgument)
+        );
+        assert_eq!(
+            kind(&["flcheck", "-o", "json", "validate"]),
+            Some(ErrorKind::UnknownArgument)
+        );
+        assert_eq!(
+            kind(&["flcheck", "check", "--dev", "--no-dev"]),
+            Some(ErrorKind::ArgumentConflict)
+        );
+        assert_eq!(
+            kind(&["flcheck", "graph", "-o", "yaml"]),
+            Some(ErrorKind::InvalidValue)
+        );
+        assert_eq!(
+            kind(&["flcheck", "graph", "-f", "graph.pdf"]),
+            Some(ErrorKind::InvalidValue)
+        );
+        assert_eq!(
+            kind(&["flcheck", "completions", "tcsh"]),
+            Some(ErrorKind::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn completions() {
+        let mut script = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut command(), "flcheck", &mut script);
+        let script = String::from_utf8(script).unwrap();
+
+        assert!(script.contains("publish-order"));
+        assert!(script.contains("--fail-level"));
+        assert!(script.contains("validation:dependency:cyclic"));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error::FlError;
+use crate::error::FlError::ConfigValidation;
+use crate::util::canonicalize;
+use crate::util::glob_to_regex;
+use crate::util::relative_path;
+use crate::util::repository_root;
+use crate::Pubspec;
+use regex::Regex;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Locations of the `CODEOWNERS` file (relative to the repository root)
+/// in the order they are searched.
+pub const CODEOWNERS_FILES: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Owners of the files of a repository as declared in a `CODEOWNERS` file.
+#[derive(Debug)]
+pub struct CodeOwners {
+    /// Directory all patterns are relative to (the repository root)
+    root: PathBuf,
+    rules: Vec<OwnerRule>,
+}
+
+#[derive(Debug)]
+struct OwnerRule {
+    regex: Regex,
+    /// Whether the pattern (ending with `/`) matches directories only
+    dir_only: bool,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// Load the given `CODEOWNERS` file. Patterns are relative to the
+    /// directory containing the file, or its parent if the file is located
+    /// in a `.github` or `docs` directory.
+    pub fn load(file: &str) -> Result<CodeOwners, FlError> {
+        let content = std::fs::read_to_string(file)?;
+        let path = Path::new(file);
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let root = match dir.file_name().and_then(|name| name.to_str()) {
+            Some(".github") | Some("docs") => dir.parent().unwrap_or(dir),
+            _ => dir,
+        };
+
+        CodeOwners::parse(&content, root)
+            .map_err(|err| ConfigValidation(format!("{}: {}", file, err)))
+    }
+
+    /// Parse the `CODEOWNERS` rules in `content`, each consisting of a
+    /// pattern followed by its owners.
+    pub fn parse(content: &str, root: &Path) -> Result<CodeOwners, String> {
+        let mut rules = Vec::new();
+
+        for (idx, line) in content.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(rule, _)| rule).trim();
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+
+            let mut regex = glob_to_regex(pattern);
+            // patterns starting with a `/` are anchored to the root
+            // even if they do not contain any other `/`
+            if pattern.starts_with('/') {
+                regex = regex.replacen("^(?:.*/)?", "^", 1);
+            }
+
+            rules.push(OwnerRule {
+                regex: Regex::new(&regex)
+                    .map_err(|_| format!("invalid pattern '{}' in line {}", pattern, idx + 1))?,
+                dir_only: pattern.ends_with('/'),
+                owners: parts.map(|owner| owner.to_owned()).collect(),
+            });
+        }
+
+        let root = root.to_str().filter(|dir| !dir.is_empty()).unwrap_or(".");
+
+        Ok(CodeOwners {
+            root: PathBuf::from(canonicalize(root).unwrap_or(root.to_owned())),
+            rules,
+        })
+    }
+
+    /// Determine the owners of the given package, i.e. of its `pubspec.yaml`.
+    ///
+    /// As in git(hub), the last matching rule takes precedence; a pattern
+    /// matching a directory applies to all files beneath it. Multiple
+    /// owners are separated by spaces.
+    pub fn owner(&self, pubspec: &Pubspec) -> Option<String> {
+        let file = relative_path(&self.root, Path::new(&pubspec.path))?;
+
+        // the file itself along with all of its parent directories
+        let mut candidates = vec![(file.as_str(), false)];
+        candidates.extend(file.match_indices('/').map(|(idx, _)| (&file[..idx], true)));
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                candidates
+                    .iter()
+                    .any(|(path, is_dir)| (*is_dir || !rule.dir_only) && rule.regex.is_match(path))
+            })
+            .map(|rule| rule.owners.join(" "))
+            .filter(|owners| !owners.is_empty())
+    }
+}
+
+/// Search for a `CODEOWNERS` file in the repository containing `dir`, i.e.
+/// the first ancestor of `dir` that contains `.git` (or `dir` itself if
+/// there is none).
+pub fn find_codeowners(dir: &Path) -> Option<PathBuf> {
+    let root = repository_root(dir).unwrap_or(dir);
+
+    CODEOWNERS_FILES
+        .iter()
+        .map(|file| root.join(file))
+        .find(|file| file.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::codeowners::CodeOwners;
+    use crate::Pubspec;
+    use std::path::Path;
+
+    #[test]
+    fn owners() {
+        let owners = CodeOwners::parse(
+            r#"
+# default owners
+*                       @org/platform
+/apps/                  @org/apps
+packages/payments_*     @org/payments @alice
+/packages/legacy        # no owner
+pubspec.yaml/           @org/nobody
+"#,
+            Path::new("/repo"),
+        )
+        .unwrap();
+
+        let owner = |path: &str| {
+            let name = path.rsplit('/').next().unwrap();
+            owners.owner(&Pubspec::test_at(name, &format!("/repo/{}", path)))
+        };
+
+        assert_eq!(owner("tools"), Some("@org/platform".to_owned()));
+        assert_eq!(owner("apps/app_foo"), Some("@org/apps".to_owned()));
+        // the last matching rule wins
+        assert_eq!(
+            owner("packages/payments_core"),
+            Some("@org/payments @alice".to_owned())
+        );
+        assert_eq!(owner("packages/legacy"), None);
+        assert_eq!(owner("packages/pkg_core"), Some("@org/platform".to_owned()));
+    }
+}
@@ -1,221 +1,1925 @@
-use crate::cli::OutputFormat;
+use crate::bump;
+use crate::bump::replace_value;
+use crate::bump::rewrite_constraint;
+use crate::bump::rewrite_version;
+use crate::bump::BumpPart;
+use crate::cache::cached_findings;
+use crate::cache::write_findings;
+use crate::cache::CacheState;
+use crate::cache::PubspecCache;
+use crate::cache::CACHE_DIR;
+use crate::cli;
+use crate::cli::GroupBy;
+use crate::cli::SHELLS;
+use crate::codeowners::CodeOwners;
+use crate::config::find_config_file;
+use crate::config::PublisherPolicy;
+use crate::config::QualityGate;
+use crate::config::CONFIG_FILE;
+use crate::custom::run_custom_validations;
 use crate::dependency::Dependency;
+use crate::diff::diff_findings;
+use crate::diff::load_findings;
+use crate::docs::workspace_docs;
+use crate::docs::DOCS_DIR;
 use crate::error::PackageValidation;
 use crate::error::ValidationLevel;
-use crate::pubdev::fetch_dep_versions;
+use crate::error::ValidationType;
+use crate::export::export_workspace;
+use crate::export::melos_config;
+use crate::export::ExportFormat;
+use crate::export::MELOS_FILE;
+use crate::graph::Graph;
+use crate::graph::GraphFormat;
+use crate::graph::GraphOptions;
+use crate::hook::hook_script;
+use crate::hook::HookType;
+use crate::imports::import_validations;
+use crate::init::infer_package_types;
+use crate::init::render_config;
+use crate::init::InferredType;
+use crate::lint::check_workspace;
+use crate::lint::check_yaml;
+use crate::lint::ConfigProblem;
+use crate::list::list_packages;
+use crate::list::ListFilter;
+use crate::metrics::append_metrics;
+use crate::metrics::prometheus_metrics;
+use crate::metrics::MetricsRecord;
+use crate::multi::load_repositories;
+use crate::overrides::package_overrides;
+use crate::probe::git_dependencies;
+use crate::probe::probe_git_dependencies;
+use crate::probe::GitProbe;
+use crate::pubdev::PubApi;
+use crate::pubdev::PubDevClient;
+use crate::pubdev::PubDevStats;
+use crate::pubdev::PubPublisher;
+use crate::pubdev::PubScore;
+use crate::pubdev::PubVersions;
+use crate::publish::publish_waves;
+use crate::pubspec::dependency_line;
+use crate::pubspec::discover_pubspecs;
+use crate::pubspec::load_pubspecs;
+use crate::pubspec::Discovery;
+use crate::render::PlainRenderer;
+use crate::report::Report;
+use crate::rules::Rule;
+use crate::sbom::render_sbom;
+use crate::sbom::sbom_components;
+use crate::serve::handle_request;
+use crate::serve::is_shutdown;
+use crate::serve::ServerState;
+use crate::serve::POLL_INTERVAL;
+use crate::snapshot::Snapshot;
+use crate::snapshot::SNAPSHOT_FILE;
+use crate::stats::Stats;
+use crate::suggest::suggest_includes;
+use crate::tree::dependency_tree;
+use crate::tree::root_packages;
+use crate::util::canonicalize;
+use crate::util::relative_path;
+use crate::util::repository_root;
+use crate::version::parse_version;
+use crate::version::VersionConstraint;
+use crate::workspace::Workspace;
 use crate::Config;
 use crate::FlError;
+use crate::FlError::BrokenGitDependencies;
+use crate::FlError::ConfigExists;
+use crate::FlError::ConfigValidation;
+use crate::FlError::GitError;
+use crate::FlError::GraphvizError;
+use crate::FlError::HookExists;
+use crate::FlError::InvalidValidationType;
+use crate::FlError::NewFindings;
+use crate::FlError::NoConfigFound;
+use crate::FlError::NoInputFiles;
+use crate::FlError::OutputError;
+use crate::FlError::RejectedDependencies;
+use crate::FlError::SkippedPackages;
+use crate::FlError::SnapshotDrift;
+use crate::FlError::TooManyWarnings;
+use crate::FlError::UnknownPackage;
+use crate::FlError::UnknownPackages;
 use crate::FlError::ValidationError;
+use crate::FlError::ValidationWarnings;
 use crate::Opts;
 use crate::Pubspec;
+use crate::Renderer;
+use clap_complete::Shell;
 use futures::future::try_join_all;
+use futures::FutureExt;
+use indicatif::ProgressBar;
+use indicatif::ProgressDrawTarget;
+use indicatif::ProgressStyle;
+use log::info;
+use log::warn;
+use rayon::iter::IndexedParallelIterator;
+use rayon::iter::IntoParallelRefIterator;
+use rayon::iter::ParallelIterator;
+use schemars::JsonSchema;
+use semver::Version;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Display;
+use std::io;
+use std::io::BufRead;
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
-#[derive(Serialize)]
-struct JsonValidationResult {
-    pub errors: Vec<PackageValidation>,
-    pub warnings: Vec<PackageValidation>,
+/// Result of the `check` command.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CheckReport {
+    pub packages: Vec<CheckedPackage>,
+    /// External packages that could not be found on pub.dev
+    pub unknown: Vec<String>,
+    /// Probed git dependencies (see `--probe-git`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<Vec<GitProbe>>,
 }
 
-#[derive(Serialize)]
-struct JsonDumpResult {
-    pub pubspecs: Vec<Pubspec>,
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CheckedPackage {
+    pub name: String,
+    pub dependencies: Vec<CheckedDependency>,
 }
 
-pub fn graph(pubspecs: Vec<Pubspec>) -> Result<(), FlError> {
-    println!("//");
-    println!("// automatically generated by flcheck <https://github.com/kongo2002/flcheck>");
-    println!("//");
-    println!("digraph dependencies {{");
-    println!("  ranksep =\"2.0 equally\";");
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CheckedDependency {
+    pub name: String,
+    pub version: String,
+    /// Latest version available on pub.dev (if known)
+    pub latest: Option<String>,
+    pub private: bool,
+    /// Dependency is part of the dev_dependencies
+    pub dev: bool,
+    /// Constraints of workspace packages this package (transitively)
+    /// depends on, that have to be satisfied as well
+    pub pinned_by: Vec<PinnedConstraint>,
+    /// Version the constraint resolves to, if it is older than the
+    /// configured `check.max_age_days`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale: Option<StaleVersion>,
+    /// Violations of the policies on external dependencies (e.g. the
+    /// `check.quality` gate)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<CheckProblem>,
+}
+
+/// Policy on external dependencies evaluated by `check`.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckRule {
+    /// pub.dev score below the `check.quality` thresholds
+    Quality,
+    /// Resolved version does not support the `check.min_dart_sdk`
+    Sdk,
+    /// Package is not published by a (allowed) verified publisher
+    Publisher,
+}
+
+impl Display for CheckRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckRule::Quality => f.write_str("quality"),
+            CheckRule::Sdk => f.write_str("sdk"),
+            CheckRule::Publisher => f.write_str("publisher"),
+        }
+    }
+}
+
+/// Violation of a `CheckRule` by an external dependency.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct CheckProblem {
+    pub rule: CheckRule,
+    pub level: ValidationLevel,
+    pub message: String,
+}
+
+/// Newest version allowed by a constraint that was published longer ago
+/// than the freshness budget permits.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct StaleVersion {
+    pub version: String,
+    /// Days since the version was published
+    pub age_days: u64,
+}
+
+/// Result of the `check --by-package` command: the external packages and
+/// the workspace packages using them.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExternalReport {
+    pub packages: Vec<ExternalUsage>,
+    /// External packages that could not be found on pub.dev
+    pub unknown: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExternalUsage {
+    pub name: String,
+    /// Latest version available on pub.dev (if known)
+    pub latest: Option<String>,
+    pub private: bool,
+    /// Distinct constraints declared on this package
+    pub constraints: Vec<ConstraintUsage>,
+    /// Workspace packages disagree on the constraint
+    pub conflicting: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConstraintUsage {
+    pub version: String,
+    /// Workspace packages declaring this constraint
+    pub packages: Vec<String>,
+}
+
+impl CheckReport {
+    /// Number of dependencies with problems of level `error` (see
+    /// `RejectedDependencies`).
+    pub fn rejected(&self) -> u32 {
+        self.packages
+            .iter()
+            .flat_map(|package| package.dependencies.iter())
+            .filter(|dep| {
+                dep.problems
+                    .iter()
+                    .any(|problem| problem.level == ValidationLevel::Error)
+            })
+            .count() as u32
+    }
+
+    /// Invert the report into the workspace packages using each
+    /// external package (sorted by name).
+    pub fn by_package(&self) -> ExternalReport {
+        let mut packages: Vec<ExternalUsage> = Vec::new();
+
+        for package in self.packages.iter() {
+            for dep in package.dependencies.iter() {
+                let idx = match packages.iter().position(|usage| usage.name == dep.name) {
+                    Some(idx) => idx,
+                    None => {
+                        packages.push(ExternalUsage {
+                            name: dep.name.clone(),
+                            latest: dep.latest.clone(),
+                            private: dep.private,
+                            constraints: Vec::new(),
+                            conflicting: false,
+                        });
+                        packages.len() - 1
+                    }
+                };
+
+                let usage = &mut packages[idx];
+                match usage
+                    .constraints
+                    .iter_mut()
+                    .find(|constraint| constraint.version == dep.version)
+                {
+                    Some(constraint) => {
+                        if !constraint.packages.contains(&package.name) {
+                            constraint.packages.push(package.name.clone());
+                        }
+                    }
+                    None => usage.constraints.push(ConstraintUsage {
+                        version: dep.version.clone(),
+                        packages: vec![package.name.clone()],
+                    }),
+                }
+                usage.conflicting = usage.constraints.len() > 1;
+            }
+        }
+
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ExternalReport {
+            packages,
+            unknown: self.unknown.clone(),
+        }
+    }
+}
+
+/// Constraint on an external package declared by a workspace package.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct PinnedConstraint {
+    pub package: String,
+    pub version: String,
+}
+
+pub fn graph(
+    opts: &Opts,
+    config: &Config,
+    pubspecs: &[Pubspec],
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let options = GraphOptions {
+        externals: opts.externals,
+        external_filter: opts.external_filter.clone(),
+    };
+    let workspace = Workspace::new(pubspecs);
+    let mut graph = Graph::from_workspace(&workspace, &options);
+
+    if opts.annotate {
+        let validations = pubspecs
+            .iter()
+            .flat_map(|pubspec| pubspec.validate(config, &workspace))
+            .collect::<Vec<_>>();
+
+        graph.annotate(&validations);
+    }
+
+    if opts.reduce {
+        graph.reduce();
+    }
+
+    match &opts.graph_file {
+        Some(file) => {
+            // graph files are always based on the dot representation
+            let mut dot = Vec::new();
+            PlainRenderer::new(&mut dot)
+                .render_graph(&graph)
+                .map_err(OutputError)?;
+
+            write_graph_file(&dot, file)
+        }
+        None => renderer.render_graph(&graph).map_err(OutputError),
+    }
+}
+
+/// Write the `dot` graph to `file`, rendered by graphviz if necessary.
+fn write_graph_file(dot: &[u8], file: &str) -> Result<(), FlError> {
+    let format = GraphFormat::from_file(file).unwrap_or(GraphFormat::Dot);
+
+    let graphviz_format = match format.graphviz_format() {
+        Some(graphviz_format) => graphviz_format,
+        None => return std::fs::write(file, dot).map_err(OutputError),
+    };
+
+    let mut child = Command::new("dot")
+        .arg(format!("-T{}", graphviz_format))
+        .arg("-o")
+        .arg(file)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => GraphvizError(
+                "executable not found (install graphviz or write a .dot file instead)".to_owned(),
+            ),
+            _ => GraphvizError(err.to_string()),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(dot)
+            .map_err(|err| GraphvizError(err.to_string()))?;
+    }
+
+    let status = child.wait().map_err(|err| GraphvizError(err.to_string()))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(GraphvizError(format!("exited with {}", status)))
+    }
+}
+
+pub async fn check(
+    opts: &Opts,
+    config: &Config,
+    pubspecs: &[Pubspec],
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let client = PubDevClient::from_settings(&opts.http_settings(config))?;
+    check_with(opts, config, pubspecs, &client, renderer).await
+}
+
+/// Run the `check` command against the given pub.dev `api`.
+async fn check_with(
+    opts: &Opts,
+    config: &Config,
+    pubspecs: &[Pubspec],
+    api: &impl PubApi,
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let mut report = check_report(opts, config, pubspecs, api).await?;
+
+    if opts.by_package {
+        renderer
+            .render_check_by_package(&report.by_package())
+            .map_err(OutputError)?;
+
+        return match report.rejected() {
+            0 => Ok(()),
+            rejected => Err(RejectedDependencies(rejected)),
+        };
+    }
+
+    if opts.probe_git || opts.probe_git_paths {
+        let dependencies = pubspecs
+            .iter()
+            .flat_map(|pubspec| git_dependencies(pubspec, opts.dev))
+            .collect();
+        report.git = Some(probe_git_dependencies(dependencies, opts.probe_git_paths)?);
+    }
+
+    renderer.render_check(&report).map_err(OutputError)?;
+
+    let rejected = report.rejected();
+    let broken = report
+        .git
+        .iter()
+        .flatten()
+        .filter(|probe| probe.problem.is_some())
+        .count();
+    if broken > 0 {
+        Err(BrokenGitDependencies(broken as u32))
+    } else if rejected > 0 {
+        Err(RejectedDependencies(rejected))
+    } else {
+        Ok(())
+    }
+}
+
+/// Determine the outdated external dependencies of all `pubspecs` based on
+/// the package versions provided by the given `api`.
+async fn check_report(
+    opts: &Opts,
+    config: &Config,
+    pubspecs: &[Pubspec],
+    api: &impl PubApi,
+) -> Result<CheckReport, FlError> {
+    let unique_packages = pubspecs
+        .iter()
+        .flat_map(|pkg| {
+            checked_dependencies(pkg, opts.dev)
+                .into_iter()
+                .flat_map(|(dep, _)| match dep {
+                    Dependency::PubDev { name, .. } => Some(name),
+                    _ => None,
+                })
+        })
+        .filter(|name| !config.is_private_package(name))
+        .collect::<HashSet<_>>();
+
+    // the quality gate applies to dependencies new to the workspace only
+    let baseline = if config.check.quality.is_enabled() {
+        baseline_dependencies(pubspecs, opts.since.as_deref().unwrap_or("HEAD"))?
+    } else {
+        HashSet::new()
+    };
+    let scored = unique_packages
+        .iter()
+        .filter(|name| config.check.quality.applies_to(name) && !baseline.contains(**name))
+        .collect::<Vec<_>>();
+    let published = unique_packages
+        .iter()
+        .filter(|name| config.check.publishers.applies_to(name))
+        .collect::<Vec<_>>();
+
+    let started = Instant::now();
+    let progress = fetch_progress(opts, unique_packages.len() + scored.len() + published.len());
+    let (versions, scores, publishers) = futures::try_join!(
+        try_join_all(
+            unique_packages
+                .iter()
+                .map(|package| api.fetch_dep_versions(package).inspect(|_| progress.inc(1))),
+        ),
+        try_join_all(
+            scored
+                .iter()
+                .map(|package| api.fetch_score(package).inspect(|_| progress.inc(1))),
+        ),
+        try_join_all(
+            published
+                .iter()
+                .map(|package| api.fetch_publisher(package).inspect(|_| progress.inc(1))),
+        )
+    )?;
+    progress.finish_and_clear();
+
+    if opts.timings {
+        print_timings(started.elapsed(), api.stats());
+    }
+
+    let lookup = versions
+        .into_iter()
+        .flatten()
+        .map(|pubversion| (pubversion.name.clone(), pubversion))
+        .collect::<HashMap<_, _>>();
+
+    let scores = scores
+        .into_iter()
+        .flatten()
+        .map(|score| (score.name.clone(), score))
+        .collect::<HashMap<_, _>>();
+    let publishers = publishers
+        .into_iter()
+        .flatten()
+        .map(|publisher| (publisher.name.clone(), publisher))
+        .collect::<HashMap<_, _>>();
+
+    let mut unknown = unique_packages
+        .into_iter()
+        .filter(|name| !lookup.contains_key(*name))
+        .cloned()
+        .collect::<Vec<_>>();
+    unknown.sort_unstable();
+
+    if !unknown.is_empty() && !opts.allow_unknown {
+        return Err(UnknownPackages(unknown.join(", ")));
+    }
+
+    let now = SystemTime::now();
+    let workspace = Workspace::new(pubspecs);
+    let packages = pubspecs
+        .iter()
+        .enumerate()
+        .map(|(idx, pubspec)| {
+            let internal = workspace
+                .closure(idx)
+                .into_iter()
+                .map(|dep| &pubspecs[dep])
+                .collect::<Vec<_>>();
+
+            CheckedPackage {
+                name: pubspec.name.clone(),
+                dependencies: checked_dependencies(pubspec, opts.dev)
+                    .into_iter()
+                    .flat_map(|(dep, dev)| match dep {
+                        Dependency::PubDev { name, version, .. } => Some(CheckedDependency {
+                            name: name.clone(),
+                            version: version.clone(),
+                            latest: lookup.get(name).map(|vsn| vsn.latest.clone()),
+                            private: config.is_private_package(name),
+                            dev,
+                            pinned_by: pinned_constraints(name, &internal),
+                            stale: config.check.max_age_days.and_then(|max_age_days| {
+                                stale_version(version, lookup.get(name)?, max_age_days, now)
+                            }),
+                            problems: dependency_problems(
+                                config,
+                                name,
+                                version,
+                                lookup.get(name),
+                                scores.get(name),
+                                publishers.get(name),
+                            ),
+                        }),
+                        _ => None,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(CheckReport {
+        packages,
+        unknown,
+        git: None,
+    })
+}
 
+/// External packages the given `pubspecs` depended on at the git revision
+/// `since`. Packages that did not exist at that revision yet depended on
+/// nothing.
+fn baseline_dependencies(pubspecs: &[Pubspec], since: &str) -> Result<HashSet<String>, FlError> {
+    let git = |dir: &str, args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .map_err(|err| GitError(err.to_string()))
+    };
+
+    let Some(first) = pubspecs.first() else {
+        return Ok(HashSet::new());
+    };
+    let revision = format!("{}^{{commit}}", since);
+    let output = git(&first.dir_path, &["rev-parse", "--verify", &revision])?;
+    if !output.status.success() {
+        return Err(GitError(format!(
+            "cannot compare dependencies to '{}': {}",
+            since,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let mut baseline = HashSet::new();
     for pubspec in pubspecs {
-        println!("  // {}", pubspec.name);
-        println!("  {} []", pubspec.name);
-
-        for dep in pubspec.dependencies {
-            match dep.effective() {
-                Dependency::Local { name, .. } => println!("  {} -> {};", pubspec.name, name),
-                Dependency::Git { name, .. } => println!("  {} -> {};", pubspec.name, name),
-                _ => {}
+        let Some(file) = Path::new(&pubspec.path).file_name() else {
+            continue;
+        };
+        let object = format!("{}:./{}", since, file.to_string_lossy());
+        let output = git(&pubspec.dir_path, &["show", &object])?;
+        if !output.status.success() {
+            continue;
+        }
+
+        let content = String::from_utf8_lossy(&output.stdout);
+        if let Ok(previous) = Pubspec::parse(&pubspec.path, &content) {
+            baseline.extend(
+                previous
+                    .dependencies
+                    .iter()
+                    .chain(previous.dev_dependencies.iter())
+                    .map(|dep| dep.name().clone()),
+            );
+        }
+    }
+
+    Ok(baseline)
+}
+
+/// Determine the violations of the policies on external dependencies
+/// (`check.quality`, `check.min_dart_sdk` and `check.publishers`) by the
+/// package `name` based on its pub.dev metadata (if fetched). The `score`
+/// is fetched for dependencies subject to the quality gate only, i.e. new
+/// ones that are not exempt.
+fn dependency_problems(
+    config: &Config,
+    name: &str,
+    constraint: &str,
+    versions: Option<&PubVersions>,
+    score: Option<&PubScore>,
+    publisher: Option<&PubPublisher>,
+) -> Vec<CheckProblem> {
+    let mut problems = Vec::new();
+
+    if let Some(score) = score {
+        problems.extend(quality_problems(score, &config.check.quality));
+    }
+    if let (Some(versions), Some(min_sdk)) = (versions, &config.check.min_dart_sdk) {
+        problems.extend(sdk_problem(constraint, versions, min_sdk));
+    }
+    if let Some(publisher) = publisher.filter(|_| config.check.publishers.applies_to(name)) {
+        problems.extend(publisher_problem(publisher, &config.check.publishers));
+    }
+
+    problems
+}
+
+/// Determine the version the `constraint` resolves to, i.e. the newest
+/// allowed one (preferring stable versions).
+fn resolved_version<'a>(constraint: &str, versions: &'a PubVersions) -> Option<&'a String> {
+    let constraint = VersionConstraint::parse(constraint)?;
+    let allowed = versions
+        .versions
+        .iter()
+        .flat_map(|version| Some((parse_version(version)?, version)))
+        .filter(|(parsed, _)| constraint.allows(parsed))
+        .collect::<Vec<_>>();
+    let (_, version) = allowed
+        .iter()
+        .filter(|(parsed, _)| parsed.pre.is_empty())
+        .max()
+        .or_else(|| allowed.iter().max())?;
+
+    Some(version)
+}
+
+/// Determine the version the `constraint` resolves to, if it was published
+/// more than `max_age_days` before `now`.
+fn stale_version(
+    constraint: &str,
+    versions: &PubVersions,
+    max_age_days: u64,
+    now: SystemTime,
+) -> Option<StaleVersion> {
+    let version = resolved_version(constraint, versions)?;
+    let age = now.duration_since(*versions.published.get(version)?).ok()?;
+    let age_days = age.as_secs() / 86400;
+
+    (age_days > max_age_days).then(|| StaleVersion {
+        version: version.to_string(),
+        age_days,
+    })
+}
+
+/// Check whether the version the `constraint` resolves to supports the
+/// Dart SDK `min_sdk` according to its `environment`.
+fn sdk_problem(
+    constraint: &str,
+    versions: &PubVersions,
+    min_sdk: &Version,
+) -> Option<CheckProblem> {
+    let version = resolved_version(constraint, versions)?;
+    let sdk = versions.sdk.get(version)?;
+
+    if VersionConstraint::parse_sdk(sdk)?.allows(min_sdk) {
+        return None;
+    }
+
+    Some(CheckProblem {
+        rule: CheckRule::Sdk,
+        level: ValidationLevel::Error,
+        message: format!(
+            "{} requires Dart SDK '{}' (minimum {})",
+            version, sdk, min_sdk
+        ),
+    })
+}
+
+/// Check whether the package of the given `publisher` complies with the
+/// `check.publishers` policy.
+fn publisher_problem(publisher: &PubPublisher, policy: &PublisherPolicy) -> Option<CheckProblem> {
+    let message = match &publisher.publisher_id {
+        None => "not published by a verified publisher".to_owned(),
+        Some(id) if !policy.allowed.is_empty() && !policy.allowed.contains(id) => {
+            format!("published by '{}' (not an allowed publisher)", id)
+        }
+        Some(_) => return None,
+    };
+
+    Some(CheckProblem {
+        rule: CheckRule::Publisher,
+        level: policy.level.clone(),
+        message,
+    })
+}
+
+/// Determine the violations of the `check.quality` gate by a package of the
+/// given pub.dev `score`.
+fn quality_problems(score: &PubScore, gate: &QualityGate) -> Vec<CheckProblem> {
+    let mut messages = Vec::new();
+
+    if let (Some(points), Some(min)) = (score.points, gate.min_points) {
+        if points < min {
+            messages.push(format!("{} pub points (minimum {})", points, min));
+        }
+    }
+    if let (Some(popularity), Some(min)) = (score.popularity, gate.min_popularity) {
+        if popularity < min {
+            messages.push(format!("popularity of {}% (minimum {}%)", popularity, min));
+        }
+    }
+
+    messages
+        .into_iter()
+        .map(|message| CheckProblem {
+            rule: CheckRule::Quality,
+            level: gate.level.clone(),
+            message,
+        })
+        .collect()
+}
+
+/// Dependencies of the given `pubspec` considered by the `check` command
+/// (flagged whether being a dev dependency).
+fn checked_dependencies(pubspec: &Pubspec, dev: bool) -> Vec<(&Dependency, bool)> {
+    let dev_deps = pubspec.dev_dependencies.iter().filter(|_| dev);
+
+    pubspec
+        .dependencies
+        .iter()
+        .map(|dep| (dep, false))
+        .chain(dev_deps.map(|dep| (dep, true)))
+        .collect()
+}
+
+/// Constraints on the external package `name` declared by any of the given
+/// `internal` packages.
+fn pinned_constraints(name: &str, internal: &[&Pubspec]) -> Vec<PinnedConstraint> {
+    internal
+        .iter()
+        .flat_map(|pubspec| {
+            pubspec.dependencies.iter().flat_map(|dep| match dep {
+                Dependency::PubDev {
+                    name: dep_name,
+                    version,
+                    ..
+                } if dep_name == name => Some(PinnedConstraint {
+                    package: pubspec.name.clone(),
+                    version: version.clone(),
+                }),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+/// Progress bar of the pub.dev requests of the `check` command, which is
+/// drawn on stderr (if it is a terminal) unless running `--quiet`.
+fn fetch_progress(opts: &Opts, len: usize) -> ProgressBar {
+    if opts.quiet {
+        return ProgressBar::hidden();
+    }
+
+    let progress = ProgressBar::with_draw_target(Some(len as u64), ProgressDrawTarget::stderr());
+    if let Ok(style) = ProgressStyle::with_template("fetching pub.dev {bar:30} {pos}/{len}") {
+        progress.set_style(style);
+    }
+    progress
+}
+
+fn print_timings(elapsed: Duration, stats: PubDevStats) {
+    eprintln!("timings:");
+    eprintln!("  total: {:.2}s", elapsed.as_secs_f64());
+    eprintln!("  requests: {}", stats.requests);
+    eprintln!("  throttled: {}", stats.throttled);
+    eprintln!(
+        "  waiting for throttling: {:.2}s",
+        stats.throttle_wait.as_secs_f64()
+    );
+}
+
+pub fn stats(
+    config: &Config,
+    pubspecs: &[Pubspec],
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let stats = Stats::collect(config, pubspecs);
+
+    renderer.render_stats(&stats).map_err(OutputError)
+}
+
+pub fn tree(opts: &Opts, pubspecs: &[Pubspec], renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    let workspace = Workspace::new(pubspecs);
+    let roots = match opts.args.first() {
+        Some(name) => vec![workspace
+            .package(name)
+            .ok_or_else(|| UnknownPackage(name.to_owned()))?],
+        None => root_packages(&workspace),
+    };
+
+    let trees = roots
+        .into_iter()
+        .map(|root| dependency_tree(root, &workspace, opts.all))
+        .collect::<Vec<_>>();
+
+    renderer.render_tree(&trees).map_err(OutputError)
+}
+
+pub fn list(
+    opts: &Opts,
+    config: &Config,
+    pubspecs: &[Pubspec],
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    if let Some(name) = &opts.package_type {
+        if !config
+            .package_types
+            .iter()
+            .any(|pkg_type| &pkg_type.name == name)
+        {
+            return Err(ConfigValidation(format!("unknown package type '{}'", name)));
+        }
+    }
+
+    let filter = ListFilter {
+        package_type: opts.package_type.clone(),
+        public_only: opts.public_only,
+        depends_on: opts.depends_on.clone(),
+        transitively_depends_on: opts.transitively_depends_on.clone(),
+        no_dependents: opts.no_dependents,
+    };
+    let current_dir = std::env::current_dir().map_err(OutputError)?;
+    let packages = list_packages(config, pubspecs, &filter, &current_dir);
+
+    renderer.render_packages(&packages).map_err(OutputError)
+}
+
+/// List the active dependency overrides of all (non-blacklisted) packages.
+pub fn overrides(
+    config: &Config,
+    pubspecs: &[Pubspec],
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let overrides = pubspecs
+        .iter()
+        .filter(|pubspec| !config.is_blacklisted(&pubspec.path))
+        .flat_map(package_overrides)
+        .collect::<Vec<_>>();
+
+    renderer.render_overrides(&overrides).map_err(OutputError)
+}
+
+pub fn publish_order(pubspecs: &[Pubspec], renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    let waves = publish_waves(pubspecs)?;
+
+    renderer.render_publish_order(&waves).map_err(OutputError)
+}
+
+pub fn bump(opts: &Opts, pubspecs: &[Pubspec], renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    let package = &opts.args[0];
+    let part = match opts.args.get(1) {
+        Some(part) => BumpPart::parse(part).ok_or_else(|| {
+            ConfigValidation(format!(
+                "invalid version part '{}' (valid: major, minor, patch)",
+                part
+            ))
+        })?,
+        None => BumpPart::Patch,
+    };
+
+    let mut report = bump::bump(package, part, pubspecs)?;
+
+    if opts.write {
+        let constraint = format!("^{}", report.new_version);
+        let mut paths = report
+            .outdated
+            .iter()
+            .map(|outdated| outdated.path.as_str())
+            .collect::<Vec<_>>();
+        paths.dedup();
+
+        for path in paths {
+            let content = std::fs::read_to_string(path)?;
+            let rewritten = rewrite_constraint(&content, package, &constraint);
+            std::fs::write(path, rewritten).map_err(OutputError)?;
+        }
+
+        if let Some(pubspec) = pubspecs.iter().find(|pubspec| &pubspec.name == package) {
+            let content = std::fs::read_to_string(&pubspec.path)?;
+            let rewritten = rewrite_version(&content, &report.new_version);
+            std::fs::write(&pubspec.path, rewritten).map_err(OutputError)?;
+        }
+
+        report.written = true;
+    }
+
+    renderer.render_bump(&report).map_err(OutputError)
+}
+
+pub fn rules(config: &Config, renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    renderer
+        .render_rules(&Rule::all(config))
+        .map_err(OutputError)
+}
+
+pub fn explain(opts: &Opts, config: &Config, renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    let code = &opts.args[0];
+    let validation_type =
+        ValidationType::parse(code).ok_or_else(|| InvalidValidationType(code.to_owned()))?;
+
+    renderer
+        .render_rule(&Rule::new(&validation_type, config))
+        .map_err(OutputError)
+}
+
+pub async fn config_check(
+    opts: &Opts,
+    config_file: &str,
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let config_yaml = Config::load_yaml(config_file, &opts.overrides, &opts.http_options()).await?;
+    let mut problems = check_yaml(&config_yaml);
+    let is_error = |problem: &ConfigProblem| problem.level == ValidationLevel::Error;
+
+    // the workspace is checked against valid configurations only
+    if !problems.iter().any(is_error) {
+        match Config::from_yaml(config_yaml, config_file) {
+            Ok(config) => {
+                let pubspecs =
+                    load_pubspecs(&opts.root_dirs(&config), &config, opts.follow_symlinks)?;
+                problems.extend(check_workspace(&config, &pubspecs));
             }
+            Err(err) => problems.push(ConfigProblem {
+                level: ValidationLevel::Error,
+                key: String::new(),
+                message: err.to_string(),
+            }),
+        }
+    }
+
+    renderer
+        .render_config_problems(&problems)
+        .map_err(OutputError)?;
+
+    match problems.iter().filter(|problem| is_error(problem)).count() {
+        0 => Ok(()),
+        errors => Err(ConfigValidation(format!("{} error(s)", errors))),
+    }
+}
+
+pub fn init(opts: &Opts) -> Result<(), FlError> {
+    let config_file = opts
+        .config_file
+        .clone()
+        .unwrap_or_else(|| CONFIG_FILE.to_owned());
+
+    if Path::new(&config_file).exists() && !opts.force {
+        return Err(ConfigExists(config_file));
+    }
+
+    let config = Config::default();
+    let root_dirs = opts.root_dirs(&config);
+    let pubspecs = load_pubspecs(&root_dirs, &config, opts.follow_symlinks)?;
+    if pubspecs.is_empty() {
+        return Err(NoInputFiles(root_dirs.join(", ")));
+    }
+
+    // ask for every package type when running in a terminal only
+    let interactive = io::stdin().is_terminal();
+    let mut package_types = Vec::new();
+    for inferred in infer_package_types(&pubspecs) {
+        if !interactive || confirm_package_type(&inferred)? {
+            package_types.push(inferred.package_type);
+        }
+    }
+
+    if package_types.is_empty() {
+        return Err(ConfigValidation("no package types selected".to_owned()));
+    }
+
+    // root directories are stored relative to the configuration file
+    let config_dir = Path::new(&config_file)
+        .parent()
+        .and_then(|dir| dir.to_str())
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or(".");
+    let config_dir = canonicalize(config_dir).unwrap_or(config_dir.to_owned());
+    let roots = opts
+        .root_dirs
+        .iter()
+        .map(|root| {
+            relative_path(Path::new(&config_dir), Path::new(root))
+                .map(|rel| if rel.is_empty() { ".".to_owned() } else { rel })
+                .unwrap_or(root.clone())
+        })
+        .collect::<Vec<_>>();
+
+    std::fs::write(&config_file, render_config(&package_types, &roots))?;
+    if !opts.quiet {
+        eprintln!(
+            "wrote {} with {} package type(s), see `flcheck config-check`",
+            config_file,
+            package_types.len()
+        );
+    }
+    Ok(())
+}
+
+fn confirm_package_type(inferred: &InferredType) -> Result<bool, FlError> {
+    let pkg_type = &inferred.package_type;
+    eprint!(
+        "package type '{}' (dir_prefix: {}, {} package(s), includes: {})? [Y/n] ",
+        pkg_type.name,
+        pkg_type.prefixes.join(", "),
+        inferred.packages.len(),
+        if pkg_type.includes.is_empty() {
+            "-".to_owned()
+        } else {
+            pkg_type.includes.join(", ")
         }
+    );
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(
+        answer.trim().to_lowercase().as_str(),
+        "" | "y" | "yes"
+    ))
+}
+
+pub fn dump(pubspecs: &[Pubspec], renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    renderer.render_dump(pubspecs).map_err(OutputError)
+}
+
+pub fn validate(
+    opts: &Opts,
+    config: &Config,
+    discovery: &Discovery,
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let pubspecs = &discovery.pubspecs;
+
+    if opts.suggest_config {
+        let suggestions = suggest_includes(config, pubspecs);
+
+        return renderer
+            .render_include_suggestions(&suggestions)
+            .map_err(OutputError);
+    }
+
+    let mut selected = selected_packages(opts, pubspecs)?;
+    if opts.stdin {
+        let listed = listed_packages(io::stdin().lock(), pubspecs)?;
+        selected.retain(|pubspec| listed.iter().any(|listed| listed.path == pubspec.path));
+    }
+    let complete = selected.len() == pubspecs.len();
+    let workspace = Workspace::new(pubspecs);
+    let mut validations = match &discovery.cache {
+        Some(cache) if opts.changed_only => incremental_findings(config, &workspace, cache)?,
+        _ => validation_findings(config, &selected, &workspace)?,
+    };
+
+    // findings of all packages are the base of the next incremental run
+    if let Some(cache) = discovery.cache.as_ref().filter(|_| complete) {
+        if let Err(err) = write_findings(cache, config.fingerprint(), pubspecs, &validations) {
+            warn!("cannot write cached findings: {}", err);
+        }
+    }
+
+    // import findings depend on the dart files and are never cached
+    if opts.imports {
+        validations.extend(import_findings(config, &selected, &workspace)?);
+        order_by_package(&workspace, &mut validations);
+    }
+
+    match opts.group_by {
+        GroupBy::Package => renderer.render_validations(&validations),
+        GroupBy::Owner => {
+            // unowned packages last
+            validations
+                .sort_by_key(|validation| (validation.owner.is_none(), validation.owner.clone()));
+            renderer.render_validations_by_owner(&validations)
+        }
+    }
+    .map_err(OutputError)?;
+
+    let now = SystemTime::now();
+    if let Some(file) = &opts.append_metrics {
+        let record = MetricsRecord::new(&validations, selected.len(), now);
+        append_metrics(file, &record).map_err(OutputError)?;
+    }
+    if let Some(file) = &opts.metrics_file {
+        let stats = Stats::collect(config, pubspecs);
+        std::fs::write(file, prometheus_metrics(&validations, &stats, now)).map_err(OutputError)?;
+    }
+
+    // the findings are incomplete if any package was skipped
+    if opts.strict {
+        let unnamed = pubspecs
+            .iter()
+            .filter(|pubspec| pubspec.name.is_empty())
+            .map(|pubspec| (pubspec.path.as_str(), "missing package name"));
+        let problems = discovery
+            .skipped
+            .iter()
+            .map(|skipped| (skipped.path.as_str(), skipped.reason.as_str()))
+            .chain(unnamed)
+            .collect::<Vec<_>>();
+
+        // only the first document of a pubspec is considered
+        let multi_document = pubspecs
+            .iter()
+            .filter(|pubspec| pubspec.extra_documents > 0)
+            .map(|pubspec| pubspec.path.as_str())
+            .collect::<Vec<_>>();
+
+        for (path, reason) in &problems {
+            warn!("skipped {}: {}", path, reason);
+        }
+        for path in &multi_document {
+            warn!("{}: multiple YAML documents", path);
+        }
+        if !problems.is_empty() || !multi_document.is_empty() {
+            return Err(SkippedPackages(
+                (problems.len() + multi_document.len()) as u32,
+            ));
+        }
+    }
+
+    check_fail_level(opts, &validations)
+}
+
+/// Validate the `selected` packages (including custom validations) and
+/// annotate the findings, which are ordered by package.
+pub(crate) fn validation_findings(
+    config: &Config,
+    selected: &[&Pubspec],
+    workspace: &Workspace,
+) -> Result<Vec<PackageValidation>, FlError> {
+    // packages are validated in parallel, the findings are collected in
+    // the order of the packages nonetheless
+    let validations = selected
+        .par_iter()
+        .flat_map_iter(|pubspec| pubspec.validate(config, workspace))
+        .collect::<Vec<_>>();
+
+    complete_findings(config, selected, workspace, validations)
+}
+
+/// Complete the `validations` of the `selected` packages by the findings
+/// of the custom validations, dropping ignored findings and annotating the
+/// remaining ones.
+fn complete_findings(
+    config: &Config,
+    selected: &[&Pubspec],
+    workspace: &Workspace,
+    mut validations: Vec<PackageValidation>,
+) -> Result<Vec<PackageValidation>, FlError> {
+    // findings of custom validations are limited to the selected packages
+    // as well (dropping those of unknown packages)
+    let names = selected
+        .iter()
+        .map(|pubspec| pubspec.name.as_str())
+        .collect::<HashSet<_>>();
+    validations.extend(
+        run_custom_validations(config, workspace.packages)?
+            .into_iter()
+            .filter(|validation| names.contains(validation.package_name.as_str())),
+    );
+
+    drop_ignored(workspace, &mut validations);
+    order_by_package(workspace, &mut validations);
+    dedup_cycles(workspace, &mut validations);
+    annotate_findings(config, workspace, &mut validations)?;
+
+    Ok(validations)
+}
+
+/// Drop all findings that are ignored via the `flcheck: ignore` annotation
+/// of the dependency they refer to.
+fn drop_ignored(workspace: &Workspace, validations: &mut Vec<PackageValidation>) {
+    validations.retain(|validation| {
+        !workspace
+            .package(&validation.package_name)
+            .is_some_and(|pubspec| pubspec.ignores(validation))
+    });
+}
+
+/// Cross-check the dart imports of the `selected` packages against their
+/// dependencies (see `--imports`).
+fn import_findings(
+    config: &Config,
+    selected: &[&Pubspec],
+    workspace: &Workspace,
+) -> Result<Vec<PackageValidation>, FlError> {
+    let mut validations = selected
+        .par_iter()
+        .flat_map_iter(|pubspec| import_validations(pubspec, config))
+        .collect::<Vec<_>>();
+
+    drop_ignored(workspace, &mut validations);
+    annotate_findings(config, workspace, &mut validations)?;
+
+    Ok(validations)
+}
+
+/// Validate the packages affected by changes since the previous run only
+/// (the changed packages and all packages depending on them), reusing the
+/// cached findings of all other packages. Findings depending on other
+/// files than the pubspecs are never reused (see
+/// `ValidationType::reads_files`), neither are their owners.
+fn incremental_findings(
+    config: &Config,
+    workspace: &Workspace,
+    cache: &CacheState,
+) -> Result<Vec<PackageValidation>, FlError> {
+    let pubspecs = workspace.packages;
+    let Some(cached) = cached_findings(cache, config.fingerprint()) else {
+        info!("no cached findings of this configuration, validating all packages");
+        return validation_findings(config, &pubspecs.iter().collect::<Vec<_>>(), workspace);
+    };
+
+    // dependents of removed packages are affected as well
+    let changed = pubspecs
+        .iter()
+        .filter(|pubspec| {
+            cached.changed.contains(&pubspec.path)
+                || pubspec
+                    .dependencies
+                    .iter()
+                    .chain(pubspec.dev_dependencies.iter())
+                    .any(|dep| cached.removed.contains(dep.name()))
+        })
+        .flat_map(|pubspec| workspace.index(pubspec))
+        .collect::<Vec<_>>();
+    let mut affected = vec![false; pubspecs.len()];
+    for idx in workspace.affected(&changed) {
+        affected[idx] = true;
+    }
+    info!(
+        "validating {} of {} package(s) affected by changes",
+        affected.iter().filter(|affected| **affected).count(),
+        pubspecs.len()
+    );
+
+    let mut validations = cached
+        .findings
+        .into_iter()
+        .filter(|validation| {
+            !validation.code.reads_files()
+                && workspace
+                    .by_name
+                    .get(validation.package_name.as_str())
+                    .is_some_and(|&idx| !affected[idx])
+        })
+        .collect::<Vec<_>>();
+    validations.extend(
+        pubspecs
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(idx, pubspec)| {
+                if affected[idx] {
+                    pubspec.validate(config, workspace)
+                } else {
+                    pubspec.validate_files(config)
+                }
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    complete_findings(
+        config,
+        &pubspecs.iter().collect::<Vec<_>>(),
+        workspace,
+        validations,
+    )
+}
+
+/// Keep all findings of a package together (in the order of the packages).
+fn order_by_package(workspace: &Workspace, validations: &mut [PackageValidation]) {
+    validations.sort_by_key(|validation| workspace.by_name.get(validation.package_name.as_str()));
+}
+
+/// Report every cycle once only instead of once per package involved,
+/// preferably by the package the (canonical) cycle description starts with.
+fn dedup_cycles(workspace: &Workspace, validations: &mut Vec<PackageValidation>) {
+    let starts_cycle = |validation: &PackageValidation| {
+        workspace
+            .package(&validation.package_name)
+            .is_some_and(|pubspec| {
+                validation
+                    .error
+                    .starts_with(&format!("cyclic dependency {} ->", pubspec.name))
+            })
+    };
+
+    // index of the reported finding of every cycle
+    let mut reported: HashMap<&str, usize> = HashMap::new();
+    for (idx, validation) in validations.iter().enumerate() {
+        if validation.code != ValidationType::CyclicDependency {
+            continue;
+        }
+        match reported.get(validation.error.as_str()) {
+            Some(&other) if starts_cycle(&validations[other]) || !starts_cycle(validation) => {}
+            _ => {
+                reported.insert(&validation.error, idx);
+            }
+        }
+    }
+
+    let keep = reported.into_values().collect::<HashSet<_>>();
+    let mut idx = 0;
+    validations.retain(|validation| {
+        idx += 1;
+        validation.code != ValidationType::CyclicDependency || keep.contains(&(idx - 1))
+    });
+}
+
+/// Fail if the findings exceed the `--fail-level` or `--max-warnings`.
+fn check_fail_level(opts: &Opts, validations: &[PackageValidation]) -> Result<(), FlError> {
+    let count = |level: ValidationLevel| {
+        validations
+            .iter()
+            .filter(|validation| validation.level == level)
+            .count() as u32
+    };
+    let num_errors = count(ValidationLevel::Error);
+    let num_warnings = count(ValidationLevel::Warning);
+
+    match opts.fail_level {
+        ValidationLevel::Error | ValidationLevel::Warning if num_errors > 0 => {
+            return Err(ValidationError(num_errors))
+        }
+        ValidationLevel::Warning if num_warnings > 0 => {
+            return Err(ValidationWarnings(num_warnings))
+        }
+        _ => {}
+    }
+
+    match opts.max_warnings {
+        Some(max_warnings) if num_warnings > max_warnings => {
+            Err(TooManyWarnings(num_warnings, max_warnings))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Annotate all findings with the pubspec file (and the line of the
+/// dependency along with a fix, if possible) and the owner of their package:
+/// the `owner` in the `flcheck` section of the pubspec takes precedence over
+/// the one determined via the `CODEOWNERS` file (if any).
+fn annotate_findings(
+    config: &Config,
+    workspace: &Workspace,
+    validations: &mut [PackageValidation],
+) -> Result<(), FlError> {
+    let codeowners = config
+        .codeowners
+        .as_deref()
+        .map(CodeOwners::load)
+        .transpose()?;
+    // contents of the pubspec files (read once per package)
+    let mut contents: HashMap<String, Option<String>> = HashMap::new();
+
+    for validation in validations.iter_mut() {
+        let Some(pubspec) = workspace.package(&validation.package_name) else {
+            continue;
+        };
+
+        validation.owner = pubspec.owner.clone().or_else(|| {
+            codeowners
+                .as_ref()
+                .and_then(|codeowners| codeowners.owner(pubspec))
+        });
+        validation.package_type = pubspec
+            .package_type(config)
+            .map(|pkg_type| pkg_type.name.clone());
+        // findings located in other files (e.g. dart imports) are kept as is
+        let located = validation.file.is_some();
+        let file = validation.file.get_or_insert_with(|| pubspec.path.clone());
+
+        // paths relative to the repository (or workspace) root are stable
+        // across checkouts in different locations
+        let dir = Path::new(&pubspec.dir_path);
+        let root = repository_root(dir).or_else(|| {
+            config
+                .roots
+                .iter()
+                .map(Path::new)
+                .find(|root| dir.starts_with(root))
+        });
+        validation.relative_file = root.and_then(|root| relative_path(root, Path::new(file)));
+
+        if located {
+            continue;
+        }
+
+        let Some(dependency) = &validation.dependency else {
+            continue;
+        };
+        let Some(content) = contents
+            .entry(pubspec.path.clone())
+            .or_insert_with(|| std::fs::read_to_string(&pubspec.path).ok())
+        else {
+            continue;
+        };
+
+        validation.line = dependency_line(content, dependency);
+        validation.fix = validation
+            .line
+            .and_then(|line| content.lines().nth(line - 1))
+            .and_then(|text| suggested_fix(validation, dependency, text, workspace));
     }
 
-    println!("}}");
     Ok(())
 }
 
-pub async fn check(pubspecs: Vec<Pubspec>) -> Result<(), FlError> {
-    let unique_packages = pubspecs
+/// Compute the replacement of the line `text` (declaring `dependency`)
+/// fixing the given finding. Only constraints not matching the version of
+/// a workspace package can be fixed (as `bump --write` does).
+fn suggested_fix(
+    validation: &PackageValidation,
+    dependency: &str,
+    text: &str,
+    workspace: &Workspace,
+) -> Option<String> {
+    match validation.code {
+        ValidationType::IncompatibleVersion => {
+            let version = workspace.package(dependency)?.version.as_ref()?;
+
+            replace_value(text, dependency, &format!("^{}", version))
+        }
+        _ => None,
+    }
+}
+
+/// Determine the packages selected for validation via the package
+/// directory (`validate PATH`), `--package` and `--exclude`. All packages
+/// are still used to resolve dependencies.
+fn selected_packages<'a>(
+    opts: &Opts,
+    pubspecs: &'a [Pubspec],
+) -> Result<Vec<&'a Pubspec>, FlError> {
+    let package = opts
+        .args
+        .first()
+        .map(|path| package_at(path, pubspecs).ok_or_else(|| UnknownPackage(path.to_owned())))
+        .transpose()?;
+
+    let unknown = opts
+        .packages
         .iter()
-        .flat_map(|pkg| {
-            pkg.dependencies.iter().flat_map(|dep| match dep {
-                Dependency::PubDev { name, .. } => Some(name),
-                _ => None,
-            })
-        })
-        .collect::<HashSet<_>>();
+        .chain(opts.exclude_packages.iter())
+        .find(|name| !pubspecs.iter().any(|pubspec| &&pubspec.name == name));
 
-    let versions = try_join_all(
-        unique_packages
+    if let Some(name) = unknown {
+        return Err(UnknownPackage(name.to_owned()));
+    }
+
+    let selected: Vec<&Pubspec> = if opts.affected_by.is_empty() {
+        pubspecs.iter().collect()
+    } else {
+        let files = opts
+            .affected_by
             .iter()
-            .map(|package| fetch_dep_versions(package)),
-    )
-    .await?;
+            .flat_map(|file| canonicalize(file))
+            .collect::<Vec<_>>();
+        let workspace = Workspace::new(pubspecs);
+        let changed = pubspecs
+            .iter()
+            .enumerate()
+            .filter(|(_, pubspec)| {
+                let path = canonicalize(&pubspec.path).unwrap_or_else(|| pubspec.path.clone());
+                files.contains(&path)
+            })
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
 
-    let lookup = versions
+        workspace
+            .affected(&changed)
+            .into_iter()
+            .map(|idx| &pubspecs[idx])
+            .collect()
+    };
+
+    Ok(selected
         .into_iter()
-        .map(|pubversion| (pubversion.name.clone(), pubversion))
-        .collect::<HashMap<_, _>>();
+        .filter(|pubspec| package.is_none_or(|package| package.path == pubspec.path))
+        .filter(|pubspec| opts.packages.is_empty() || opts.packages.contains(&pubspec.name))
+        .filter(|pubspec| !opts.exclude_packages.contains(&pubspec.name))
+        .collect())
+}
 
-    for pubspec in pubspecs {
-        println!("{}", pubspec.name);
-
-        for dep in pubspec.dependencies {
-            match dep {
-                Dependency::PubDev { name, version, .. } => {
-                    let pub_version = lookup.get(&name).map(|vsn| &vsn.latest);
-                    println!(
-                        "  {}: {} [{}]",
-                        name,
-                        version,
-                        pub_version.map_or("<unknown>", String::as_str)
-                    );
-                }
-                _ => {}
-            }
+/// Determine the packages of the pubspec files (or package directories)
+/// read line by line from `reader` (see `--stdin`). Paths of no package of
+/// the workspace are skipped, e.g. removed pubspecs or other changed files.
+fn listed_packages<R: BufRead>(reader: R, pubspecs: &[Pubspec]) -> Result<Vec<&Pubspec>, FlError> {
+    let mut listed = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let path = line.trim();
+        if path.is_empty() {
+            continue;
+        }
+
+        match package_at(path, pubspecs) {
+            Some(pubspec) => listed.push(pubspec),
+            None => warn!("{}: no package of the workspace", path),
         }
     }
-    Ok(())
+
+    Ok(listed)
 }
 
-pub fn dump(opts: Opts, pubspecs: Vec<Pubspec>) -> Result<(), FlError> {
-    match opts.output {
-        OutputFormat::Json => {
-            let json_output = JsonDumpResult { pubspecs };
+/// Find the package located in the directory `path` (or whose pubspec.yaml
+/// is given as `path`).
+fn package_at<'a>(path: &str, pubspecs: &'a [Pubspec]) -> Option<&'a Pubspec> {
+    let path = Path::new(path);
+    let dir = match path.parent() {
+        Some(parent) if path.is_file() && parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) if path.is_file() => parent,
+        _ => path,
+    };
+    let dir = canonicalize(&dir.to_string_lossy())?;
 
-            serde_json::to_string(&json_output)
-                .ok()
-                .iter()
-                .for_each(|json| println!("{}", json));
-        }
-        OutputFormat::Plain => {
-            for pubspec in pubspecs {
-                print_pubspec_plain(pubspec)
-            }
-        }
+    pubspecs
+        .iter()
+        .find(|pubspec| canonicalize(&pubspec.dir_path).as_ref() == Some(&dir))
+}
+
+pub fn install_hook(opts: &Opts) -> Result<(), FlError> {
+    let hook_type = match opts.args.first() {
+        Some(hook) => HookType::parse(hook).ok_or_else(|| {
+            ConfigValidation(format!(
+                "invalid hook '{}' (valid: pre-commit, pre-push)",
+                hook
+            ))
+        })?,
+        None => HookType::PreCommit,
+    };
+
+    // respects worktrees and a configured `core.hooksPath`
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .map_err(|err| GitError(err.to_string()))?;
+    if !output.status.success() {
+        return Err(GitError(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ));
+    }
+
+    let hooks_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let hook_file = hooks_dir.join(hook_type.file_name());
+    if hook_file.exists() && !opts.force {
+        return Err(HookExists(hook_file.display().to_string()));
+    }
+
+    std::fs::create_dir_all(&hooks_dir)?;
+    std::fs::write(&hook_file, hook_script(hook_type))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook_file, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    if !opts.quiet {
+        eprintln!("installed {}", hook_file.display());
     }
     Ok(())
 }
 
-fn print_pubspec_plain(pubspec: Pubspec) {
-    println!("{} [{}]:", pubspec.name, pubspec.path);
+pub fn completions(opts: &Opts) -> Result<(), FlError> {
+    let shell = opts.args[0].parse::<Shell>().map_err(|_| {
+        ConfigValidation(format!(
+            "invalid shell '{}' (valid: {})",
+            opts.args[0],
+            SHELLS.join(", ")
+        ))
+    })?;
+
+    clap_complete::generate(
+        shell,
+        &mut cli::command(),
+        "flcheck",
+        &mut std::io::stdout(),
+    );
+
+    Ok(())
+}
+
+pub fn snapshot(
+    opts: &Opts,
+    pubspecs: &[Pubspec],
+    renderer: &mut dyn Renderer,
+) -> Result<(), FlError> {
+    let file = opts.args.get(1).map_or(SNAPSHOT_FILE, |file| file.as_str());
+    let actual = Snapshot::from_pubspecs(pubspecs);
+
+    if opts.args[0] == "write" {
+        std::fs::write(file, actual.to_json()).map_err(OutputError)?;
 
-    if !pubspec.dependencies.is_empty() {
-        println!("  dependencies:");
-        for dep in pubspec.dependencies.iter() {
-            print_dependency_plain(dep)
+        if !opts.quiet {
+            eprintln!(
+                "wrote {} (packages: {}, dependencies: {})",
+                file,
+                actual.packages.len(),
+                actual.dependencies.len()
+            );
         }
+        return Ok(());
     }
 
-    if !pubspec.dev_dependencies.is_empty() {
-        println!("  dev-dependencies:");
-        for dep in pubspec.dev_dependencies.iter() {
-            print_dependency_plain(dep)
-        }
+    let drift = Snapshot::load(file)?.drift(&actual);
+
+    renderer
+        .render_snapshot_drift(&drift)
+        .map_err(OutputError)?;
+
+    if drift.is_empty() {
+        Ok(())
+    } else {
+        Err(SnapshotDrift(drift.len() as u32))
     }
 }
 
-fn print_dependency_plain(dep: &Dependency) {
-    println!("    {} {}", dep.name(), dep.details());
-    dep.overridden().iter().for_each(|override_dependency| {
-        println!(
-            "      override: {} {}",
-            override_dependency.name(),
-            override_dependency.details()
-        );
-    })
-}
+/// Print the internal dependencies in the format of an external build
+/// system, with paths relative to the repository root. The melos
+/// configuration is written (or updated) in the repository root instead.
+pub fn export(opts: &Opts, config: &Config, pubspecs: &[Pubspec]) -> Result<(), FlError> {
+    let Some(format) = opts.export_format else {
+        return Err(ConfigValidation("missing export --format".to_owned()));
+    };
 
-fn print_validation_plain(validation: &PackageValidation) {
-    println!(
-        "{}: {}: {} [{}]",
-        validation.level, validation.package_name, validation.error, validation.code
-    );
+    let current_dir = std::env::current_dir().map_err(OutputError)?;
+    let root = repository_root(&current_dir).unwrap_or(&current_dir);
 
-    validation.description.iter().for_each(|desc| {
-        println!("\t{}", desc);
-    })
+    if format == ExportFormat::Melos {
+        let file = root.join(MELOS_FILE);
+        let existing = if file.exists() {
+            std::fs::read_to_string(&file)?
+        } else {
+            String::new()
+        };
+
+        std::fs::write(&file, melos_config(&existing, pubspecs, root)?).map_err(OutputError)?;
+
+        if !opts.quiet {
+            eprintln!("wrote {}", file.display());
+        }
+        return Ok(());
+    }
+
+    let export = export_workspace(format, config, pubspecs, root)?;
+
+    write!(io::stdout(), "{}", export).map_err(OutputError)
 }
 
-pub fn validate(opts: Opts, config: Config, pubspecs: Vec<Pubspec>) -> Result<(), FlError> {
-    let num_errors = match opts.output {
-        OutputFormat::Plain => {
-            let mut num_errors = 0u32;
-            for pubspec in pubspecs.iter() {
-                let validation_errors = pubspec.validate(&config, &pubspecs);
-                let grouped = group_validations(validation_errors);
+/// Write the software bill of materials of all external dependencies (see
+/// `sbom_components`) in the requested `--format`.
+pub fn sbom(opts: &Opts, config: &Config, pubspecs: &[Pubspec]) -> Result<(), FlError> {
+    let Some(format) = opts.sbom_format else {
+        return Err(ConfigValidation("missing sbom --format".to_owned()));
+    };
 
-                for error in grouped.errors {
-                    num_errors += 1;
+    let components = sbom_components(config, pubspecs);
+    let sbom = render_sbom(format, &components, SystemTime::now())?;
 
-                    print_validation_plain(&error)
-                }
+    writeln!(io::stdout(), "{}", sbom).map_err(OutputError)
+}
 
-                for warning in grouped.warnings {
-                    print_validation_plain(&warning)
-                }
-            }
-            num_errors
-        }
-        OutputFormat::Json => {
-            let validations = pubspecs
-                .iter()
-                .flat_map(|pubspec| pubspec.validate(&config, &pubspecs))
-                .collect::<Vec<_>>();
+/// Write the markdown documentation of the workspace (see
+/// `workspace_docs`) into the `--out` directory, with paths relative to
+/// the repository root.
+pub fn docs(opts: &Opts, config: &Config, pubspecs: &[Pubspec]) -> Result<(), FlError> {
+    let codeowners = config
+        .codeowners
+        .as_deref()
+        .map(CodeOwners::load)
+        .transpose()?;
+    let current_dir = std::env::current_dir().map_err(OutputError)?;
+    let root = repository_root(&current_dir).unwrap_or(&current_dir);
+    let out = Path::new(opts.docs_dir.as_deref().unwrap_or(DOCS_DIR));
 
-            let grouped = group_validations(validations);
+    std::fs::create_dir_all(out).map_err(OutputError)?;
 
-            serde_json::to_string(&grouped)
-                .ok()
-                .iter()
-                .for_each(|json| println!("{}", json));
+    let pages = workspace_docs(config, pubspecs, codeowners.as_ref(), root);
+    for page in pages.iter() {
+        std::fs::write(out.join(&page.file), &page.content).map_err(OutputError)?;
+    }
 
-            grouped.errors.len() as u32
-        }
+    if !opts.quiet {
+        eprintln!("wrote {} pages to {}", pages.len(), out.display());
+    }
+    Ok(())
+}
+
+pub fn diff(opts: &Opts, renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    let (Some(before), Some(after)) = (&opts.before, &opts.after) else {
+        return Err(ConfigValidation(
+            "both --before and --after are required".to_owned(),
+        ));
     };
 
-    if num_errors > 0 {
-        Err(ValidationError(num_errors))
-    } else {
+    let diff = diff_findings(load_findings(before)?, load_findings(after)?);
+
+    renderer.render_diff(&diff).map_err(OutputError)?;
+
+    // fixed and persisting findings do not fail
+    if diff.new.is_empty() {
         Ok(())
+    } else {
+        Err(NewFindings(diff.new.len() as u32))
     }
 }
 
-fn group_validations(validations: Vec<PackageValidation>) -> JsonValidationResult {
-    let mut warnings = Vec::new();
-    let mut errors = Vec::new();
+/// Validate the packages of all repositories listed in the `--repos` file,
+/// each with its own configuration, and report the findings by repository.
+pub async fn multi(opts: &Opts, renderer: &mut dyn Renderer) -> Result<(), FlError> {
+    let Some(repos_file) = &opts.repos else {
+        return Err(ConfigValidation("--repos is required".to_owned()));
+    };
+
+    let mut validations = Vec::new();
+    for repo in load_repositories(repos_file)? {
+        let config_file = match &repo.config {
+            Some(file) => file.clone(),
+            None => find_config_file(Path::new(&repo.path))
+                .and_then(|file| file.to_str().map(|path| path.to_owned()))
+                .ok_or_else(|| {
+                    NoConfigFound(format!("{} in repository '{}'", CONFIG_FILE, repo.name))
+                })?,
+        };
+        info!("using configuration of '{}': {}", repo.name, config_file);
+
+        let mut config = Config::load(&config_file, &opts.overrides, &opts.http_options()).await?;
+        let root_dirs = if config.roots.is_empty() {
+            vec![canonicalize(&repo.path).unwrap_or(repo.path.clone())]
+        } else {
+            config.roots.clone()
+        };
 
-    for validation in validations {
-        match validation.level {
-            ValidationLevel::Error => errors.push(validation),
-            ValidationLevel::Warning => warnings.push(validation),
-            ValidationLevel::None => {}
+        let pubspecs = load_pubspecs(&root_dirs, &config, opts.follow_symlinks)?;
+        info!(
+            "discovered {} package(s) in repository '{}'",
+            pubspecs.len(),
+            repo.name
+        );
+        if pubspecs.is_empty() {
+            return Err(NoInputFiles(root_dirs.join(", ")));
         }
+        config.roots = root_dirs;
+
+        let selected = pubspecs.iter().collect::<Vec<_>>();
+        let findings = validation_findings(&config, &selected, &Workspace::new(&pubspecs))?;
+        validations.extend(findings.into_iter().map(|finding| PackageValidation {
+            repo: Some(repo.name.clone()),
+            ..finding
+        }));
+    }
+
+    renderer
+        .render_validations_by_repo(&validations)
+        .map_err(OutputError)?;
+
+    check_fail_level(opts, &validations)
+}
+
+/// Answer JSON-RPC requests on stdin (see `serve::handle_request`) until
+/// shut down, while reloading changed pubspecs in the background.
+pub fn serve(
+    opts: &Opts,
+    mut config: Config,
+    root_dirs: Vec<String>,
+    cache: Option<PubspecCache>,
+) -> Result<(), FlError> {
+    // without the cache on disk changes are detected in memory only
+    let persistent = cache.is_some();
+    let mut cache = cache.unwrap_or_else(|| PubspecCache::empty(Path::new(CACHE_DIR)));
+
+    let pubspecs =
+        discover_pubspecs(&root_dirs, &config, opts.follow_symlinks, Some(&mut cache))?.pubspecs;
+    if pubspecs.is_empty() {
+        return Err(NoInputFiles(root_dirs.join(", ")));
     }
+    info!("serving {} package(s)", pubspecs.len());
+
+    config.roots = root_dirs.clone();
+    let state = Arc::new(RwLock::new(ServerState { config, pubspecs }));
+
+    let follow_symlinks = opts.follow_symlinks;
+    let watched = Arc::clone(&state);
+    thread::spawn(move || loop {
+        if persistent {
+            if let Err(err) = cache.save() {
+                warn!("cannot write pubspec cache: {}", err);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+
+        cache = cache.next_run();
+        let discovery = {
+            let state = watched.read().unwrap_or_else(PoisonError::into_inner);
+            discover_pubspecs(&root_dirs, &state.config, follow_symlinks, Some(&mut cache))
+        };
+
+        match discovery {
+            Ok(discovery) if cache.is_changed() => {
+                info!("reloaded {} package(s)", discovery.pubspecs.len());
+                watched
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .pubspecs = discovery.pubspecs;
+            }
+            Ok(_) => {}
+            Err(err) => warn!("cannot reload packages: {}", err),
+        }
+    });
 
-    JsonValidationResult {
-        warnings: warnings,
-        errors: errors,
+    let mut stdout = io::stdout();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = {
+            let state = state.read().unwrap_or_else(PoisonError::into_inner);
+            handle_request(&line, &state)
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .map_err(OutputError)?;
+        }
+
+        if is_shutdown(&line) {
+            break;
+        }
     }
+
+    Ok(())
+}
+
+pub fn schema() -> Result<(), FlError> {
+    let schema =
+        serde_json::to_string_pretty(&Report::schema()).map_err(|err| OutputError(err.into()))?;
+
+    writeln!(io::stdout(), "{}", schema).map_err(OutputError)
 }
 
 pub fn example_config() {
     println!(
-        r#"# Package types list rules for packages that describe
+        r#"# Minimum version of flcheck that is required for this configuration.
+# min_version: '1.2.0'
+
+# Configuration (file relative to this one or an https URL) this
+# configuration is based on. Mappings are merged, all other values
+# configured in here replace the ones of the extended configuration.
+# extends: ../shared/flcheck.yaml
+
+# Package types list rules for packages that describe
 # what package is allowed to depend on each other.
 #
 # The typical recommended setup is a hierachy like the following:
@@ -240,11 +1944,18 @@ package_types:
     # are allowed to import themselves)
     includes:
       - app
+    # maximum length of the chain of local dependencies (excluding
+    # dev_dependencies) rooted at packages of this type (optional)
+    # max_depth: 4
 
   app:
     dir_prefix: 'app_'
     includes:
       - shared
+    # names of packages every package of this type has to depend on
+    # (optional)
+    # requires:
+    #   - shared_logging
 
   shared:
     dir_prefix: 'shared_'
@@ -259,6 +1970,12 @@ package_types:
       - 'util_'
     includes:
       - package
+    # packages of this type must not depend on flutter plugins with native
+    # platform code (`validation:dependency:native-plugin`, optional)
+    # pure_dart: true
+    # packages of this type must contain a `_test.dart` file in their `test`
+    # directory (`validation:tests:missing`, optional)
+    # require_tests: true
 
 # List of patterns (regular expressions) that match package directories
 # that should be excluded from all validations and checks.
@@ -267,16 +1984,945 @@ package_types:
 blacklist:
   - '/example'
 
+# List of directories (relative to this configuration file) packages are
+# discovered in and merged into one workspace. Directories given via
+# `-d` take precedence. If not specified, the current directory is used.
+# roots:
+#   - apps
+#   - packages
+
+# List of glob patterns of directories (relative to the apps directory)
+# packages are discovered in. Patterns without a '/' match directories
+# of that name at any depth. If not specified, all directories are
+# considered.
+# include:
+#   - 'apps/**'
+#   - 'packages/**'
+
+# List of patterns (regular expressions) of git repositories that are
+# part of the workspace. Git dependencies on these repositories are
+# resolved to the workspace package whose directory ends with the
+# dependency's `path` (or by name if no path is given) and validated just
+# like local dependencies.
+# internal_repositories:
+#   - 'github.com[:/]company/monorepo'
+
+# Local dependencies public packages (`flcheck: is_public: true`) of the
+# given package types may have: `git_only` (none at all, default), `public`
+# (on other public packages) or `same_repository` (on other public packages
+# declaring the same `repository` in their pubspec.yaml).
+# public_package_policy:
+#   package: same_repository
+
+# List of patterns (regular expressions) of external package names that
+# are private (e.g. hosted on a private package repository) and are not
+# looked up on pub.dev by the `check` command.
+# private_packages:
+#   - '^company_'
+
+# List of patterns (regular expressions) of external package names that
+# are needed during development only and are reported when declared in
+# `dependencies` (in addition to well-known ones like build_runner, mockito
+# or flutter_lints).
+# dev_only_packages:
+#   - '_generator$'
+
+# Files and directories (with a trailing `/`) all packages of the given
+# package types have to contain (`validation:structure:missing`).
+# structure:
+#   package:
+#     - analysis_options.yaml
+#     - README.md
+#     - lib/src/
+
+# Shared lint configuration the analysis_options.yaml of every package has
+# to include (`validation:analysis-options:include`).
+# analysis_options:
+#   include: package:company_lints/analysis_options.yaml
+
+# Styles the version constraints of external (pub.dev) dependencies whose
+# names match the glob pattern `packages` have to be written in
+# (`validation:dependency:constraint-style`): `any`, `caret` (^1.2.3),
+# `exact` (1.2.3) or `range` (>=1.2.3 <2.0.0). Every matching entry may
+# `require` a style and/or `forbid` a list of styles.
+# constraint_styles:
+#   - packages: '*'
+#     forbid: [any]
+#   - packages: 'firebase_*'
+#     require: exact
+
+# Groups of external packages serving the same purpose, only one of which
+# should be used across the workspace (`validation:dependency:alternatives`).
+# The packages of a group are given as a list or separated by `|`.
+# alternatives:
+#   http-clients: dio|http|chopper
+#   state-management: [provider, riverpod, flutter_bloc]
+
+# CODEOWNERS file (relative to this configuration file) determining the
+# owners validation findings are annotated with. If not specified,
+# '.github/CODEOWNERS', 'CODEOWNERS' and 'docs/CODEOWNERS' are searched in
+# the repository root. An `owner` in the `flcheck` section of a pubspec
+# takes precedence.
+# codeowners: .github/CODEOWNERS
+
+# Settings of the requests to pub.dev (`check` command): a `proxy` all
+# requests are sent through, a PEM file of additional root certificates
+# (`ca_bundle`, relative to this configuration file), a `timeout` in
+# seconds and the `user_agent` (defaults to 'flcheck/<version>').
+# http:
+#   proxy: 'http://proxy.company.com:3128'
+#   ca_bundle: certs/company-ca.pem
+#   timeout: 30
+
+# Settings of the `check` command: dependencies whose constraint resolves
+# to a version published more than `max_age_days` ago are reported as
+# stale (even if it is the latest version within the constraint).
+# Dependencies may be subject to a `quality` gate as well: packages below
+# the given pub points or popularity (in percent) according to pub.dev fail
+# the check unless they are `exempt` (e.g. dependencies that were added
+# before the gate was introduced). With `min_dart_sdk`, dependencies whose
+# resolved version does not support the given Dart SDK are rejected (e.g.
+# to prepare a migration to Dart 3). The `publishers` policy requires
+# dependencies to be published by a `verified` publisher and/or one of the
+# `allowed` publishers (reported with the given `level`, `warn` by default).
+# check:
+#   max_age_days: 365
+#   min_dart_sdk: 3.0.0
+#   publishers:
+#     verified: true
+#     allowed: [dart.dev, google.dev]
+#     exempt: [legacy_charts]
+#   quality:
+#     min_points: 120
+#     min_popularity: 70
+#     exempt: [legacy_charts]
+
+# List of glob patterns of directories that are not descended into when
+# discovering packages. If not specified, the directories 'build',
+# '.dart_tool', 'ios' and 'android' are skipped.
+exclude:
+  - build
+  - .dart_tool
+  - ios
+  - android
+
 # You can configure what kind of validations are associated with what
 # level of severity: `error` for errors, `warn` for warnings or
 # `none` to be ignored.
 # If not specified, every validation defaults to `error`
+#
+# The level may be scoped to packages whose directory matches any of
+# the given `paths` (regular expressions), e.g. in order to only warn
+# in legacy parts of the code base. Multiple scoped levels are given
+# as a list, the first matching one takes precedence.
 validations:
   validation:dev-dependency:git: error
   validation:dependency:unknown: error
   validation:dependency:unallowed: error
-  validation:dependency:cyclic: error
+  validation:dependency:cyclic:
+    - level: warn
+      paths:
+        - 'legacy/.*'
+    - level: error
   validation:public:dependency:non-git: error
+  validation:dependency:version: error
+  validation:public:dependency:private: error
+  validation:public:dependency:repository: error
+  validation:dependency:depth: error
+  validation:dependency:cross-app: error
+  validation:dependency:self: error
+  validation:dependency:duplicate: error
+  validation:dependency:outside-workspace: error
+  validation:dependency:required: error
+  validation:dependency:native-plugin: warn
+  validation:asset:missing: error
+  validation:dependency:malformed: error
+  validation:pubspec:invalid: error
+  validation:rule:deny: error
+  validation:rule:require: error
+  validation:dependency:dev-only: warn
+  validation:import:undeclared: error
+  validation:dependency:unused: warn
+  validation:tests:missing: error
+  validation:structure:missing: error
+  validation:analysis-options:include: error
+  validation:dependency:constraint-style: error
+  validation:public:changelog: error
+  validation:dependency:alternatives: warn
+  validation:dependency:path: error
+  validation:package:duplicate: error
+
+# Declarative constraints on the (non-dev) dependencies of packages, given
+# as glob patterns of package names (or of the package type, owner or tags
+# declared in the `flcheck` section of the pubspecs via `type:`, `owner:`
+# or `tag:`): `deny` dependencies of packages matching `from` on packages
+# matching `to` (local or external) and `require` packages matching
+# `package` to depend on a package matching `depends_on`.
+# rules:
+#   - deny: {{ from: 'app_*', to: 'pkg_experimental_*' }}
+#     description: experimental packages must not be shipped
+#   - deny: {{ from: 'type:shared', to: 'tag:legacy' }}
+#   - require: {{ package: 'app_*', depends_on: 'shared_analytics' }}
+
+# Organization specific validations performed by external commands (run
+# via the shell in the directory of this configuration file). Each
+# command receives the workspace (as printed by `flcheck dump -o json`)
+# on stdin and prints its findings as a JSON list on stdout, e.g.
+# [{{"package": "app_foo", "message": "missing LICENSE"}}]
+# custom_validations:
+#   acme:license:
+#     command: ./tools/check_license.sh
+#     level: warn
+#     description: every package must contain a LICENSE file
 "#
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::write_findings;
+    use crate::cache::PubspecCache;
+    use crate::cli::parse_opts;
+    use crate::command::check_fail_level;
+    use crate::command::check_report;
+    use crate::command::check_with;
+    use crate::command::fetch_progress;
+    use crate::command::incremental_findings;
+    use crate::command::listed_packages;
+    use crate::command::publisher_problem;
+    use crate::command::selected_packages;
+    use crate::command::validate;
+    use crate::command::validation_findings;
+    use crate::command::CheckReport;
+    use crate::command::CheckRule;
+    use crate::config::Config;
+    use crate::config::PublisherPolicy;
+    use crate::config::QualityGate;
+    use crate::dependency::Dependency;
+    use crate::error::FlError;
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::pubdev::MockPubApi;
+    use crate::pubdev::PubPublisher;
+    use crate::pubspec::discover_pubspecs;
+    use crate::pubspec::Discovery;
+    use crate::pubspec::SkippedPath;
+    use crate::render::PlainRenderer;
+    use crate::version::parse_version;
+    use crate::workspace::Workspace;
+    use crate::Pubspec;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    /// Directory of the package `name` in a new git repository `repo` (in
+    /// the temp directory) whose committed pubspec has the given content.
+    fn committed_package(repo: &str, name: &str, pubspec: &str) -> PathBuf {
+        let repo = std::env::temp_dir().join(format!("{}-{}", repo, std::process::id()));
+        let dir = repo.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("pubspec.yaml"), pubspec).unwrap();
+
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=flcheck",
+                    "-c",
+                    "user.email=flcheck@localhost",
+                ])
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "initial"]);
+
+        dir
+    }
+
+    #[tokio::test]
+    async fn check_with_mock_api() {
+        let pubspecs = vec![Pubspec::test("app_foo")
+            .with_deps(vec![
+                Pubspec::hosted("http", "^0.13.0"),
+                Pubspec::hosted("pkg_gone", "^1.0.0"),
+            ])
+            .with_dev_deps(vec![Pubspec::hosted("lints", "^2.0.0")])];
+        let api = MockPubApi::default()
+            .with_package("http", &["0.13.0", "1.2.0"])
+            .with_package("lints", &["2.0.0"]);
+        let config = Config::default();
+
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+        assert!(matches!(
+            check_report(&opts, &config, &pubspecs, &api).await,
+            Err(FlError::UnknownPackages(_))
+        ));
+
+        let opts = parse_opts(["flcheck", "check", "--allow-unknown"]).unwrap();
+        let report = check_report(&opts, &config, &pubspecs, &api).await.unwrap();
+
+        assert_eq!(report.unknown, vec!["pkg_gone"]);
+        let deps = &report.packages[0].dependencies;
+        assert_eq!(deps.len(), 3);
+        assert_eq!(deps[0].latest.as_deref(), Some("1.2.0"));
+        assert_eq!(deps[1].latest, None);
+        assert!(deps[2].dev);
+        assert_eq!(deps[2].latest.as_deref(), Some("2.0.0"));
+        assert!(deps.iter().all(|dep| dep.stale.is_none()));
+    }
+
+    #[tokio::test]
+    async fn check_freshness_budget() {
+        let pubspecs = vec![Pubspec {
+            dependencies: vec![
+                Pubspec::hosted("http", "^0.13.0"),
+                Pubspec::hosted("path", "^1.8.0"),
+                Pubspec::hosted("intl", "^0.18.0"),
+            ],
+            ..Pubspec::parse("/tmp/app_foo/pubspec.yaml", "name: app_foo").unwrap()
+        }];
+        let days_ago = |days: u64| SystemTime::now() - Duration::from_secs(days * 86400);
+        let api = MockPubApi::default()
+            .with_package("http", &["0.13.5", "0.13.6", "1.2.0"])
+            .with_published("http", "0.13.5", days_ago(800))
+            .with_published("http", "0.13.6", days_ago(500))
+            .with_published("http", "1.2.0", days_ago(10))
+            .with_package("path", &["1.8.0", "1.9.0"])
+            .with_published("path", "1.8.0", days_ago(900))
+            .with_published("path", "1.9.0", days_ago(100))
+            .with_package("intl", &["0.18.0", "0.18.1-dev"])
+            .with_published("intl", "0.18.0", days_ago(400))
+            .with_published("intl", "0.18.1-dev", days_ago(1));
+        let mut config = Config::default();
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+
+        let stale = |report: CheckReport| {
+            report.packages[0]
+                .dependencies
+                .iter()
+                .map(|dep| dep.stale.as_ref().map(|stale| stale.version.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let report = check_report(&opts, &config, &pubspecs, &api).await;
+        assert_eq!(stale(report.unwrap()), vec![None, None, None]);
+
+        // the newest allowed version is stale even though it is the
+        // latest within the constraint (pre-releases are not considered)
+        config.check.max_age_days = Some(365);
+        let report = check_report(&opts, &config, &pubspecs, &api).await;
+        assert_eq!(
+            stale(report.unwrap()),
+            vec![Some("0.13.6".to_owned()), None, Some("0.18.0".to_owned())]
+        );
+    }
+
+    #[tokio::test]
+    async fn check_quality_gate() {
+        let dir = committed_package("flcheck-quality", "app_foo", "name: app_foo");
+        let pubspecs = vec![Pubspec {
+            dependencies: vec![
+                Pubspec::hosted("http", "^1.0.0"),
+                Pubspec::hosted("leftpad", "^0.1.0"),
+                Pubspec::hosted("legacy", "^2.0.0"),
+            ],
+            ..Pubspec::parse(&dir.join("pubspec.yaml").to_string_lossy(), "name: app_foo").unwrap()
+        }];
+        let api = MockPubApi::default()
+            .with_package("http", &["1.2.0"])
+            .with_score("http", 150, Some(99))
+            .with_package("leftpad", &["0.1.0"])
+            .with_score("leftpad", 90, Some(20))
+            .with_package("legacy", &["2.0.0"])
+            .with_score("legacy", 40, None);
+        let mut config = Config::default();
+        config.check.quality = QualityGate {
+            min_points: Some(120),
+            min_popularity: Some(50),
+            exempt: vec!["legacy".to_owned()],
+            level: ValidationLevel::Error,
+        };
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+
+        let report = check_report(&opts, &config, &pubspecs, &api).await.unwrap();
+        let problems = report.packages[0]
+            .dependencies
+            .iter()
+            .map(|dep| {
+                dep.problems
+                    .iter()
+                    .map(|problem| problem.message.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            problems,
+            vec![
+                vec![],
+                vec![
+                    "90 pub points (minimum 120)",
+                    "popularity of 20% (minimum 50%)"
+                ],
+                vec![],
+            ]
+        );
+
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn check_quality_gate_new_dependencies() {
+        let dir = committed_package(
+            "flcheck-quality-new",
+            "app_foo",
+            "name: app_foo\ndependencies:\n  oldpad: ^1.0.0\n",
+        );
+        let pubspecs = vec![Pubspec {
+            dependencies: vec![
+                Pubspec::hosted("oldpad", "^1.0.0"),
+                Pubspec::hosted("newpad", "^1.0.0"),
+            ],
+            ..Pubspec::parse(&dir.join("pubspec.yaml").to_string_lossy(), "name: app_foo").unwrap()
+        }];
+        let api = MockPubApi::default()
+            .with_package("oldpad", &["1.0.0"])
+            .with_score("oldpad", 20, Some(5))
+            .with_package("newpad", &["1.0.0"])
+            .with_score("newpad", 30, None);
+        let mut config = Config::default();
+        config.check.quality = QualityGate {
+            min_points: Some(120),
+            level: ValidationLevel::Warning,
+            ..QualityGate::default()
+        };
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+
+        // dependencies already declared at HEAD are not gated
+        let report = check_report(&opts, &config, &pubspecs, &api).await.unwrap();
+        let dependencies = &report.packages[0].dependencies;
+        assert!(dependencies[0].problems.is_empty());
+        assert_eq!(dependencies[1].problems.len(), 1);
+        assert_eq!(dependencies[1].problems[0].rule, CheckRule::Quality);
+        assert_eq!(dependencies[1].problems[0].level, ValidationLevel::Warning);
+        assert_eq!(
+            dependencies[1].problems[0].message,
+            "30 pub points (minimum 120)"
+        );
+
+        let opts = parse_opts(["flcheck", "check", "--since", "no-such-ref"]).unwrap();
+        assert!(matches!(
+            check_report(&opts, &config, &pubspecs, &api).await,
+            Err(FlError::GitError(_))
+        ));
+
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn check_sdk_compatibility() {
+        let pubspecs = vec![Pubspec {
+            dependencies: vec![
+                Pubspec::hosted("http", "^0.13.0"),
+                Pubspec::hosted("intl", "^0.17.0"),
+                Pubspec::hosted("legacy", "1.0.0"),
+                Pubspec::hosted("shiny", "^2.0.0"),
+            ],
+            ..Pubspec::parse("/tmp/app_foo/pubspec.yaml", "name: app_foo").unwrap()
+        }];
+        let api = MockPubApi::default()
+            .with_package("http", &["0.13.5"])
+            .with_sdk("http", "0.13.5", ">=2.12.0 <3.0.0")
+            .with_package("intl", &["0.17.0", "0.17.1"])
+            .with_sdk("intl", "0.17.0", ">=2.7.0 <3.0.0")
+            .with_sdk("intl", "0.17.1", ">=2.12.0 <3.0.0")
+            .with_package("legacy", &["1.0.0", "1.1.0"])
+            .with_sdk("legacy", "1.0.0", ">=2.7.0 <3.0.0")
+            .with_sdk("legacy", "1.1.0", ">=2.12.0 <3.0.0")
+            .with_package("shiny", &["2.1.0"])
+            .with_sdk("shiny", "2.1.0", "^3.4.0");
+        let mut config = Config::default();
+        config.check.min_dart_sdk = parse_version("3.0.0");
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+
+        let report = check_report(&opts, &config, &pubspecs, &api).await.unwrap();
+        let problems = report.packages[0]
+            .dependencies
+            .iter()
+            .flat_map(|dep| dep.problems.iter())
+            .map(|problem| (problem.rule, problem.message.as_str()))
+            .collect::<Vec<_>>();
+
+        // the pinned version is incompatible, as is the one requiring a
+        // newer SDK than the minimum
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    CheckRule::Sdk,
+                    "1.0.0 requires Dart SDK '>=2.7.0 <3.0.0' (minimum 3.0.0)"
+                ),
+                (
+                    CheckRule::Sdk,
+                    "2.1.0 requires Dart SDK '^3.4.0' (minimum 3.0.0)"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn check_publisher_policy() {
+        let pubspecs = vec![Pubspec {
+            dependencies: vec![
+                Pubspec::hosted("http", "^1.0.0"),
+                Pubspec::hosted("riverpod", "^2.0.0"),
+                Pubspec::hosted("leftpad", "^0.1.0"),
+                Pubspec::hosted("legacy", "^2.0.0"),
+            ],
+            ..Pubspec::parse("/tmp/app_foo/pubspec.yaml", "name: app_foo").unwrap()
+        }];
+        let api = MockPubApi::default()
+            .with_package("http", &["1.2.0"])
+            .with_publisher("http", "dart.dev")
+            .with_package("riverpod", &["2.5.0"])
+            .with_publisher("riverpod", "riverpod.dev")
+            .with_package("leftpad", &["0.1.0"])
+            .with_package("legacy", &["2.0.0"]);
+        let mut config = Config::default();
+        config.check.publishers = PublisherPolicy {
+            verified: true,
+            allowed: vec!["dart.dev".to_owned()],
+            exempt: vec!["legacy".to_owned()],
+            ..PublisherPolicy::default()
+        };
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+
+        let report = check_report(&opts, &config, &pubspecs, &api).await.unwrap();
+        let problems = report.packages[0]
+            .dependencies
+            .iter()
+            .flat_map(|dep| dep.problems.iter())
+            .map(|problem| (&problem.level, problem.message.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    &ValidationLevel::Warning,
+                    "published by 'riverpod.dev' (not an allowed publisher)"
+                ),
+                (
+                    &ValidationLevel::Warning,
+                    "not published by a verified publisher"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn check_by_package_rejected() {
+        let pubspecs = vec![Pubspec::test("app_foo").with_deps(vec![
+            Pubspec::hosted("http", "^1.0.0"),
+            Pubspec::hosted("leftpad", "^0.1.0"),
+        ])];
+        let api = MockPubApi::default()
+            .with_package("http", &["1.2.0"])
+            .with_publisher("http", "dart.dev")
+            .with_package("leftpad", &["0.1.0"]);
+        let mut config = Config::default();
+        config.check.publishers = PublisherPolicy {
+            verified: true,
+            level: ValidationLevel::Error,
+            ..PublisherPolicy::default()
+        };
+
+        // rejected dependencies fail no matter how the report is rendered
+        for args in [
+            &["flcheck", "check"][..],
+            &["flcheck", "check", "--by-package"],
+        ] {
+            let opts = parse_opts(args).unwrap();
+            let mut renderer = PlainRenderer::new(Vec::new());
+            let result = check_with(&opts, &config, &pubspecs, &api, &mut renderer).await;
+            assert!(matches!(result, Err(FlError::RejectedDependencies(1))));
+        }
+    }
+
+    #[test]
+    fn publisher_policies() {
+        let published = |publisher_id: Option<&str>| PubPublisher {
+            name: "http".to_owned(),
+            publisher_id: publisher_id.map(|id| id.to_owned()),
+        };
+        let message = |publisher: PubPublisher, policy: &PublisherPolicy| {
+            publisher_problem(&publisher, policy).map(|problem| problem.message)
+        };
+
+        // any verified publisher is accepted
+        let verified = PublisherPolicy {
+            verified: true,
+            ..PublisherPolicy::default()
+        };
+        assert_eq!(message(published(Some("dart.dev")), &verified), None);
+        assert_eq!(
+            message(published(None), &verified).as_deref(),
+            Some("not published by a verified publisher")
+        );
+
+        // allowed publishers imply a verified one
+        let allowed = PublisherPolicy {
+            allowed: vec!["dart.dev".to_owned(), "google.dev".to_owned()],
+            level: ValidationLevel::Error,
+            ..PublisherPolicy::default()
+        };
+        assert_eq!(message(published(Some("google.dev")), &allowed), None);
+        assert_eq!(
+            message(published(Some("example.com")), &allowed).as_deref(),
+            Some("published by 'example.com' (not an allowed publisher)")
+        );
+        let problem = publisher_problem(&published(None), &allowed).unwrap();
+        assert_eq!(problem.rule, CheckRule::Publisher);
+        assert_eq!(problem.level, ValidationLevel::Error);
+    }
+
+    #[test]
+    fn quiet_progress() {
+        let opts = parse_opts(["flcheck", "check"]).unwrap();
+        assert_eq!(fetch_progress(&opts, 3).length(), Some(3));
+
+        // nothing is drawn with `--quiet`
+        let opts = parse_opts(["flcheck", "-q", "check"]).unwrap();
+        let progress = fetch_progress(&opts, 3);
+        assert!(progress.is_hidden());
+        assert_eq!(progress.length(), None);
+
+        assert!(parse_opts(["flcheck", "check", "--quiet", "--verbose"]).is_err());
+    }
+
+    #[test]
+    fn fail_level() {
+        let finding = |level| PackageValidation {
+            package_name: "app_foo".to_owned(),
+            error: "some error".to_owned(),
+            description: None,
+            code: ValidationType::UnknownDependency,
+            level,
+            dependency: None,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        };
+        let findings = vec![
+            finding(ValidationLevel::Warning),
+            finding(ValidationLevel::Warning),
+        ];
+        let check = |args: &[&str], findings: &[PackageValidation]| {
+            let opts = parse_opts(["flcheck", "validate"].iter().chain(args)).unwrap();
+            check_fail_level(&opts, findings)
+        };
+
+        // warnings fail with `--fail-level warn` or above `--max-warnings` only
+        assert!(check(&[], &findings).is_ok());
+        assert!(matches!(
+            check(&["--fail-level", "warn"], &findings),
+            Err(FlError::ValidationWarnings(2))
+        ));
+        assert!(check(&["--max-warnings", "2"], &findings).is_ok());
+        assert!(matches!(
+            check(&["--max-warnings", "1"], &findings),
+            Err(FlError::TooManyWarnings(2, 1))
+        ));
+
+        // errors fail unless `--fail-level none`
+        let findings = vec![finding(ValidationLevel::Error)];
+        assert!(matches!(
+            check(&[], &findings),
+            Err(FlError::ValidationError(1))
+        ));
+        assert!(check(&["--fail-level", "none"], &findings).is_ok());
+    }
+
+    #[test]
+    fn cycles_reported_once() {
+        let pkg = |name: &str, dep: &str| Pubspec {
+            dependencies: vec![Dependency::Local {
+                name: dep.to_owned(),
+                path: format!("../{}", dep),
+                overridden: Box::new(None),
+            }],
+            ..Pubspec::parse(
+                &format!("/ws/{}/pubspec.yaml", name),
+                &format!("name: {}", name),
+            )
+            .unwrap()
+        };
+        let pubspecs = vec![pkg("pkg_foo", "pkg_bar"), pkg("pkg_bar", "pkg_foo")];
+        let selected = pubspecs.iter().collect::<Vec<_>>();
+
+        let cycles = validation_findings(&Config::default(), &selected, &Workspace::new(&pubspecs))
+            .unwrap()
+            .into_iter()
+            .filter(|validation| validation.code == ValidationType::CyclicDependency)
+            .collect::<Vec<_>>();
+
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].package_name, "pkg_bar");
+        assert_eq!(
+            cycles[0].error,
+            "cyclic dependency pkg_bar -> pkg_foo -> 'pkg_bar'"
+        );
+    }
+
+    #[test]
+    fn ignored_findings() {
+        let content = "
+name: pkg_foo
+dependencies:
+  mockito: ^5.0.0
+  build_runner:
+    version: ^2.0.0
+    flcheck:
+      reason: generates code at runtime
+      ignore: [validation:dependency:dev-only]
+";
+        let pubspecs = vec![Pubspec::parse("/ws/pkg_foo/pubspec.yaml", content).unwrap()];
+        let selected = pubspecs.iter().collect::<Vec<_>>();
+
+        let findings =
+            validation_findings(&Config::default(), &selected, &Workspace::new(&pubspecs)).unwrap();
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].dependency.as_deref(), Some("mockito"));
+    }
+
+    #[test]
+    fn findings_ordered_by_package() {
+        let pubspecs = (0..200)
+            .map(|idx| {
+                let content = format!("name: pkg_{}\ndependencies:\n  mockito: ^5.0.0", idx);
+                Pubspec::parse(&format!("/ws/pkg_{}/pubspec.yaml", idx), &content).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let selected = pubspecs.iter().rev().collect::<Vec<_>>();
+
+        let findings =
+            validation_findings(&Config::default(), &selected, &Workspace::new(&pubspecs)).unwrap();
+        let packages = findings
+            .iter()
+            .map(|validation| validation.package_name.as_str())
+            .collect::<Vec<_>>();
+
+        let expected = pubspecs
+            .iter()
+            .map(|pubspec| pubspec.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(packages, expected);
+    }
+
+    #[test]
+    fn strict_validation() {
+        let discovery = Discovery {
+            pubspecs: vec![
+                Pubspec::test("app_foo"),
+                Pubspec::test(""),
+                Pubspec::parse(
+                    "/tmp/pkg_multi/pubspec.yaml",
+                    "name: pkg_multi\n---\nname: pkg_other\n",
+                )
+                .unwrap(),
+            ],
+            skipped: vec![SkippedPath {
+                path: "/tmp/pkg_broken/pubspec.yaml".to_owned(),
+                reason: "invalid YAML".to_owned(),
+            }],
+            cache: None,
+        };
+        let run = |args: &[&str]| {
+            let opts = parse_opts(["flcheck", "validate"].iter().chain(args)).unwrap();
+            validate(
+                &opts,
+                &Config::default(),
+                &discovery,
+                &mut PlainRenderer::new(Vec::new()),
+            )
+        };
+
+        assert!(run(&[]).is_ok());
+        // the skipped, the unnamed and the multi-document package fail the
+        // validation
+        assert!(matches!(
+            run(&["--strict"]),
+            Err(FlError::SkippedPackages(3))
+        ));
+    }
+
+    #[test]
+    fn select_packages_by_name() {
+        let pubspecs = vec![
+            Pubspec::test("app_foo"),
+            Pubspec::test("app_bar"),
+            Pubspec::test("pkg_core"),
+        ];
+        let select = |args: &[&str]| {
+            let opts = parse_opts(["flcheck", "validate"].iter().chain(args)).unwrap();
+            selected_packages(&opts, &pubspecs).map(|selected| {
+                selected
+                    .iter()
+                    .map(|pubspec| pubspec.name.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            select(&["--package", "pkg_core", "--package", "app_foo"]).unwrap(),
+            vec!["app_foo", "pkg_core"]
+        );
+        assert_eq!(
+            select(&["--exclude", "app_bar"]).unwrap(),
+            vec!["app_foo", "pkg_core"]
+        );
+        assert_eq!(
+            select(&["--package", "app_foo", "--exclude", "app_foo"]).unwrap(),
+            Vec::<String>::new()
+        );
+        assert!(matches!(
+            select(&["--exclude", "app_gone"]),
+            Err(FlError::UnknownPackage(name)) if name == "app_gone"
+        ));
+    }
+
+    #[test]
+    fn select_package_directory() {
+        let root = std::env::temp_dir().join(format!("flcheck-select-{}", std::process::id()));
+        let pubspecs = ["app_foo", "pkg_bar"]
+            .iter()
+            .map(|name| {
+                let dir = root.join(name);
+                let content = format!("name: {}", name);
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(dir.join("pubspec.yaml"), &content).unwrap();
+                Pubspec::parse(&dir.join("pubspec.yaml").to_string_lossy(), &content).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let select = |path: &Path| {
+            let opts = parse_opts(["flcheck", "validate", path.to_str().unwrap()]).unwrap();
+            selected_packages(&opts, &pubspecs).map(|selected| {
+                selected
+                    .iter()
+                    .map(|pubspec| pubspec.name.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(select(&root.join("pkg_bar")).unwrap(), vec!["pkg_bar"]);
+        assert_eq!(
+            select(&root.join("app_foo/pubspec.yaml")).unwrap(),
+            vec!["app_foo"]
+        );
+        assert!(matches!(select(&root), Err(FlError::UnknownPackage(_))));
+
+        let input = format!(
+            "{}\n\n{}\n",
+            root.join("pkg_bar/pubspec.yaml").display(),
+            root.join("pkg_gone/pubspec.yaml").display()
+        );
+        let listed = listed_packages(input.as_bytes(), &pubspecs).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "pkg_bar");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn stdin_listed_packages() {
+        let root = std::env::temp_dir().join(format!("flcheck-stdin-{}", std::process::id()));
+        let pubspecs = ["app_foo", "pkg_bar", "pkg_baz"]
+            .iter()
+            .map(|name| {
+                let dir = root.join(name);
+                let content = format!("name: {}", name);
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(dir.join("pubspec.yaml"), &content).unwrap();
+                Pubspec::parse(&dir.join("pubspec.yaml").to_string_lossy(), &content).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // directories and pubspec files in the given order, padded and
+        // blank lines are ignored as well as paths outside the workspace
+        let input = format!(
+            "  {}  \n\n   \n{}\n{}\n{}\n",
+            root.join("pkg_baz").display(),
+            root.join("unknown").display(),
+            root.join("app_foo/pubspec.yaml").display(),
+            root.join("pkg_baz/../pkg_bar").display(),
+        );
+        let listed = listed_packages(input.as_bytes(), &pubspecs)
+            .unwrap()
+            .iter()
+            .map(|pubspec| pubspec.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(listed, vec!["pkg_baz", "app_foo", "pkg_bar"]);
+
+        assert!(listed_packages("\n".as_bytes(), &pubspecs)
+            .unwrap()
+            .is_empty());
+
+        let opts = parse_opts(["flcheck", "validate", "--stdin"]).unwrap();
+        assert!(opts.stdin);
+        assert!(parse_opts(["flcheck", "validate", "--stdin", "--changed-only"]).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn incremental_file_findings() {
+        let root = std::env::temp_dir().join(format!("flcheck-incremental-{}", std::process::id()));
+        let dir = root.join("pkg_foo");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("pubspec.yaml"),
+            "name: pkg_foo\nflutter:\n  assets:\n    - logo.png",
+        )
+        .unwrap();
+
+        let config = Config::default();
+        let roots = vec![root.to_string_lossy().to_string()];
+        let mut cache = PubspecCache::empty(&root.join(".cache"));
+        let discovery = discover_pubspecs(&roots, &config, false, Some(&mut cache)).unwrap();
+        let state = discovery.cache.unwrap();
+        let workspace = Workspace::new(&discovery.pubspecs);
+        let codes = |findings: Vec<PackageValidation>| {
+            findings
+                .into_iter()
+                .map(|finding| finding.code)
+                .collect::<Vec<_>>()
+        };
+
+        // nothing cached yet, all packages are validated
+        let findings = incremental_findings(&config, &workspace, &state).unwrap();
+        write_findings(&state, config.fingerprint(), &discovery.pubspecs, &findings).unwrap();
+        assert_eq!(codes(findings), vec![ValidationType::MissingAsset]);
+
+        // the unchanged pubspec does not keep the finding of the added asset
+        std::fs::write(dir.join("logo.png"), "").unwrap();
+        let findings = incremental_findings(&config, &workspace, &state).unwrap();
+        assert_eq!(codes(findings), vec![]);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
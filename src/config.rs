@@ -1,20 +1,63 @@
+use crate::codeowners::find_codeowners;
 use crate::error::FlError;
 use crate::error::FlError::ConfigValidation;
 use crate::error::ValidationLevel;
 use crate::error::ValidationType;
+use crate::pubdev::http_client;
+use crate::util::canonicalize;
+use crate::util::content_hash;
+use crate::util::glob_to_regex;
 use crate::util::load_yaml;
+use crate::util::normalize_path;
+use crate::util::parse_yaml;
 use crate::util::yaml_str_list;
+use crate::version::parse_version;
+use crate::version::ConstraintStyle;
 use regex::Regex;
+use reqwest::Url;
+use semver::Version;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
 use yaml_rust::Yaml;
+use yaml_rust::YamlLoader;
 
 #[derive(Debug, PartialEq)]
 pub struct PackageType {
     pub name: String,
     pub prefixes: Vec<String>,
     pub includes: Vec<String>,
+    /// Maximum length of the chain of local dependencies rooted at
+    /// packages of this type (if limited)
+    pub max_depth: Option<usize>,
+    /// Names of the packages every package of this type has to depend on
+    pub requires: Vec<String>,
+    /// Whether packages of this type must not depend on plugins with
+    /// native platform code
+    pub pure_dart: bool,
+    /// Whether packages of this type must contain tests (a `_test.dart`
+    /// file in their `test` directory)
+    pub require_tests: bool,
 }
 
 impl PackageType {
+    /// Package type of packages in directories starting with any of the
+    /// `prefixes` that may depend on packages of the types `includes`.
+    pub fn new(name: &str, prefixes: &[&str], includes: &[&str]) -> PackageType {
+        PackageType {
+            name: name.to_owned(),
+            prefixes: prefixes.iter().map(|prefix| prefix.to_string()).collect(),
+            includes: includes.iter().map(|include| include.to_string()).collect(),
+            max_depth: None,
+            requires: Vec::new(),
+            pure_dart: false,
+            require_tests: false,
+        }
+    }
+
     pub fn matches_prefix(&self, dir_name: &str) -> bool {
         self.prefixes
             .iter()
@@ -22,12 +65,419 @@ impl PackageType {
     }
 }
 
+/// Local dependencies a public package of some package type may have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PublicPackagePolicy {
+    /// No local dependencies at all (default)
+    GitOnly,
+    /// Local dependencies on other public packages
+    Public,
+    /// Local dependencies on other public packages that are published from
+    /// the same `repository`
+    SameRepository,
+}
+
+impl PublicPackagePolicy {
+    pub fn parse(value: &str) -> Option<PublicPackagePolicy> {
+        match value {
+            "git_only" => Some(PublicPackagePolicy::GitOnly),
+            "public" => Some(PublicPackagePolicy::Public),
+            "same_repository" => Some(PublicPackagePolicy::SameRepository),
+            _ => None,
+        }
+    }
+}
+
+/// Configured `ValidationLevel` of a `ValidationType` that is optionally
+/// scoped to the packages whose directory matches any of `paths`.
+#[derive(Debug)]
+pub struct ValidationSetting {
+    pub validation_type: ValidationType,
+    pub level: ValidationLevel,
+    pub paths: Vec<Regex>,
+}
+
+impl ValidationSetting {
+    fn applies_to(&self, validation_type: &ValidationType, dir_path: &str) -> bool {
+        self.validation_type == *validation_type
+            && self.paths.iter().any(|regex| regex.is_match(dir_path))
+    }
+}
+
+impl PartialEq for ValidationSetting {
+    fn eq(&self, other: &Self) -> bool {
+        self.validation_type == other.validation_type
+            && self.level == other.level
+            && regex_str_list(&self.paths) == regex_str_list(&other.paths)
+    }
+}
+
+/// Attribute of a package a `PackagePattern` is matched against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackageAttribute {
+    Name,
+    /// Package type (declared or based on the directory prefix)
+    Type,
+    /// Owner as declared via `flcheck: owner`
+    Owner,
+    /// Any of the tags declared via `flcheck: tags`
+    Tag,
+}
+
+/// Glob pattern matching package names (e.g. `app_*`) or, if prefixed
+/// with `type:`, `owner:` or `tag:`, the respective package attribute
+/// (e.g. `tag:legacy`).
+#[derive(Debug)]
+pub struct PackagePattern {
+    pub pattern: String,
+    pub attribute: PackageAttribute,
+    regex: Regex,
+}
+
+impl PackagePattern {
+    pub fn new(pattern: &str) -> Result<PackagePattern, regex::Error> {
+        let (attribute, glob) = match pattern.split_once(':') {
+            Some(("type", glob)) => (PackageAttribute::Type, glob),
+            Some(("owner", glob)) => (PackageAttribute::Owner, glob),
+            Some(("tag", glob)) => (PackageAttribute::Tag, glob),
+            _ => (PackageAttribute::Name, pattern),
+        };
+        let regex = Regex::new(&glob_to_regex(glob))?;
+
+        Ok(PackagePattern {
+            pattern: pattern.to_owned(),
+            attribute,
+            regex,
+        })
+    }
+
+    /// Whether the value of the pattern's attribute matches.
+    pub fn matches(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+}
+
+impl PartialEq for PackagePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Display for PackagePattern {
+    fn fmt(
+        &self,
+        formatter: &mut std::fmt::Formatter<'_>,
+    ) -> std::result::Result<(), std::fmt::Error> {
+        formatter.write_str(&self.pattern)
+    }
+}
+
+/// Declarative constraint on the (non-dev) dependencies of packages
+/// (see `rules`).
+#[derive(Debug, PartialEq)]
+pub enum DependencyRule {
+    /// Packages matching `from` must not depend on packages matching `to`
+    Deny {
+        from: PackagePattern,
+        to: PackagePattern,
+        description: Option<String>,
+    },
+    /// Packages matching `package` must depend on a package matching
+    /// `depends_on`
+    Require {
+        package: PackagePattern,
+        depends_on: PackagePattern,
+        description: Option<String>,
+    },
+}
+
+/// Constraint styles required or forbidden for the external (pub.dev)
+/// dependencies whose name matches the glob pattern `packages` (see
+/// `constraint_styles`).
+#[derive(Debug)]
+pub struct ConstraintPolicy {
+    pub packages: String,
+    /// Style all matching constraints have to be written in (if any)
+    pub require: Option<ConstraintStyle>,
+    pub forbid: Vec<ConstraintStyle>,
+    regex: Regex,
+}
+
+impl ConstraintPolicy {
+    pub fn matches(&self, package_name: &str) -> bool {
+        self.regex.is_match(package_name)
+    }
+}
+
+/// Group of external packages serving the same purpose (e.g. HTTP clients),
+/// only one of which should be used across the workspace (see
+/// `alternatives`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlternativeGroup {
+    pub name: String,
+    pub packages: Vec<String>,
+}
+
+impl PartialEq for ConstraintPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        self.packages == other.packages
+            && self.require == other.require
+            && self.forbid == other.forbid
+    }
+}
+
+/// Validation performed by an external command (see `custom_validations`).
+///
+/// The command receives the workspace dump (as printed by `dump -o json`)
+/// on stdin and prints its findings as a JSON list on stdout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomValidation {
+    /// User-defined code of all findings of the command
+    pub code: String,
+    /// Shell command to execute
+    pub command: String,
+    pub level: ValidationLevel,
+    pub description: Option<String>,
+    /// Directory the command is executed in (the configuration's directory)
+    pub working_dir: String,
+}
+
+/// Settings of the HTTP client querying pub.dev (e.g. by `check`) and
+/// fetching configurations extended via URL.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpSettings {
+    /// Proxy all requests are sent through (instead of `HTTPS_PROXY`)
+    pub proxy: Option<String>,
+    /// PEM file of additional root certificates (e.g. a corporate CA)
+    pub ca_bundle: Option<String>,
+    /// Timeout of every request
+    pub timeout: Option<Duration>,
+    pub user_agent: Option<String>,
+    /// Skip the verification of TLS certificates (via `--insecure` only)
+    pub insecure: bool,
+}
+
+impl HttpSettings {
+    /// Complete these settings by the ones of `fallback`, so that these
+    /// take precedence (e.g. command line options over the configuration).
+    pub fn or(self, fallback: &HttpSettings) -> HttpSettings {
+        HttpSettings {
+            proxy: self.proxy.or_else(|| fallback.proxy.clone()),
+            ca_bundle: self.ca_bundle.or_else(|| fallback.ca_bundle.clone()),
+            timeout: self.timeout.or(fallback.timeout),
+            user_agent: self.user_agent.or_else(|| fallback.user_agent.clone()),
+            insecure: self.insecure || fallback.insecure,
+        }
+    }
+}
+
+/// Settings of the `check` command evaluating the external dependencies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckSettings {
+    /// Maximum age (in days since it was published) of the version a
+    /// dependency resolves to before it is reported as stale
+    pub max_age_days: Option<u64>,
+    pub quality: QualityGate,
+    /// Dart SDK version the versions dependencies resolve to have to
+    /// support (e.g. `3.0.0` when migrating to Dart 3)
+    pub min_dart_sdk: Option<Version>,
+    pub publishers: PublisherPolicy,
+}
+
+/// Minimum pub.dev score external dependencies have to reach when they
+/// are added to the workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityGate {
+    pub min_points: Option<u32>,
+    /// Minimum popularity in percent
+    pub min_popularity: Option<u32>,
+    /// Packages that are accepted regardless of their score
+    pub exempt: Vec<String>,
+    pub level: ValidationLevel,
+}
+
+impl Default for QualityGate {
+    fn default() -> Self {
+        QualityGate {
+            min_points: None,
+            min_popularity: None,
+            exempt: Vec::new(),
+            level: ValidationLevel::Error,
+        }
+    }
+}
+
+/// Publishers external dependencies have to be published by on pub.dev.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublisherPolicy {
+    /// Require a verified publisher (instead of an individual)
+    pub verified: bool,
+    /// Verified publishers that are allowed (all if empty)
+    pub allowed: Vec<String>,
+    /// Packages that are accepted regardless of their publisher
+    pub exempt: Vec<String>,
+    pub level: ValidationLevel,
+}
+
+impl Default for PublisherPolicy {
+    fn default() -> Self {
+        PublisherPolicy {
+            verified: false,
+            allowed: Vec::new(),
+            exempt: Vec::new(),
+            level: ValidationLevel::Warning,
+        }
+    }
+}
+
+impl PublisherPolicy {
+    pub fn is_enabled(&self) -> bool {
+        (self.verified || !self.allowed.is_empty()) && self.level != ValidationLevel::None
+    }
+
+    /// Whether the external package `package_name` is subject to the policy.
+    pub fn applies_to(&self, package_name: &str) -> bool {
+        self.is_enabled() && !self.exempt.iter().any(|name| name == package_name)
+    }
+}
+
+impl QualityGate {
+    pub fn is_enabled(&self) -> bool {
+        (self.min_points.is_some() || self.min_popularity.is_some())
+            && self.level != ValidationLevel::None
+    }
+
+    /// Whether the external package `package_name` is subject to the gate.
+    pub fn applies_to(&self, package_name: &str) -> bool {
+        self.is_enabled() && !self.exempt.iter().any(|name| name == package_name)
+    }
+}
+
+/// Default name of the configuration file.
+pub const CONFIG_FILE: &str = "flcheck.yaml";
+
+/// All keys that are known in the configuration file.
+pub const CONFIG_KEYS: [&str; 22] = [
+    "min_version",
+    "extends",
+    "package_types",
+    "blacklist",
+    "validations",
+    "public_repositories",
+    "public_package_policy",
+    "internal_repositories",
+    "private_packages",
+    "dev_only_packages",
+    "structure",
+    "analysis_options",
+    "constraint_styles",
+    "include",
+    "exclude",
+    "roots",
+    "custom_validations",
+    "rules",
+    "codeowners",
+    "http",
+    "check",
+    "alternatives",
+];
+
+/// Directories that are not descended into during pubspec discovery
+/// unless `exclude` is configured explicitly.
+const DEFAULT_EXCLUDES: [&str; 4] = ["build", ".dart_tool", "ios", "android"];
+
+/// Timeout of fetching configurations extended via URL, unless a timeout
+/// is configured explicitly.
+const EXTENDS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Well-known packages that are needed during development only (code
+/// generators, test and lint packages), extended by `dev_only_packages`.
+const DEV_ONLY_PACKAGES: [&str; 17] = [
+    "build_runner",
+    "build_test",
+    "mockito",
+    "mocktail",
+    "bloc_test",
+    "test",
+    "flutter_lints",
+    "lints",
+    "very_good_analysis",
+    "pedantic",
+    "json_serializable",
+    "freezed",
+    "built_value_generator",
+    "retrofit_generator",
+    "injectable_generator",
+    "flutter_launcher_icons",
+    "flutter_native_splash",
+];
+
 #[derive(Debug)]
 pub struct Config {
     pub package_types: Vec<PackageType>,
     pub blacklist: Vec<Regex>,
-    pub validations: Vec<(ValidationType, ValidationLevel)>,
+    pub validations: Vec<ValidationSetting>,
     pub public_repositories: Vec<Regex>,
+    /// `PublicPackagePolicy` by package type name
+    pub public_package_policy: HashMap<String, PublicPackagePolicy>,
+    pub internal_repositories: Vec<Regex>,
+    pub private_packages: Vec<Regex>,
+    /// Patterns of packages that are needed during development only (in
+    /// addition to the well-known ones)
+    pub dev_only_packages: Vec<Regex>,
+    /// Files and directories (with a trailing `/`) packages have to
+    /// contain by package type name
+    pub structure: HashMap<String, Vec<String>>,
+    /// Shared lint configuration every package's `analysis_options.yaml`
+    /// has to `include` (e.g. `package:company_lints/analysis_options.yaml`)
+    pub analysis_options: Option<String>,
+    pub constraint_styles: Vec<ConstraintPolicy>,
+    pub alternatives: Vec<AlternativeGroup>,
+    pub include: Vec<Regex>,
+    pub exclude: Vec<Regex>,
+    pub roots: Vec<String>,
+    pub custom_validations: Vec<CustomValidation>,
+    pub rules: Vec<DependencyRule>,
+    /// `CODEOWNERS` file determining the owners of packages
+    pub codeowners: Option<String>,
+    pub http: HttpSettings,
+    pub check: CheckSettings,
+}
+
+impl Default for Config {
+    /// Configuration without any package types that uses the
+    /// default excludes (e.g. to discover packages only).
+    fn default() -> Self {
+        Config {
+            package_types: Vec::new(),
+            blacklist: Vec::new(),
+            validations: Vec::new(),
+            public_repositories: Vec::new(),
+            public_package_policy: HashMap::new(),
+            internal_repositories: Vec::new(),
+            private_packages: Vec::new(),
+            dev_only_packages: Vec::new(),
+            structure: HashMap::new(),
+            analysis_options: None,
+            constraint_styles: Vec::new(),
+            alternatives: Vec::new(),
+            include: Vec::new(),
+            exclude: glob_list(
+                DEFAULT_EXCLUDES
+                    .iter()
+                    .map(|glob| glob.to_string())
+                    .collect(),
+                "exclude",
+            )
+            .unwrap_or_default(),
+            roots: Vec::new(),
+            custom_validations: Vec::new(),
+            rules: Vec::new(),
+            codeowners: None,
+            http: HttpSettings::default(),
+            check: CheckSettings::default(),
+        }
+    }
 }
 
 impl PartialEq for Config {
@@ -37,6 +487,98 @@ impl PartialEq for Config {
             && regex_str_list(&self.blacklist) == regex_str_list(&other.blacklist)
             && regex_str_list(&self.public_repositories)
                 == regex_str_list(&other.public_repositories)
+            && self.public_package_policy == other.public_package_policy
+            && regex_str_list(&self.internal_repositories)
+                == regex_str_list(&other.internal_repositories)
+            && regex_str_list(&self.private_packages) == regex_str_list(&other.private_packages)
+            && regex_str_list(&self.dev_only_packages) == regex_str_list(&other.dev_only_packages)
+            && self.structure == other.structure
+            && self.analysis_options == other.analysis_options
+            && self.constraint_styles == other.constraint_styles
+            && self.alternatives == other.alternatives
+            && regex_str_list(&self.include) == regex_str_list(&other.include)
+            && regex_str_list(&self.exclude) == regex_str_list(&other.exclude)
+            && self.roots == other.roots
+            && self.custom_validations == other.custom_validations
+            && self.rules == other.rules
+            && self.codeowners == other.codeowners
+            && self.http == other.http
+            && self.check == other.check
+    }
+}
+
+/// Builder of a `Config` in code (instead of loading a configuration
+/// file), e.g. for library consumers and tests. Patterns are regular
+/// expressions as in the configuration file, all other settings are the
+/// defaults.
+#[derive(Default)]
+pub struct ConfigBuilder {
+    package_types: Vec<PackageType>,
+    blacklist: Vec<String>,
+    validations: Vec<(ValidationType, ValidationLevel)>,
+    public_repositories: Vec<String>,
+    internal_repositories: Vec<String>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    pub fn package_type(mut self, package_type: PackageType) -> ConfigBuilder {
+        self.package_types.push(package_type);
+        self
+    }
+
+    /// Skip validation of packages whose path matches `pattern`.
+    pub fn blacklist(mut self, pattern: &str) -> ConfigBuilder {
+        self.blacklist.push(pattern.to_owned());
+        self
+    }
+
+    /// Report findings of `validation_type` with the given `level`.
+    pub fn validation(
+        mut self,
+        validation_type: ValidationType,
+        level: ValidationLevel,
+    ) -> ConfigBuilder {
+        self.validations.push((validation_type, level));
+        self
+    }
+
+    /// Treat git repositories matching `pattern` as public.
+    pub fn public_repository(mut self, pattern: &str) -> ConfigBuilder {
+        self.public_repositories.push(pattern.to_owned());
+        self
+    }
+
+    /// Resolve git dependencies on repositories matching `pattern` to the
+    /// workspace packages (see `internal_repositories`).
+    pub fn internal_repository(mut self, pattern: &str) -> ConfigBuilder {
+        self.internal_repositories.push(pattern.to_owned());
+        self
+    }
+
+    /// Build the `Config`, which is validated like a loaded configuration
+    /// (e.g. at least one package type, includes of known package types).
+    pub fn build(self) -> Result<Config, FlError> {
+        Config {
+            package_types: self.package_types,
+            blacklist: regex_list(self.blacklist, "blacklist")?,
+            validations: self
+                .validations
+                .into_iter()
+                .map(|(validation_type, level)| ValidationSetting {
+                    validation_type,
+                    level,
+                    paths: Vec::new(),
+                })
+                .collect(),
+            public_repositories: regex_list(self.public_repositories, "public_repositories")?,
+            internal_repositories: regex_list(self.internal_repositories, "internal_repositories")?,
+            ..Config::default()
+        }
+        .validate()
     }
 }
 
@@ -45,6 +587,71 @@ impl Config {
         !self.package_types.is_empty()
     }
 
+    /// Fingerprint of all settings affecting validation, e.g. to discard
+    /// cached findings of another configuration.
+    pub fn fingerprint(&self) -> u64 {
+        // listing all fields ensures new settings are not missed
+        let Config {
+            package_types,
+            blacklist,
+            validations,
+            public_repositories,
+            public_package_policy,
+            internal_repositories,
+            private_packages,
+            dev_only_packages,
+            structure,
+            analysis_options,
+            constraint_styles,
+            alternatives,
+            include,
+            exclude,
+            roots,
+            custom_validations,
+            rules,
+            codeowners,
+            http: _,
+            check: _,
+        } = self;
+        // order of the maps is random
+        let policies = public_package_policy.iter().collect::<BTreeMap<_, _>>();
+        let structure = structure.iter().collect::<BTreeMap<_, _>>();
+
+        content_hash(&format!(
+            "{:?} {:?}",
+            (
+                package_types,
+                regex_str_list(blacklist),
+                validations,
+                regex_str_list(public_repositories),
+                policies,
+                regex_str_list(internal_repositories),
+            ),
+            (
+                regex_str_list(private_packages),
+                regex_str_list(dev_only_packages),
+                structure,
+                analysis_options,
+                constraint_styles,
+                alternatives,
+                regex_str_list(include),
+                regex_str_list(exclude),
+                roots,
+                custom_validations,
+                rules,
+                codeowners,
+            )
+        ))
+    }
+
+    /// Determine the (first) `PackageType` matching the package
+    /// in the directory `dir_name`.
+    pub fn package_type(&self, dir_name: &str) -> Option<&PackageType> {
+        self.package_types
+            .iter()
+            .find(|pkg_type| pkg_type.matches_prefix(dir_name))
+    }
+
     pub fn is_blacklisted(&self, full_path: &str) -> bool {
         self.blacklist.iter().any(|regex| regex.is_match(full_path))
     }
@@ -55,85 +662,299 @@ impl Config {
             .any(|regex| regex.is_match(git_repo))
     }
 
+    /// Whether the git repository `git_repo` is part of the workspace,
+    /// meaning git dependencies on it refer to workspace packages.
+    pub fn is_internal_repo(&self, git_repo: &str) -> bool {
+        self.internal_repositories
+            .iter()
+            .any(|regex| regex.is_match(git_repo))
+    }
+
+    /// Determine the `PublicPackagePolicy` of packages of the given
+    /// package type.
+    pub fn public_package_policy(&self, pkg_type: Option<&PackageType>) -> PublicPackagePolicy {
+        pkg_type
+            .and_then(|pkg_type| self.public_package_policy.get(&pkg_type.name))
+            .copied()
+            .unwrap_or(PublicPackagePolicy::GitOnly)
+    }
+
+    /// Whether the external package `package_name` is known to be private,
+    /// meaning it is not looked up on pub.dev.
+    pub fn is_private_package(&self, package_name: &str) -> bool {
+        self.private_packages
+            .iter()
+            .any(|regex| regex.is_match(package_name))
+    }
+
+    /// Whether the external package `package_name` is needed during
+    /// development only, meaning it belongs into `dev_dependencies`.
+    pub fn is_dev_only_package(&self, package_name: &str) -> bool {
+        DEV_ONLY_PACKAGES.contains(&package_name)
+            || self
+                .dev_only_packages
+                .iter()
+                .any(|regex| regex.is_match(package_name))
+    }
+
+    /// Whether the directory at `rel_path` (relative to the root directory)
+    /// must not be descended into during pubspec discovery.
+    pub fn is_excluded_dir(&self, rel_path: &str) -> bool {
+        self.exclude.iter().any(|regex| regex.is_match(rel_path))
+    }
+
+    /// Whether a package located in the directory `rel_path` (relative to
+    /// the root directory) is part of the discovered packages.
+    ///
+    /// Without any `include` patterns configured every package is included,
+    /// otherwise the directory itself or one of its parents has to match.
+    pub fn is_included_dir(&self, rel_path: &str) -> bool {
+        if self.include.is_empty() {
+            return true;
+        }
+
+        Path::new(rel_path)
+            .ancestors()
+            .flat_map(|dir| dir.to_str())
+            .filter(|dir| !dir.is_empty())
+            .any(|dir| self.include.iter().any(|regex| regex.is_match(dir)))
+    }
+
     /// Determine the configured `ValidationLevel` for the given
-    /// `ValidationType`.
+    /// `ValidationType` (ignoring any path scoped levels).
     ///
     /// Defaults to `ValidationLevel::Error` if not configured.
     pub fn validation_level(&self, validation_type: &ValidationType) -> ValidationLevel {
         self.validations
             .iter()
-            .find(|(typ, _)| typ == validation_type)
-            .map(|(_, level)| level.clone())
-            .unwrap_or(ValidationLevel::Error)
+            .find(|setting| setting.validation_type == *validation_type && setting.paths.is_empty())
+            .map(|setting| setting.level.clone())
+            .unwrap_or_else(|| validation_type.default_level())
+    }
+
+    /// Determine the `ValidationLevel` for the given `ValidationType` of the
+    /// package located in `dir_path`: the first level scoped to a matching
+    /// path takes precedence over the (unscoped) configured level.
+    pub fn validation_level_at(
+        &self,
+        validation_type: &ValidationType,
+        dir_path: &str,
+    ) -> ValidationLevel {
+        self.validations
+            .iter()
+            .find(|setting| setting.applies_to(validation_type, dir_path))
+            .map(|setting| setting.level.clone())
+            .unwrap_or_else(|| self.validation_level(validation_type))
     }
 
     /// Attempt to load `Config` from the given file name that
     /// is expected to be a YAML file.
-    pub fn load(file: &str) -> Result<Config, FlError> {
-        let config_yaml = load_yaml(file)?;
-        return Config::load_from_yaml(config_yaml);
+    ///
+    /// Configurations referenced via `extends` (files or URLs) are
+    /// loaded and merged first, so that the extending configuration
+    /// takes precedence. URLs are fetched with the given `http` settings
+    /// completed by the `http` section of the configuration `file`. The
+    /// `overrides` (dotted key paths and YAML values) are applied last.
+    pub async fn load(
+        file: &str,
+        overrides: &[(String, String)],
+        http: &HttpSettings,
+    ) -> Result<Config, FlError> {
+        let config_yaml = Config::load_yaml(file, overrides, http).await?;
+
+        Config::from_yaml(config_yaml, file)
+    }
+
+    /// Load the (merged) `Yaml` of the configuration `file` without
+    /// parsing it into a `Config` (see `Config::load`).
+    pub async fn load_yaml(
+        file: &str,
+        overrides: &[(String, String)],
+        http: &HttpSettings,
+    ) -> Result<Yaml, FlError> {
+        let mut config_yaml = load_extended(file, http).await?;
+        for (key, value) in overrides {
+            config_yaml = merge_yaml(config_yaml, override_yaml(key, value)?);
+        }
+        Ok(config_yaml)
+    }
+
+    /// Parse the `Yaml` of the configuration `file` into a valid `Config`.
+    pub fn from_yaml(config_yaml: Yaml, file: &str) -> Result<Config, FlError> {
+        let mut config = Config::load_from_yaml(config_yaml)?;
+
+        // root directories are relative to the configuration file
+        let config_dir = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+        config.roots = config
+            .roots
+            .iter()
+            .flat_map(|root| config_dir.join(root).to_str().map(|dir| dir.to_owned()))
+            .map(|dir| canonicalize(&dir).unwrap_or(dir))
+            .collect();
+
+        // custom validations are executed in the configuration's directory
+        let working_dir = config_dir
+            .to_str()
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or(".");
+        let working_dir = canonicalize(working_dir).unwrap_or(working_dir.to_owned());
+        for custom in config.custom_validations.iter_mut() {
+            custom.working_dir = working_dir.clone();
+        }
+
+        // as is the CA bundle of the HTTP client
+        config.http.ca_bundle = config
+            .http
+            .ca_bundle
+            .as_ref()
+            .and_then(|file| config_dir.join(file).to_str().map(|file| file.to_owned()));
+
+        // the `CODEOWNERS` file is relative to the configuration file as
+        // well, otherwise it is searched in the repository root
+        config.codeowners = match &config.codeowners {
+            Some(file) => config_dir.join(file).to_str().map(|file| file.to_owned()),
+            None => find_codeowners(Path::new(&working_dir))
+                .and_then(|file| file.to_str().map(|file| file.to_owned())),
+        };
+
+        Ok(config)
     }
 
     /// Try to parse the given `Yaml` into a valid `Config`
     fn load_from_yaml(config_yaml: Yaml) -> Result<Config, FlError> {
+        // check the version first, as older versions might not be able
+        // to parse the configuration at all
+        if let Some(min_version) = config_yaml["min_version"].as_str() {
+            check_min_version(min_version, env!("CARGO_PKG_VERSION"))?;
+        }
+
         let empty = Default::default();
 
-        let package_types = config_yaml["package_types"]
-            .as_hash()
-            .unwrap_or(&empty)
-            .into_iter()
-            .flat_map(|(key, value)| {
-                let name = key.as_str().unwrap_or("").to_owned();
-                let includes = yaml_str_list(&value["includes"]);
-
-                // `dir_prefix` may be either a string or a list of strings
-                let prefix = value["dir_prefix"].as_str().unwrap_or("").to_owned();
-                let prefixes = if prefix.is_empty() {
-                    yaml_str_list(&value["dir_prefix"])
-                } else {
-                    vec![prefix]
-                };
+        let package_types = package_types(&config_yaml);
 
-                if name.is_empty() {
-                    None
-                } else {
-                    Some(PackageType {
-                        name,
-                        prefixes,
-                        includes,
-                    })
-                }
-            });
+        let mut validations = Vec::new();
+        for (key, value) in config_yaml["validations"].as_hash().unwrap_or(&empty) {
+            let type_str = key.as_str().unwrap_or("");
+            let validation_type = ValidationType::parse(type_str)
+                .ok_or(FlError::InvalidValidationType(type_str.to_owned()))?;
 
-        let validations = config_yaml["validations"]
-            .as_hash()
-            .unwrap_or(&empty)
-            .into_iter()
-            .map(|(key, value)| {
-                let type_str = key.as_str().unwrap_or("");
-                let level_str = value.as_str().unwrap_or("");
-                let validation_type = ValidationType::from_str(type_str)
-                    .ok_or(FlError::InvalidValidationType(type_str.to_owned()))?;
-                let validation_level = ValidationLevel::from_str(level_str).ok_or(
-                    FlError::InvalidValidationLevel(level_str.to_owned(), type_str.to_owned()),
-                )?;
-
-                Ok((validation_type, validation_level))
-            })
-            .collect::<Result<Vec<(ValidationType, ValidationLevel)>, FlError>>()?;
+            // the level may be given either directly, as a mapping
+            // scoped to some paths or as a list of those
+            let entries = match value.as_vec() {
+                Some(entries) => entries.iter().collect(),
+                None => vec![value],
+            };
+
+            for entry in entries {
+                validations.push(validation_setting(&validation_type, type_str, entry)?);
+            }
+        }
 
         let public_repositories = regex_list(
             yaml_str_list(&config_yaml["public_repositories"]),
             "public repository",
         )?;
 
+        let mut public_package_policy = HashMap::new();
+        for (key, value) in config_yaml["public_package_policy"]
+            .as_hash()
+            .unwrap_or(&empty)
+        {
+            let type_str = key.as_str().unwrap_or("");
+            let policy_str = value.as_str().unwrap_or("");
+            let policy = PublicPackagePolicy::parse(policy_str).ok_or_else(|| {
+                ConfigValidation(format!(
+                    "invalid public package policy '{}' of package type '{}' (valid: git_only, public, same_repository)",
+                    policy_str, type_str
+                ))
+            })?;
+            public_package_policy.insert(type_str.to_owned(), policy);
+        }
+
+        let internal_repositories = regex_list(
+            yaml_str_list(&config_yaml["internal_repositories"]),
+            "internal repository",
+        )?;
+
+        let private_packages = regex_list(
+            yaml_str_list(&config_yaml["private_packages"]),
+            "private package",
+        )?;
+
+        let dev_only_packages = regex_list(
+            yaml_str_list(&config_yaml["dev_only_packages"]),
+            "dev-only package",
+        )?;
+
         let blacklist = regex_list(yaml_str_list(&config_yaml["blacklist"]), "blacklist")?;
 
+        let include = glob_list(yaml_str_list(&config_yaml["include"]), "include")?;
+
+        // the default excludes apply only if no `exclude` is configured at all
+        let exclude_globs = if config_yaml["exclude"].is_badvalue() {
+            DEFAULT_EXCLUDES
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect()
+        } else {
+            yaml_str_list(&config_yaml["exclude"])
+        };
+        let exclude = glob_list(exclude_globs, "exclude")?;
+
+        let roots = yaml_str_list(&config_yaml["roots"]);
+
+        let mut custom_validations = Vec::new();
+        for (key, value) in config_yaml["custom_validations"]
+            .as_hash()
+            .unwrap_or(&empty)
+        {
+            custom_validations.push(custom_validation(key.as_str().unwrap_or(""), value)?);
+        }
+
+        let mut structure = HashMap::new();
+        for (key, value) in config_yaml["structure"].as_hash().unwrap_or(&empty) {
+            structure.insert(key.as_str().unwrap_or("").to_owned(), yaml_str_list(value));
+        }
+
+        let analysis_options = config_yaml["analysis_options"]["include"]
+            .as_str()
+            .map(|include| include.to_owned());
+
+        let constraint_styles = constraint_policies(&config_yaml)?;
+
+        let alternatives = alternative_groups(&config_yaml["alternatives"])?;
+
+        let rules = dependency_rules(&config_yaml)?;
+
+        let codeowners = config_yaml["codeowners"]
+            .as_str()
+            .map(|file| file.to_owned());
+
+        let http = http_settings(&config_yaml["http"])?;
+
+        let check = check_settings(&config_yaml["check"])?;
+
         let config = Config {
-            package_types: package_types.collect(),
+            package_types,
             blacklist,
             validations,
             public_repositories,
+            public_package_policy,
+            internal_repositories,
+            private_packages,
+            dev_only_packages,
+            structure,
+            analysis_options,
+            constraint_styles,
+            alternatives,
+            include,
+            exclude,
+            roots,
+            custom_validations,
+            rules,
+            codeowners,
+            http,
+            check,
         };
 
         config.validate()
@@ -149,11 +970,37 @@ impl Config {
     ///   - non empty package types
     ///   - each package must have at least one `dir_prefix`
     ///   - each package's includes must exist
+    ///   - each public package policy must refer to an existing package
+    ///   - each required structure must refer to an existing package
     fn validate(self) -> Result<Config, FlError> {
         if !self.is_valid() {
             return Err(ConfigValidation("no package types configured".to_owned()));
         }
 
+        let mut policy_types = self.public_package_policy.keys().collect::<Vec<_>>();
+        policy_types.sort();
+        if let Some(unknown) = policy_types
+            .into_iter()
+            .find(|pkg_type| !self.package_exists(pkg_type))
+        {
+            return Err(ConfigValidation(format!(
+                "public_package_policy: unknown package type '{}'",
+                unknown
+            )));
+        }
+
+        let mut structure_types = self.structure.keys().collect::<Vec<_>>();
+        structure_types.sort();
+        if let Some(unknown) = structure_types
+            .into_iter()
+            .find(|pkg_type| !self.package_exists(pkg_type))
+        {
+            return Err(ConfigValidation(format!(
+                "structure: unknown package type '{}'",
+                unknown
+            )));
+        }
+
         self.package_types
             .iter()
             .flat_map(|package| {
@@ -183,63 +1030,624 @@ impl Config {
     }
 }
 
-/// Convert a list of `Regex` into a list of their
-/// respective string representations (used for equality tests).
-fn regex_str_list(regexes: &Vec<Regex>) -> Vec<&str> {
-    regexes.iter().map(|rgx| rgx.as_str()).collect::<Vec<_>>()
+/// Parse the `http` settings of the configuration: `proxy`, `ca_bundle`,
+/// `timeout` (in seconds) and `user_agent`.
+fn http_settings(value: &Yaml) -> Result<HttpSettings, FlError> {
+    let string = |key: &str| value[key].as_str().map(|value| value.to_owned());
+
+    let timeout = match &value["timeout"] {
+        Yaml::BadValue | Yaml::Null => None,
+        timeout => {
+            let seconds = timeout
+                .as_i64()
+                .map(|seconds| seconds as f64)
+                .or_else(|| timeout.as_f64())
+                .filter(|seconds| *seconds > 0.0)
+                .ok_or_else(|| {
+                    ConfigValidation(
+                        "http: timeout must be a positive number of seconds".to_owned(),
+                    )
+                })?;
+            Some(Duration::from_secs_f64(seconds))
+        }
+    };
+
+    Ok(HttpSettings {
+        proxy: string("proxy"),
+        ca_bundle: string("ca_bundle"),
+        timeout,
+        user_agent: string("user_agent"),
+        insecure: false,
+    })
 }
 
-/// Try to parse a list of strings into a list of valid
-/// regular expressions.
-fn regex_list(strings: Vec<String>, config_type: &str) -> Result<Vec<Regex>, FlError> {
-    strings
-        .iter()
-        .map(|entry| {
-            Regex::new(entry).map_err(|_| {
+/// Parse the `check` settings of the configuration: `max_age_days`, the
+/// `quality` gate, `min_dart_sdk` and the `publishers` policy.
+fn check_settings(value: &Yaml) -> Result<CheckSettings, FlError> {
+    let max_age_days = match &value["max_age_days"] {
+        Yaml::BadValue | Yaml::Null => None,
+        days => Some(days.as_i64().filter(|days| *days > 0).ok_or_else(|| {
+            ConfigValidation("check: max_age_days must be a positive number of days".to_owned())
+        })? as u64),
+    };
+
+    let quality = &value["quality"];
+    let threshold = |key: &str, max: i64| match &quality[key] {
+        Yaml::BadValue | Yaml::Null => Ok(None),
+        threshold => threshold
+            .as_i64()
+            .filter(|threshold| (0..=max).contains(threshold))
+            .map(|threshold| Some(threshold as u32))
+            .ok_or_else(|| {
                 ConfigValidation(format!(
-                    "invalid regex in {} entry: '{}'",
-                    config_type, entry
+                    "check: quality: {} must be a number between 0 and {}",
+                    key, max
                 ))
-            })
-        })
-        .collect()
-}
+            }),
+    };
 
-#[cfg(test)]
-mod tests {
-    use crate::config::PackageType;
-    use crate::Config;
-    use regex::Regex;
-    use yaml_rust::YamlLoader;
+    let min_dart_sdk = match &value["min_dart_sdk"] {
+        Yaml::BadValue | Yaml::Null => None,
+        sdk => Some(sdk.as_str().and_then(parse_version).ok_or_else(|| {
+            ConfigValidation("check: min_dart_sdk must be a version like '3.0.0'".to_owned())
+        })?),
+    };
 
-    #[test]
-    fn load_config_empty() {
-        let docs = YamlLoader::load_from_str("").unwrap();
+    let quality_level = match quality["level"].as_str() {
+        Some(level_str) => ValidationLevel::parse(level_str).ok_or_else(|| {
+            FlError::InvalidValidationLevel(level_str.to_owned(), "check: quality".to_owned())
+        })?,
+        None => ValidationLevel::Error,
+    };
 
-        assert_eq!(docs.is_empty(), true);
-    }
+    let publishers = &value["publishers"];
+    let level = match publishers["level"].as_str() {
+        Some(level_str) => ValidationLevel::parse(level_str).ok_or_else(|| {
+            FlError::InvalidValidationLevel(level_str.to_owned(), "check: publishers".to_owned())
+        })?,
+        None => ValidationLevel::Warning,
+    };
+    let publishers = PublisherPolicy {
+        verified: publishers["verified"].as_bool().unwrap_or(false),
+        allowed: yaml_str_list(&publishers["allowed"]),
+        exempt: yaml_str_list(&publishers["exempt"]),
+        level,
+    };
 
-    #[test]
-    fn load_config_no_package_types() {
-        let mut docs = YamlLoader::load_from_str("package_types:").unwrap();
-        let config = Config::load_from_yaml(docs.remove(0));
+    Ok(CheckSettings {
+        max_age_days,
+        min_dart_sdk,
+        publishers,
+        quality: QualityGate {
+            min_points: threshold("min_points", i64::from(u32::MAX))?,
+            min_popularity: threshold("min_popularity", 100)?,
+            exempt: yaml_str_list(&quality["exempt"]),
+            level: quality_level,
+        },
+    })
+}
 
-        assert_eq!(config.is_err(), true);
+/// Parse the custom validation of the given `code` from its configuration
+/// `Yaml` (a mapping with `command`, `level` and `description`).
+fn custom_validation(code: &str, value: &Yaml) -> Result<CustomValidation, FlError> {
+    if code.is_empty() || ValidationType::parse(code).is_some() {
+        return Err(ConfigValidation(format!(
+            "custom validation: invalid code '{}' (must not be empty or a built-in validation)",
+            code
+        )));
     }
 
-    #[test]
-    fn load_config_missing_dir_prefix() {
-        let mut docs = YamlLoader::load_from_str(
-            "
-package_types:
-  app:
-    dir_prefix:
-    ",
-        )
+    let command = value["command"].as_str().unwrap_or("").trim().to_owned();
+    if command.is_empty() {
+        return Err(ConfigValidation(format!(
+            "custom validation '{}': missing command",
+            code
+        )));
+    }
+
+    let level = match value["level"].as_str() {
+        Some(level_str) => ValidationLevel::parse(level_str).ok_or_else(|| {
+            FlError::InvalidValidationLevel(level_str.to_owned(), code.to_owned())
+        })?,
+        None => ValidationLevel::Error,
+    };
+
+    Ok(CustomValidation {
+        code: code.to_owned(),
+        command,
+        level,
+        description: value["description"].as_str().map(|desc| desc.to_owned()),
+        working_dir: ".".to_owned(),
+    })
+}
+
+/// Parse the `rules` of the given configuration `Yaml`, each being a
+/// mapping with either a `deny` (`from`, `to`) or a `require` (`package`,
+/// `depends_on`) constraint and an optional `description`.
+pub(crate) fn dependency_rules(config_yaml: &Yaml) -> Result<Vec<DependencyRule>, FlError> {
+    let entries = match &config_yaml["rules"] {
+        Yaml::BadValue | Yaml::Null => return Ok(Vec::new()),
+        Yaml::Array(entries) => entries,
+        _ => return Err(ConfigValidation("rules: expected a list".to_owned())),
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let pattern = |value: &Yaml, key: &str| {
+                let pattern = value[key].as_str().unwrap_or("");
+                if pattern.is_empty() {
+                    return Err(ConfigValidation(format!(
+                        "rules[{}]: missing '{}'",
+                        idx, key
+                    )));
+                }
+                PackagePattern::new(pattern).map_err(|_| {
+                    ConfigValidation(format!("rules[{}]: invalid pattern '{}'", idx, pattern))
+                })
+            };
+            let description = entry["description"].as_str().map(|desc| desc.to_owned());
+
+            match (&entry["deny"], &entry["require"]) {
+                (deny, Yaml::BadValue) if !deny.is_badvalue() => Ok(DependencyRule::Deny {
+                    from: pattern(deny, "from")?,
+                    to: pattern(deny, "to")?,
+                    description,
+                }),
+                (Yaml::BadValue, require) if !require.is_badvalue() => {
+                    Ok(DependencyRule::Require {
+                        package: pattern(require, "package")?,
+                        depends_on: pattern(require, "depends_on")?,
+                        description,
+                    })
+                }
+                _ => Err(ConfigValidation(format!(
+                    "rules[{}]: expected either 'deny' or 'require'",
+                    idx
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Parse the `alternatives` of the configuration, a mapping of group names
+/// to the packages of the group, given as a list or separated by `|` (e.g.
+/// `dio|http|chopper`).
+fn alternative_groups(value: &Yaml) -> Result<Vec<AlternativeGroup>, FlError> {
+    let empty = Default::default();
+
+    value
+        .as_hash()
+        .unwrap_or(&empty)
+        .iter()
+        .map(|(key, packages)| {
+            let name = key.as_str().unwrap_or("").to_owned();
+            let packages = match packages.as_str() {
+                Some(packages) => packages
+                    .split('|')
+                    .map(|package| package.trim().to_owned())
+                    .filter(|package| !package.is_empty())
+                    .collect(),
+                None => yaml_str_list(packages),
+            };
+
+            if packages.len() < 2 {
+                return Err(ConfigValidation(format!(
+                    "alternatives: group '{}' has to list at least two packages",
+                    name
+                )));
+            }
+            Ok(AlternativeGroup { name, packages })
+        })
+        .collect()
+}
+
+/// Parse the `constraint_styles` of the given configuration `Yaml`, a list
+/// of glob patterns of package names (`packages`) along with the constraint
+/// style they `require` and/or a list of styles they `forbid`.
+pub(crate) fn constraint_policies(config_yaml: &Yaml) -> Result<Vec<ConstraintPolicy>, FlError> {
+    let entries = match &config_yaml["constraint_styles"] {
+        Yaml::BadValue | Yaml::Null => return Ok(Vec::new()),
+        Yaml::Array(entries) => entries,
+        _ => {
+            return Err(ConfigValidation(
+                "constraint_styles: expected a list".to_owned(),
+            ))
+        }
+    };
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let style = |value: &str| {
+                ConstraintStyle::parse(value).ok_or_else(|| {
+                    ConfigValidation(format!(
+                        "constraint_styles[{}]: invalid style '{}' (valid: any, caret, exact, range)",
+                        idx, value
+                    ))
+                })
+            };
+
+            let packages = entry["packages"].as_str().unwrap_or("");
+            if packages.is_empty() {
+                return Err(ConfigValidation(format!(
+                    "constraint_styles[{}]: missing 'packages'",
+                    idx
+                )));
+            }
+            let regex = Regex::new(&glob_to_regex(packages)).map_err(|_| {
+                ConfigValidation(format!(
+                    "constraint_styles[{}]: invalid pattern '{}'",
+                    idx, packages
+                ))
+            })?;
+
+            let require = entry["require"].as_str().map(style).transpose()?;
+            let forbid = yaml_str_list(&entry["forbid"])
+                .iter()
+                .map(|value| style(value))
+                .collect::<Result<Vec<_>, _>>()?;
+            if require.is_none() && forbid.is_empty() {
+                return Err(ConfigValidation(format!(
+                    "constraint_styles[{}]: expected 'require' or 'forbid'",
+                    idx
+                )));
+            }
+
+            Ok(ConstraintPolicy {
+                packages: packages.to_owned(),
+                require,
+                forbid,
+                regex,
+            })
+        })
+        .collect()
+}
+
+/// Extract all (named) package types of the given configuration `Yaml`.
+pub(crate) fn package_types(config_yaml: &Yaml) -> Vec<PackageType> {
+    let empty = Default::default();
+
+    config_yaml["package_types"]
+        .as_hash()
+        .unwrap_or(&empty)
+        .into_iter()
+        .flat_map(|(key, value)| {
+            let name = key.as_str().unwrap_or("").to_owned();
+            let includes = yaml_str_list(&value["includes"]);
+
+            // `dir_prefix` may be either a string or a list of strings
+            let prefix = value["dir_prefix"].as_str().unwrap_or("").to_owned();
+            let prefixes = if prefix.is_empty() {
+                yaml_str_list(&value["dir_prefix"])
+            } else {
+                vec![prefix]
+            };
+
+            if name.is_empty() {
+                None
+            } else {
+                Some(PackageType {
+                    name,
+                    prefixes,
+                    includes,
+                    max_depth: value["max_depth"]
+                        .as_i64()
+                        .and_then(|depth| usize::try_from(depth).ok()),
+                    requires: yaml_str_list(&value["requires"]),
+                    pure_dart: value["pure_dart"].as_bool().unwrap_or(false),
+                    require_tests: value["require_tests"].as_bool().unwrap_or(false),
+                })
+            }
+        })
+        .collect()
+}
+
+/// Location a configuration is loaded from.
+#[derive(Debug, PartialEq)]
+enum ConfigSource {
+    File(PathBuf),
+    Url(Url),
+}
+
+impl ConfigSource {
+    /// Resolve the `extends` reference relative to this source.
+    fn resolve(&self, reference: &str) -> Result<ConfigSource, FlError> {
+        let invalid = || ConfigValidation(format!("invalid extends '{}'", reference));
+
+        if reference.starts_with("https://") || reference.starts_with("http://") {
+            return Url::parse(reference)
+                .map(ConfigSource::Url)
+                .map_err(|_| invalid());
+        }
+
+        match self {
+            ConfigSource::File(file) => {
+                let dir = file.parent().unwrap_or_else(|| Path::new(""));
+                Ok(ConfigSource::File(dir.join(reference)))
+            }
+            ConfigSource::Url(url) => url
+                .join(reference)
+                .map(ConfigSource::Url)
+                .map_err(|_| invalid()),
+        }
+    }
+
+    async fn load(&self, http: &HttpSettings) -> Result<Yaml, FlError> {
+        match self {
+            ConfigSource::File(file) => load_yaml(&file.to_string_lossy()),
+            ConfigSource::Url(url) => {
+                let settings = HttpSettings {
+                    timeout: Some(http.timeout.unwrap_or(EXTENDS_TIMEOUT)),
+                    ..http.clone()
+                };
+                let content = http_client(&settings)?
+                    .get(url.clone())
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+
+                parse_yaml(&content, url.as_str())
+            }
+        }
+    }
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::File(file) => write!(formatter, "{}", normalize_path(file).display()),
+            ConfigSource::Url(url) => write!(formatter, "{}", url),
+        }
+    }
+}
+
+/// Load the configuration `file` along with all configurations it
+/// (transitively) extends, merged into one `Yaml`.
+async fn load_extended(file: &str, http: &HttpSettings) -> Result<Yaml, FlError> {
+    let mut source = ConfigSource::File(PathBuf::from(file));
+    let mut seen = vec![source.to_string()];
+    let mut chain = vec![source.load(http).await?];
+    let http = http.clone().or(&http_settings(&chain[0]["http"])?);
+
+    while let Some(reference) = chain.last().and_then(|yaml| yaml["extends"].as_str()) {
+        source = source.resolve(reference)?;
+
+        let name = source.to_string();
+        let cyclic = seen.contains(&name);
+        seen.push(name);
+
+        if cyclic {
+            return Err(ConfigValidation(format!(
+                "cyclic extends: {}",
+                seen.join(" -> ")
+            )));
+        }
+
+        chain.push(source.load(&http).await?);
+    }
+
+    // merge from the base configuration down to the requested one
+    Ok(chain
+        .into_iter()
+        .rev()
+        .reduce(merge_yaml)
+        .unwrap_or(Yaml::Null))
+}
+
+/// Merge the `child` YAML into its `parent`: mappings are merged
+/// recursively, all other values of `child` replace the parent ones.
+fn merge_yaml(parent: Yaml, child: Yaml) -> Yaml {
+    match (parent, child) {
+        (Yaml::Hash(mut parent), Yaml::Hash(child)) => {
+            for (key, value) in child {
+                let merged = match parent.remove(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => value,
+                };
+                parent.insert(key, merged);
+            }
+            Yaml::Hash(parent)
+        }
+        (_, child) => child,
+    }
+}
+
+/// Build the `Yaml` of a single override of the value at the dotted
+/// `key` path (e.g. `validations.validation:dependency:cyclic`).
+///
+/// The `value` is parsed as YAML itself, so that lists may be given
+/// as well (e.g. `exclude=[build, generated]`).
+fn override_yaml(key: &str, value: &str) -> Result<Yaml, FlError> {
+    let parsed = YamlLoader::load_from_str(value)
+        .ok()
+        .and_then(|mut docs| {
+            if docs.is_empty() {
+                None
+            } else {
+                Some(docs.remove(0))
+            }
+        })
+        .unwrap_or_else(|| Yaml::String(value.to_owned()));
+
+    key.rsplit('.').try_fold(parsed, |child, part| {
+        if part.is_empty() {
+            return Err(ConfigValidation(format!("invalid override key '{}'", key)));
+        }
+
+        let mut hash = yaml_rust::yaml::Hash::new();
+        hash.insert(Yaml::String(part.to_owned()), child);
+        Ok(Yaml::Hash(hash))
+    })
+}
+
+/// Parse a single `ValidationSetting` that is either given as a plain level
+/// (`warn`) or as a mapping of `level` and `paths` (regular expressions).
+fn validation_setting(
+    validation_type: &ValidationType,
+    type_str: &str,
+    value: &Yaml,
+) -> Result<ValidationSetting, FlError> {
+    let (level_str, paths) = match value.as_str() {
+        Some(level) => (level, Vec::new()),
+        None => (
+            value["level"].as_str().unwrap_or(""),
+            yaml_str_list(&value["paths"]),
+        ),
+    };
+
+    let level = ValidationLevel::parse(level_str).ok_or(FlError::InvalidValidationLevel(
+        level_str.to_owned(),
+        type_str.to_owned(),
+    ))?;
+
+    Ok(ValidationSetting {
+        validation_type: validation_type.clone(),
+        level,
+        paths: regex_list(paths, "validation paths")?,
+    })
+}
+
+/// Verify the `current` version of flcheck is at least the configured
+/// `min_version` (missing minor/patch components default to zero).
+pub(crate) fn check_min_version(min_version: &str, current: &str) -> Result<(), FlError> {
+    let required = parse_version(min_version)
+        .ok_or_else(|| ConfigValidation(format!("invalid min_version '{}'", min_version)))?;
+    let installed = parse_version(current)
+        .ok_or_else(|| ConfigValidation(format!("invalid flcheck version '{}'", current)))?;
+
+    if installed < required {
+        Err(FlError::VersionTooOld(
+            min_version.to_owned(),
+            current.to_owned(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Search for the configuration file starting in `start_dir` and continuing
+/// upwards until the repository root (a directory containing `.git`) or the
+/// root of the file system is reached.
+pub fn find_config_file(start_dir: &Path) -> Option<PathBuf> {
+    for dir in start_dir.ancestors() {
+        let candidate = dir.join(CONFIG_FILE);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+/// Convert a list of `Regex` into a list of their
+/// respective string representations (used for equality tests).
+fn regex_str_list(regexes: &[Regex]) -> Vec<&str> {
+    regexes.iter().map(|rgx| rgx.as_str()).collect::<Vec<_>>()
+}
+
+/// Try to parse a list of strings into a list of valid
+/// regular expressions.
+fn regex_list(strings: Vec<String>, config_type: &str) -> Result<Vec<Regex>, FlError> {
+    strings
+        .iter()
+        .map(|entry| {
+            Regex::new(entry).map_err(|_| {
+                ConfigValidation(format!(
+                    "invalid regex in {} entry: '{}'",
+                    config_type, entry
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Try to parse a list of glob patterns into a list of valid
+/// regular expressions.
+fn glob_list(globs: Vec<String>, config_type: &str) -> Result<Vec<Regex>, FlError> {
+    regex_list(
+        globs.iter().map(|glob| glob_to_regex(glob)).collect(),
+        config_type,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::check_min_version;
+    use crate::config::find_config_file;
+    use crate::config::merge_yaml;
+    use crate::config::override_yaml;
+    use crate::config::regex_str_list;
+    use crate::config::AlternativeGroup;
+    use crate::config::CheckSettings;
+    use crate::config::ConfigBuilder;
+    use crate::config::ConfigSource;
+    use crate::config::CustomValidation;
+    use crate::config::DependencyRule;
+    use crate::config::HttpSettings;
+    use crate::config::PackageType;
+    use crate::config::PublicPackagePolicy;
+    use crate::config::PublisherPolicy;
+    use crate::config::QualityGate;
+    use crate::config::CONFIG_FILE;
+    use crate::config::DEFAULT_EXCLUDES;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::util::glob_to_regex;
+    use crate::util::yaml_str_list;
+    use crate::version::ConstraintStyle;
+    use crate::Config;
+    use crate::FlError;
+    use regex::Regex;
+    use reqwest::Url;
+    use semver::Version;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use yaml_rust::YamlLoader;
+
+    fn default_exclude() -> Vec<Regex> {
+        DEFAULT_EXCLUDES
+            .iter()
+            .map(|glob| Regex::new(&glob_to_regex(glob)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn load_config_empty() {
+        let docs = YamlLoader::load_from_str("").unwrap();
+
+        assert!(docs.is_empty());
+    }
+
+    #[test]
+    fn load_config_no_package_types() {
+        let mut docs = YamlLoader::load_from_str("package_types:").unwrap();
+        let config = Config::load_from_yaml(docs.remove(0));
+
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn load_config_missing_dir_prefix() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix:
+    ",
+        )
         .unwrap();
         let config = Config::load_from_yaml(docs.remove(0));
 
-        assert_eq!(config.is_err(), true);
+        assert!(config.is_err());
     }
 
     #[test]
@@ -260,11 +1668,31 @@ package_types:
                 package_types: vec![PackageType {
                     name: "app".to_owned(),
                     prefixes: vec!["app".to_owned()],
-                    includes: Vec::new()
+                    includes: Vec::new(),
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
                 }],
                 blacklist: Vec::new(),
                 validations: Vec::new(),
                 public_repositories: Vec::new(),
+                public_package_policy: HashMap::new(),
+                internal_repositories: Vec::new(),
+                private_packages: Vec::new(),
+                dev_only_packages: Vec::new(),
+                structure: HashMap::new(),
+                analysis_options: None,
+                constraint_styles: Vec::new(),
+                alternatives: Vec::new(),
+                include: Vec::new(),
+                exclude: default_exclude(),
+                roots: Vec::new(),
+                custom_validations: Vec::new(),
+                rules: Vec::new(),
+                codeowners: None,
+                http: HttpSettings::default(),
+                check: CheckSettings::default(),
             }
         )
     }
@@ -290,11 +1718,31 @@ blacklist:
                 package_types: vec![PackageType {
                     name: "app".to_owned(),
                     prefixes: vec!["app".to_owned()],
-                    includes: Vec::new()
+                    includes: Vec::new(),
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
                 }],
                 blacklist: vec![Regex::new("one").unwrap(), Regex::new("two").unwrap()],
                 validations: Vec::new(),
                 public_repositories: Vec::new(),
+                public_package_policy: HashMap::new(),
+                internal_repositories: Vec::new(),
+                private_packages: Vec::new(),
+                dev_only_packages: Vec::new(),
+                structure: HashMap::new(),
+                analysis_options: None,
+                constraint_styles: Vec::new(),
+                alternatives: Vec::new(),
+                include: Vec::new(),
+                exclude: default_exclude(),
+                roots: Vec::new(),
+                custom_validations: Vec::new(),
+                rules: Vec::new(),
+                codeowners: None,
+                http: HttpSettings::default(),
+                check: CheckSettings::default(),
             }
         )
     }
@@ -320,11 +1768,31 @@ public_repositories:
                 package_types: vec![PackageType {
                     name: "app".to_owned(),
                     prefixes: vec!["app".to_owned()],
-                    includes: Vec::new()
+                    includes: Vec::new(),
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
                 }],
                 blacklist: Vec::new(),
                 validations: Vec::new(),
                 public_repositories: vec![Regex::new("one").unwrap(), Regex::new("two").unwrap()],
+                public_package_policy: HashMap::new(),
+                internal_repositories: Vec::new(),
+                private_packages: Vec::new(),
+                dev_only_packages: Vec::new(),
+                structure: HashMap::new(),
+                analysis_options: None,
+                constraint_styles: Vec::new(),
+                alternatives: Vec::new(),
+                include: Vec::new(),
+                exclude: default_exclude(),
+                roots: Vec::new(),
+                custom_validations: Vec::new(),
+                rules: Vec::new(),
+                codeowners: None,
+                http: HttpSettings::default(),
+                check: CheckSettings::default(),
             }
         )
     }
@@ -341,7 +1809,7 @@ public_repositories:
     ",
         );
 
-        assert_eq!(docs.is_err(), true)
+        assert!(docs.is_err())
     }
 
     #[test]
@@ -358,7 +1826,140 @@ public_repositories:
         .unwrap();
         let config = Config::load_from_yaml(docs.remove(0));
 
-        assert_eq!(config.is_err(), true)
+        assert!(config.is_err())
+    }
+
+    #[test]
+    fn load_config_public_package_policy() {
+        let load = |policy: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "
+package_types:
+  app:
+    dir_prefix: app_
+  package:
+    dir_prefix: pkg_
+public_package_policy:
+  {}
+    ",
+                policy
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0))
+        };
+
+        let config = load("package: same_repository").unwrap();
+        assert_eq!(
+            config.public_package_policy(config.package_type("pkg_foo")),
+            PublicPackagePolicy::SameRepository
+        );
+        assert_eq!(
+            config.public_package_policy(config.package_type("app_foo")),
+            PublicPackagePolicy::GitOnly
+        );
+
+        assert_eq!(
+            load("package: local").unwrap_err().to_string(),
+            "invalid configuration: invalid public package policy 'local' of package type \
+             'package' (valid: git_only, public, same_repository)"
+        );
+        assert_eq!(
+            load("shared: public").unwrap_err().to_string(),
+            "invalid configuration: public_package_policy: unknown package type 'shared'"
+        );
+    }
+
+    #[test]
+    fn load_config_custom_validations() {
+        let load = |custom: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "
+package_types:
+  app:
+    dir_prefix: app_
+custom_validations:
+  {}
+    ",
+                custom
+            ))
+            .unwrap();
+            Config::from_yaml(docs.remove(0), "/repo/flcheck.yaml")
+        };
+
+        let config = load("acme:license: { command: ./license.sh, level: warn }").unwrap();
+        assert_eq!(
+            config.custom_validations,
+            vec![CustomValidation {
+                code: "acme:license".to_owned(),
+                command: "./license.sh".to_owned(),
+                level: ValidationLevel::Warning,
+                description: None,
+                working_dir: "/repo".to_owned(),
+            }]
+        );
+
+        assert_eq!(
+            load("acme:license: { level: warn }")
+                .unwrap_err()
+                .to_string(),
+            "invalid configuration: custom validation 'acme:license': missing command"
+        );
+        assert!(load("validation:dependency:cyclic: { command: 'true' }").is_err());
+    }
+
+    #[test]
+    fn load_config_rules() {
+        let load = |rules: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "
+package_types:
+  app:
+    dir_prefix: app_
+rules:
+{}
+    ",
+                rules
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0))
+        };
+
+        let config = load(
+            "
+  - deny: { from: 'app_*', to: 'pkg_experimental_*' }
+  - require: { package: 'app_*', depends_on: shared_analytics }
+    description: apps have to track usage",
+        )
+        .unwrap();
+
+        match &config.rules[..] {
+            [DependencyRule::Deny { from, to, .. }, DependencyRule::Require {
+                package,
+                depends_on,
+                description,
+            }] => {
+                assert!(from.matches("app_foo"));
+                assert!(!from.matches("pkg_app_foo"));
+                assert!(to.matches("pkg_experimental_ui"));
+                assert_eq!(package.pattern, "app_*");
+                assert!(depends_on.matches("shared_analytics"));
+                assert_eq!(description.as_deref(), Some("apps have to track usage"));
+            }
+            rules => panic!("unexpected rules: {:?}", rules),
+        }
+
+        assert_eq!(
+            load("  - require: { package: 'app_*' }")
+                .unwrap_err()
+                .to_string(),
+            "invalid configuration: rules[0]: missing 'depends_on'"
+        );
+        assert_eq!(
+            load("  - allow: { from: 'app_*', to: 'pkg_*' }")
+                .unwrap_err()
+                .to_string(),
+            "invalid configuration: rules[0]: expected either 'deny' or 'require'"
+        );
     }
 
     #[test]
@@ -370,6 +1971,8 @@ package_types:
     dir_prefix:
     - 'app-'
     - 'app_'
+    max_depth: 2
+    requires: [shared_logging]
     ",
         )
         .unwrap();
@@ -381,11 +1984,31 @@ package_types:
                 package_types: vec![PackageType {
                     name: "app".to_owned(),
                     prefixes: vec!["app-".to_owned(), "app_".to_owned()],
-                    includes: Vec::new()
+                    includes: Vec::new(),
+                    max_depth: Some(2),
+                    requires: vec!["shared_logging".to_owned()],
+                    pure_dart: false,
+                    require_tests: false,
                 }],
                 blacklist: Vec::new(),
                 validations: Vec::new(),
                 public_repositories: Vec::new(),
+                public_package_policy: HashMap::new(),
+                internal_repositories: Vec::new(),
+                private_packages: Vec::new(),
+                dev_only_packages: Vec::new(),
+                structure: HashMap::new(),
+                analysis_options: None,
+                constraint_styles: Vec::new(),
+                alternatives: Vec::new(),
+                include: Vec::new(),
+                exclude: default_exclude(),
+                roots: Vec::new(),
+                custom_validations: Vec::new(),
+                rules: Vec::new(),
+                codeowners: None,
+                http: HttpSettings::default(),
+                check: CheckSettings::default(),
             }
         )
     }
@@ -413,18 +2036,607 @@ package_types:
                     PackageType {
                         name: "app".to_owned(),
                         prefixes: vec!["app".to_owned()],
-                        includes: Vec::new()
+                        includes: Vec::new(),
+                        max_depth: None,
+                        requires: Vec::new(),
+                        pure_dart: false,
+                        require_tests: false,
                     },
                     PackageType {
                         name: "pkg".to_owned(),
                         prefixes: vec!["pkg".to_owned()],
-                        includes: vec!["app".to_owned()]
+                        includes: vec!["app".to_owned()],
+                        max_depth: None,
+                        requires: Vec::new(),
+                        pure_dart: false,
+                        require_tests: false,
                     }
                 ],
                 blacklist: Vec::new(),
                 validations: Vec::new(),
                 public_repositories: Vec::new(),
+                public_package_policy: HashMap::new(),
+                internal_repositories: Vec::new(),
+                private_packages: Vec::new(),
+                dev_only_packages: Vec::new(),
+                structure: HashMap::new(),
+                analysis_options: None,
+                constraint_styles: Vec::new(),
+                alternatives: Vec::new(),
+                include: Vec::new(),
+                exclude: default_exclude(),
+                roots: Vec::new(),
+                custom_validations: Vec::new(),
+                rules: Vec::new(),
+                codeowners: None,
+                http: HttpSettings::default(),
+                check: CheckSettings::default(),
             }
         )
     }
+
+    #[test]
+    fn load_config_include_exclude() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+include:
+- 'apps/**'
+- packages
+exclude:
+- 'legacy/*'
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0)).unwrap();
+
+        assert!(config.is_included_dir("apps/app_one"));
+        assert!(config.is_included_dir("nested/packages/pkg_one"));
+        assert!(!config.is_included_dir("tools/tool_one"));
+
+        assert!(config.is_excluded_dir("legacy/app_old"));
+        assert!(!config.is_excluded_dir("legacy"));
+        assert!(!config.is_excluded_dir("apps/app_one/build"));
+    }
+
+    #[test]
+    fn load_config_default_exclude() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0)).unwrap();
+
+        assert!(config.is_included_dir("apps/app_one"));
+        assert!(config.is_excluded_dir("apps/app_one/build"));
+        assert!(config.is_excluded_dir("apps/app_one/ios"));
+        assert!(!config.is_excluded_dir("apps/app_one/lib"));
+        assert!(!config.is_excluded_dir("apps/app_one/android_utils"));
+    }
+
+    #[test]
+    fn load_config_private_packages() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+private_packages:
+- '^company_'
+dev_only_packages:
+- '_generator$'
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0)).unwrap();
+
+        assert!(config.is_private_package("company_lints"));
+        assert!(!config.is_private_package("http"));
+
+        assert!(config.is_dev_only_package("build_runner"));
+        assert!(config.is_dev_only_package("company_generator"));
+        assert!(!config.is_dev_only_package("company_lints"));
+    }
+
+    #[test]
+    fn load_config_structure() {
+        let load = |structure: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "package_types: {{ app: {{ dir_prefix: app }} }}\nstructure: {}",
+                structure
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0)).map(|config| config.structure)
+        };
+
+        let structure = load("{ app: [README.md, lib/src/] }").unwrap();
+        assert_eq!(structure["app"], vec!["README.md", "lib/src/"]);
+
+        assert!(load("{ package: [README.md] }").is_err());
+    }
+
+    #[test]
+    fn load_config_constraint_styles() {
+        let load = |styles: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "package_types: {{ app: {{ dir_prefix: app }} }}\nconstraint_styles: {}",
+                styles
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0)).map(|config| config.constraint_styles)
+        };
+
+        let styles = load("[{ packages: 'firebase_*', require: exact, forbid: [any] }]").unwrap();
+        assert_eq!(styles[0].packages, "firebase_*");
+        assert_eq!(styles[0].require, Some(ConstraintStyle::Exact));
+        assert_eq!(styles[0].forbid, vec![ConstraintStyle::Any]);
+        assert!(styles[0].matches("firebase_core"));
+        assert!(!styles[0].matches("http"));
+
+        assert!(load("[{ packages: '*' }]").is_err());
+        assert!(load("[{ packages: '*', require: pinned }]").is_err());
+        assert!(load("[{ forbid: [any] }]").is_err());
+    }
+
+    #[test]
+    fn load_config_http() {
+        let load = |http: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "package_types: {{ app: {{ dir_prefix: app }} }}\nhttp: {}",
+                http
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0)).map(|config| config.http)
+        };
+
+        assert_eq!(
+            load("{ proxy: 'http://proxy:3128', ca_bundle: ca.pem, timeout: 2.5 }").unwrap(),
+            HttpSettings {
+                proxy: Some("http://proxy:3128".to_owned()),
+                ca_bundle: Some("ca.pem".to_owned()),
+                timeout: Some(Duration::from_millis(2500)),
+                user_agent: None,
+                insecure: false,
+            }
+        );
+        assert_eq!(
+            load("{ timeout: 10 }").unwrap().timeout,
+            Some(Duration::from_secs(10))
+        );
+        assert!(load("{ timeout: 0 }").is_err());
+        assert!(load("{ timeout: soon }").is_err());
+    }
+
+    #[test]
+    fn load_config_alternatives() {
+        let load = |alternatives: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "package_types: {{ app: {{ dir_prefix: app }} }}\nalternatives: {}",
+                alternatives
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0)).map(|config| config.alternatives)
+        };
+
+        assert_eq!(
+            load("{ http-clients: 'dio|http | chopper', mocks: [mockito, mocktail] }").unwrap(),
+            vec![
+                AlternativeGroup {
+                    name: "http-clients".to_owned(),
+                    packages: vec!["dio".to_owned(), "http".to_owned(), "chopper".to_owned()],
+                },
+                AlternativeGroup {
+                    name: "mocks".to_owned(),
+                    packages: vec!["mockito".to_owned(), "mocktail".to_owned()],
+                },
+            ]
+        );
+        assert!(load("{ http-clients: dio }").is_err());
+    }
+
+    #[test]
+    fn load_config_check() {
+        let load = |check: &str| {
+            let mut docs = YamlLoader::load_from_str(&format!(
+                "package_types: {{ app: {{ dir_prefix: app }} }}\ncheck: {}",
+                check
+            ))
+            .unwrap();
+            Config::load_from_yaml(docs.remove(0)).map(|config| config.check)
+        };
+
+        assert_eq!(
+            load("{ max_age_days: 365 }").unwrap(),
+            CheckSettings {
+                max_age_days: Some(365),
+                quality: QualityGate::default(),
+                min_dart_sdk: None,
+                publishers: PublisherPolicy::default(),
+            }
+        );
+
+        let quality =
+            load("{ quality: { min_points: 120, min_popularity: 80, exempt: [legacy] } }")
+                .unwrap()
+                .quality;
+        assert_eq!(quality.min_points, Some(120));
+        assert_eq!(quality.min_popularity, Some(80));
+        assert!(quality.applies_to("http"));
+        assert!(!quality.applies_to("legacy"));
+        assert!(!QualityGate::default().applies_to("http"));
+        assert!(load("{ quality: { min_popularity: 120 } }").is_err());
+        assert!(load("{ quality: { min_points: many } }").is_err());
+        assert_eq!(quality.level, ValidationLevel::Error);
+        assert_eq!(
+            load("{ quality: { min_points: 100, level: warn } }")
+                .unwrap()
+                .quality
+                .level,
+            ValidationLevel::Warning
+        );
+        assert!(load("{ quality: { level: fatal } }").is_err());
+
+        assert_eq!(
+            load("{ min_dart_sdk: '3.0' }").unwrap().min_dart_sdk,
+            Some(Version::new(3, 0, 0))
+        );
+        assert!(load("{ min_dart_sdk: latest }").is_err());
+
+        let publishers = load("{ publishers: { verified: true, exempt: [legacy] } }")
+            .unwrap()
+            .publishers;
+        assert_eq!(publishers.level, ValidationLevel::Warning);
+        assert!(publishers.applies_to("http"));
+        assert!(!publishers.applies_to("legacy"));
+        let publishers = load("{ publishers: { allowed: [dart.dev], level: none } }")
+            .unwrap()
+            .publishers;
+        assert!(!publishers.is_enabled());
+        assert!(load("{ publishers: { verified: true, level: fatal } }").is_err());
+        assert_eq!(load("{}").unwrap().max_age_days, None);
+        assert!(load("{ max_age_days: 0 }").is_err());
+        assert!(load("{ max_age_days: a year }").is_err());
+    }
+
+    #[test]
+    fn load_config_roots() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+roots:
+- apps
+- packages
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0)).unwrap();
+
+        assert_eq!(config.roots, vec!["apps".to_owned(), "packages".to_owned()]);
+    }
+
+    #[test]
+    fn find_config_file_upwards() {
+        let root = std::env::temp_dir().join(format!("flcheck-find-config-{}", std::process::id()));
+        let nested = root.join("repo").join("apps").join("app_one");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(root.join("repo").join(".git")).unwrap();
+
+        // configuration outside of the repository is not considered
+        std::fs::write(root.join(CONFIG_FILE), "").unwrap();
+        assert_eq!(find_config_file(&nested), None);
+
+        std::fs::write(root.join("repo").join(CONFIG_FILE), "").unwrap();
+        assert_eq!(
+            find_config_file(&nested),
+            Some(root.join("repo").join(CONFIG_FILE))
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn min_version() {
+        assert!(check_min_version("1.2.0", "1.2.0").is_ok());
+        assert!(check_min_version("1.1", "1.2.0").is_ok());
+        assert!(check_min_version("v1", "1.2.0").is_ok());
+        assert!(check_min_version("1.3", "1.2.0").is_err());
+        assert!(check_min_version("2.0.0", "1.2.0").is_err());
+        assert!(check_min_version("latest", "1.2.0").is_err());
+    }
+
+    #[test]
+    fn load_config_min_version_too_new() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+min_version: '999.0.0'
+package_types:
+  app:
+    dir_prefix: app
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0));
+
+        assert!(matches!(config, Err(FlError::VersionTooOld(_, _))));
+    }
+
+    #[test]
+    fn load_config_scoped_validations() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+validations:
+  validation:dependency:unknown: warn
+  validation:dependency:cyclic:
+    level: warn
+    paths: ['legacy/.*']
+  validation:dependency:unallowed:
+    - level: none
+      paths: ['/generated/']
+    - level: warn
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0)).unwrap();
+
+        let cyclic = ValidationType::CyclicDependency;
+        let unallowed = ValidationType::DependencyNotAllowed;
+
+        assert_eq!(config.validations.len(), 4);
+        assert_eq!(config.validation_level(&cyclic), ValidationLevel::Error);
+        assert_eq!(
+            config.validation_level_at(&cyclic, "/repo/legacy/app_old"),
+            ValidationLevel::Warning
+        );
+        assert_eq!(
+            config.validation_level_at(&cyclic, "/repo/apps/app_new"),
+            ValidationLevel::Error
+        );
+        assert_eq!(
+            config.validation_level_at(&unallowed, "/repo/generated/app_gen"),
+            ValidationLevel::None
+        );
+        assert_eq!(
+            config.validation_level_at(&unallowed, "/repo/apps/app_new"),
+            ValidationLevel::Warning
+        );
+        assert_eq!(
+            config.validation_level_at(&ValidationType::UnknownDependency, "/repo/legacy/app_old"),
+            ValidationLevel::Warning
+        );
+    }
+
+    #[test]
+    fn load_config_scoped_validations_invalid_level() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+validations:
+  validation:dependency:cyclic:
+    paths: ['legacy/.*']
+    ",
+        )
+        .unwrap();
+        let config = Config::load_from_yaml(docs.remove(0));
+
+        assert!(matches!(config, Err(FlError::InvalidValidationLevel(_, _))));
+    }
+
+    #[test]
+    fn merge_extended_yaml() {
+        let mut parent = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+validations:
+  validation:dependency:cyclic: error
+  validation:dependency:unknown: error
+exclude:
+  - build
+    ",
+        )
+        .unwrap();
+        let mut child = YamlLoader::load_from_str(
+            "
+extends: base.yaml
+validations:
+  validation:dependency:cyclic: warn
+exclude:
+  - generated
+    ",
+        )
+        .unwrap();
+
+        let merged = merge_yaml(parent.remove(0), child.remove(0));
+
+        assert_eq!(
+            merged["package_types"]["app"]["dir_prefix"].as_str(),
+            Some("app")
+        );
+        assert_eq!(
+            merged["validations"]["validation:dependency:cyclic"].as_str(),
+            Some("warn")
+        );
+        assert_eq!(
+            merged["validations"]["validation:dependency:unknown"].as_str(),
+            Some("error")
+        );
+        assert_eq!(yaml_str_list(&merged["exclude"]), vec!["generated"]);
+    }
+
+    #[test]
+    fn resolve_extends() {
+        let file = ConfigSource::File(PathBuf::from("/repo/flcheck.yaml"));
+        let url = ConfigSource::Url(Url::parse("https://example.com/policy/base.yaml").unwrap());
+
+        assert_eq!(
+            file.resolve("../shared/base.yaml").unwrap(),
+            ConfigSource::File(PathBuf::from("/repo/../shared/base.yaml"))
+        );
+        assert_eq!(
+            file.resolve("https://example.com/base.yaml").unwrap(),
+            ConfigSource::Url(Url::parse("https://example.com/base.yaml").unwrap())
+        );
+        assert_eq!(
+            url.resolve("common.yaml").unwrap(),
+            ConfigSource::Url(Url::parse("https://example.com/policy/common.yaml").unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn load_extended_config() {
+        let root = std::env::temp_dir().join(format!("flcheck-extends-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("shared")).unwrap();
+        std::fs::write(
+            root.join("shared").join("base.yaml"),
+            "
+package_types:
+  app:
+    dir_prefix: app
+validations:
+  validation:dependency:cyclic: warn
+",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join(CONFIG_FILE),
+            "
+extends: shared/base.yaml
+blacklist:
+  - legacy
+",
+        )
+        .unwrap();
+        std::fs::write(root.join("cyclic.yaml"), "extends: cyclic.yaml").unwrap();
+        std::fs::write(
+            root.join("remote.yaml"),
+            "
+extends: https://example.com/base.yaml
+http:
+  ca_bundle: /nonexistent/config.pem
+",
+        )
+        .unwrap();
+
+        let http = HttpSettings::default();
+        let config = Config::load(&root.join(CONFIG_FILE).to_string_lossy(), &[], &http)
+            .await
+            .unwrap();
+        let cyclic = Config::load(&root.join("cyclic.yaml").to_string_lossy(), &[], &http).await;
+
+        assert_eq!(config.package_types.len(), 1);
+        assert_eq!(config.blacklist.len(), 1);
+        assert_eq!(
+            config.validation_level(&ValidationType::CyclicDependency),
+            ValidationLevel::Warning
+        );
+        assert!(matches!(cyclic, Err(FlError::ConfigValidation(_))));
+
+        // URLs are fetched with the http settings of the extending
+        // configuration, command line options take precedence
+        let remote = root.join("remote.yaml").to_string_lossy().to_string();
+        let options = HttpSettings {
+            ca_bundle: Some("/nonexistent/options.pem".to_owned()),
+            ..HttpSettings::default()
+        };
+        for (http, bundle) in [
+            (&http, "/nonexistent/config.pem"),
+            (&options, "/nonexistent/options.pem"),
+        ] {
+            match Config::load(&remote, &[], http).await {
+                Err(FlError::ConfigValidation(message)) => assert!(message.contains(bundle)),
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
+            }
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn override_values() {
+        let mut docs = YamlLoader::load_from_str(
+            "
+package_types:
+  app:
+    dir_prefix: app
+validations:
+  validation:dependency:unknown: warn
+    ",
+        )
+        .unwrap();
+
+        let overridden = [
+            ("validations.validation:dependency:cyclic", "warn"),
+            ("exclude", "[build, generated]"),
+            ("min_version", "1.0"),
+        ]
+        .iter()
+        .fold(docs.remove(0), |yaml, (key, value)| {
+            merge_yaml(yaml, override_yaml(key, value).unwrap())
+        });
+        let config = Config::load_from_yaml(overridden).unwrap();
+
+        assert_eq!(
+            config.validation_level(&ValidationType::CyclicDependency),
+            ValidationLevel::Warning
+        );
+        assert_eq!(
+            config.validation_level(&ValidationType::UnknownDependency),
+            ValidationLevel::Warning
+        );
+        assert_eq!(
+            regex_str_list(&config.exclude),
+            vec![glob_to_regex("build"), glob_to_regex("generated")]
+        );
+        assert!(override_yaml("validations..cyclic", "warn").is_err());
+    }
+
+    #[test]
+    fn build_config() {
+        let config = ConfigBuilder::new()
+            .package_type(PackageType::new("app", &["app_"], &["package"]))
+            .package_type(PackageType::new("package", &["pkg_"], &["package"]))
+            .blacklist("/legacy/")
+            .validation(ValidationType::CyclicDependency, ValidationLevel::Warning)
+            .public_repository("github.com/company/")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.package_types.len(), 2);
+        assert!(config.is_blacklisted("/ws/legacy/pkg_old"));
+        assert!(config.is_public_repo("https://github.com/company/utils.git"));
+        assert_eq!(
+            config.validation_level(&ValidationType::CyclicDependency),
+            ValidationLevel::Warning
+        );
+        assert_eq!(
+            regex_str_list(&config.exclude),
+            regex_str_list(&Config::default().exclude)
+        );
+
+        // validated like a loaded configuration
+        assert!(ConfigBuilder::new().build().is_err());
+        assert!(ConfigBuilder::new()
+            .package_type(PackageType::new("app", &["app_"], &["unknown"]))
+            .build()
+            .is_err());
+        assert!(ConfigBuilder::new()
+            .package_type(PackageType::new("app", &["app_"], &[]))
+            .blacklist("(")
+            .build()
+            .is_err());
+    }
 }
@@ -0,0 +1,262 @@
+use crate::config::DependencyRule;
+use crate::config::PackageAttribute;
+use crate::config::PackagePattern;
+use crate::error::PackageValidation;
+use crate::error::ValidationType;
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::Pubspec;
+
+/// Whether the given package matches the pattern. Packages outside of
+/// the workspace (`package` is `None`) are matched by name only.
+fn matches(
+    pattern: &PackagePattern,
+    name: &str,
+    package: Option<&Pubspec>,
+    config: &Config,
+) -> bool {
+    match pattern.attribute {
+        PackageAttribute::Name => pattern.matches(name),
+        PackageAttribute::Type => package
+            .and_then(|package| package.package_type(config))
+            .is_some_and(|pkg_type| pattern.matches(&pkg_type.name)),
+        PackageAttribute::Owner => package
+            .and_then(|package| package.owner.as_ref())
+            .is_some_and(|owner| pattern.matches(owner)),
+        PackageAttribute::Tag => {
+            package.is_some_and(|package| package.tags.iter().any(|tag| pattern.matches(tag)))
+        }
+    }
+}
+
+/// Evaluate all declarative `rules` of the `Config` against the (non-dev)
+/// dependencies of the given package.
+///
+/// Dependencies are matched by the name of the workspace package they
+/// resolve to or their own name otherwise (e.g. pub.dev packages).
+pub fn rule_validations(
+    pubspec: &Pubspec,
+    config: &Config,
+    workspace: &Workspace,
+) -> Vec<PackageValidation> {
+    let deny = pubspec.is_enabled(config, &ValidationType::DeniedByRule);
+    let require = pubspec.is_enabled(config, &ValidationType::RequiredByRule);
+    if config.rules.is_empty() || !(deny || require) {
+        return vec![];
+    }
+
+    let dependencies = pubspec
+        .dependencies
+        .iter()
+        .map(|dep| {
+            let resolved = pubspec.resolve_internal(dep, config, workspace);
+            let name = resolved
+                .map(|resolved| &resolved.name)
+                .unwrap_or_else(|| dep.name());
+            (dep.name(), name, resolved)
+        })
+        .collect::<Vec<_>>();
+
+    let validation = |error: String, code: ValidationType, description: &Option<String>, dep| {
+        PackageValidation {
+            package_name: pubspec.name.clone(),
+            error,
+            description: description.clone(),
+            level: config.validation_level_at(&code, &pubspec.dir_path),
+            code,
+            dependency: dep,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        }
+    };
+
+    let mut validations = Vec::new();
+
+    for rule in config.rules.iter() {
+        match rule {
+            DependencyRule::Deny {
+                from,
+                to,
+                description,
+            } if deny && matches(from, &pubspec.name, Some(pubspec), config) => {
+                for (dep_name, name, resolved) in dependencies.iter() {
+                    if matches(to, name, *resolved, config) {
+                        validations.push(validation(
+                            format!("dependency '{}' denied by rule {} -> {}", name, from, to),
+                            ValidationType::DeniedByRule,
+                            description,
+                            Some(dep_name.to_string()),
+                        ));
+                    }
+                }
+            }
+            DependencyRule::Require {
+                package,
+                depends_on,
+                description,
+            } if require
+                && matches(package, &pubspec.name, Some(pubspec), config)
+                && !dependencies
+                    .iter()
+                    .any(|(_, name, resolved)| matches(depends_on, name, *resolved, config)) =>
+            {
+                validations.push(validation(
+                    format!(
+                        "missing dependency on '{}' required by rule {}",
+                        depends_on, package
+                    ),
+                    ValidationType::RequiredByRule,
+                    description,
+                    None,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    validations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DependencyRule;
+    use crate::config::PackagePattern;
+    use crate::config::PackageType;
+    use crate::config::ValidationSetting;
+    use crate::constraint::rule_validations;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::workspace::Workspace;
+    use crate::Config;
+    use crate::Pubspec;
+
+    fn pattern(pattern: &str) -> PackagePattern {
+        PackagePattern::new(pattern).unwrap()
+    }
+
+    #[test]
+    fn deny_and_require() {
+        let config = Config {
+            rules: vec![
+                DependencyRule::Deny {
+                    from: pattern("app_*"),
+                    to: pattern("pkg_experimental_*"),
+                    description: None,
+                },
+                DependencyRule::Require {
+                    package: pattern("app_*"),
+                    depends_on: pattern("shared_analytics"),
+                    description: Some("apps have to track usage".to_owned()),
+                },
+            ],
+            ..Default::default()
+        };
+        let all = vec![
+            Pubspec::test("app_foo").with_local_deps(&["pkg_experimental_ui"]),
+            Pubspec::test("app_bar").with_local_deps(&["shared_analytics", "pkg_experimental_ui"]),
+            Pubspec::test("pkg_experimental_ui").with_local_deps(&["shared_analytics"]),
+            Pubspec::test("shared_analytics"),
+        ];
+
+        let codes = |pubspec: &Pubspec| {
+            rule_validations(pubspec, &config, &Workspace::new(&all))
+                .into_iter()
+                .map(|validation| validation.code)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            codes(&all[0]),
+            vec![ValidationType::DeniedByRule, ValidationType::RequiredByRule]
+        );
+        assert_eq!(codes(&all[1]), vec![ValidationType::DeniedByRule]);
+        assert_eq!(codes(&all[2]), vec![]);
+
+        let validations = rule_validations(&all[0], &config, &Workspace::new(&all));
+        assert_eq!(
+            validations[0].error,
+            "dependency 'pkg_experimental_ui' denied by rule app_* -> pkg_experimental_*"
+        );
+        assert_eq!(
+            validations[1].description,
+            Some("apps have to track usage".to_owned())
+        );
+
+        // rules of disabled validations are not evaluated at all
+        let config = Config {
+            validations: vec![ValidationSetting {
+                validation_type: ValidationType::DeniedByRule,
+                level: ValidationLevel::None,
+                paths: Vec::new(),
+            }],
+            ..config
+        };
+        let codes = rule_validations(&all[0], &config, &Workspace::new(&all))
+            .into_iter()
+            .map(|validation| validation.code)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec![ValidationType::RequiredByRule]);
+    }
+
+    #[test]
+    fn package_attributes() {
+        let config = Config {
+            package_types: vec![PackageType {
+                name: "shared".to_owned(),
+                prefixes: vec!["shared_".to_owned()],
+                includes: Vec::new(),
+                max_depth: None,
+                requires: Vec::new(),
+                pure_dart: false,
+                require_tests: false,
+            }],
+            rules: vec![
+                DependencyRule::Deny {
+                    from: pattern("type:shared"),
+                    to: pattern("tag:legacy"),
+                    description: None,
+                },
+                DependencyRule::Require {
+                    package: pattern("owner:team-*"),
+                    depends_on: pattern("shared_*"),
+                    description: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let all = vec![
+            Pubspec {
+                package_type: Some("shared".to_owned()),
+                owner: Some("team-payments".to_owned()),
+                ..Pubspec::test("ui_kit").with_local_deps(&["old_ui", "shared_core"])
+            },
+            Pubspec {
+                tags: vec!["legacy".to_owned()],
+                ..Pubspec::test("old_ui")
+            },
+            Pubspec {
+                owner: Some("team-core".to_owned()),
+                ..Pubspec::test("shared_core").with_local_deps(&["old_ui"])
+            },
+        ];
+
+        let codes = |pubspec: &Pubspec| {
+            rule_validations(pubspec, &config, &Workspace::new(&all))
+                .into_iter()
+                .map(|validation| validation.code)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(codes(&all[0]), vec![ValidationType::DeniedByRule]);
+        assert_eq!(codes(&all[1]), vec![]);
+        assert_eq!(
+            codes(&all[2]),
+            vec![ValidationType::DeniedByRule, ValidationType::RequiredByRule]
+        );
+    }
+}
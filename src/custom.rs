@@ -0,0 +1,202 @@
+use crate::config::CustomValidation;
+use crate::error::FlError;
+use crate::error::FlError::CustomValidationError;
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::error::ValidationType;
+use crate::report::Report;
+use crate::report::ReportContent;
+use crate::Config;
+use crate::Pubspec;
+use serde::Deserialize;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+
+/// Single finding as printed by a custom validation command.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CustomFinding {
+    /// Name of the package the finding refers to
+    pub package: String,
+    pub message: String,
+    #[serde(default)]
+    pub dependency: Option<String>,
+    /// Description overriding the one of the custom validation (if any)
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Run all configured custom validations (except those with level `none`)
+/// against the workspace and collect their findings.
+pub fn run_custom_validations(
+    config: &Config,
+    pubspecs: &[Pubspec],
+) -> Result<Vec<PackageValidation>, FlError> {
+    let mut validations = Vec::new();
+
+    for custom in config
+        .custom_validations
+        .iter()
+        .filter(|custom| custom.level != ValidationLevel::None)
+    {
+        let findings = run_command(custom, pubspecs)?;
+
+        validations.extend(findings.into_iter().map(|finding| PackageValidation {
+            package_name: finding.package,
+            error: finding.message,
+            description: finding.description.or_else(|| custom.description.clone()),
+            code: ValidationType::Custom(custom.code.clone()),
+            level: custom.level.clone(),
+            dependency: finding.dependency,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        }));
+    }
+
+    Ok(validations)
+}
+
+/// Execute the command of the given custom validation (via the shell),
+/// writing the workspace dump (identical to the JSON output of the `dump`
+/// command) to its stdin and parsing its stdout.
+fn run_command(
+    custom: &CustomValidation,
+    pubspecs: &[Pubspec],
+) -> Result<Vec<CustomFinding>, FlError> {
+    let failed = |msg: String| CustomValidationError(custom.code.clone(), msg);
+
+    let input = serde_json::to_vec(&Report::new(ReportContent::Dump { pubspecs }))
+        .map_err(|err| failed(err.to_string()))?;
+
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+
+    let mut child = Command::new(shell)
+        .args([flag, &custom.command])
+        .current_dir(&custom.working_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|err| failed(err.to_string()))?;
+
+    // write the input from a separate thread so that commands printing
+    // output before consuming all of stdin do not block; commands that
+    // do not read stdin at all are fine as well
+    let stdin = child.stdin.take();
+    let writer = std::thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&input);
+        }
+    });
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| failed(err.to_string()))?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(failed(format!("command exited with {}", output.status)));
+    }
+
+    parse_findings(&output.stdout).map_err(failed)
+}
+
+/// Parse the findings printed by a custom validation command: a JSON list
+/// of objects with `package`, `message` and optionally `dependency` and
+/// `description`. Empty output means no findings.
+pub fn parse_findings(output: &[u8]) -> Result<Vec<CustomFinding>, String> {
+    if output.iter().all(|byte| byte.is_ascii_whitespace()) {
+        return Ok(Vec::new());
+    }
+
+    serde_json::from_slice(output).map_err(|err| format!("invalid output: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::CustomValidation;
+    use crate::custom::parse_findings;
+    use crate::custom::run_custom_validations;
+    use crate::custom::CustomFinding;
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::Config;
+    use crate::Pubspec;
+
+    #[test]
+    fn findings() {
+        assert_eq!(parse_findings(b" \n"), Ok(vec![]));
+        assert_eq!(
+            parse_findings(br#"[{"package": "app_foo", "message": "missing LICENSE"}]"#),
+            Ok(vec![CustomFinding {
+                package: "app_foo".to_owned(),
+                message: "missing LICENSE".to_owned(),
+                dependency: None,
+                description: None,
+            }])
+        );
+        assert!(parse_findings(b"{\"package\": \"app_foo\"}").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_command() {
+        let custom = |code: &str, command: &str, level| CustomValidation {
+            code: code.to_owned(),
+            command: command.to_owned(),
+            level,
+            description: Some("custom rule".to_owned()),
+            working_dir: ".".to_owned(),
+        };
+        let pubspecs = vec![Pubspec::test("app_foo")];
+
+        // the command receives the workspace dump on stdin
+        let config = Config {
+            custom_validations: vec![
+                custom(
+                    "acme:name",
+                    r#"grep -q '"name":"app_foo"' && echo '[{"package": "app_foo", "message": "found"}]'"#,
+                    ValidationLevel::Warning,
+                ),
+                custom("acme:skipped", "exit 1", ValidationLevel::None),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            run_custom_validations(&config, &pubspecs).unwrap(),
+            vec![PackageValidation {
+                package_name: "app_foo".to_owned(),
+                error: "found".to_owned(),
+                description: Some("custom rule".to_owned()),
+                code: ValidationType::Custom("acme:name".to_owned()),
+                level: ValidationLevel::Warning,
+                dependency: None,
+                owner: None,
+                file: None,
+                relative_file: None,
+                line: None,
+                fix: None,
+                repo: None,
+                package_type: None,
+            }]
+        );
+
+        let config = Config {
+            custom_validations: vec![custom("acme:fail", "exit 3", ValidationLevel::Error)],
+            ..Default::default()
+        };
+
+        assert!(run_custom_validations(&config, &pubspecs).is_err());
+    }
+}
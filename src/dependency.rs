@@ -1,7 +1,16 @@
+use crate::error::ValidationType;
+use crate::version::VersionConstraint;
 use crate::Config;
+use schemars::JsonSchema;
+use serde::Deserialize;
 use serde::Serialize;
+use std::fmt;
+use std::fmt::Display;
 
-#[derive(Debug, Serialize)]
+/// Dependency of a package, serialized with its kind as `type` (`local`,
+/// `git` or `pubdev`) along with the overriding dependency (if any).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum Dependency {
     Local {
         name: String,
@@ -83,29 +92,28 @@ impl Dependency {
         }
     }
 
+    /// Parsed version constraint of a pub.dev dependency (if valid).
+    pub fn constraint(&self) -> Option<VersionConstraint> {
+        match self {
+            Dependency::PubDev { version, .. } => VersionConstraint::parse(version),
+            _ => None,
+        }
+    }
+
     /// Whether this dependency is a "local" dependency, meaning
     /// it references a package in the current/same repository.
     pub fn is_local(&self) -> bool {
-        match self {
-            Dependency::Local { .. } => true,
-            _ => false,
-        }
+        matches!(self, Dependency::Local { .. })
     }
 
     /// Whether this dependency is a reference to a git repository.
     pub fn is_git(&self) -> bool {
-        match self {
-            Dependency::Git { .. } => true,
-            _ => false,
-        }
+        matches!(self, Dependency::Git { .. })
     }
 
     /// Whether the dependency is a package hosted on pub.dev
     pub fn is_pubdev(&self) -> bool {
-        match self {
-            Dependency::PubDev { .. } => true,
-            _ => false,
-        }
+        matches!(self, Dependency::PubDev { .. })
     }
 
     /// Whether this dependency refers to a git repository that
@@ -116,4 +124,51 @@ impl Dependency {
             _ => false,
         }
     }
+
+    /// Whether this dependency is a reference to an internal git repository
+    /// (see `internal_repositories`), meaning it refers to a package of the
+    /// workspace.
+    pub fn is_internal(&self, config: &Config) -> bool {
+        match self {
+            Dependency::Git { git, .. } => config.is_internal_repo(git),
+            _ => false,
+        }
+    }
+}
+
+/// Settings of a dependency declared in a `flcheck` section nested under the
+/// dependency in the pubspec.yaml, e.g. exceptions of single validations.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct DependencyAnnotation {
+    /// Name of the annotated dependency
+    pub dependency: String,
+    /// Why the dependency (or its exceptions) is needed
+    pub reason: Option<String>,
+    /// Codes of the validations that are ignored for this dependency
+    pub ignore: Vec<String>,
+    /// Whether the dependency refers to a workspace package (just like git
+    /// dependencies on `internal_repositories`)
+    pub internal: bool,
+}
+
+impl DependencyAnnotation {
+    /// Whether findings of the given validation `code` are ignored.
+    pub fn ignores(&self, code: &ValidationType) -> bool {
+        self.ignore.iter().any(|ignored| ignored == code.as_str())
+    }
+}
+
+/// Name and details of the dependency followed by the chain of overriding
+/// dependencies (if any), e.g. `pkg_core [public, ^1.0.0] -> [local, ../pkg_core]`.
+impl Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name(), self.details())?;
+
+        let mut current = self;
+        while let Some(override_dependency) = current.overridden() {
+            write!(f, " -> {}", override_dependency.details())?;
+            current = override_dependency;
+        }
+        Ok(())
+    }
 }
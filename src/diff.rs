@@ -0,0 +1,158 @@
+use crate::error::FlError;
+use crate::error::FlError::InvalidReport;
+use crate::error::PackageValidation;
+use crate::report::REPORT_VERSION;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Findings of two validation runs compared with each other.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct FindingsDiff {
+    /// Findings of the second run only
+    pub new: Vec<PackageValidation>,
+    /// Findings of the first run only
+    pub fixed: Vec<PackageValidation>,
+    /// Findings of both runs
+    pub persisting: Vec<PackageValidation>,
+}
+
+/// Validation report as printed by `validate -o json`.
+#[derive(Deserialize)]
+struct ValidationReport {
+    version: u32,
+    kind: String,
+    findings: Vec<PackageValidation>,
+}
+
+/// Load the findings of the validation report (JSON output of the
+/// `validate` command) in the given `file`.
+pub fn load_findings(file: &str) -> Result<Vec<PackageValidation>, FlError> {
+    let content = std::fs::read_to_string(file)?;
+
+    parse_findings(&content).map_err(|err| InvalidReport(file.to_owned(), err))
+}
+
+fn parse_findings(content: &str) -> Result<Vec<PackageValidation>, String> {
+    let report: ValidationReport = serde_json::from_str(content).map_err(|err| err.to_string())?;
+
+    if report.kind != "validations" {
+        return Err(format!(
+            "expected a report of kind 'validations' (found '{}')",
+            report.kind
+        ));
+    }
+    if report.version > REPORT_VERSION {
+        return Err(format!(
+            "unsupported version {} (supported: {})",
+            report.version, REPORT_VERSION
+        ));
+    }
+
+    Ok(report.findings)
+}
+
+/// Whether both findings refer to the same problem. Annotations like the
+/// owner or the line in the pubspec are not considered.
+fn same_finding(a: &PackageValidation, b: &PackageValidation) -> bool {
+    a.repo == b.repo
+        && a.package_name == b.package_name
+        && a.code == b.code
+        && a.dependency == b.dependency
+        && a.error == b.error
+}
+
+/// Compare the findings `before` with the ones `after` some change.
+pub fn diff_findings(
+    before: Vec<PackageValidation>,
+    after: Vec<PackageValidation>,
+) -> FindingsDiff {
+    let mut fixed = before;
+    let mut new = Vec::new();
+    let mut persisting = Vec::new();
+
+    for finding in after {
+        // every finding before matches one finding after at most
+        match fixed.iter().position(|other| same_finding(other, &finding)) {
+            Some(idx) => {
+                fixed.remove(idx);
+                persisting.push(finding);
+            }
+            None => new.push(finding),
+        }
+    }
+
+    FindingsDiff {
+        new,
+        fixed,
+        persisting,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::diff_findings;
+    use crate::diff::parse_findings;
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+
+    fn finding(package: &str, dependency: &str) -> PackageValidation {
+        PackageValidation {
+            package_name: package.to_owned(),
+            error: format!("dependency to '{}' is not allowed", dependency),
+            description: None,
+            code: ValidationType::DependencyNotAllowed,
+            level: ValidationLevel::Error,
+            dependency: Some(dependency.to_owned()),
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        }
+    }
+
+    #[test]
+    fn diff() {
+        let before = vec![
+            finding("app_foo", "app_bar"),
+            finding("pkg_core", "app_foo"),
+        ];
+        let after = vec![
+            PackageValidation {
+                line: Some(4),
+                ..finding("pkg_core", "app_foo")
+            },
+            finding("pkg_core", "app_bar"),
+        ];
+
+        let diff = diff_findings(before, after);
+
+        assert_eq!(diff.new, vec![finding("pkg_core", "app_bar")]);
+        assert_eq!(diff.fixed, vec![finding("app_foo", "app_bar")]);
+        assert_eq!(diff.persisting[0].package_name, "pkg_core");
+    }
+
+    #[test]
+    fn parse() {
+        let findings = parse_findings(
+            r#"{"version":1,"kind":"validations","findings":[{"package_name":"app_foo",
+            "error":"found","description":null,"code":"acme:license","level":"warn",
+            "dependency":null}],"summary":{"errors":0,"warnings":1,"packages":1}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            findings[0].code,
+            ValidationType::Custom("acme:license".to_owned())
+        );
+        assert_eq!(findings[0].level, ValidationLevel::Warning);
+
+        assert!(parse_findings(r#"{"version":1,"kind":"dump","pubspecs":[]}"#).is_err());
+        assert!(parse_findings(r#"{"version":2,"kind":"validations","findings":[]}"#).is_err());
+        assert!(parse_findings("[]").is_err());
+    }
+}
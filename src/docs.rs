@@ -0,0 +1,266 @@
+use crate::codeowners::CodeOwners;
+use crate::graph::Graph;
+use crate::graph::GraphOptions;
+use crate::util::relative_path;
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::Pubspec;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Directory the `docs` command writes to unless given via `--out`.
+pub const DOCS_DIR: &str = "docs";
+
+/// Overview page of the generated documentation.
+pub const INDEX_PAGE: &str = "index.md";
+
+/// Markdown page of the generated workspace documentation.
+#[derive(Debug, PartialEq)]
+pub struct DocsPage {
+    /// File name of the page (relative to the output directory)
+    pub file: String,
+    pub content: String,
+}
+
+/// Metadata of a package as documented on the overview and its own page.
+struct PackageInfo<'a> {
+    pubspec: &'a Pubspec,
+    package_type: String,
+    owner: Option<String>,
+}
+
+fn page_file(pubspec: &Pubspec) -> String {
+    format!("{}.md", pubspec.name)
+}
+
+fn link(pubspec: &Pubspec) -> String {
+    format!("[{}]({})", pubspec.name, page_file(pubspec))
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Generate the markdown documentation of the workspace: an overview
+/// (`INDEX_PAGE`) with the graph of the internal (non-dev) dependencies and
+/// one page per package listing its metadata, dependencies and dependents.
+///
+/// Owners are taken from the pubspecs or the given `codeowners`, paths are
+/// relative to `root`.
+pub fn workspace_docs(
+    config: &Config,
+    pubspecs: &[Pubspec],
+    codeowners: Option<&CodeOwners>,
+    root: &Path,
+) -> Vec<DocsPage> {
+    let packages = pubspecs
+        .iter()
+        .filter(|pubspec| !pubspec.name.is_empty())
+        .map(|pubspec| PackageInfo {
+            pubspec,
+            package_type: pubspec
+                .package_type(config)
+                .map(|pkg_type| pkg_type.name.clone())
+                .unwrap_or_else(|| "-".to_owned()),
+            owner: pubspec
+                .owner
+                .clone()
+                .or_else(|| codeowners.and_then(|codeowners| codeowners.owner(pubspec))),
+        })
+        .collect::<Vec<_>>();
+
+    let workspace = Workspace::new(pubspecs);
+    let mut pages = vec![DocsPage {
+        file: INDEX_PAGE.to_owned(),
+        content: overview(&packages, &workspace),
+    }];
+    pages.extend(packages.iter().map(|package| DocsPage {
+        file: page_file(package.pubspec),
+        content: package_page(package, config, &workspace, root),
+    }));
+
+    pages
+}
+
+fn overview(packages: &[PackageInfo], workspace: &Workspace) -> String {
+    let mut graph = Graph::from_workspace(workspace, &GraphOptions::default());
+    let nodes = graph.nodes.clone();
+    graph
+        .edges
+        .retain(|edge| !edge.dev && nodes.contains(&edge.to));
+    graph.reduce();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Workspace\n");
+    let _ = writeln!(out, "## Architecture\n");
+    let _ = writeln!(
+        out,
+        "Internal dependencies (without dev dependencies and those implied by transitive ones).\n"
+    );
+    let _ = writeln!(out, "```mermaid\ngraph TD");
+    for node in graph.nodes.iter().filter(|node| !node.is_empty()) {
+        let _ = writeln!(out, "  {}", node);
+    }
+    for edge in graph.edges.iter() {
+        let _ = writeln!(out, "  {} --> {}", edge.from, edge.to);
+    }
+    let _ = writeln!(out, "```\n");
+
+    let _ = writeln!(out, "## Packages\n");
+    let _ = writeln!(out, "| Package | Type | Owner | Public |");
+    let _ = writeln!(out, "| --- | --- | --- | --- |");
+    for package in packages {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            link(package.pubspec),
+            package.package_type,
+            package.owner.as_deref().unwrap_or("-"),
+            yes_no(package.pubspec.is_public)
+        );
+    }
+
+    out
+}
+
+fn package_page(
+    package: &PackageInfo,
+    config: &Config,
+    workspace: &Workspace,
+    root: &Path,
+) -> String {
+    let pubspec = package.pubspec;
+    let path = relative_path(root, Path::new(&pubspec.dir_path))
+        .filter(|path| !path.is_empty())
+        .unwrap_or_else(|| pubspec.dir_path.clone());
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", pubspec.name);
+    let _ = writeln!(out, "[Workspace]({})\n", INDEX_PAGE);
+    let _ = writeln!(out, "| | |\n| --- | --- |");
+    let _ = writeln!(out, "| Type | {} |", package.package_type);
+    let _ = writeln!(
+        out,
+        "| Owner | {} |",
+        package.owner.as_deref().unwrap_or("-")
+    );
+    let _ = writeln!(out, "| Public | {} |", yes_no(pubspec.is_public));
+    let _ = writeln!(
+        out,
+        "| Version | {} |",
+        pubspec.version.as_deref().unwrap_or("-")
+    );
+    let _ = writeln!(out, "| Path | `{}` |", path);
+
+    for (title, deps) in [
+        ("Dependencies", &pubspec.dependencies),
+        ("Dev dependencies", &pubspec.dev_dependencies),
+    ] {
+        let _ = writeln!(out, "\n## {}\n", title);
+        if deps.is_empty() {
+            let _ = writeln!(out, "None");
+        }
+        for dep in deps {
+            let details = dep.effective().details();
+            let details = details.trim_start_matches('[').trim_end_matches(']');
+            match pubspec.resolve_internal(dep, config, workspace) {
+                Some(resolved) => {
+                    let _ = writeln!(out, "- {} ({})", link(resolved), details);
+                }
+                None => {
+                    let _ = writeln!(out, "- {} ({})", dep.name(), details);
+                }
+            }
+        }
+    }
+
+    // packages directly depending on this one
+    let _ = writeln!(out, "\n## Dependents\n");
+    let dependents = workspace
+        .packages
+        .iter()
+        .filter(|other| !other.name.is_empty())
+        .flat_map(|other| {
+            let depends = |deps: &[crate::dependency::Dependency]| {
+                deps.iter().any(|dep| {
+                    other
+                        .resolve_internal(dep, config, workspace)
+                        .is_some_and(|resolved| resolved.path == pubspec.path)
+                })
+            };
+
+            if depends(&other.dependencies) {
+                Some(link(other))
+            } else if depends(&other.dev_dependencies) {
+                Some(format!("{} (dev)", link(other)))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if dependents.is_empty() {
+        let _ = writeln!(out, "None");
+    }
+    for dependent in dependents {
+        let _ = writeln!(out, "- {}", dependent);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dependency::Dependency;
+    use crate::docs::workspace_docs;
+    use crate::docs::INDEX_PAGE;
+    use crate::Config;
+    use crate::Pubspec;
+    use std::path::Path;
+
+    #[test]
+    fn pages() {
+        let pubspecs = vec![
+            Pubspec::test_at("pkg_core", "/ws/pkg_core").with_owner("team-pkg_core"),
+            Pubspec {
+                dev_dependencies: vec![Dependency::PubDev {
+                    name: "mockito".to_owned(),
+                    version: "^5.0.0".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("pkg_ui", "/ws/pkg_ui")
+                    .with_local_deps(&["pkg_core"])
+                    .with_owner("team-pkg_ui")
+            },
+            Pubspec::test_at("app_shop", "/ws/app_shop")
+                .with_local_deps(&["pkg_ui", "pkg_core"])
+                .with_owner("team-app_shop"),
+        ];
+
+        let pages = workspace_docs(&Config::default(), &pubspecs, None, Path::new("/ws"));
+        let files = pages
+            .iter()
+            .map(|page| page.file.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![INDEX_PAGE, "pkg_core.md", "pkg_ui.md", "app_shop.md"]
+        );
+
+        // the transitively implied dependency is omitted in the graph
+        let index = &pages[0].content;
+        assert!(index.contains("  app_shop --> pkg_ui\n"));
+        assert!(!index.contains("app_shop --> pkg_core"));
+        assert!(index.contains("| [pkg_ui](pkg_ui.md) | - | team-pkg_ui | no |"));
+
+        let ui = &pages[2].content;
+        assert!(ui.contains("| Path | `pkg_ui` |"));
+        assert!(ui.contains("## Dependencies\n\n- [pkg_core](pkg_core.md) (local, ../pkg_core)\n"));
+        assert!(ui.contains("## Dev dependencies\n\n- mockito (public, ^5.0.0)\n"));
+        assert!(ui.contains("## Dependents\n\n- [app_shop](app_shop.md)\n"));
+        assert!(pages[3].content.contains("## Dependents\n\nNone\n"));
+    }
+}
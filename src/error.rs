@@ -1,3 +1,11 @@
+use schemars::gen::SchemaGenerator;
+use schemars::schema::InstanceType;
+use schemars::schema::Metadata;
+use schemars::schema::Schema;
+use schemars::schema::SchemaObject;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 use std::fmt::Display;
@@ -15,14 +23,143 @@ pub enum FlError {
     HttpError(#[from] reqwest::Error),
     #[error("invalid configuration: {0}")]
     ConfigValidation(String),
+    #[error("configuration requires flcheck {0} or newer (installed: {1}), please upgrade")]
+    VersionTooOld(String, String),
     #[error("validation: {0} error(s)")]
     ValidationError(u32),
+    #[error("validation: {0} warning(s)")]
+    ValidationWarnings(u32),
+    #[error("validation: {0} warning(s) exceed the maximum of {1}")]
+    TooManyWarnings(u32, u32),
     #[error("no input files found (directory: {0})")]
     NoInputFiles(String),
     #[error("invalid validation type '{0}'")]
     InvalidValidationType(String),
     #[error("invalid validation level '{0}' [{1}] (supported: error, warn, none)")]
     InvalidValidationLevel(String, String),
+    #[error("failed to write output: {0}")]
+    OutputError(std::io::Error),
+    #[error("package(s) not found on pub.dev: {0} (see --allow-unknown)")]
+    UnknownPackages(String),
+    #[error("unknown package '{0}'")]
+    UnknownPackage(String),
+    #[error("configuration file '{0}' already exists (use --force to overwrite)")]
+    ConfigExists(String),
+    #[error("failed to run graphviz 'dot': {0}")]
+    GraphvizError(String),
+    #[error("cyclic dependency: {0}")]
+    DependencyCycle(String),
+    #[error("invalid version of package '{0}': {1}")]
+    InvalidVersion(String, String),
+    #[error("hook '{0}' already exists (use --force to overwrite)")]
+    HookExists(String),
+    #[error("git failed: {0}")]
+    GitError(String),
+    #[error("custom validation '{0}' failed: {1}")]
+    CustomValidationError(String, String),
+    #[error("invalid report '{0}': {1}")]
+    InvalidReport(String, String),
+    #[error("diff: {0} new finding(s)")]
+    NewFindings(u32),
+    #[error("invalid snapshot '{0}': {1}")]
+    InvalidSnapshot(String, String),
+    #[error("snapshot: {0} difference(s) to the workspace (update via `snapshot write`)")]
+    SnapshotDrift(u32),
+    #[error("strict: {0} package(s) skipped, without name or with multiple YAML documents")]
+    SkippedPackages(u32),
+    #[error("check: {0} git dependency(ies) unreachable or referring to missing refs")]
+    BrokenGitDependencies(u32),
+    #[error("check: {0} dependency(ies) rejected by the configured policies")]
+    RejectedDependencies(u32),
+    #[error("pub.dev requested to retry '{0}' after {1}s (waiting at most {2}s)")]
+    RateLimited(String, u64, u64),
+}
+
+/// Successful execution
+pub const EXIT_SUCCESS: i32 = 0;
+/// Validation (or check) failed
+pub const EXIT_VALIDATION: i32 = 1;
+/// Invalid configuration or command line arguments
+pub const EXIT_CONFIG: i32 = 2;
+/// Failed to read input or write output
+pub const EXIT_IO: i32 = 3;
+/// Network request failed
+pub const EXIT_NETWORK: i32 = 4;
+
+/// Description of an exit code of the flcheck executable.
+#[derive(Debug, Serialize)]
+pub struct ExitCode {
+    pub code: i32,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// List of all exit codes of the flcheck executable.
+pub fn exit_codes() -> Vec<ExitCode> {
+    vec![
+        ExitCode {
+            code: EXIT_SUCCESS,
+            name: "success",
+            description: "successful execution",
+        },
+        ExitCode {
+            code: EXIT_VALIDATION,
+            name: "validation",
+            description: "validation (or check) failed according to the fail level",
+        },
+        ExitCode {
+            code: EXIT_CONFIG,
+            name: "config",
+            description: "invalid configuration or command line arguments",
+        },
+        ExitCode {
+            code: EXIT_IO,
+            name: "io",
+            description: "failed to read input files or write output",
+        },
+        ExitCode {
+            code: EXIT_NETWORK,
+            name: "network",
+            description: "network request (e.g. to pub.dev) failed",
+        },
+    ]
+}
+
+impl FlError {
+    /// Exit code the executable terminates with on this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            FlError::ValidationError(_)
+            | FlError::ValidationWarnings(_)
+            | FlError::TooManyWarnings(_, _)
+            | FlError::UnknownPackages(_)
+            | FlError::DependencyCycle(_)
+            | FlError::NewFindings(_)
+            | FlError::SnapshotDrift(_)
+            | FlError::SkippedPackages(_)
+            | FlError::BrokenGitDependencies(_)
+            | FlError::RejectedDependencies(_) => EXIT_VALIDATION,
+            FlError::NoConfigFound(_)
+            | FlError::YamlReadError(_)
+            | FlError::ConfigValidation(_)
+            | FlError::VersionTooOld(_, _)
+            | FlError::InvalidValidationType(_)
+            | FlError::InvalidValidationLevel(_, _)
+            | FlError::UnknownPackage(_)
+            | FlError::InvalidVersion(_, _)
+            | FlError::HookExists(_)
+            | FlError::InvalidReport(_, _)
+            | FlError::InvalidSnapshot(_, _)
+            | FlError::ConfigExists(_) => EXIT_CONFIG,
+            FlError::FileReadError(_)
+            | FlError::OutputError(_)
+            | FlError::NoInputFiles(_)
+            | FlError::GraphvizError(_)
+            | FlError::GitError(_)
+            | FlError::CustomValidationError(_, _) => EXIT_IO,
+            FlError::HttpError(_) | FlError::RateLimited(_, _, _) => EXIT_NETWORK,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,7 +170,7 @@ pub enum ValidationLevel {
 }
 
 impl ValidationLevel {
-    fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         match self {
             ValidationLevel::Error => "error",
             ValidationLevel::Warning => "warn",
@@ -50,10 +187,10 @@ impl ValidationLevel {
         LEVELS.iter()
     }
 
-    pub fn from_str(input: &str) -> Option<ValidationLevel> {
+    pub fn parse(input: &str) -> Option<ValidationLevel> {
         ValidationLevel::values()
             .find(|level| level.as_str() == input)
-            .map(|level| level.clone())
+            .cloned()
     }
 }
 
@@ -75,6 +212,37 @@ impl Serialize for ValidationLevel {
     }
 }
 
+impl<'de> Deserialize<'de> for ValidationLevel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let level = String::deserialize(deserializer)?;
+        ValidationLevel::parse(&level).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid validation level '{}'", level))
+        })
+    }
+}
+
+impl JsonSchema for ValidationLevel {
+    fn schema_name() -> String {
+        "ValidationLevel".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(
+                ValidationLevel::values()
+                    .map(|level| level.as_str().into())
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValidationType {
     GitDevDependency,
@@ -82,10 +250,38 @@ pub enum ValidationType {
     DependencyNotAllowed,
     CyclicDependency,
     NonGitDependencyInPublicPackage,
+    IncompatibleVersion,
+    PrivateDependencyInPublicPackage,
+    ForeignRepositoryDependencyInPublicPackage,
+    DependencyDepth,
+    CrossAppDependency,
+    SelfDependency,
+    DuplicateDependency,
+    OutsideWorkspace,
+    MissingRequiredDependency,
+    NativePluginDependency,
+    MissingAsset,
+    MalformedDependency,
+    InvalidPubspec,
+    DeniedByRule,
+    RequiredByRule,
+    DevOnlyDependency,
+    UndeclaredImport,
+    UnusedDependency,
+    MissingTests,
+    MissingStructure,
+    AnalysisOptions,
+    ConstraintStyle,
+    PublicChangelog,
+    AlternativePackages,
+    DependencyPathMismatch,
+    DuplicatePackage,
+    /// Finding of a command configured in `custom_validations`
+    Custom(String),
 }
 
 impl ValidationType {
-    fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         match self {
             ValidationType::GitDevDependency => "validation:dev-dependency:git",
             ValidationType::UnknownDependency => "validation:dependency:unknown",
@@ -94,24 +290,108 @@ impl ValidationType {
             ValidationType::NonGitDependencyInPublicPackage => {
                 "validation:public:dependency:non-git"
             }
+            ValidationType::IncompatibleVersion => "validation:dependency:version",
+            ValidationType::PrivateDependencyInPublicPackage => {
+                "validation:public:dependency:private"
+            }
+            ValidationType::ForeignRepositoryDependencyInPublicPackage => {
+                "validation:public:dependency:repository"
+            }
+            ValidationType::DependencyDepth => "validation:dependency:depth",
+            ValidationType::CrossAppDependency => "validation:dependency:cross-app",
+            ValidationType::SelfDependency => "validation:dependency:self",
+            ValidationType::DuplicateDependency => "validation:dependency:duplicate",
+            ValidationType::OutsideWorkspace => "validation:dependency:outside-workspace",
+            ValidationType::MissingRequiredDependency => "validation:dependency:required",
+            ValidationType::NativePluginDependency => "validation:dependency:native-plugin",
+            ValidationType::MissingAsset => "validation:asset:missing",
+            ValidationType::MalformedDependency => "validation:dependency:malformed",
+            ValidationType::InvalidPubspec => "validation:pubspec:invalid",
+            ValidationType::DeniedByRule => "validation:rule:deny",
+            ValidationType::RequiredByRule => "validation:rule:require",
+            ValidationType::DevOnlyDependency => "validation:dependency:dev-only",
+            ValidationType::UndeclaredImport => "validation:import:undeclared",
+            ValidationType::UnusedDependency => "validation:dependency:unused",
+            ValidationType::MissingTests => "validation:tests:missing",
+            ValidationType::MissingStructure => "validation:structure:missing",
+            ValidationType::AnalysisOptions => "validation:analysis-options:include",
+            ValidationType::ConstraintStyle => "validation:dependency:constraint-style",
+            ValidationType::PublicChangelog => "validation:public:changelog",
+            ValidationType::AlternativePackages => "validation:dependency:alternatives",
+            ValidationType::DependencyPathMismatch => "validation:dependency:path",
+            ValidationType::DuplicatePackage => "validation:package:duplicate",
+            ValidationType::Custom(code) => code,
         }
     }
 
+    /// All built-in validation types (without custom validations).
     pub fn values() -> Iter<'static, ValidationType> {
-        static TYPES: [ValidationType; 5] = [
+        static TYPES: [ValidationType; 31] = [
             ValidationType::GitDevDependency,
             ValidationType::UnknownDependency,
             ValidationType::DependencyNotAllowed,
             ValidationType::CyclicDependency,
             ValidationType::NonGitDependencyInPublicPackage,
+            ValidationType::IncompatibleVersion,
+            ValidationType::PrivateDependencyInPublicPackage,
+            ValidationType::ForeignRepositoryDependencyInPublicPackage,
+            ValidationType::DependencyDepth,
+            ValidationType::CrossAppDependency,
+            ValidationType::SelfDependency,
+            ValidationType::DuplicateDependency,
+            ValidationType::OutsideWorkspace,
+            ValidationType::MissingRequiredDependency,
+            ValidationType::NativePluginDependency,
+            ValidationType::MissingAsset,
+            ValidationType::MalformedDependency,
+            ValidationType::InvalidPubspec,
+            ValidationType::DeniedByRule,
+            ValidationType::RequiredByRule,
+            ValidationType::DevOnlyDependency,
+            ValidationType::UndeclaredImport,
+            ValidationType::UnusedDependency,
+            ValidationType::MissingTests,
+            ValidationType::MissingStructure,
+            ValidationType::AnalysisOptions,
+            ValidationType::ConstraintStyle,
+            ValidationType::PublicChangelog,
+            ValidationType::AlternativePackages,
+            ValidationType::DependencyPathMismatch,
+            ValidationType::DuplicatePackage,
         ];
         TYPES.iter()
     }
 
-    pub fn from_str(input: &str) -> Option<ValidationType> {
+    /// Level of the validation unless configured otherwise.
+    pub fn default_level(&self) -> ValidationLevel {
+        match self {
+            ValidationType::NativePluginDependency
+            | ValidationType::DevOnlyDependency
+            | ValidationType::UnusedDependency
+            | ValidationType::AlternativePackages => ValidationLevel::Warning,
+            _ => ValidationLevel::Error,
+        }
+    }
+
+    /// Whether findings of this validation depend on files other than the
+    /// pubspecs (e.g. changelogs or custom validation commands), so they
+    /// cannot be reused by incremental runs (see `--changed-only`).
+    pub fn reads_files(&self) -> bool {
+        matches!(
+            self,
+            ValidationType::MissingAsset
+                | ValidationType::MissingTests
+                | ValidationType::MissingStructure
+                | ValidationType::AnalysisOptions
+                | ValidationType::PublicChangelog
+                | ValidationType::Custom(_)
+        )
+    }
+
+    pub fn parse(input: &str) -> Option<ValidationType> {
         ValidationType::values()
             .find(|typ| typ.as_str() == input)
-            .map(|typ| typ.clone())
+            .cloned()
     }
 }
 
@@ -133,11 +413,105 @@ impl Serialize for ValidationType {
     }
 }
 
-#[derive(Debug, Serialize, PartialEq)]
+impl<'de> Deserialize<'de> for ValidationType {
+    /// Codes other than the built-in ones refer to custom validations.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = String::deserialize(deserializer)?;
+        Ok(ValidationType::parse(&code).unwrap_or(ValidationType::Custom(code)))
+    }
+}
+
+impl JsonSchema for ValidationType {
+    fn schema_name() -> String {
+        "ValidationType".to_owned()
+    }
+
+    // custom validations report their own codes, so the built-in ones
+    // are listed as examples only
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            metadata: Some(Box::new(Metadata {
+                description: Some("code of the validation rule (see `flcheck rules`)".to_owned()),
+                examples: ValidationType::values()
+                    .map(|validation_type| validation_type.as_str().into())
+                    .collect(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct PackageValidation {
     pub package_name: String,
     pub error: String,
     pub description: Option<String>,
     pub code: ValidationType,
     pub level: ValidationLevel,
+    /// Dependency the validation refers to (if any)
+    pub dependency: Option<String>,
+    /// Owner of the package (via `flcheck.owner` or `CODEOWNERS`)
+    pub owner: Option<String>,
+    /// Pubspec file of the package
+    pub file: Option<String>,
+    /// `file` relative to the repository root of the package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_file: Option<String>,
+    /// Line (1-based) of the dependency in the pubspec file (if any)
+    pub line: Option<usize>,
+    /// Replacement of the `line` fixing the finding (if any)
+    pub fix: Option<String>,
+    /// Repository of the package (see `multi`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Type of the package (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_type: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::exit_codes;
+    use crate::error::FlError;
+    use crate::error::EXIT_CONFIG;
+    use crate::error::EXIT_IO;
+    use crate::error::EXIT_NETWORK;
+    use crate::error::EXIT_SUCCESS;
+    use crate::error::EXIT_VALIDATION;
+
+    #[test]
+    fn distinct_exit_codes() {
+        let codes = exit_codes()
+            .iter()
+            .map(|exit_code| exit_code.code)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            vec![
+                EXIT_SUCCESS,
+                EXIT_VALIDATION,
+                EXIT_CONFIG,
+                EXIT_IO,
+                EXIT_NETWORK
+            ]
+        );
+
+        assert_eq!(FlError::ValidationWarnings(2).exit_code(), EXIT_VALIDATION);
+        assert_eq!(FlError::TooManyWarnings(5, 3).exit_code(), EXIT_VALIDATION);
+        assert_eq!(
+            FlError::InvalidValidationType("foo".to_owned()).exit_code(),
+            EXIT_CONFIG
+        );
+        assert_eq!(FlError::NoInputFiles(".".to_owned()).exit_code(), EXIT_IO);
+        assert_eq!(
+            FlError::RateLimited("http".to_owned(), 600, 300).exit_code(),
+            EXIT_NETWORK
+        );
+    }
 }
@@ -0,0 +1,460 @@
+use crate::graph::Graph;
+use crate::graph::GraphOptions;
+use crate::publish::publish_waves;
+use crate::util::relative_path;
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::FlError;
+use crate::Pubspec;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Melos configuration file updated by `export --format melos`.
+pub const MELOS_FILE: &str = "melos.yaml";
+
+/// First line of the bootstrap order comment in the melos configuration.
+const BOOTSTRAP_HINT: &str = "# bootstrap order (generated by flcheck export):";
+
+/// Formats of the workspace export consumed by external build systems.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// `BUILD.bazel` stubs of all packages
+    Bazel,
+    /// `BUCK` stubs of all packages
+    Buck,
+    /// Project graph as printed by `nx graph --file`
+    Nx,
+    /// `packages` of the melos configuration
+    Melos,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<ExportFormat> {
+        match value {
+            "bazel" => Some(ExportFormat::Bazel),
+            "buck" => Some(ExportFormat::Buck),
+            "nx" => Some(ExportFormat::Nx),
+            "melos" => Some(ExportFormat::Melos),
+            _ => None,
+        }
+    }
+}
+
+/// Package of the workspace along with its internal dependencies.
+struct ExportPackage<'a> {
+    pubspec: &'a Pubspec,
+    /// Directory relative to the workspace root (`/`-separated)
+    dir: String,
+    dependencies: Vec<String>,
+    dev_dependencies: Vec<String>,
+}
+
+/// Export the internal dependencies of the `pubspecs` in the given `format`.
+/// All paths are relative to the given `root` directory.
+pub fn export_workspace(
+    format: ExportFormat,
+    config: &Config,
+    pubspecs: &[Pubspec],
+    root: &Path,
+) -> Result<String, FlError> {
+    let packages = export_packages(pubspecs, root);
+
+    match format {
+        ExportFormat::Bazel => Ok(build_files(&packages, "BUILD.bazel", "//visibility:public")),
+        ExportFormat::Buck => Ok(build_files(&packages, "BUCK", "PUBLIC")),
+        ExportFormat::Nx => Ok(nx_graph(config, &packages)),
+        ExportFormat::Melos => melos_config("", pubspecs, root),
+    }
+}
+
+fn export_packages<'a>(pubspecs: &'a [Pubspec], root: &Path) -> Vec<ExportPackage<'a>> {
+    let graph = Graph::from_workspace(&Workspace::new(pubspecs), &GraphOptions::default());
+
+    let mut packages = pubspecs
+        .iter()
+        .map(|pubspec| {
+            let dependencies = |dev: bool| {
+                let mut names = graph
+                    .edges_from(&pubspec.name)
+                    .filter(|edge| edge.dev == dev && graph.nodes.contains(&edge.to))
+                    .map(|edge| edge.to.clone())
+                    .collect::<Vec<_>>();
+                names.sort_unstable();
+                names.dedup();
+                names
+            };
+
+            ExportPackage {
+                pubspec,
+                dir: relative_path(root, Path::new(&pubspec.dir_path))
+                    .unwrap_or_else(|| pubspec.dir_path.trim_start_matches('/').to_owned()),
+                dependencies: dependencies(false),
+                dev_dependencies: dependencies(true),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    packages.sort_by(|a, b| a.pubspec.name.cmp(&b.pubspec.name));
+    packages
+}
+
+/// Build file stubs (Bazel or Buck) declaring a `dart_library` per package
+/// that depends on the targets of its (non-dev) internal dependencies.
+fn build_files(packages: &[ExportPackage], file_name: &str, visibility: &str) -> String {
+    let label = |name: &str| {
+        packages
+            .iter()
+            .find(|package| package.pubspec.name == name)
+            .map(|package| format!("//{}:{}", package.dir, name))
+            .unwrap_or_default()
+    };
+
+    let mut out = String::new();
+    for package in packages {
+        let file = match package.dir.as_str() {
+            "" => file_name.to_owned(),
+            dir => format!("{}/{}", dir, file_name),
+        };
+
+        out.push_str(&format!("# {}\n", file));
+        out.push_str("dart_library(\n");
+        out.push_str(&format!("    name = \"{}\",\n", package.pubspec.name));
+        out.push_str("    srcs = glob([\"lib/**/*.dart\"]),\n");
+        out.push_str("    deps = [\n");
+        for dependency in &package.dependencies {
+            out.push_str(&format!("        \"{}\",\n", label(dependency)));
+        }
+        out.push_str("    ],\n");
+        out.push_str(&format!("    visibility = [\"{}\"],\n", visibility));
+        out.push_str(")\n\n");
+    }
+
+    out.pop();
+    out
+}
+
+/// Project graph in the format of `nx graph --file`.
+#[derive(Serialize)]
+struct NxGraph<'a> {
+    graph: NxProjects<'a>,
+}
+
+#[derive(Serialize)]
+struct NxProjects<'a> {
+    nodes: BTreeMap<&'a str, NxNode<'a>>,
+    dependencies: BTreeMap<&'a str, Vec<NxDependency<'a>>>,
+}
+
+#[derive(Serialize)]
+struct NxNode<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    project_type: &'static str,
+    data: NxNodeData<'a>,
+}
+
+#[derive(Serialize)]
+struct NxNodeData<'a> {
+    root: &'a str,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct NxDependency<'a> {
+    source: &'a str,
+    target: &'a str,
+    #[serde(rename = "type")]
+    dependency_type: &'static str,
+}
+
+/// Nx project graph of all packages including dev dependencies. Packages
+/// of a type no (other) package type includes are considered applications,
+/// all other ones libraries.
+fn nx_graph(config: &Config, packages: &[ExportPackage]) -> String {
+    let mut nodes = BTreeMap::new();
+    let mut dependencies = BTreeMap::new();
+
+    for package in packages {
+        let name = package.pubspec.name.as_str();
+        let package_type = package.pubspec.package_type(config);
+        let is_app = package_type.is_some_and(|pkg_type| {
+            !config
+                .package_types
+                .iter()
+                .any(|other| other.includes.contains(&pkg_type.name))
+        });
+
+        let mut tags = package_type
+            .map(|pkg_type| format!("type:{}", pkg_type.name))
+            .into_iter()
+            .collect::<Vec<_>>();
+        tags.extend(package.pubspec.tags.iter().cloned());
+
+        nodes.insert(
+            name,
+            NxNode {
+                name,
+                project_type: if is_app { "app" } else { "lib" },
+                data: NxNodeData {
+                    root: &package.dir,
+                    tags,
+                },
+            },
+        );
+
+        let mut targets = package
+            .dependencies
+            .iter()
+            .chain(package.dev_dependencies.iter())
+            .collect::<Vec<_>>();
+        targets.sort_unstable();
+        targets.dedup();
+
+        dependencies.insert(
+            name,
+            targets
+                .into_iter()
+                .map(|target| NxDependency {
+                    source: name,
+                    target: target.as_str(),
+                    dependency_type: "static",
+                })
+                .collect(),
+        );
+    }
+
+    let graph = NxGraph {
+        graph: NxProjects {
+            nodes,
+            dependencies,
+        },
+    };
+
+    let mut json = serde_json::to_string_pretty(&graph).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+/// Update the melos configuration `content` (empty if there is none yet)
+/// with the `packages` globs matching all `pubspecs` and a comment listing
+/// the order the packages are bootstrapped in. All other settings (e.g.
+/// `scripts`) are kept as they are.
+pub fn melos_config(content: &str, pubspecs: &[Pubspec], root: &Path) -> Result<String, FlError> {
+    let waves = publish_waves(pubspecs)?;
+
+    let mut globs = pubspecs
+        .iter()
+        .map(|pubspec| {
+            let dir = relative_path(root, Path::new(&pubspec.dir_path)).unwrap_or_default();
+            match dir.rsplit_once('/') {
+                Some((parent, _)) => format!("{}/*", parent),
+                None if dir.is_empty() => ".".to_owned(),
+                None => "*".to_owned(),
+            }
+        })
+        .collect::<Vec<_>>();
+    globs.sort_unstable();
+    globs.dedup();
+
+    let mut block = vec![BOOTSTRAP_HINT.to_owned()];
+    block.extend(
+        waves
+            .iter()
+            .enumerate()
+            .map(|(idx, wave)| format!("#   {}. {}", idx + 1, wave.join(", "))),
+    );
+    block.push("packages:".to_owned());
+    block.extend(globs.iter().map(|glob| format!("  - {}", glob)));
+
+    let mut lines = Vec::new();
+    let mut replaced = false;
+    let mut existing = content.lines().peekable();
+
+    while let Some(line) = existing.next() {
+        // skip the previous bootstrap order and `packages` block
+        let skipped = if line == BOOTSTRAP_HINT {
+            |line: &str| line.starts_with("#   ")
+        } else if line.trim_end() == "packages:" {
+            |line: &str| line.starts_with(' ') || line.starts_with('-')
+        } else {
+            lines.push(line.to_owned());
+            continue;
+        };
+
+        while existing.next_if(|line| skipped(line)).is_some() {}
+
+        if !replaced && line != BOOTSTRAP_HINT {
+            lines.append(&mut block);
+            replaced = true;
+        }
+    }
+
+    if !replaced {
+        if !lines.iter().any(|line| line.starts_with("name:")) {
+            let name = root
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("workspace");
+            lines.insert(0, format!("name: {}", name));
+        }
+
+        let idx = lines
+            .iter()
+            .position(|line| line.starts_with("name:"))
+            .map_or(0, |idx| idx + 1);
+        block.insert(0, String::new());
+        lines.splice(idx..idx, block);
+    }
+
+    let mut config = lines.join("\n");
+    config.push('\n');
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PackageType;
+    use crate::export::export_workspace;
+    use crate::export::melos_config;
+    use crate::export::ExportFormat;
+    use crate::Config;
+    use crate::Pubspec;
+    use std::path::Path;
+
+    fn pkg_type(name: &str, includes: &[&str]) -> PackageType {
+        PackageType {
+            name: name.to_owned(),
+            prefixes: vec![format!("{}_", name)],
+            includes: includes.iter().map(|include| include.to_string()).collect(),
+            max_depth: None,
+            requires: Vec::new(),
+            pure_dart: false,
+            require_tests: false,
+        }
+    }
+
+    fn workspace() -> Vec<Pubspec> {
+        vec![
+            Pubspec::test_at("pkg_test", "/repo/packages/pkg_test"),
+            Pubspec::test_at("app_foo", "/repo/apps/app_foo")
+                .with_local_deps(&["pkg_core", "http"])
+                .with_local_dev_deps(&["pkg_test"]),
+            Pubspec::test_at("pkg_core", "/repo/packages/pkg_core")
+                .with_local_dev_deps(&["pkg_test"]),
+        ]
+    }
+
+    #[test]
+    fn export_bazel() {
+        let build = export_workspace(
+            ExportFormat::Bazel,
+            &Config::default(),
+            &workspace(),
+            Path::new("/repo"),
+        )
+        .unwrap();
+
+        assert!(build.starts_with(
+            r#"# apps/app_foo/BUILD.bazel
+dart_library(
+    name = "app_foo",
+    srcs = glob(["lib/**/*.dart"]),
+    deps = [
+        "//packages/pkg_core:pkg_core",
+    ],
+    visibility = ["//visibility:public"],
+)
+
+# packages/pkg_core/BUILD.bazel
+"#
+        ));
+
+        let buck = export_workspace(
+            ExportFormat::Buck,
+            &Config::default(),
+            &workspace(),
+            Path::new("/repo"),
+        )
+        .unwrap();
+
+        assert!(buck.contains("# packages/pkg_test/BUCK\n"));
+        assert!(buck.contains("visibility = [\"PUBLIC\"]"));
+    }
+
+    #[test]
+    fn export_nx() {
+        let config = Config {
+            package_types: vec![pkg_type("app", &["pkg"]), pkg_type("pkg", &["pkg"])],
+            ..Config::default()
+        };
+
+        let json =
+            export_workspace(ExportFormat::Nx, &config, &workspace(), Path::new("/repo")).unwrap();
+        let graph: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            graph["graph"]["nodes"]["app_foo"],
+            serde_json::json!({
+                "name": "app_foo",
+                "type": "app",
+                "data": { "root": "apps/app_foo", "tags": ["type:app"] }
+            })
+        );
+        assert_eq!(graph["graph"]["nodes"]["pkg_core"]["type"], "lib");
+        assert_eq!(
+            graph["graph"]["dependencies"]["app_foo"],
+            serde_json::json!([
+                { "source": "app_foo", "target": "pkg_core", "type": "static" },
+                { "source": "app_foo", "target": "pkg_test", "type": "static" }
+            ])
+        );
+        assert_eq!(
+            graph["graph"]["dependencies"]["pkg_test"],
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn export_melos() {
+        let expected = r#"name: repo
+
+# bootstrap order (generated by flcheck export):
+#   1. pkg_core, pkg_test
+#   2. app_foo
+packages:
+  - apps/*
+  - packages/*
+"#;
+
+        assert_eq!(
+            melos_config("", &workspace(), Path::new("/repo")).unwrap(),
+            expected
+        );
+
+        let existing = r#"name: acme
+# bootstrap order (generated by flcheck export):
+#   1. pkg_old
+packages:
+  - old/**
+
+scripts:
+  test: melos exec -- flutter test
+"#;
+
+        assert_eq!(
+            melos_config(existing, &workspace(), Path::new("/repo")).unwrap(),
+            r#"name: acme
+# bootstrap order (generated by flcheck export):
+#   1. pkg_core, pkg_test
+#   2. app_foo
+packages:
+  - apps/*
+  - packages/*
+
+scripts:
+  test: melos exec -- flutter test
+"#
+        );
+    }
+}
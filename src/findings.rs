@@ -0,0 +1,483 @@
+use crate::bump::BumpReport;
+use crate::command::CheckReport;
+use crate::command::ExternalReport;
+use crate::diff::FindingsDiff;
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::graph::Graph;
+use crate::lint::ConfigProblem;
+use crate::list::PackageEntry;
+use crate::overrides::OverrideEntry;
+use crate::rules::Rule;
+use crate::snapshot::SnapshotDrift;
+use crate::stats::Stats;
+use crate::suggest::IncludeSuggestion;
+use crate::tree::TreeNode;
+use crate::Pubspec;
+use crate::Renderer;
+use serde::Serialize;
+use std::io;
+use std::io::Write;
+
+/// Formats of validation findings consumed by CI integrations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FindingsFormat {
+    /// Checkstyle XML (e.g. Jenkins warnings-ng, reviewdog)
+    Checkstyle,
+    /// Reviewdog Diagnostic Format (JSON)
+    Rdjson,
+}
+
+impl FindingsFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            FindingsFormat::Checkstyle => "checkstyle",
+            FindingsFormat::Rdjson => "rdjson",
+        }
+    }
+}
+
+/// Paths the findings are reported at (see `--path-style`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PathStyle {
+    #[default]
+    Absolute,
+    /// Relative to the repository root (if known)
+    Relative,
+}
+
+impl PathStyle {
+    pub fn parse(value: &str) -> Option<PathStyle> {
+        match value {
+            "absolute" => Some(PathStyle::Absolute),
+            "relative" => Some(PathStyle::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// Output of validation findings in a format of `FindingsFormat`.
+///
+/// All other results are not supported and fail to render.
+pub struct FindingsRenderer<W: Write> {
+    out: W,
+    format: FindingsFormat,
+    path_style: PathStyle,
+}
+
+impl<W: Write> FindingsRenderer<W> {
+    pub fn new(out: W, format: FindingsFormat) -> FindingsRenderer<W> {
+        FindingsRenderer {
+            out,
+            format,
+            path_style: PathStyle::default(),
+        }
+    }
+
+    pub fn with_path_style(self, path_style: PathStyle) -> FindingsRenderer<W> {
+        FindingsRenderer { path_style, ..self }
+    }
+
+    /// Path the given finding is reported at: its (relative) file or the
+    /// package name for findings without file.
+    fn path<'a>(&self, finding: &'a PackageValidation) -> &'a str {
+        let relative = match self.path_style {
+            PathStyle::Absolute => None,
+            PathStyle::Relative => finding.relative_file.as_deref(),
+        };
+
+        relative
+            .or(finding.file.as_deref())
+            .unwrap_or(&finding.package_name)
+    }
+
+    fn unsupported(&self) -> io::Result<()> {
+        Err(io::Error::other(format!(
+            "not supported by {} output",
+            self.format.name()
+        )))
+    }
+
+    fn checkstyle(&mut self, findings: &[&PackageValidation]) -> io::Result<()> {
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(self.out, r#"<checkstyle version="4.3">"#)?;
+
+        let mut files: Vec<(&str, Vec<&PackageValidation>)> = Vec::new();
+        for finding in findings {
+            let file = self.path(finding);
+            match files.iter_mut().find(|(name, _)| *name == file) {
+                Some((_, file_findings)) => file_findings.push(finding),
+                None => files.push((file, vec![finding])),
+            }
+        }
+
+        for (file, file_findings) in files {
+            writeln!(self.out, r#"  <file name="{}">"#, xml_escape(file))?;
+            for finding in file_findings {
+                let severity = match finding.level {
+                    ValidationLevel::Error => "error",
+                    _ => "warning",
+                };
+                writeln!(
+                    self.out,
+                    r#"    <error line="{}" severity="{}" message="{}" source="{}"/>"#,
+                    finding.line.unwrap_or(1),
+                    severity,
+                    xml_escape(&finding.error),
+                    xml_escape(&finding.code.to_string())
+                )?;
+            }
+            writeln!(self.out, "  </file>")?;
+        }
+
+        writeln!(self.out, "</checkstyle>")
+    }
+
+    fn rdjson(&mut self, findings: &[&PackageValidation]) -> io::Result<()> {
+        let diagnostics = findings
+            .iter()
+            .map(|finding| {
+                // suggestions replace the whole line of the dependency
+                let suggestions = finding
+                    .line
+                    .zip(finding.fix.as_ref())
+                    .map(|(line, fix)| RdSuggestion {
+                        range: RdRange {
+                            start: RdPosition { line, column: 1 },
+                            end: Some(RdPosition {
+                                line: line + 1,
+                                column: 1,
+                            }),
+                        },
+                        text: format!("{}\n", fix),
+                    })
+                    .into_iter()
+                    .collect();
+
+                RdDiagnostic {
+                    message: &finding.error,
+                    location: RdLocation {
+                        path: self.path(finding),
+                        range: finding.line.map(|line| RdRange {
+                            start: RdPosition { line, column: 1 },
+                            end: None,
+                        }),
+                    },
+                    severity: match finding.level {
+                        ValidationLevel::Error => "ERROR",
+                        _ => "WARNING",
+                    },
+                    code: RdCode {
+                        value: finding.code.to_string(),
+                    },
+                    suggestions,
+                }
+            })
+            .collect();
+
+        let result = RdResult {
+            source: RdSource {
+                name: "flcheck",
+                url: env!("CARGO_PKG_REPOSITORY"),
+            },
+            diagnostics,
+        };
+
+        serde_json::to_writer(&mut self.out, &result)?;
+        writeln!(self.out)
+    }
+}
+
+/// Diagnostic result of the Reviewdog Diagnostic Format (rdjson).
+#[derive(Serialize)]
+struct RdResult<'a> {
+    source: RdSource,
+    diagnostics: Vec<RdDiagnostic<'a>>,
+}
+
+#[derive(Serialize)]
+struct RdSource {
+    name: &'static str,
+    url: &'static str,
+}
+
+#[derive(Serialize)]
+struct RdDiagnostic<'a> {
+    message: &'a str,
+    location: RdLocation<'a>,
+    severity: &'static str,
+    code: RdCode,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<RdSuggestion>,
+}
+
+#[derive(Serialize)]
+struct RdLocation<'a> {
+    path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<RdRange>,
+}
+
+#[derive(Serialize)]
+struct RdRange {
+    start: RdPosition,
+    /// Exclusive end of the range
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<RdPosition>,
+}
+
+/// Position with 1-based line and column.
+#[derive(Serialize)]
+struct RdPosition {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize)]
+struct RdCode {
+    value: String,
+}
+
+#[derive(Serialize)]
+struct RdSuggestion {
+    range: RdRange,
+    text: String,
+}
+
+impl<W: Write> Renderer for FindingsRenderer<W> {
+    fn render_validations(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        let findings = validations
+            .iter()
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect::<Vec<_>>();
+
+        match self.format {
+            FindingsFormat::Checkstyle => self.checkstyle(&findings),
+            FindingsFormat::Rdjson => self.rdjson(&findings),
+        }
+    }
+
+    fn render_dump(&mut self, _pubspecs: &[Pubspec]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_check(&mut self, _report: &CheckReport) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_check_by_package(&mut self, _report: &ExternalReport) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_graph(&mut self, _graph: &Graph) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_stats(&mut self, _stats: &Stats) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_tree(&mut self, _trees: &[TreeNode]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_rules(&mut self, _rules: &[Rule]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_rule(&mut self, _rule: &Rule) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_config_problems(&mut self, _problems: &[ConfigProblem]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_publish_order(&mut self, _waves: &[Vec<String>]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_bump(&mut self, _report: &BumpReport) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_include_suggestions(&mut self, _suggestions: &[IncludeSuggestion]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_diff(&mut self, _diff: &FindingsDiff) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_snapshot_drift(&mut self, _drift: &SnapshotDrift) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_packages(&mut self, _packages: &[PackageEntry]) -> io::Result<()> {
+        self.unsupported()
+    }
+
+    fn render_overrides(&mut self, _overrides: &[OverrideEntry]) -> io::Result<()> {
+        self.unsupported()
+    }
+}
+
+/// Escape the given `text` for use in XML attributes.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::findings::FindingsFormat;
+    use crate::findings::FindingsRenderer;
+    use crate::findings::PathStyle;
+    use crate::Renderer;
+
+    fn validation(package: &str, level: ValidationLevel, line: Option<usize>) -> PackageValidation {
+        PackageValidation {
+            package_name: package.to_owned(),
+            error: format!("dependency to 'app_<{}>' is not allowed", package),
+            description: None,
+            code: ValidationType::DependencyNotAllowed,
+            level,
+            dependency: None,
+            owner: None,
+            file: Some(format!("/ws/{}/pubspec.yaml", package)),
+            relative_file: None,
+            line,
+            fix: None,
+            repo: None,
+            package_type: None,
+        }
+    }
+
+    #[test]
+    fn checkstyle() {
+        let validations = vec![
+            validation("foo", ValidationLevel::Error, Some(7)),
+            validation("foo", ValidationLevel::Warning, None),
+            validation("bar", ValidationLevel::None, None),
+        ];
+
+        let mut out = Vec::new();
+        FindingsRenderer::new(&mut out, FindingsFormat::Checkstyle)
+            .render_validations(&validations)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="/ws/foo/pubspec.yaml">
+    <error line="7" severity="error" message="dependency to &apos;app_&lt;foo&gt;&apos; is not allowed" source="validation:dependency:unallowed"/>
+    <error line="1" severity="warning" message="dependency to &apos;app_&lt;foo&gt;&apos; is not allowed" source="validation:dependency:unallowed"/>
+  </file>
+</checkstyle>
+"#
+        );
+
+        let mut out = Vec::new();
+        assert!(FindingsRenderer::new(&mut out, FindingsFormat::Checkstyle)
+            .render_publish_order(&[])
+            .is_err());
+    }
+
+    #[test]
+    fn rdjson() {
+        let validations = vec![
+            PackageValidation {
+                code: ValidationType::IncompatibleVersion,
+                fix: Some("  pkg_core: ^2.0.0".to_owned()),
+                ..validation("foo", ValidationLevel::Error, Some(7))
+            },
+            validation("bar", ValidationLevel::Warning, None),
+        ];
+
+        let mut out = Vec::new();
+        FindingsRenderer::new(&mut out, FindingsFormat::Rdjson)
+            .render_validations(&validations)
+            .unwrap();
+        let output: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(output["source"]["name"], "flcheck");
+        assert_eq!(
+            output["diagnostics"][0],
+            serde_json::json!({
+                "message": "dependency to 'app_<foo>' is not allowed",
+                "location": {
+                    "path": "/ws/foo/pubspec.yaml",
+                    "range": { "start": { "line": 7, "column": 1 } }
+                },
+                "severity": "ERROR",
+                "code": { "value": "validation:dependency:version" },
+                "suggestions": [{
+                    "range": {
+                        "start": { "line": 7, "column": 1 },
+                        "end": { "line": 8, "column": 1 }
+                    },
+                    "text": "  pkg_core: ^2.0.0\n"
+                }]
+            })
+        );
+        assert_eq!(
+            output["diagnostics"][1],
+            serde_json::json!({
+                "message": "dependency to 'app_<bar>' is not allowed",
+                "location": { "path": "/ws/bar/pubspec.yaml" },
+                "severity": "WARNING",
+                "code": { "value": "validation:dependency:unallowed" }
+            })
+        );
+    }
+
+    #[test]
+    fn relative_paths() {
+        let validations = vec![
+            PackageValidation {
+                relative_file: Some("foo/pubspec.yaml".to_owned()),
+                ..validation("foo", ValidationLevel::Error, Some(7))
+            },
+            // findings without relative path keep the absolute one
+            validation("bar", ValidationLevel::Warning, None),
+        ];
+        let paths = |path_style: PathStyle| {
+            let mut out = Vec::new();
+            FindingsRenderer::new(&mut out, FindingsFormat::Rdjson)
+                .with_path_style(path_style)
+                .render_validations(&validations)
+                .unwrap();
+            let output: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+            output["diagnostics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|diagnostic| diagnostic["location"]["path"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            paths(PathStyle::Absolute),
+            vec!["/ws/foo/pubspec.yaml", "/ws/bar/pubspec.yaml"]
+        );
+        assert_eq!(
+            paths(PathStyle::Relative),
+            vec!["foo/pubspec.yaml", "/ws/bar/pubspec.yaml"]
+        );
+    }
+}
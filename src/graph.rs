@@ -0,0 +1,412 @@
+use crate::dependency::Dependency;
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::error::ValidationType;
+use crate::workspace::Workspace;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// File formats the dependency graph can be written as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Svg,
+    Png,
+}
+
+impl GraphFormat {
+    /// Determine the format of the given graph `file` by its extension.
+    pub fn from_file(file: &str) -> Option<GraphFormat> {
+        let extension = Path::new(file).extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "dot" | "gv" => Some(GraphFormat::Dot),
+            "svg" => Some(GraphFormat::Svg),
+            "png" => Some(GraphFormat::Png),
+            _ => None,
+        }
+    }
+
+    /// Output format argument of the graphviz `dot` executable
+    /// (`None` if no rendering is necessary).
+    pub fn graphviz_format(&self) -> Option<&'static str> {
+        match self {
+            GraphFormat::Dot => None,
+            GraphFormat::Svg => Some("svg"),
+            GraphFormat::Png => Some("png"),
+        }
+    }
+}
+
+/// Directed dependency between two packages.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    /// Dependency is part of the dev_dependencies
+    pub dev: bool,
+    /// Dependency is replaced via dependency_overrides
+    pub overridden: bool,
+    /// Dependency is part of a cycle or not allowed (see `annotate`)
+    pub violation: bool,
+}
+
+/// Options controlling what is part of the dependency graph.
+#[derive(Debug, Default)]
+pub struct GraphOptions {
+    /// Include external (pub.dev) dependencies
+    pub externals: bool,
+    /// Include only the external dependencies matching the filter
+    pub external_filter: Option<Regex>,
+}
+
+impl GraphOptions {
+    fn includes_external(&self, name: &str) -> bool {
+        self.externals
+            && self
+                .external_filter
+                .as_ref()
+                .is_none_or(|filter| filter.is_match(name))
+    }
+}
+
+/// Dependency graph of all packages of the workspace.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct Graph {
+    pub nodes: Vec<String>,
+    /// External (pub.dev) packages that are part of the graph
+    pub externals: Vec<String>,
+    pub edges: Vec<Edge>,
+    /// Packages involved in cyclic or not allowed dependencies (see `annotate`)
+    pub violations: Vec<String>,
+}
+
+impl Graph {
+    /// Build the dependency graph of the given workspace consisting of
+    /// all (effective) local and git dependencies (including dev
+    /// dependencies) as well as external dependencies if requested via
+    /// `options`. Dependencies on workspace packages refer to the name of
+    /// the package they resolve to.
+    pub fn from_workspace(workspace: &Workspace, options: &GraphOptions) -> Graph {
+        let pubspecs = workspace.packages;
+        let nodes = pubspecs
+            .iter()
+            .map(|pubspec| pubspec.name.clone())
+            .collect();
+        let edges = pubspecs
+            .iter()
+            .flat_map(|pubspec| {
+                let deps = pubspec.dependencies.iter().map(|dep| (dep, false));
+                let dev_deps = pubspec.dev_dependencies.iter().map(|dep| (dep, true));
+
+                deps.chain(dev_deps).flat_map(|(dep, dev)| {
+                    let to = match dep.effective() {
+                        Dependency::Local { name, .. } | Dependency::Git { name, .. } => workspace
+                            .resolve(pubspec, dep)
+                            .map_or(name, |idx| &pubspecs[idx].name),
+                        Dependency::PubDev { name, .. } if options.includes_external(name) => name,
+                        _ => return None,
+                    };
+
+                    Some(Edge {
+                        from: pubspec.name.clone(),
+                        to: to.clone(),
+                        dev,
+                        overridden: dep.overridden().is_some(),
+                        violation: false,
+                    })
+                })
+            })
+            .collect();
+
+        let mut externals = pubspecs
+            .iter()
+            .flat_map(|pubspec| {
+                pubspec
+                    .dependencies
+                    .iter()
+                    .chain(pubspec.dev_dependencies.iter())
+            })
+            .flat_map(|dep| match dep.effective() {
+                Dependency::PubDev { name, .. } if options.includes_external(name) => {
+                    Some(name.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        externals.sort_unstable();
+        externals.dedup();
+
+        Graph {
+            nodes,
+            externals,
+            edges,
+            violations: Vec::new(),
+        }
+    }
+
+    /// Mark all packages and dependencies involved in cyclic or not
+    /// allowed dependencies based on the given `validations`.
+    pub fn annotate(&mut self, validations: &[PackageValidation]) {
+        let violations = validations.iter().filter(|validation| {
+            validation.level != ValidationLevel::None
+                && matches!(
+                    validation.code,
+                    ValidationType::CyclicDependency
+                        | ValidationType::DependencyNotAllowed
+                        | ValidationType::CrossAppDependency
+                )
+        });
+
+        for validation in violations {
+            let dependency = validation.dependency.as_ref();
+
+            for edge in self.edges.iter_mut() {
+                if edge.from == validation.package_name && Some(&edge.to) == dependency {
+                    edge.violation = true;
+                }
+            }
+
+            self.violations.push(validation.package_name.clone());
+            self.violations.extend(dependency.cloned());
+        }
+
+        self.violations.sort_unstable();
+        self.violations.dedup();
+    }
+
+    /// All edges originating from the node `name`.
+    pub fn edges_from<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Edge> {
+        self.edges.iter().filter(move |edge| edge.from == name)
+    }
+
+    /// Remove all edges that are implied by transitivity, e.g. `a -> c`
+    /// if `a -> b -> c` exists as well. Edges are removed one after
+    /// another so that the reachability is retained even with cycles.
+    /// Violations (see `annotate`) are kept in any case.
+    pub fn reduce(&mut self) {
+        let mut idx = 0;
+
+        while idx < self.edges.len() {
+            let edge = &self.edges[idx];
+
+            if !edge.violation && self.reachable_without(&edge.from, &edge.to) {
+                self.edges.remove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+    }
+
+    /// Whether `to` is reachable from `from` without using any direct
+    /// edge between both.
+    fn reachable_without(&self, from: &str, to: &str) -> bool {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(current) = queue.pop_front() {
+            for edge in self.edges_from(current) {
+                if edge.from == from && edge.to == to {
+                    continue;
+                }
+                if edge.to == to {
+                    return true;
+                }
+                if seen.insert(edge.to.as_str()) {
+                    queue.push_back(&edge.to);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dependency::Dependency;
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::graph::Edge;
+    use crate::graph::Graph;
+    use crate::graph::GraphFormat;
+    use crate::graph::GraphOptions;
+    use crate::workspace::Workspace;
+    use crate::Pubspec;
+    use regex::Regex;
+
+    fn edge(from: &str, to: &str) -> Edge {
+        Edge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            dev: false,
+            overridden: false,
+            violation: false,
+        }
+    }
+
+    #[test]
+    fn graph_externals() {
+        let pubspecs = vec![
+            Pubspec::test("app").with_deps(vec![
+                Pubspec::hosted("http", "^1.0.0"),
+                Pubspec::hosted("flutter_bloc", "^1.0.0"),
+            ]),
+            Pubspec::test("pkg").with_deps(vec![Pubspec::hosted("http", "^1.0.0")]),
+        ];
+
+        let without = Graph::from_workspace(&Workspace::new(&pubspecs), &GraphOptions::default());
+        assert!(without.externals.is_empty());
+        assert!(without.edges.is_empty());
+
+        let options = GraphOptions {
+            externals: true,
+            external_filter: Some(Regex::new("^http").unwrap()),
+        };
+        let graph = Graph::from_workspace(&Workspace::new(&pubspecs), &options);
+
+        assert_eq!(graph.externals, vec!["http"]);
+        assert_eq!(graph.edges, vec![edge("app", "http"), edge("pkg", "http")]);
+    }
+
+    #[test]
+    fn graph_annotate() {
+        let pubspecs = vec![
+            Pubspec::test("app").with_local_deps(&["pkg", "other"]),
+            Pubspec::test("pkg").with_local_deps(&["app"]),
+            Pubspec::test("other"),
+        ];
+        let validation = |package: &str, dependency: &str, code, level| PackageValidation {
+            package_name: package.to_owned(),
+            error: String::new(),
+            description: None,
+            code,
+            level,
+            dependency: Some(dependency.to_owned()),
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        };
+
+        let mut graph = Graph::from_workspace(&Workspace::new(&pubspecs), &GraphOptions::default());
+        graph.annotate(&[
+            validation(
+                "app",
+                "pkg",
+                ValidationType::CyclicDependency,
+                ValidationLevel::Error,
+            ),
+            validation(
+                "pkg",
+                "app",
+                ValidationType::DependencyNotAllowed,
+                ValidationLevel::Warning,
+            ),
+            validation(
+                "app",
+                "other",
+                ValidationType::DependencyNotAllowed,
+                ValidationLevel::None,
+            ),
+        ]);
+
+        let violations = graph
+            .edges
+            .iter()
+            .map(|edge| edge.violation)
+            .collect::<Vec<_>>();
+
+        assert_eq!(violations, vec![true, false, true]);
+        assert_eq!(graph.violations, vec!["app", "pkg"]);
+    }
+
+    #[test]
+    fn graph_reduce() {
+        let mut graph = Graph {
+            nodes: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+            externals: Vec::new(),
+            edges: vec![
+                edge("a", "b"),
+                edge("a", "c"),
+                edge("b", "a"),
+                edge("b", "c"),
+                edge("c", "d"),
+                Edge {
+                    violation: true,
+                    ..edge("a", "d")
+                },
+            ],
+            violations: Vec::new(),
+        };
+
+        graph.reduce();
+
+        // cycle 'a <-> b' retains reachability of 'c'
+        assert_eq!(
+            graph.edges,
+            vec![
+                edge("a", "b"),
+                edge("b", "a"),
+                edge("b", "c"),
+                edge("c", "d"),
+                Edge {
+                    violation: true,
+                    ..edge("a", "d")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn graph_dev_dependencies_and_overrides() {
+        let mut app = Pubspec::test("app").with_deps(vec![Pubspec::hosted("pkg", "^1.0.0")
+            .with_override(Dependency::Local {
+                name: "pkg".to_owned(),
+                path: "../pkg".to_owned(),
+                overridden: Box::new(None),
+            })]);
+        app.dev_dependencies.push(Dependency::Local {
+            name: "test_utils".to_owned(),
+            path: "../test_utils".to_owned(),
+            overridden: Box::new(None),
+        });
+        let pubspecs = vec![app, Pubspec::test("pkg"), Pubspec::test("test_utils")];
+
+        let graph = Graph::from_workspace(&Workspace::new(&pubspecs), &GraphOptions::default());
+
+        assert_eq!(
+            graph.edges,
+            vec![
+                Edge {
+                    overridden: true,
+                    ..edge("app", "pkg")
+                },
+                Edge {
+                    dev: true,
+                    ..edge("app", "test_utils")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn graph_format_from_file() {
+        assert_eq!(GraphFormat::from_file("out.dot"), Some(GraphFormat::Dot));
+        assert_eq!(
+            GraphFormat::from_file("graphs/out.SVG"),
+            Some(GraphFormat::Svg)
+        );
+        assert_eq!(GraphFormat::from_file("out.png"), Some(GraphFormat::Png));
+        assert_eq!(GraphFormat::from_file("out.pdf"), None);
+        assert_eq!(GraphFormat::from_file("out"), None);
+    }
+}
@@ -0,0 +1,96 @@
+/// Git hooks that can be installed via `flcheck install-hook`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookType {
+    PreCommit,
+    PrePush,
+}
+
+impl HookType {
+    pub fn parse(value: &str) -> Option<HookType> {
+        match value {
+            "pre-commit" => Some(HookType::PreCommit),
+            "pre-push" => Some(HookType::PrePush),
+            _ => None,
+        }
+    }
+
+    /// File name of the hook in the git hooks directory.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            HookType::PreCommit => "pre-commit",
+            HookType::PrePush => "pre-push",
+        }
+    }
+
+    /// Shell command listing the files changed by the commit(s) the
+    /// hook is run for.
+    fn changed_files(&self) -> &'static str {
+        match self {
+            HookType::PreCommit => "git diff --cached --name-only --diff-filter=ACMR",
+            // without an upstream branch all packages are validated
+            HookType::PrePush => {
+                "git diff --name-only --diff-filter=ACMR '@{upstream}...HEAD' 2>/dev/null \
+                 || exec \"$FLCHECK\" validate"
+            }
+        }
+    }
+}
+
+/// Render the hook script validating the packages affected by the
+/// changed pubspec files (or all packages if the configuration changed).
+pub fn hook_script(hook_type: HookType) -> String {
+    format!(
+        r#"#!/bin/sh
+#
+# flcheck {hook} hook (generated by `flcheck install-hook`)
+#
+# Validates all packages affected by the changed pubspec.yaml files.
+# The flcheck executable may be overridden via FLCHECK.
+
+FLCHECK="${{FLCHECK:-flcheck}}"
+
+changed=$({changed})
+
+# configuration changes may affect all packages
+if printf '%s\n' "$changed" | grep -qE '(^|/)flcheck\.yaml$'; then
+  exec "$FLCHECK" validate
+fi
+
+IFS='
+'
+set --
+for file in $changed; do
+  case "$file" in
+    pubspec.yaml|*/pubspec.yaml) set -- "$@" --affected-by "$file" ;;
+  esac
+done
+
+if [ $# -eq 0 ]; then
+  exit 0
+fi
+
+exec "$FLCHECK" validate "$@"
+"#,
+        hook = hook_type.file_name(),
+        changed = hook_type.changed_files()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hook::hook_script;
+    use crate::hook::HookType;
+
+    #[test]
+    fn scripts() {
+        let pre_commit = hook_script(HookType::PreCommit);
+        assert!(pre_commit.starts_with("#!/bin/sh\n"));
+        assert!(
+            pre_commit.contains("changed=$(git diff --cached --name-only --diff-filter=ACMR)\n")
+        );
+        assert!(pre_commit.contains("FLCHECK=\"${FLCHECK:-flcheck}\"\n"));
+
+        let pre_push = hook_script(HookType::PrePush);
+        assert!(pre_push.contains("'@{upstream}...HEAD'"));
+    }
+}
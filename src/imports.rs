@@ -0,0 +1,293 @@
+use crate::dependency::Dependency;
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::error::ValidationType;
+use crate::util::load_yaml;
+use crate::Config;
+use crate::Pubspec;
+use std::path::Path;
+use walkdir::WalkDir;
+use yaml_rust::Yaml;
+
+/// Directories (relative to the package) whose dart files are analyzed.
+pub const SOURCE_DIRS: [&str; 2] = ["lib", "test"];
+
+/// `package:` import (or export) of a dart file.
+#[derive(Debug, PartialEq)]
+pub struct Import {
+    /// Name of the imported package
+    pub package: String,
+    /// Dart file (relative to the package directory)
+    pub file: String,
+    /// Line (1-based) of the import
+    pub line: usize,
+}
+
+/// Extract the packages imported or exported by the given dart `content`
+/// along with their (1-based) lines, including the alternatives of
+/// conditional imports.
+pub fn parse_imports(content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .flat_map(|(idx, line)| {
+            let line = line.trim_start();
+            let uri = line
+                .strip_prefix("import")
+                .or_else(|| line.strip_prefix("export"))
+                .or_else(|| {
+                    // alternative of a conditional import: `if (dart.library.io) '...'`
+                    line.strip_prefix("if")
+                        .and_then(|rest| rest.split_once(')'))
+                        .map(|(_, uri)| uri)
+                })?
+                .trim_start();
+
+            let package = uri
+                .strip_prefix('\'')
+                .or_else(|| uri.strip_prefix('"'))?
+                .strip_prefix("package:")?
+                .split('/')
+                .next()
+                .filter(|name| !name.is_empty())?;
+
+            Some((idx + 1, package.to_owned()))
+        })
+        .collect()
+}
+
+/// Collect the `package:` imports of all dart files in the `SOURCE_DIRS`
+/// of the given package.
+pub fn package_imports(pubspec: &Pubspec) -> Vec<Import> {
+    let mut imports = Vec::new();
+
+    for source_dir in SOURCE_DIRS {
+        let dir = Path::new(&pubspec.dir_path).join(source_dir);
+        let files = WalkDir::new(&dir)
+            .sort_by_file_name()
+            .into_iter()
+            .flatten()
+            .filter(|entry| {
+                entry.file_type().is_file()
+                    && entry.path().extension().is_some_and(|ext| ext == "dart")
+            });
+
+        for entry in files {
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let file = entry
+                .path()
+                .strip_prefix(&pubspec.dir_path)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .into_owned();
+
+            imports.extend(
+                parse_imports(&content)
+                    .into_iter()
+                    .map(|(line, package)| Import {
+                        package,
+                        file: file.clone(),
+                        line,
+                    }),
+            );
+        }
+    }
+
+    imports
+}
+
+/// Cross-check the imports of the given package against its declared
+/// dependencies: imports of packages that are not declared (`lib` may import
+/// regular dependencies only) and regular dependencies that are never
+/// imported.
+pub fn import_validations(pubspec: &Pubspec, config: &Config) -> Vec<PackageValidation> {
+    let enabled = |code: ValidationType| {
+        config.validation_level_at(&code, &pubspec.dir_path) != ValidationLevel::None
+    };
+    // scanning the dart files is skipped if both validations are disabled
+    if config.is_blacklisted(&pubspec.path)
+        || !(enabled(ValidationType::UndeclaredImport) || enabled(ValidationType::UnusedDependency))
+    {
+        return vec![];
+    }
+
+    let imports = package_imports(pubspec);
+    // SDK dependencies (e.g. `flutter`) are not part of the model
+    let yaml = load_yaml(&pubspec.path).ok();
+    let declared = |section: &str, deps: &[Dependency], package: &str| {
+        deps.iter().any(|dep| dep.name() == package)
+            || yaml.as_ref().is_some_and(|yaml| {
+                yaml[section]
+                    .as_hash()
+                    .is_some_and(|deps| deps.contains_key(&Yaml::String(package.to_owned())))
+            })
+    };
+    let mut validations = Vec::new();
+    // every package is reported once per file
+    let mut reported: Vec<(&str, &str)> = Vec::new();
+
+    for import in imports.iter() {
+        if import.package == pubspec.name
+            || declared("dependencies", &pubspec.dependencies, &import.package)
+        {
+            continue;
+        }
+
+        let in_lib = Path::new(&import.file).starts_with("lib");
+        let dev_dependency = declared(
+            "dev_dependencies",
+            &pubspec.dev_dependencies,
+            &import.package,
+        );
+        let key = (import.file.as_str(), import.package.as_str());
+        if (!in_lib && dev_dependency) || reported.contains(&key) {
+            continue;
+        }
+        reported.push(key);
+
+        let code = ValidationType::UndeclaredImport;
+        validations.push(PackageValidation {
+            package_name: pubspec.name.clone(),
+            error: format!(
+                "import of undeclared package '{}' in {}",
+                import.package, import.file
+            ),
+            description: Some(if dev_dependency {
+                format!(
+                    "'{}' is a dev dependency, move it into dependencies",
+                    import.package
+                )
+            } else {
+                format!("add '{}' to the dependencies", import.package)
+            }),
+            level: config.validation_level_at(&code, &pubspec.dir_path),
+            code,
+            dependency: Some(import.package.clone()),
+            owner: None,
+            file: Path::new(&pubspec.dir_path)
+                .join(&import.file)
+                .to_str()
+                .map(|file| file.to_owned()),
+            relative_file: None,
+            line: Some(import.line),
+            fix: None,
+            repo: None,
+            package_type: None,
+        });
+    }
+
+    let code = ValidationType::UnusedDependency;
+    validations.extend(
+        pubspec
+            .dependencies
+            .iter()
+            .filter(|dep| !imports.iter().any(|import| &import.package == dep.name()))
+            .map(|dep| PackageValidation {
+                package_name: pubspec.name.clone(),
+                error: format!("dependency '{}' is never imported", dep.name()),
+                description: None,
+                level: config.validation_level_at(&code, &pubspec.dir_path),
+                code: code.clone(),
+                dependency: Some(dep.name().clone()),
+                owner: None,
+                file: None,
+                relative_file: None,
+                line: None,
+                fix: None,
+                repo: None,
+                package_type: None,
+            }),
+    );
+
+    validations.retain(|validation| validation.level != ValidationLevel::None);
+    validations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ValidationType;
+    use crate::imports::import_validations;
+    use crate::imports::parse_imports;
+    use crate::Config;
+    use crate::Pubspec;
+
+    #[test]
+    fn imports() {
+        let content = r#"
+import 'dart:io';
+import 'package:http/http.dart' as http;
+export "package:pkg_core/pkg_core.dart" show Core;
+import 'src/local.dart';
+import 'package:pkg_io/stub.dart'
+    if (dart.library.io) 'package:pkg_io_native/native.dart';
+// import 'package:commented/out.dart';
+"#;
+
+        assert_eq!(
+            parse_imports(content),
+            vec![
+                (3, "http".to_owned()),
+                (4, "pkg_core".to_owned()),
+                (6, "pkg_io".to_owned()),
+                (7, "pkg_io_native".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn undeclared_and_unused() {
+        let dir = std::env::temp_dir().join(format!("flcheck-imports-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib/src")).unwrap();
+        std::fs::create_dir_all(dir.join("test")).unwrap();
+        std::fs::write(
+            dir.join("lib/src/api.dart"),
+            "import 'package:http/http.dart';\nimport 'package:mockito/mockito.dart';\nimport 'package:pkg_foo/pkg_foo.dart';\nimport 'package:flutter/widgets.dart';\n",
+        )
+        .unwrap();
+        let content = "name: pkg_foo\ndependencies:\n  flutter:\n    sdk: flutter\n";
+        std::fs::write(dir.join("pubspec.yaml"), content).unwrap();
+        std::fs::write(
+            dir.join("test/api_test.dart"),
+            "import 'package:mockito/mockito.dart';\nimport 'package:path/path.dart';\n",
+        )
+        .unwrap();
+
+        let pubspec = Pubspec::parse(&format!("{}/pubspec.yaml", dir.display()), content)
+            .unwrap()
+            .with_deps(vec![
+                Pubspec::hosted("http", "^1.0.0"),
+                Pubspec::hosted("intl", "^1.0.0"),
+            ])
+            .with_dev_deps(vec![Pubspec::hosted("mockito", "^1.0.0")]);
+
+        let validations = import_validations(&pubspec, &Config::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let findings = validations
+            .iter()
+            .map(|validation| (&validation.code, validation.error.as_str(), validation.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            findings,
+            vec![
+                (
+                    &ValidationType::UndeclaredImport,
+                    "import of undeclared package 'mockito' in lib/src/api.dart",
+                    Some(2)
+                ),
+                (
+                    &ValidationType::UndeclaredImport,
+                    "import of undeclared package 'path' in test/api_test.dart",
+                    Some(2)
+                ),
+                (
+                    &ValidationType::UnusedDependency,
+                    "dependency 'intl' is never imported",
+                    None
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use crate::config::PackageType;
+use crate::workspace::Workspace;
+use crate::Pubspec;
+use std::collections::BTreeMap;
+
+/// Package type inferred from the directory names of the workspace.
+#[derive(Debug, PartialEq)]
+pub struct InferredType {
+    pub package_type: PackageType,
+    /// Names of the packages matching the package type
+    pub packages: Vec<String>,
+}
+
+/// Infer package types from the directory names of all `pubspecs`:
+/// packages sharing the same prefix (up to and including the first
+/// underscore) form one package type. The includes of every type are
+/// derived from the existing local dependencies.
+pub fn infer_package_types(pubspecs: &[Pubspec]) -> Vec<InferredType> {
+    let type_name = |dir_name: &str| dir_prefix(dir_name).trim_end_matches('_').to_owned();
+
+    let workspace = Workspace::new(pubspecs);
+    let mut types: BTreeMap<String, InferredType> = BTreeMap::new();
+
+    for pubspec in pubspecs {
+        let name = type_name(&pubspec.dir_name);
+        if name.is_empty() {
+            continue;
+        }
+
+        let inferred = types.entry(name.clone()).or_insert_with(|| InferredType {
+            package_type: PackageType {
+                name,
+                prefixes: Vec::new(),
+                includes: Vec::new(),
+                max_depth: None,
+                requires: Vec::new(),
+                pure_dart: false,
+                require_tests: false,
+            },
+            packages: Vec::new(),
+        });
+
+        let pkg_type = &mut inferred.package_type;
+        pkg_type.prefixes.push(dir_prefix(&pubspec.dir_name));
+        pkg_type.includes.extend(
+            pubspec
+                .dependencies
+                .iter()
+                .flat_map(|dep| pubspec.resolve_dependency(dep, &workspace))
+                .map(|resolved| type_name(&resolved.dir_name))
+                .filter(|include| !include.is_empty()),
+        );
+        inferred.packages.push(pubspec.name.clone());
+    }
+
+    types
+        .into_values()
+        .map(|mut inferred| {
+            let pkg_type = &mut inferred.package_type;
+            pkg_type.prefixes.sort_unstable();
+            pkg_type.prefixes.dedup();
+            pkg_type.includes.sort_unstable();
+            pkg_type.includes.dedup();
+            inferred
+        })
+        .collect()
+}
+
+/// Prefix of the given directory name up to and including the first
+/// underscore (or the whole name if there is none).
+fn dir_prefix(dir_name: &str) -> String {
+    match dir_name.find('_') {
+        Some(idx) => dir_name[..=idx].to_owned(),
+        None => dir_name.to_owned(),
+    }
+}
+
+/// Render the configuration file of the given package types and roots.
+pub fn render_config(package_types: &[PackageType], roots: &[String]) -> String {
+    let mut config = String::from(
+        "# Generated by `flcheck init`, see `flcheck example` for all options.\n\n\
+         package_types:\n",
+    );
+
+    for pkg_type in package_types {
+        config.push_str(&format!("\n  {}:\n", pkg_type.name));
+
+        match pkg_type.prefixes.as_slice() {
+            [prefix] => config.push_str(&format!("    dir_prefix: '{}'\n", prefix)),
+            prefixes => {
+                config.push_str("    dir_prefix:\n");
+                for prefix in prefixes {
+                    config.push_str(&format!("      - '{}'\n", prefix));
+                }
+            }
+        }
+
+        // only keep includes of package types that are configured
+        let includes = pkg_type
+            .includes
+            .iter()
+            .filter(|include| package_types.iter().any(|other| &&other.name == include))
+            .collect::<Vec<_>>();
+
+        if !includes.is_empty() {
+            config.push_str("    includes:\n");
+            for include in includes {
+                config.push_str(&format!("      - {}\n", include));
+            }
+        }
+    }
+
+    if !roots.is_empty() {
+        config.push_str("\nroots:\n");
+        for root in roots {
+            config.push_str(&format!("  - '{}'\n", root));
+        }
+    }
+
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PackageType;
+    use crate::init::infer_package_types;
+    use crate::init::render_config;
+    use crate::Pubspec;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn infer_types() {
+        let all = vec![
+            Pubspec::test("main").with_local_deps(&["app_one", "app_two"]),
+            Pubspec::test("app_one").with_local_deps(&["pkg_core"]),
+            Pubspec::test("app_two").with_local_deps(&["pkg_ui"]),
+            Pubspec::test("pkg_core"),
+            Pubspec::test("pkg_ui").with_local_deps(&["pkg_core"]),
+        ];
+
+        let inferred = infer_package_types(&all);
+        let types = inferred
+            .iter()
+            .map(|inferred| &inferred.package_type)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            types,
+            vec![
+                &PackageType {
+                    name: "app".to_owned(),
+                    prefixes: vec!["app_".to_owned()],
+                    includes: vec!["pkg".to_owned()],
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
+                },
+                &PackageType {
+                    name: "main".to_owned(),
+                    prefixes: vec!["main".to_owned()],
+                    includes: vec!["app".to_owned()],
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
+                },
+                &PackageType {
+                    name: "pkg".to_owned(),
+                    prefixes: vec!["pkg_".to_owned()],
+                    includes: vec!["pkg".to_owned()],
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
+                },
+            ]
+        );
+        assert_eq!(inferred[0].packages, vec!["app_one", "app_two"]);
+    }
+
+    #[test]
+    fn render_valid_yaml() {
+        let types = vec![
+            PackageType {
+                name: "app".to_owned(),
+                prefixes: vec!["app_".to_owned()],
+                includes: vec!["pkg".to_owned(), "removed".to_owned()],
+                max_depth: None,
+                requires: Vec::new(),
+                pure_dart: false,
+                require_tests: false,
+            },
+            PackageType {
+                name: "pkg".to_owned(),
+                prefixes: vec!["pkg".to_owned(), "pkg_".to_owned()],
+                includes: Vec::new(),
+                max_depth: None,
+                requires: Vec::new(),
+                pure_dart: false,
+                require_tests: false,
+            },
+        ];
+
+        let config = render_config(&types, &["packages".to_owned()]);
+        let yaml = YamlLoader::load_from_str(&config).unwrap().remove(0);
+
+        assert_eq!(
+            yaml["package_types"]["app"]["dir_prefix"].as_str(),
+            Some("app_")
+        );
+        assert_eq!(
+            yaml["package_types"]["app"]["includes"]
+                .as_vec()
+                .map(|v| v.len()),
+            Some(1)
+        );
+        assert_eq!(
+            yaml["package_types"]["pkg"]["dir_prefix"]
+                .as_vec()
+                .map(|v| v.len()),
+            Some(2)
+        );
+        assert_eq!(yaml["roots"][0].as_str(), Some("packages"));
+    }
+}
@@ -0,0 +1,50 @@
+//! flcheck - check, validate and analyze flutter (dart) package dependencies.
+//!
+//! Besides the `flcheck` CLI the crate can be used as a library, e.g. to
+//! validate workspaces programmatically or to supply a custom `Renderer`.
+
+pub mod bump;
+pub mod cache;
+pub mod cli;
+pub mod codeowners;
+pub mod command;
+pub mod config;
+pub mod constraint;
+pub mod custom;
+pub mod dependency;
+pub mod diff;
+pub mod docs;
+pub mod error;
+pub mod export;
+pub mod findings;
+pub mod graph;
+pub mod hook;
+pub mod imports;
+pub mod init;
+pub mod lint;
+pub mod list;
+pub mod metrics;
+pub mod multi;
+pub mod overrides;
+pub mod probe;
+pub mod pubdev;
+pub mod publish;
+pub mod pubspec;
+pub mod render;
+pub mod report;
+pub mod rules;
+pub mod sbom;
+pub mod serve;
+pub mod snapshot;
+pub mod stats;
+pub mod suggest;
+pub mod tree;
+pub mod util;
+pub mod version;
+pub mod workspace;
+
+pub use crate::cli::Opts;
+pub use crate::config::Config;
+pub use crate::error::FlError;
+pub use crate::pubspec::Pubspec;
+pub use crate::render::Renderer;
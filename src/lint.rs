@@ -0,0 +1,514 @@
+use crate::config::check_min_version;
+use crate::config::constraint_policies;
+use crate::config::dependency_rules;
+use crate::config::package_types;
+use crate::config::PackageType;
+use crate::config::CONFIG_KEYS;
+use crate::dependency::Dependency;
+use crate::error::FlError;
+use crate::error::ValidationLevel;
+use crate::error::ValidationType;
+use crate::util::glob_to_regex;
+use crate::Config;
+use crate::Pubspec;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use yaml_rust::Yaml;
+
+/// Problem of the configuration as reported by the `config-check` command.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct ConfigProblem {
+    pub level: ValidationLevel,
+    /// Configuration key the problem refers to
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigProblem {
+    fn error<K: Into<String>>(key: K, message: String) -> ConfigProblem {
+        ConfigProblem {
+            level: ValidationLevel::Error,
+            key: key.into(),
+            message,
+        }
+    }
+
+    fn warning<K: Into<String>>(key: K, message: String) -> ConfigProblem {
+        ConfigProblem {
+            level: ValidationLevel::Warning,
+            key: key.into(),
+            message,
+        }
+    }
+}
+
+/// Check the configuration `Yaml` for all problems that can be
+/// determined without looking at the workspace's packages.
+pub fn check_yaml(config_yaml: &Yaml) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+
+    if let Some(hash) = config_yaml.as_hash() {
+        for key in hash.keys() {
+            let name = key.as_str().unwrap_or("");
+            if !CONFIG_KEYS.contains(&name) {
+                problems.push(ConfigProblem::error(name, "unknown key".to_owned()));
+            }
+        }
+    }
+
+    if let Some(min_version) = config_yaml["min_version"].as_str() {
+        if let Err(err) = check_min_version(min_version, env!("CARGO_PKG_VERSION")) {
+            problems.push(ConfigProblem::error("min_version", err.to_string()));
+        }
+    }
+
+    check_package_types(config_yaml, &mut problems);
+    check_validations(config_yaml, &mut problems);
+    check_custom_validations(config_yaml, &mut problems);
+
+    if let Err(FlError::ConfigValidation(message)) = dependency_rules(config_yaml) {
+        problems.push(ConfigProblem::error("rules", message));
+    }
+    if let Err(FlError::ConfigValidation(message)) = constraint_policies(config_yaml) {
+        problems.push(ConfigProblem::error("constraint_styles", message));
+    }
+
+    for key in [
+        "blacklist",
+        "public_repositories",
+        "internal_repositories",
+        "private_packages",
+        "dev_only_packages",
+    ] {
+        check_patterns(
+            config_yaml,
+            key,
+            |pattern| pattern.to_owned(),
+            &mut problems,
+        );
+    }
+    for key in ["include", "exclude"] {
+        check_patterns(config_yaml, key, glob_to_regex, &mut problems);
+    }
+
+    problems
+}
+
+/// Check the (valid) `Config` against the packages of the workspace,
+/// e.g. for patterns that do not match anything at all.
+pub fn check_workspace(config: &Config, pubspecs: &[Pubspec]) -> Vec<ConfigProblem> {
+    let mut problems = Vec::new();
+
+    for pkg_type in config.package_types.iter() {
+        let unused = !pubspecs.iter().any(|pubspec| match &pubspec.package_type {
+            Some(name) => name == &pkg_type.name,
+            None => pkg_type.matches_prefix(&pubspec.dir_name),
+        });
+
+        if unused {
+            problems.push(ConfigProblem::warning(
+                format!("package_types.{}", pkg_type.name),
+                "dir_prefix matches no package".to_owned(),
+            ));
+        }
+    }
+
+    for pubspec in pubspecs {
+        if let Some(name) = &pubspec.package_type {
+            if pubspec.package_type(config).is_none() {
+                problems.push(ConfigProblem::error(
+                    "package_types",
+                    format!(
+                        "unknown package type '{}' declared by package '{}'",
+                        name, pubspec.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    let dependencies = pubspecs
+        .iter()
+        .flat_map(|pubspec| {
+            pubspec
+                .dependencies
+                .iter()
+                .chain(pubspec.dev_dependencies.iter())
+        })
+        .map(|dep| dep.effective())
+        .collect::<Vec<_>>();
+
+    let paths = pubspecs.iter().map(|pubspec| pubspec.path.as_str());
+    let dir_paths = pubspecs.iter().map(|pubspec| pubspec.dir_path.as_str());
+    let external_packages = dependencies
+        .iter()
+        .filter(|dep| dep.is_pubdev())
+        .map(|dep| dep.name().as_str());
+    let git_repos = dependencies.iter().flat_map(|dep| match dep {
+        Dependency::Git { git, .. } => Some(git.as_str()),
+        _ => None,
+    });
+
+    unmatched_patterns("blacklist", &config.blacklist, paths, &mut problems);
+    unmatched_patterns(
+        "private_packages",
+        &config.private_packages,
+        external_packages,
+        &mut problems,
+    );
+    unmatched_patterns(
+        "public_repositories",
+        &config.public_repositories,
+        git_repos.clone(),
+        &mut problems,
+    );
+    unmatched_patterns(
+        "internal_repositories",
+        &config.internal_repositories,
+        git_repos,
+        &mut problems,
+    );
+
+    for setting in config.validations.iter() {
+        unmatched_patterns(
+            &format!("validations.{}", setting.validation_type),
+            &setting.paths,
+            dir_paths.clone(),
+            &mut problems,
+        );
+    }
+
+    problems
+}
+
+fn check_package_types(config_yaml: &Yaml, problems: &mut Vec<ConfigProblem>) {
+    let pkg_types = package_types(config_yaml);
+    if pkg_types.is_empty() {
+        problems.push(ConfigProblem::error(
+            "package_types",
+            "no package types configured".to_owned(),
+        ));
+        return;
+    }
+
+    for (idx, pkg_type) in pkg_types.iter().enumerate() {
+        let key = format!("package_types.{}", pkg_type.name);
+
+        if pkg_type.prefixes.is_empty() {
+            problems.push(ConfigProblem::error(&key, "empty dir_prefix".to_owned()));
+        }
+
+        for include in pkg_type.includes.iter() {
+            if !pkg_types.iter().any(|other| &other.name == include) {
+                problems.push(ConfigProblem::error(
+                    &key,
+                    format!("unknown include '{}'", include),
+                ));
+            }
+        }
+
+        let max_depth = &config_yaml["package_types"][pkg_type.name.as_str()]["max_depth"];
+        if !max_depth.is_badvalue() && pkg_type.max_depth.is_none() {
+            problems.push(ConfigProblem::error(
+                &key,
+                "invalid max_depth (expected a non-negative number)".to_owned(),
+            ));
+        }
+
+        // the first package type matching a directory wins, so types whose
+        // prefixes are all covered by a previous type are never used
+        let shadowed_by = pkg_types[..idx].iter().find(|other| {
+            pkg_type.prefixes.iter().all(|prefix| {
+                other
+                    .prefixes
+                    .iter()
+                    .any(|other_prefix| prefix.starts_with(other_prefix))
+            })
+        });
+
+        if let Some(other) = shadowed_by.filter(|_| !pkg_type.prefixes.is_empty()) {
+            problems.push(ConfigProblem::warning(
+                &key,
+                format!("unreachable, shadowed by package type '{}'", other.name),
+            ));
+        }
+    }
+
+    for cycle in include_cycles(&pkg_types) {
+        problems.push(ConfigProblem::warning(
+            format!("package_types.{}", cycle[0]),
+            format!("cyclic includes {}", cycle.join(" -> ")),
+        ));
+    }
+}
+
+/// Determine all cycles between the includes of different package types.
+fn include_cycles(pkg_types: &[PackageType]) -> Vec<Vec<String>> {
+    let mut cycles: Vec<Vec<String>> = Vec::new();
+
+    for start in pkg_types.iter() {
+        let mut stack = vec![vec![start.name.clone()]];
+
+        while let Some(path) = stack.pop() {
+            let current = path
+                .last()
+                .and_then(|name| pkg_types.iter().find(|pkg_type| &pkg_type.name == name));
+
+            for include in current.iter().flat_map(|pkg_type| pkg_type.includes.iter()) {
+                if include == path.last().unwrap() {
+                    continue;
+                }
+
+                if include == &start.name {
+                    let mut cycle = path.clone();
+                    cycle.push(include.clone());
+
+                    // report every cycle once only (starting at its smallest member)
+                    let mut members = path.clone();
+                    members.sort_unstable();
+                    if members[0] == start.name {
+                        cycles.push(cycle);
+                    }
+                } else if !path.contains(include) {
+                    let mut next = path.clone();
+                    next.push(include.clone());
+                    stack.push(next);
+                }
+            }
+        }
+    }
+
+    cycles
+}
+
+fn check_validations(config_yaml: &Yaml, problems: &mut Vec<ConfigProblem>) {
+    let empty = Default::default();
+
+    for (key, value) in config_yaml["validations"].as_hash().unwrap_or(&empty) {
+        let type_str = key.as_str().unwrap_or("");
+        let key = format!("validations.{}", type_str);
+
+        if ValidationType::parse(type_str).is_none() {
+            problems.push(ConfigProblem::error(&key, "unknown validation".to_owned()));
+        }
+
+        let entries = match value.as_vec() {
+            Some(entries) => entries.iter().collect(),
+            None => vec![value],
+        };
+
+        for entry in entries {
+            let level = entry.as_str().or_else(|| entry["level"].as_str());
+            if level.and_then(ValidationLevel::parse).is_none() {
+                problems.push(ConfigProblem::error(
+                    &key,
+                    format!(
+                        "invalid level '{}' (supported: error, warn, none)",
+                        level.unwrap_or("")
+                    ),
+                ));
+            }
+
+            for path in entry["paths"].as_vec().into_iter().flatten() {
+                let pattern = path.as_str().unwrap_or("");
+                if Regex::new(pattern).is_err() {
+                    problems.push(ConfigProblem::error(
+                        &key,
+                        format!("invalid pattern '{}'", pattern),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn check_custom_validations(config_yaml: &Yaml, problems: &mut Vec<ConfigProblem>) {
+    let empty = Default::default();
+
+    for (key, value) in config_yaml["custom_validations"]
+        .as_hash()
+        .unwrap_or(&empty)
+    {
+        let code = key.as_str().unwrap_or("");
+        let key = format!("custom_validations.{}", code);
+
+        if ValidationType::parse(code).is_some() {
+            problems.push(ConfigProblem::error(
+                &key,
+                "code of a built-in validation".to_owned(),
+            ));
+        }
+
+        if value["command"].as_str().unwrap_or("").trim().is_empty() {
+            problems.push(ConfigProblem::error(&key, "missing command".to_owned()));
+        }
+
+        if let Some(level) = value["level"].as_str() {
+            if ValidationLevel::parse(level).is_none() {
+                problems.push(ConfigProblem::error(
+                    &key,
+                    format!("invalid level '{}' (supported: error, warn, none)", level),
+                ));
+            }
+        }
+    }
+}
+
+fn check_patterns<F: Fn(&str) -> String>(
+    config_yaml: &Yaml,
+    key: &str,
+    to_regex: F,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    let value = &config_yaml[key];
+    if value.is_badvalue() || value.is_null() {
+        return;
+    }
+
+    match value.as_vec() {
+        Some(entries) => {
+            for entry in entries {
+                let pattern = entry.as_str().unwrap_or("");
+                if Regex::new(&to_regex(pattern)).is_err() {
+                    problems.push(ConfigProblem::error(
+                        key,
+                        format!("invalid pattern '{}'", pattern),
+                    ));
+                }
+            }
+        }
+        None => problems.push(ConfigProblem::error(key, "expected a list".to_owned())),
+    }
+}
+
+fn unmatched_patterns<'a, I: Iterator<Item = &'a str> + Clone>(
+    key: &str,
+    patterns: &[Regex],
+    values: I,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    for pattern in patterns {
+        if !values.clone().any(|value| pattern.is_match(value)) {
+            problems.push(ConfigProblem::warning(
+                key,
+                format!("pattern '{}' matches nothing in the workspace", pattern),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ValidationLevel;
+    use crate::lint::check_yaml;
+    use crate::lint::ConfigProblem;
+    use yaml_rust::YamlLoader;
+
+    fn problems(yaml: &str) -> Vec<ConfigProblem> {
+        let mut docs = YamlLoader::load_from_str(yaml).unwrap();
+        check_yaml(&docs.remove(0))
+    }
+
+    #[test]
+    fn valid_config() {
+        let problems = problems(
+            "
+package_types:
+  app:
+    dir_prefix: app_
+    includes:
+      - package
+  package:
+    dir_prefix: pkg_
+    includes:
+      - package
+validations:
+  validation:dependency:cyclic: warn
+",
+        );
+
+        assert_eq!(problems, vec![]);
+    }
+
+    #[test]
+    fn all_problems() {
+        let problems = problems(
+            "
+package_types:
+  app:
+    dir_prefix: app
+    includes:
+      - missing
+  app_legacy:
+    dir_prefix: app_legacy
+    max_depth: -1
+  empty:
+    includes:
+blacklist: '^legacy'
+private_packages:
+  - '(unclosed'
+validation:
+  validation:dependency:cyclic: warn
+validations:
+  validation:dependency:unknown: warning
+  validation:foo: warn
+custom_validations:
+  validation:dependency:self:
+    command: ./check.sh
+    level: warning
+rules:
+  - deny: { from: 'app_*' }
+",
+        );
+
+        let messages = problems
+            .iter()
+            .map(|problem| format!("{} {} {}", problem.level, problem.key, problem.message))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            vec![
+                "error validation unknown key",
+                "error package_types.app unknown include 'missing'",
+                "error package_types.app_legacy invalid max_depth (expected a non-negative number)",
+                "warn package_types.app_legacy unreachable, shadowed by package type 'app'",
+                "error package_types.empty empty dir_prefix",
+                "error validations.validation:dependency:unknown invalid level 'warning' (supported: error, warn, none)",
+                "error validations.validation:foo unknown validation",
+                "error custom_validations.validation:dependency:self code of a built-in validation",
+                "error custom_validations.validation:dependency:self invalid level 'warning' (supported: error, warn, none)",
+                "error rules rules[0]: missing 'to'",
+                "error blacklist expected a list",
+                "error private_packages invalid pattern '(unclosed'",
+            ]
+        );
+    }
+
+    #[test]
+    fn include_cycles() {
+        let problems = problems(
+            "
+package_types:
+  app:
+    dir_prefix: app_
+    includes:
+      - feature
+  feature:
+    dir_prefix: feature_
+    includes:
+      - app
+",
+        );
+
+        assert_eq!(
+            problems,
+            vec![ConfigProblem {
+                level: ValidationLevel::Warning,
+                key: "package_types.app".to_owned(),
+                message: "cyclic includes app -> feature -> app".to_owned(),
+            }]
+        );
+    }
+}
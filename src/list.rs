@@ -0,0 +1,215 @@
+use crate::util::relative_path;
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::Path;
+
+/// Package as listed by the `list` command.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct PackageEntry {
+    pub name: String,
+    /// Package type (declared or based on the directory prefix)
+    pub package_type: Option<String>,
+    pub public: bool,
+    /// Number of (non-dev) dependencies
+    pub dependencies: usize,
+    /// Directory of the package (relative to the current directory if
+    /// located below it)
+    pub path: String,
+}
+
+/// Filters of the `list` command, all of which have to match.
+#[derive(Debug, Default)]
+pub struct ListFilter {
+    /// Name of the package type
+    pub package_type: Option<String>,
+    pub public_only: bool,
+    /// Name of a (non-dev) dependency
+    pub depends_on: Option<String>,
+    /// Name of a direct or transitive (non-dev) dependency
+    pub transitively_depends_on: Option<String>,
+    /// Packages no other package depends on (including dev dependencies)
+    pub no_dependents: bool,
+}
+
+impl ListFilter {
+    fn matches(&self, idx: usize, config: &Config, workspace: &Workspace) -> bool {
+        let pubspecs = workspace.packages;
+        let pubspec = &pubspecs[idx];
+        let type_matches = self.package_type.as_ref().is_none_or(|name| {
+            pubspec
+                .package_type(config)
+                .is_some_and(|pkg_type| &pkg_type.name == name)
+        });
+        let depends_on = self
+            .depends_on
+            .as_ref()
+            .is_none_or(|name| pubspec.dependencies.iter().any(|dep| dep.name() == name));
+
+        // dependencies of all packages the package depends on, which may
+        // be external packages as well
+        let transitively_depends_on = self.transitively_depends_on.as_ref().is_none_or(|name| {
+            std::iter::once(idx)
+                .chain(workspace.closure(idx))
+                .map(|dep| &pubspecs[dep])
+                .any(|package| package.dependencies.iter().any(|dep| dep.name() == name))
+        });
+        let no_dependents = !self.no_dependents || workspace.affected(&[idx]).len() == 1;
+
+        type_matches
+            && depends_on
+            && transitively_depends_on
+            && no_dependents
+            && (pubspec.is_public || !self.public_only)
+    }
+}
+
+/// List the packages matching the given `filter` with their paths relative
+/// to `root`.
+pub fn list_packages(
+    config: &Config,
+    pubspecs: &[Pubspec],
+    filter: &ListFilter,
+    root: &Path,
+) -> Vec<PackageEntry> {
+    let workspace = Workspace::new(pubspecs);
+
+    pubspecs
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| filter.matches(*idx, config, &workspace))
+        .map(|(_, pubspec)| pubspec)
+        .map(|pubspec| PackageEntry {
+            name: pubspec.name.clone(),
+            package_type: pubspec
+                .package_type(config)
+                .map(|pkg_type| pkg_type.name.clone()),
+            public: pubspec.is_public,
+            dependencies: pubspec.dependencies.len(),
+            path: relative_path(root, Path::new(&pubspec.dir_path))
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| pubspec.dir_path.clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PackageType;
+    use crate::dependency::Dependency;
+    use crate::list::list_packages;
+    use crate::list::ListFilter;
+    use crate::Config;
+    use crate::Pubspec;
+    use std::path::Path;
+
+    #[test]
+    fn filters() {
+        let config = Config {
+            package_types: vec![PackageType {
+                name: "app".to_owned(),
+                prefixes: vec!["app_".to_owned()],
+                includes: Vec::new(),
+                max_depth: None,
+                requires: Vec::new(),
+                pure_dart: false,
+                require_tests: false,
+            }],
+            ..Config::default()
+        };
+        let pubspecs = vec![
+            Pubspec {
+                is_public: true,
+                ..Pubspec::test_at("shared_ui", "/ws/shared_ui")
+            },
+            Pubspec::test_at("app_shop", "/ws/app_shop").with_local_deps(&["shared_ui"]),
+            Pubspec::test_at("app_admin", "/ws/app_admin").with_local_deps(&["app_shop"]),
+        ];
+        let names = |filter: ListFilter| {
+            list_packages(&config, &pubspecs, &filter, Path::new("/ws"))
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect::<Vec<_>>()
+        };
+
+        let all = list_packages(&config, &pubspecs, &ListFilter::default(), Path::new("/ws"));
+        assert_eq!(all[1].package_type.as_deref(), Some("app"));
+        assert_eq!(all[1].dependencies, 1);
+        assert_eq!(all[1].path, "app_shop");
+
+        let apps = ListFilter {
+            package_type: Some("app".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(names(apps), vec!["app_shop", "app_admin"]);
+
+        let public = ListFilter {
+            public_only: true,
+            ..Default::default()
+        };
+        assert_eq!(names(public), vec!["shared_ui"]);
+
+        let dependents = ListFilter {
+            depends_on: Some("shared_ui".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(names(dependents), vec!["app_shop"]);
+
+        let transitive = ListFilter {
+            transitively_depends_on: Some("shared_ui".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(names(transitive), vec!["app_shop", "app_admin"]);
+
+        let unused = ListFilter {
+            no_dependents: true,
+            ..Default::default()
+        };
+        assert_eq!(names(unused), vec!["app_admin"]);
+    }
+
+    #[test]
+    fn transitive_and_unused_filters() {
+        let http = Dependency::PubDev {
+            name: "http".to_owned(),
+            version: "^1.0.0".to_owned(),
+            overridden: Box::new(None),
+        };
+        let pubspecs = vec![
+            Pubspec::test_at("pkg_core", "/ws/pkg_core").with_deps(vec![http]),
+            Pubspec::test_at("pkg_test", "/ws/pkg_test").with_local_deps(&["pkg_core"]),
+            Pubspec::test_at("app_shop", "/ws/app_shop")
+                .with_local_deps(&["pkg_core"])
+                .with_local_dev_deps(&["pkg_test"]),
+        ];
+        let names = |filter: ListFilter| {
+            list_packages(&Config::default(), &pubspecs, &filter, Path::new("/ws"))
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect::<Vec<_>>()
+        };
+
+        // external dependencies of internal ones count as well
+        let transitive = ListFilter {
+            transitively_depends_on: Some("http".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(names(transitive), vec!["pkg_core", "pkg_test", "app_shop"]);
+
+        // dev dependencies make a package used
+        let unused = ListFilter {
+            no_dependents: true,
+            ..Default::default()
+        };
+        assert_eq!(names(unused), vec!["app_shop"]);
+
+        let combined = ListFilter {
+            transitively_depends_on: Some("pkg_core".to_owned()),
+            no_dependents: true,
+            ..Default::default()
+        };
+        assert_eq!(names(combined), vec!["app_shop"]);
+    }
+}
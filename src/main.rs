@@ -1,17 +1,18 @@
-use crate::cli::{OptCommand, Opts};
-use crate::config::Config;
-use crate::error::FlError;
-use crate::pubspec::Pubspec;
-use crate::FlError::NoInputFiles;
-
-mod cli;
-mod command;
-mod config;
-mod dependency;
-mod error;
-mod pubdev;
-mod pubspec;
-mod util;
+use flcheck::cache::PubspecCache;
+use flcheck::cache::CACHE_DIR;
+use flcheck::cli;
+use flcheck::cli::{OptCommand, Opts};
+use flcheck::command;
+use flcheck::config;
+use flcheck::config::CONFIG_FILE;
+use flcheck::render;
+use flcheck::workspace;
+use flcheck::Config;
+use flcheck::FlError;
+use flcheck::FlError::NoConfigFound;
+use log::info;
+use log::LevelFilter;
+use std::path::Path;
 
 async fn run(opts: Opts) -> Result<(), FlError> {
     // no need to load any pubspecs if we only want to
@@ -21,33 +22,141 @@ async fn run(opts: Opts) -> Result<(), FlError> {
         return Ok(());
     }
 
-    let config = Config::load(&opts.config_file)?;
+    if matches!(opts.command, OptCommand::Completions) {
+        return command::completions(&opts);
+    }
+
+    if matches!(opts.command, OptCommand::Schema) {
+        return command::schema();
+    }
+
+    // initialization creates the configuration in the first place
+    if matches!(opts.command, OptCommand::Init) {
+        return command::init(&opts);
+    }
+
+    if matches!(opts.command, OptCommand::InstallHook) {
+        return command::install_hook(&opts);
+    }
+
+    // validation reports are compared without any configuration
+    if matches!(opts.command, OptCommand::Diff) {
+        let mut renderer = render::renderer(&opts.output, opts.color, opts.path_style);
+        return command::diff(&opts, renderer.as_mut());
+    }
+
+    // every repository comes with its own configuration
+    if matches!(opts.command, OptCommand::Multi) {
+        let mut renderer = render::renderer(&opts.output, opts.color, opts.path_style);
+        return command::multi(&opts, renderer.as_mut()).await;
+    }
+
+    let config_file = match &opts.config_file {
+        Some(file) => file.clone(),
+        None => find_config()?,
+    };
+
+    info!("using configuration: {}", config_file);
+
+    let mut renderer = render::renderer(&opts.output, opts.color, opts.path_style);
 
-    let loaded_pubspecs: Result<Vec<Pubspec>, _> = pubspec::find_pubspecs(&opts.root_dir)
-        .iter()
-        .map(|pubspec| Pubspec::load(pubspec))
-        .collect();
+    // the configuration check reports all problems of the configuration
+    // instead of failing on the first one while loading
+    if matches!(opts.command, OptCommand::ConfigCheck) {
+        return command::config_check(&opts, &config_file, renderer.as_mut()).await;
+    }
+
+    let mut config = Config::load(&config_file, &opts.overrides, &opts.http_options()).await?;
 
-    let pubspecs = loaded_pubspecs?;
-    if pubspecs.is_empty() {
-        return Err(NoInputFiles(opts.root_dir));
+    // rules are independent of any packages as well
+    match opts.command {
+        OptCommand::Rules => return command::rules(&config, renderer.as_mut()),
+        OptCommand::Explain => return command::explain(&opts, &config, renderer.as_mut()),
+        _ => {}
     }
 
+    let root_dirs = opts.root_dirs(&config);
+    let cache = (!opts.no_cache).then(|| {
+        let config_dir = Path::new(&config_file).parent().unwrap_or(Path::new(""));
+        PubspecCache::open(&config_dir.join(CACHE_DIR))
+    });
+
+    // the server discovers the packages itself and keeps them up to date
+    if matches!(opts.command, OptCommand::Serve) {
+        return command::serve(&opts, config, root_dirs, cache);
+    }
+
+    let discovery = workspace::discover(&root_dirs, &config, opts.follow_symlinks, cache)?;
+    let pubspecs = &discovery.pubspecs;
+
+    // validations refer to the effective root directories of the workspace
+    config.roots = root_dirs;
+
     match opts.command {
-        OptCommand::Validate => command::validate(opts, config, pubspecs),
-        OptCommand::Dump => command::dump(opts, pubspecs),
-        OptCommand::Check => command::check(pubspecs).await,
-        OptCommand::Graph => command::graph(pubspecs),
-        OptCommand::ExampleConfig => unreachable!(),
+        OptCommand::Validate => command::validate(&opts, &config, &discovery, renderer.as_mut()),
+        OptCommand::Dump => command::dump(pubspecs, renderer.as_mut()),
+        OptCommand::Check => command::check(&opts, &config, pubspecs, renderer.as_mut()).await,
+        OptCommand::Graph => command::graph(&opts, &config, pubspecs, renderer.as_mut()),
+        OptCommand::Stats => command::stats(&config, pubspecs, renderer.as_mut()),
+        OptCommand::Tree => command::tree(&opts, pubspecs, renderer.as_mut()),
+        OptCommand::PublishOrder => command::publish_order(pubspecs, renderer.as_mut()),
+        OptCommand::List => command::list(&opts, &config, pubspecs, renderer.as_mut()),
+        OptCommand::Overrides => command::overrides(&config, pubspecs, renderer.as_mut()),
+        OptCommand::Bump => command::bump(&opts, pubspecs, renderer.as_mut()),
+        OptCommand::Snapshot => command::snapshot(&opts, pubspecs, renderer.as_mut()),
+        OptCommand::Export => command::export(&opts, &config, pubspecs),
+        OptCommand::Sbom => command::sbom(&opts, &config, pubspecs),
+        OptCommand::Docs => command::docs(&opts, &config, pubspecs),
+        OptCommand::Rules
+        | OptCommand::Explain
+        | OptCommand::ConfigCheck
+        | OptCommand::Init
+        | OptCommand::InstallHook
+        | OptCommand::Completions
+        | OptCommand::Schema
+        | OptCommand::Diff
+        | OptCommand::Multi
+        | OptCommand::Serve
+        | OptCommand::ExampleConfig => unreachable!(),
     }
 }
 
+/// Find the configuration file in the current working directory
+/// or any of its parent directories.
+fn find_config() -> Result<String, FlError> {
+    let cwd = std::env::current_dir()?;
+
+    config::find_config_file(&cwd)
+        .and_then(|file| file.to_str().map(|path| path.to_owned()))
+        .ok_or_else(|| NoConfigFound(format!("{} in {} and parents", CONFIG_FILE, cwd.display())))
+}
+
+/// Initialize logging to stderr: warnings by default, details with
+/// `--verbose` and nothing with `--quiet`. The `FLCHECK_LOG` environment
+/// variable (e.g. `flcheck::pubdev=trace`) takes precedence.
+fn init_logging(opts: &Opts) {
+    let level = if opts.verbose {
+        LevelFilter::Debug
+    } else if opts.quiet {
+        LevelFilter::Off
+    } else {
+        LevelFilter::Warn
+    };
+
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_env("FLCHECK_LOG")
+        .format_target(false)
+        .init();
+}
+
 #[tokio::main]
 async fn main() {
     let opts = cli::get_opts();
+    init_logging(&opts);
 
     if let Err(err) = run(opts).await {
         eprintln!("{}", err);
-        std::process::exit(1);
+        std::process::exit(err.exit_code());
     }
 }
@@ -0,0 +1,287 @@
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::report::Summary;
+use crate::stats::DependencyCounts;
+use crate::stats::Stats;
+use crate::stats::UNKNOWN_PACKAGE_TYPE;
+use crate::util::format_timestamp;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::time::SystemTime;
+
+/// Summary of a validation run appended to the metrics file (see
+/// `validate --append-metrics`), one JSON object per line.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct MetricsRecord {
+    /// Time of the run (RFC 3339, UTC)
+    pub timestamp: String,
+    /// Number of validated packages
+    pub packages: usize,
+    pub summary: Summary,
+}
+
+impl MetricsRecord {
+    /// Summarize the (visible) findings of validating `packages` packages
+    /// at `time`.
+    pub fn new(validations: &[PackageValidation], packages: usize, time: SystemTime) -> Self {
+        let findings = validations
+            .iter()
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect::<Vec<_>>();
+
+        MetricsRecord {
+            timestamp: format_timestamp(time),
+            packages,
+            summary: Summary::of_findings(&findings),
+        }
+    }
+}
+
+/// Append the given record as a single line to `file` (created if it does
+/// not exist yet).
+pub fn append_metrics(file: &str, record: &MetricsRecord) -> io::Result<()> {
+    let line = serde_json::to_string(record)?;
+    let mut out = OpenOptions::new().create(true).append(true).open(file)?;
+
+    writeln!(out, "{}", line)
+}
+
+/// Render the (visible) findings counts by code, level and package type
+/// along with the workspace statistics as gauges in the Prometheus text
+/// exposition format (see `validate --metrics-file`).
+pub fn prometheus_metrics(
+    validations: &[PackageValidation],
+    stats: &Stats,
+    time: SystemTime,
+) -> String {
+    let mut findings: BTreeMap<(String, &str, &str), usize> = BTreeMap::new();
+    for validation in validations
+        .iter()
+        .filter(|validation| validation.level != ValidationLevel::None)
+    {
+        let package_type = validation
+            .package_type
+            .as_deref()
+            .unwrap_or(UNKNOWN_PACKAGE_TYPE);
+        let key = (
+            validation.code.to_string(),
+            validation.level.as_str(),
+            package_type,
+        );
+        *findings.entry(key).or_insert(0) += 1;
+    }
+
+    let mut out = String::new();
+    gauge(
+        &mut out,
+        "flcheck_findings",
+        "Number of validation findings.",
+        findings.iter().map(|((code, level, package_type), count)| {
+            let labels = [
+                ("code", code.as_str()),
+                ("level", level),
+                ("package_type", package_type),
+            ];
+            (labels.to_vec(), *count as f64)
+        }),
+    );
+    gauge(
+        &mut out,
+        "flcheck_packages",
+        "Number of packages of the workspace.",
+        stats.package_types.iter().map(|(package_type, count)| {
+            (vec![("package_type", package_type.as_str())], *count as f64)
+        }),
+    );
+
+    let sources = |counts: &DependencyCounts, dev: &'static str| {
+        [
+            ("local", counts.local),
+            ("git", counts.git),
+            ("pubdev", counts.pubdev),
+        ]
+        .map(|(source, count)| (vec![("source", source), ("dev", dev)], count as f64))
+    };
+    gauge(
+        &mut out,
+        "flcheck_dependencies",
+        "Number of declared dependencies.",
+        sources(&stats.dependencies, "false")
+            .into_iter()
+            .chain(sources(&stats.dev_dependencies, "true")),
+    );
+    gauge(
+        &mut out,
+        "flcheck_external_packages",
+        "Number of distinct external (pub.dev) packages.",
+        [(Vec::new(), stats.external_packages as f64)],
+    );
+    gauge(
+        &mut out,
+        "flcheck_dependency_depth_max",
+        "Longest chain of local dependencies.",
+        [(Vec::new(), stats.max_depth as f64)],
+    );
+    gauge(
+        &mut out,
+        "flcheck_dependency_depth_average",
+        "Average depth of the local dependencies of all packages.",
+        [(Vec::new(), stats.average_depth)],
+    );
+
+    let seconds = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    gauge(
+        &mut out,
+        "flcheck_last_run_timestamp_seconds",
+        "Time of the validation run (unix epoch).",
+        [(Vec::new(), seconds as f64)],
+    );
+
+    out
+}
+
+/// Append the gauge `name` with the given samples (labels and value) to
+/// `out`.
+fn gauge<'a, I>(out: &mut String, name: &str, help: &str, samples: I)
+where
+    I: IntoIterator<Item = (Vec<(&'a str, &'a str)>, f64)>,
+{
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+
+    for (labels, value) in samples {
+        let labels = labels
+            .iter()
+            .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+            .collect::<Vec<_>>();
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+        }
+    }
+}
+
+/// Escape backslashes, double quotes and line feeds of a label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::metrics::append_metrics;
+    use crate::metrics::escape_label;
+    use crate::metrics::prometheus_metrics;
+    use crate::metrics::MetricsRecord;
+    use crate::stats::Stats;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    fn validation(code: ValidationType, level: ValidationLevel) -> PackageValidation {
+        PackageValidation {
+            package_name: "app_foo".to_owned(),
+            error: "some error".to_owned(),
+            description: None,
+            code,
+            level,
+            dependency: None,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: Some("app".to_owned()),
+        }
+    }
+
+    #[test]
+    fn append_records() {
+        let file =
+            std::env::temp_dir().join(format!("flcheck-metrics-{}.jsonl", std::process::id()));
+        let file = file.to_string_lossy();
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1706702400);
+        let validations = vec![
+            validation(ValidationType::CyclicDependency, ValidationLevel::Error),
+            validation(ValidationType::UnusedDependency, ValidationLevel::Warning),
+            validation(ValidationType::UnusedDependency, ValidationLevel::None),
+        ];
+
+        append_metrics(&file, &MetricsRecord::new(&validations, 3, time)).unwrap();
+        append_metrics(&file, &MetricsRecord::new(&[], 3, time)).unwrap();
+
+        let content = std::fs::read_to_string(file.as_ref()).unwrap();
+        std::fs::remove_file(file.as_ref()).unwrap();
+
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "{\"timestamp\":\"2024-01-31T12:00:00Z\",\"packages\":3,\"summary\":{\"errors\":1,\
+                 \"warnings\":1,\"packages\":1,\"by_code\":[{\"name\":\"validation:dependency:cyclic\",\
+                 \"errors\":1,\"warnings\":0},{\"name\":\"validation:dependency:unused\",\"errors\":0,\
+                 \"warnings\":1}],\"by_package_type\":[{\"name\":\"app\",\"errors\":1,\"warnings\":1}]}}",
+                "{\"timestamp\":\"2024-01-31T12:00:00Z\",\"packages\":3,\"summary\":{\"errors\":0,\
+                 \"warnings\":0,\"packages\":0}}",
+            ]
+        );
+    }
+
+    #[test]
+    fn prometheus() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1706702400);
+        let validations = vec![
+            validation(ValidationType::CyclicDependency, ValidationLevel::Error),
+            validation(ValidationType::CyclicDependency, ValidationLevel::Error),
+            PackageValidation {
+                package_type: None,
+                ..validation(ValidationType::UnusedDependency, ValidationLevel::Warning)
+            },
+            validation(ValidationType::UnusedDependency, ValidationLevel::None),
+        ];
+        let stats = Stats::collect(&Config::default(), &[]);
+
+        let metrics = prometheus_metrics(&validations, &stats, time);
+        let samples = metrics
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        assert!(metrics.starts_with(
+            "# HELP flcheck_findings Number of validation findings.\n\
+             # TYPE flcheck_findings gauge\n"
+        ));
+        assert_eq!(
+            samples,
+            vec![
+                "flcheck_findings{code=\"validation:dependency:cyclic\",level=\"error\",package_type=\"app\"} 2",
+                "flcheck_findings{code=\"validation:dependency:unused\",level=\"warn\",package_type=\"<none>\"} 1",
+                "flcheck_dependencies{source=\"local\",dev=\"false\"} 0",
+                "flcheck_dependencies{source=\"git\",dev=\"false\"} 0",
+                "flcheck_dependencies{source=\"pubdev\",dev=\"false\"} 0",
+                "flcheck_dependencies{source=\"local\",dev=\"true\"} 0",
+                "flcheck_dependencies{source=\"git\",dev=\"true\"} 0",
+                "flcheck_dependencies{source=\"pubdev\",dev=\"true\"} 0",
+                "flcheck_external_packages 0",
+                "flcheck_dependency_depth_max 0",
+                "flcheck_dependency_depth_average 0",
+                "flcheck_last_run_timestamp_seconds 1706702400",
+            ]
+        );
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
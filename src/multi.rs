@@ -0,0 +1,118 @@
+use crate::error::FlError;
+use crate::error::FlError::ConfigValidation;
+use crate::util::load_yaml;
+use std::path::Path;
+use yaml_rust::Yaml;
+
+/// Repository validated by the `multi` command.
+#[derive(Debug, PartialEq)]
+pub struct Repository {
+    /// Name the findings of the repository are tagged with
+    pub name: String,
+    /// Directory of the repository (packages are discovered in unless the
+    /// configuration specifies `roots`)
+    pub path: String,
+    /// Configuration file of the repository, searched in the repository's
+    /// directory unless given
+    pub config: Option<String>,
+}
+
+/// Load the repositories listed in the given `file` (see `parse_repositories`).
+pub fn load_repositories(file: &str) -> Result<Vec<Repository>, FlError> {
+    let yaml = load_yaml(file)?;
+    let base_dir = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+
+    parse_repositories(&yaml, base_dir)
+        .map_err(|err| ConfigValidation(format!("{}: {}", file, err)))
+}
+
+/// Parse the `repos` of the given `yaml`, each with a `path` (relative to
+/// `base_dir`), an optional `name` (defaults to the directory name) and an
+/// optional `config` file (relative to the repository).
+fn parse_repositories(yaml: &Yaml, base_dir: &Path) -> Result<Vec<Repository>, String> {
+    let entries = yaml["repos"]
+        .as_vec()
+        .ok_or_else(|| "missing list of 'repos'".to_owned())?;
+
+    let mut repos: Vec<Repository> = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        // a plain string is the path of the repository
+        let path = entry
+            .as_str()
+            .or_else(|| entry["path"].as_str())
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| format!("repository #{} without 'path'", idx + 1))?;
+        let dir = base_dir.join(path);
+
+        let name = entry["name"]
+            .as_str()
+            .map(|name| name.to_owned())
+            .or_else(|| {
+                dir.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.to_owned())
+            })
+            .unwrap_or_else(|| path.to_owned());
+
+        if repos.iter().any(|repo| repo.name == name) {
+            return Err(format!("duplicate repository name '{}'", name));
+        }
+
+        repos.push(Repository {
+            name,
+            path: dir.to_str().unwrap_or(path).to_owned(),
+            config: entry["config"]
+                .as_str()
+                .and_then(|config| dir.join(config).to_str().map(|file| file.to_owned())),
+        });
+    }
+
+    Ok(repos)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::multi::parse_repositories;
+    use crate::multi::Repository;
+    use std::path::Path;
+    use yaml_rust::YamlLoader;
+
+    fn parse(content: &str) -> Result<Vec<Repository>, String> {
+        let yaml = YamlLoader::load_from_str(content).unwrap().remove(0);
+        parse_repositories(&yaml, Path::new("/audit"))
+    }
+
+    #[test]
+    fn repositories() {
+        let repos = parse(
+            "
+repos:
+  - ../shop
+  - name: payments
+    path: /src/payments-app
+    config: ci/flcheck.yaml
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            repos,
+            vec![
+                Repository {
+                    name: "shop".to_owned(),
+                    path: "/audit/../shop".to_owned(),
+                    config: None,
+                },
+                Repository {
+                    name: "payments".to_owned(),
+                    path: "/src/payments-app".to_owned(),
+                    config: Some("/src/payments-app/ci/flcheck.yaml".to_owned()),
+                },
+            ]
+        );
+
+        assert!(parse("repos: [{ name: shop }]").is_err());
+        assert!(parse("repos: [../shop, /other/shop]").is_err());
+        assert!(parse("repositories: []").is_err());
+    }
+}
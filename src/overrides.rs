@@ -0,0 +1,194 @@
+use crate::pubspec::extract_dependency;
+use crate::util::load_yaml;
+use crate::util::normalize_path;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use yaml_rust::Yaml;
+
+/// File next to the pubspec.yaml whose `dependency_overrides` replace the
+/// ones of the pubspec.yaml (if present).
+pub const OVERRIDES_FILE: &str = "pubspec_overrides.yaml";
+
+/// Suspicious override as reported by the `overrides` command.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverrideProblem {
+    /// Override resolving to the declared dependency itself
+    NoOp,
+    /// Local override whose path does not exist
+    MissingPath,
+}
+
+impl Display for OverrideProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideProblem::NoOp => f.write_str("no-op, same as the declared dependency"),
+            OverrideProblem::MissingPath => f.write_str("path does not exist"),
+        }
+    }
+}
+
+/// Active dependency override of a package.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct OverrideEntry {
+    pub package: String,
+    pub dependency: String,
+    /// File declaring the override (pubspec.yaml or pubspec_overrides.yaml)
+    pub file: String,
+    /// Source of the declared dependency (none for overrides of transitive
+    /// dependencies), e.g. `[public, ^1.0.0]`
+    pub original: Option<String>,
+    /// Source of the overriding dependency, e.g. `[local, ../pkg_core]`
+    pub effective: String,
+    pub problem: Option<OverrideProblem>,
+}
+
+/// Short description of the source of the dependency `name` declared as
+/// `value` (see `Dependency::details`).
+fn source(name: &str, value: &Yaml) -> String {
+    extract_dependency(name, value)
+        .map(|dep| dep.details())
+        .unwrap_or_else(|| "[unknown]".to_owned())
+}
+
+/// Collect the active overrides of the given package: the
+/// `dependency_overrides` of its pubspec_overrides.yaml or, if there is
+/// none, the ones of its pubspec.yaml.
+pub fn package_overrides(pubspec: &Pubspec) -> Vec<OverrideEntry> {
+    let Ok(yaml) = load_yaml(&pubspec.path) else {
+        return Vec::new();
+    };
+    let dir = Path::new(&pubspec.dir_path);
+    let overrides_yaml = load_yaml(&dir.join(OVERRIDES_FILE).to_string_lossy())
+        .ok()
+        .filter(|overrides| !overrides["dependency_overrides"].is_badvalue());
+
+    let (file, overrides) = match &overrides_yaml {
+        Some(overrides) => (OVERRIDES_FILE, &overrides["dependency_overrides"]),
+        None => ("pubspec.yaml", &yaml["dependency_overrides"]),
+    };
+    let empty = Default::default();
+
+    overrides
+        .as_hash()
+        .unwrap_or(&empty)
+        .iter()
+        .flat_map(|(key, value)| {
+            let name = key.as_str().filter(|name| !name.is_empty())?;
+            let declared = [&yaml["dependencies"], &yaml["dev_dependencies"]]
+                .into_iter()
+                .map(|deps| &deps[name])
+                .find(|value| !value.is_badvalue());
+
+            let local_dir = value["path"]
+                .as_str()
+                .map(|path| normalize_path(&dir.join(path)));
+            let problem = if local_dir.as_ref().is_some_and(|dir| !dir.is_dir()) {
+                Some(OverrideProblem::MissingPath)
+            } else if declared.is_some_and(|declared| {
+                declared == value
+                    || declared["path"]
+                        .as_str()
+                        .is_some_and(|path| Some(normalize_path(&dir.join(path))) == local_dir)
+            }) {
+                Some(OverrideProblem::NoOp)
+            } else {
+                None
+            };
+
+            Some(OverrideEntry {
+                package: pubspec.name.clone(),
+                dependency: name.to_owned(),
+                file: file.to_owned(),
+                original: declared.map(|declared| source(name, declared)),
+                effective: source(name, value),
+                problem,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::overrides::package_overrides;
+    use crate::overrides::OverrideProblem;
+    use crate::Pubspec;
+
+    #[test]
+    fn overrides() {
+        let dir = std::env::temp_dir().join(format!("flcheck-overrides-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("app/lib")).unwrap();
+        std::fs::create_dir_all(dir.join("pkg_core")).unwrap();
+        std::fs::create_dir_all(dir.join("pkg_ui")).unwrap();
+        let content = "
+name: app
+dependencies:
+  pkg_core: ^1.0.0
+  pkg_ui:
+    path: ../pkg_ui
+dependency_overrides:
+  pkg_core:
+    path: ../pkg_core
+";
+        std::fs::write(dir.join("app/pubspec.yaml"), content).unwrap();
+        let pubspec =
+            Pubspec::parse(&format!("{}/app/pubspec.yaml", dir.display()), content).unwrap();
+
+        let overrides = package_overrides(&pubspec);
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].file, "pubspec.yaml");
+        assert_eq!(overrides[0].original.as_deref(), Some("[public, ^1.0.0]"));
+        assert_eq!(overrides[0].effective, "[local, ../pkg_core]");
+        assert_eq!(overrides[0].problem, None);
+
+        // pubspec_overrides.yaml replaces the overrides of the pubspec.yaml
+        std::fs::write(
+            dir.join("app/pubspec_overrides.yaml"),
+            "
+dependency_overrides:
+  pkg_ui:
+    path: ../pkg_ui/
+  pkg_missing:
+    path: ../pkg_missing
+  intl: 0.18.0
+",
+        )
+        .unwrap();
+        let overrides = package_overrides(&pubspec);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let entries = overrides
+            .iter()
+            .map(|entry| {
+                (
+                    entry.dependency.as_str(),
+                    entry.file.as_str(),
+                    entry.original.is_some(),
+                    entry.problem,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "pkg_ui",
+                    "pubspec_overrides.yaml",
+                    true,
+                    Some(OverrideProblem::NoOp)
+                ),
+                (
+                    "pkg_missing",
+                    "pubspec_overrides.yaml",
+                    false,
+                    Some(OverrideProblem::MissingPath)
+                ),
+                ("intl", "pubspec_overrides.yaml", false, None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use crate::dependency::Dependency;
+use crate::error::FlError;
+use crate::error::FlError::GitError;
+use crate::util::load_yaml;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+use std::thread;
+
+/// Problem of a git dependency found via `check --probe-git`.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitProblem {
+    /// Repository does not exist (anymore) or is not accessible
+    Unreachable,
+    /// Repository has no branch or tag named as the referenced `ref`
+    MissingRef,
+    /// Referenced `path` contains no pubspec.yaml (see `--probe-git-paths`)
+    MissingPath,
+}
+
+impl Display for GitProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitProblem::Unreachable => f.write_str("repository not reachable"),
+            GitProblem::MissingRef => f.write_str("ref does not exist"),
+            GitProblem::MissingPath => f.write_str("no pubspec.yaml at path"),
+        }
+    }
+}
+
+/// Git dependency of a package along with the result of probing its
+/// repository.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct GitProbe {
+    pub package: String,
+    pub dependency: String,
+    pub url: String,
+    /// Branch, tag or commit the dependency refers to (if any)
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    /// Directory of the package inside the repository (if any)
+    pub path: Option<String>,
+    /// Dependency is part of the dev_dependencies
+    pub dev: bool,
+    pub problem: Option<GitProblem>,
+    /// Error reported by git (if the repository is not reachable)
+    pub message: Option<String>,
+}
+
+/// Whether the given ref is a (possibly abbreviated) commit hash, which
+/// cannot be looked up remotely unless it is the tip of some branch.
+fn is_commit(git_ref: &str) -> bool {
+    (7..=40).contains(&git_ref.len()) && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Collect the (declared) git dependencies of the given pubspec, including
+/// the dev dependencies if requested. The `ref` is read from the
+/// pubspec.yaml as it is not part of the `Dependency`.
+pub fn git_dependencies(pubspec: &Pubspec, dev: bool) -> Vec<GitProbe> {
+    let yaml = load_yaml(&pubspec.path).ok();
+    let dev_deps = pubspec.dev_dependencies.iter().filter(|_| dev);
+
+    pubspec
+        .dependencies
+        .iter()
+        .map(|dep| (dep, false))
+        .chain(dev_deps.map(|dep| (dep, true)))
+        .flat_map(|(dep, dev)| match dep {
+            Dependency::Git {
+                name, git, path, ..
+            } => {
+                let section = if dev {
+                    "dev_dependencies"
+                } else {
+                    "dependencies"
+                };
+                let git_ref = yaml
+                    .as_ref()
+                    .and_then(|yaml| yaml[section][name.as_str()]["git"]["ref"].as_str())
+                    .map(|git_ref| git_ref.to_owned());
+
+                Some(GitProbe {
+                    package: pubspec.name.clone(),
+                    dependency: name.clone(),
+                    url: git.clone(),
+                    git_ref,
+                    path: Some(path.trim_start_matches("./").trim_matches('/'))
+                        .filter(|path| !path.is_empty())
+                        .map(|path| path.to_owned()),
+                    dev,
+                    problem: None,
+                    message: None,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Probe the repository `url` via `git ls-remote`, verifying the given
+/// branch or tag exists as well. Commits are not verified.
+fn probe(url: &str, git_ref: Option<&str>) -> Result<Option<(GitProblem, String)>, FlError> {
+    let mut args = vec!["ls-remote", "--exit-code", url];
+    args.extend(git_ref.filter(|git_ref| !is_commit(git_ref)));
+    let output = git(&args)?;
+
+    // exit code 2: no matching refs (or no refs at all)
+    match output.status.code() {
+        Some(0) => Ok(None),
+        Some(2) if git_ref.is_some() => Ok(Some((GitProblem::MissingRef, String::new()))),
+        Some(2) => Ok(None),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr
+                .lines()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or_default();
+
+            Ok(Some((GitProblem::Unreachable, message.trim().to_owned())))
+        }
+    }
+}
+
+/// Run git with the given arguments (failing instead of prompting for
+/// credentials).
+fn git(args: &[&str]) -> Result<Output, FlError> {
+    Command::new("git")
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| GitError(format!("cannot run 'git {}': {}", args[0], err)))
+}
+
+/// Determine which of the given `paths` (`""` for the repository root)
+/// contain no pubspec.yaml at `git_ref` of the repository `url`. The
+/// commit is fetched (without any file contents) into a temporary
+/// repository, abbreviated commit hashes cannot be fetched and are skipped.
+fn missing_paths(
+    url: &str,
+    git_ref: Option<&str>,
+    paths: &[&str],
+    dir: &Path,
+) -> Result<Vec<String>, FlError> {
+    if git_ref.is_some_and(|git_ref| is_commit(git_ref) && git_ref.len() < 40) {
+        return Ok(Vec::new());
+    }
+
+    let dir_str = dir.to_string_lossy();
+    let fetched = git(&["init", "--bare", "--quiet", &dir_str])?
+        .status
+        .success()
+        && git(&[
+            "-C",
+            &dir_str,
+            "fetch",
+            "--quiet",
+            "--depth",
+            "1",
+            "--filter=blob:none",
+            url,
+            git_ref.unwrap_or("HEAD"),
+        ])?
+        .status
+        .success();
+
+    let mut missing = Vec::new();
+    for path in paths {
+        let file = if path.is_empty() {
+            "pubspec.yaml".to_owned()
+        } else {
+            format!("{}/pubspec.yaml", path)
+        };
+        // trees are fetched even if blobs are filtered
+        let listed = fetched
+            && git(&["-C", &dir_str, "ls-tree", "FETCH_HEAD", "--", &file])
+                .is_ok_and(|output| output.status.success() && !output.stdout.is_empty());
+        if !listed {
+            missing.push(path.to_string());
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+    Ok(missing)
+}
+
+/// Probe the repositories of all given git dependencies (every distinct
+/// repository and ref once, concurrently) and record the problems found.
+/// With `paths` the packages' directories are verified to contain a
+/// pubspec.yaml at the referenced ref as well (fetching the repositories).
+pub fn probe_git_dependencies(
+    mut probes: Vec<GitProbe>,
+    paths: bool,
+) -> Result<Vec<GitProbe>, FlError> {
+    let mut targets = probes
+        .iter()
+        .map(|probe| (probe.url.clone(), probe.git_ref.clone()))
+        .collect::<Vec<_>>();
+    targets.sort_unstable();
+    targets.dedup();
+
+    let results = thread::scope(|scope| {
+        let handles = targets
+            .iter()
+            .enumerate()
+            .map(|(idx, (url, git_ref))| {
+                let mut dirs = probes
+                    .iter()
+                    .filter(|probe| paths && &probe.url == url && &probe.git_ref == git_ref)
+                    .map(|probe| probe.path.as_deref().unwrap_or(""))
+                    .collect::<Vec<_>>();
+                dirs.sort_unstable();
+                dirs.dedup();
+                let fetch_dir = std::env::temp_dir().join(format!(
+                    "flcheck-probe-{}-{}",
+                    std::process::id(),
+                    idx
+                ));
+
+                scope.spawn(move || {
+                    let git_ref = git_ref.as_deref();
+                    match probe(url, git_ref)? {
+                        Some(problem) => Ok(Err(problem)),
+                        None if dirs.is_empty() => Ok(Ok(Vec::new())),
+                        None => missing_paths(url, git_ref, &dirs, &fetch_dir).map(Ok),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        targets
+            .iter()
+            .zip(handles)
+            .map(|(target, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(GitError("git ls-remote panicked".to_owned())));
+                result.map(|problem| (target, problem))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()
+    })?;
+
+    for probe in probes.iter_mut() {
+        let target = (probe.url.clone(), probe.git_ref.clone());
+        match results.get(&target) {
+            Some(Err((problem, message))) => {
+                probe.problem = Some(*problem);
+                probe.message = Some(message.clone()).filter(|message| !message.is_empty());
+            }
+            Some(Ok(missing)) if missing.contains(&probe.path.clone().unwrap_or_default()) => {
+                probe.problem = Some(GitProblem::MissingPath);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(probes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::probe::git_dependencies;
+    use crate::probe::missing_paths;
+    use crate::probe::probe_git_dependencies;
+    use crate::probe::GitProblem;
+    use crate::Pubspec;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) -> bool {
+        Command::new("git")
+            .args([
+                "-c",
+                "user.name=flcheck",
+                "-c",
+                "user.email=flcheck@localhost",
+            ])
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn probe_repositories() {
+        let dir = std::env::temp_dir().join(format!("flcheck-probe-test-{}", std::process::id()));
+        let repo = dir.join("repo");
+        std::fs::create_dir_all(repo.join("packages/pkg_core")).unwrap();
+        std::fs::write(repo.join("pubspec.yaml"), "name: pkg_root").unwrap();
+        std::fs::write(
+            repo.join("packages/pkg_core/pubspec.yaml"),
+            "name: pkg_core",
+        )
+        .unwrap();
+        let initialized = git(&repo, &["init", "--quiet", "-b", "main"])
+            && git(&repo, &["add", "."])
+            && git(&repo, &["commit", "--quiet", "-m", "init"]);
+        if !initialized {
+            // git is not available
+            std::fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+
+        let content = format!(
+            "
+name: app
+dependencies:
+  pkg_root:
+    git: {repo}
+  pkg_core:
+    git:
+      url: {repo}
+      path: packages/pkg_core
+  pkg_typo:
+    git:
+      url: {repo}
+      path: packages/pkg_cor
+  pkg_ui:
+    git:
+      url: {repo}
+      ref: v1.0.0
+  pkg_gone:
+    git:
+      url: {gone}
+      ref: 0a1b2c3d
+dev_dependencies:
+  pkg_test:
+    git:
+      url: {repo}
+      ref: main
+      path: ./packages/pkg_core/
+",
+            repo = repo.display(),
+            gone = dir.join("gone.git").display()
+        );
+        let path = dir.join("pubspec.yaml");
+        std::fs::write(&path, &content).unwrap();
+        let pubspec = Pubspec::parse(&path.to_string_lossy(), &content).unwrap();
+
+        assert_eq!(git_dependencies(&pubspec, false).len(), 5);
+
+        let problems = |paths: bool| {
+            probe_git_dependencies(git_dependencies(&pubspec, true), paths)
+                .unwrap()
+                .into_iter()
+                .map(|probe| (probe.dependency, probe.problem))
+                .collect::<Vec<_>>()
+        };
+        let shallow = problems(false);
+        let deep = problems(true);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let expected = |typo: Option<GitProblem>| {
+            vec![
+                ("pkg_root".to_owned(), None),
+                ("pkg_core".to_owned(), None),
+                ("pkg_typo".to_owned(), typo),
+                ("pkg_ui".to_owned(), Some(GitProblem::MissingRef)),
+                ("pkg_gone".to_owned(), Some(GitProblem::Unreachable)),
+                ("pkg_test".to_owned(), None),
+            ]
+        };
+        assert_eq!(shallow, expected(None));
+        assert_eq!(deep, expected(Some(GitProblem::MissingPath)));
+    }
+
+    #[test]
+    fn missing_paths_at_ref() {
+        let dir = std::env::temp_dir().join(format!("flcheck-paths-test-{}", std::process::id()));
+        let repo = dir.join("repo");
+        std::fs::create_dir_all(repo.join("packages/pkg_core")).unwrap();
+        std::fs::write(repo.join("pubspec.yaml"), "name: pkg_root").unwrap();
+        let initialized = git(&repo, &["init", "--quiet", "-b", "main"])
+            && git(&repo, &["add", "."])
+            && git(&repo, &["commit", "--quiet", "-m", "init"])
+            && git(&repo, &["tag", "v1.0.0"]);
+        if !initialized {
+            // git is not available
+            std::fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        let initial = Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .unwrap();
+        let initial = String::from_utf8_lossy(&initial.stdout).trim().to_owned();
+
+        // package added after the tag
+        std::fs::write(
+            repo.join("packages/pkg_core/pubspec.yaml"),
+            "name: pkg_core",
+        )
+        .unwrap();
+        assert!(git(&repo, &["add", "."]) && git(&repo, &["commit", "--quiet", "-m", "core"]));
+
+        let url = repo.to_string_lossy();
+        let fetch_dir = dir.join("fetch");
+        let paths = ["", "packages/pkg_core", "packages/pkg_cor"];
+        let missing = |git_ref: Option<&str>| {
+            let missing = missing_paths(&url, git_ref, &paths, &fetch_dir).unwrap();
+            assert!(!fetch_dir.exists());
+            missing
+        };
+
+        assert_eq!(missing(None), vec!["packages/pkg_cor"]);
+        assert_eq!(missing(Some("main")), vec!["packages/pkg_cor"]);
+        assert_eq!(
+            missing(Some("v1.0.0")),
+            vec!["packages/pkg_core", "packages/pkg_cor"]
+        );
+        assert_eq!(
+            missing(Some(&initial)),
+            vec!["packages/pkg_core", "packages/pkg_cor"]
+        );
+        // abbreviated commits cannot be fetched
+        assert!(missing(Some(&initial[..8])).is_empty());
+
+        // nothing can be verified in a repository that cannot be fetched
+        let gone = dir.join("gone.git");
+        assert_eq!(
+            missing_paths(&gone.to_string_lossy(), None, &paths, &fetch_dir).unwrap(),
+            vec!["", "packages/pkg_core", "packages/pkg_cor"]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,11 +1,80 @@
+use crate::config::HttpSettings;
+use crate::util::parse_timestamp;
 use crate::FlError;
+use crate::FlError::ConfigValidation;
+use crate::FlError::RateLimited;
+use log::debug;
+use log::info;
+use log::warn;
+use reqwest::header::HeaderMap;
+use reqwest::header::RETRY_AFTER;
+use reqwest::Certificate;
+use reqwest::Proxy;
+use reqwest::Response;
+use reqwest::StatusCode;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Number of times a throttled request is retried before giving up.
+const MAX_RETRIES: u32 = 5;
+
+/// Backoff used for throttled requests that do not specify a `Retry-After`.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Longest `Retry-After` delay waited for, requests fail on longer ones.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// `X-RateLimit-Reset` values above this (~31 years) are no delays but
+/// points in time (seconds since the epoch).
+const EPOCH_RESET_THRESHOLD: u64 = 1_000_000_000;
 
 #[derive(Debug)]
 pub struct PubVersions {
     pub name: String,
     pub latest: String,
     pub versions: Vec<String>,
+    /// Point in time each version was published (if known)
+    pub published: HashMap<String, SystemTime>,
+    /// Dart SDK constraint of each version (if declared)
+    pub sdk: HashMap<String, String>,
+}
+
+/// Score of a package as computed by pub.dev.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PubScore {
+    pub name: String,
+    /// Pub points granted by the package analysis
+    pub points: Option<u32>,
+    /// Popularity in percent (not reported for all packages)
+    pub popularity: Option<u32>,
+}
+
+/// Publisher of a package on pub.dev.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PubPublisher {
+    pub name: String,
+    /// Verified publisher (domain) of the package, `None` if the package is
+    /// published by an individual
+    pub publisher_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PubDevPublisher {
+    publisher_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PubDevScore {
+    granted_points: Option<u32>,
+    popularity_score: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -17,15 +86,635 @@ struct PubDevPackage {
 #[derive(Deserialize)]
 struct PubDevVersion {
     version: String,
+    published: Option<String>,
+    pubspec: Option<PubDevPubspec>,
 }
 
-pub async fn fetch_dep_versions(package_name: &str) -> Result<PubVersions, FlError> {
-    let url = format!("https://pub.dev/api/packages/{}", package_name);
-    let res = reqwest::get(url).await?.json::<PubDevPackage>().await?;
+#[derive(Deserialize)]
+struct PubDevPubspec {
+    environment: Option<HashMap<String, Option<String>>>,
+}
+
+/// API providing the versions of packages published on pub.dev.
+pub trait PubApi {
+    /// Fetch the available versions of the package `package_name`.
+    ///
+    /// Returns `None` if the package is not known to pub.dev (e.g. private or
+    /// unpublished packages).
+    fn fetch_dep_versions(
+        &self,
+        package_name: &str,
+    ) -> impl Future<Output = Result<Option<PubVersions>, FlError>>;
+
+    /// Fetch the score of the package `package_name`.
+    ///
+    /// Returns `None` if the package is not known to pub.dev.
+    fn fetch_score(
+        &self,
+        package_name: &str,
+    ) -> impl Future<Output = Result<Option<PubScore>, FlError>>;
+
+    /// Fetch the publisher of the package `package_name`.
+    ///
+    /// Returns `None` if the package is not known to pub.dev.
+    fn fetch_publisher(
+        &self,
+        package_name: &str,
+    ) -> impl Future<Output = Result<Option<PubPublisher>, FlError>>;
+
+    /// Statistics of all requests issued so far.
+    fn stats(&self) -> PubDevStats {
+        PubDevStats::default()
+    }
+}
+
+/// Request statistics collected by the `PubDevClient`.
+#[derive(Debug, Default, Clone)]
+pub struct PubDevStats {
+    pub requests: u32,
+    pub throttled: u32,
+    pub throttle_wait: Duration,
+}
+
+#[derive(Default)]
+struct Throttle {
+    until: Option<Instant>,
+    /// Point in time up to which the throttling is accounted in the stats
+    accounted: Option<Instant>,
+    stats: PubDevStats,
+}
+
+impl Throttle {
+    /// Time remaining until the throttling (if any) has passed. The wait is
+    /// accounted once only, no matter how many requests are waiting.
+    fn remaining(&mut self, now: Instant) -> Option<Duration> {
+        let until = self.until?;
+        let remaining = until.checked_duration_since(now)?;
+
+        let from = self.accounted.map_or(now, |accounted| accounted.max(now));
+        self.stats.throttle_wait += until.saturating_duration_since(from);
+        self.accounted = Some(until);
+
+        Some(remaining)
+    }
+}
+
+/// Client of the pub.dev API that adaptively throttles all requests
+/// as soon as pub.dev signals rate limiting.
+pub struct PubDevClient {
+    client: reqwest::Client,
+    throttle: Mutex<Throttle>,
+}
+
+impl Default for PubDevClient {
+    fn default() -> Self {
+        PubDevClient::new()
+    }
+}
+
+impl PubDevClient {
+    pub fn new() -> PubDevClient {
+        PubDevClient {
+            client: reqwest::Client::new(),
+            throttle: Mutex::new(Throttle::default()),
+        }
+    }
+
+    /// Create a client based on the given `settings`, e.g. sending all
+    /// requests via a proxy or trusting the certificates of a corporate CA.
+    pub fn from_settings(settings: &HttpSettings) -> Result<PubDevClient, FlError> {
+        Ok(PubDevClient {
+            client: http_client(settings)?,
+            throttle: Mutex::new(Throttle::default()),
+        })
+    }
+}
+
+/// Build an HTTP client based on the given `settings` (proxy, CA bundle,
+/// timeout, user agent and TLS verification).
+pub fn http_client(settings: &HttpSettings) -> Result<reqwest::Client, FlError> {
+    let user_agent = settings
+        .user_agent
+        .clone()
+        .unwrap_or_else(|| format!("flcheck/{}", env!("CARGO_PKG_VERSION")));
+    let mut builder = reqwest::Client::builder().user_agent(user_agent);
+
+    if let Some(proxy) = &settings.proxy {
+        let proxy = Proxy::all(proxy)
+            .map_err(|err| ConfigValidation(format!("http: invalid proxy '{}': {}", proxy, err)))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(file) = &settings.ca_bundle {
+        let content = std::fs::read_to_string(file).map_err(|err| {
+            ConfigValidation(format!("http: cannot read CA bundle '{}': {}", file, err))
+        })?;
+        let certificates = pem_certificates(&content);
+        if certificates.is_empty() {
+            return Err(ConfigValidation(format!(
+                "http: no certificates found in CA bundle '{}'",
+                file
+            )));
+        }
+
+        for pem in certificates {
+            let certificate = Certificate::from_pem(pem.as_bytes()).map_err(|err| {
+                ConfigValidation(format!("http: invalid CA bundle '{}': {}", file, err))
+            })?;
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if let Some(timeout) = settings.timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    if settings.insecure {
+        warn!("verification of TLS certificates is disabled");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    Ok(builder.build()?)
+}
+
+impl PubApi for PubDevClient {
+    async fn fetch_dep_versions(&self, package_name: &str) -> Result<Option<PubVersions>, FlError> {
+        let url = format!("https://pub.dev/api/packages/{}", package_name);
+        let Some(response) = self.get(&url, package_name).await? else {
+            return Ok(None);
+        };
+
+        let res = response.json::<PubDevPackage>().await?;
+        let published = res
+            .versions
+            .iter()
+            .flat_map(|v| {
+                let timestamp = v.published.as_deref().and_then(parse_timestamp)?;
+                Some((v.version.clone(), timestamp))
+            })
+            .collect();
+        let sdk = res
+            .versions
+            .iter()
+            .flat_map(|v| {
+                let environment = v.pubspec.as_ref()?.environment.as_ref()?;
+                Some((v.version.clone(), environment.get("sdk")?.clone()?))
+            })
+            .collect();
+
+        Ok(Some(PubVersions {
+            name: package_name.to_owned(),
+            latest: res.latest.version,
+            versions: res.versions.into_iter().map(|v| v.version).collect(),
+            published,
+            sdk,
+        }))
+    }
+
+    async fn fetch_score(&self, package_name: &str) -> Result<Option<PubScore>, FlError> {
+        let url = format!("https://pub.dev/api/packages/{}/score", package_name);
+        let Some(response) = self.get(&url, package_name).await? else {
+            return Ok(None);
+        };
+
+        let res = response.json::<PubDevScore>().await?;
+
+        Ok(Some(PubScore {
+            name: package_name.to_owned(),
+            points: res.granted_points,
+            popularity: res
+                .popularity_score
+                .map(|score| (score * 100.0).round() as u32),
+        }))
+    }
+
+    async fn fetch_publisher(&self, package_name: &str) -> Result<Option<PubPublisher>, FlError> {
+        let url = format!("https://pub.dev/api/packages/{}/publisher", package_name);
+        let Some(response) = self.get(&url, package_name).await? else {
+            return Ok(None);
+        };
+
+        let res = response.json::<PubDevPublisher>().await?;
+
+        Ok(Some(PubPublisher {
+            name: package_name.to_owned(),
+            publisher_id: res.publisher_id,
+        }))
+    }
+
+    fn stats(&self) -> PubDevStats {
+        self.throttle.lock().unwrap().stats.clone()
+    }
+}
+
+impl PubDevClient {
+    /// Request the given `url` (regarding the package `package_name`),
+    /// retrying as long as pub.dev rate limits the requests. Returns `None`
+    /// if the package is not found.
+    async fn get(&self, url: &str, package_name: &str) -> Result<Option<Response>, FlError> {
+        let mut attempt = 0;
+
+        let response = loop {
+            self.wait().await;
+
+            let started = Instant::now();
+            let response = self.client.get(url).send().await?;
+            self.throttle.lock().unwrap().stats.requests += 1;
+            debug!(
+                "GET {} -> {} in {:.2?}",
+                url,
+                response.status(),
+                started.elapsed()
+            );
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+                let backoff =
+                    backoff(response.headers(), attempt, package_name, SystemTime::now())?;
+
+                info!(
+                    "rate limited by pub.dev, retrying {} in {:.2?}",
+                    package_name, backoff
+                );
+                self.pause(backoff, true);
+                attempt += 1;
+                continue;
+            }
+
+            // pre-emptively slow down all remaining requests
+            // if the rate limit is about to be exceeded
+            if let Some(reset) = rate_limit_reset(response.headers(), SystemTime::now()) {
+                self.pause(reset, false);
+            }
+
+            break response;
+        };
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?))
+    }
+
+    /// Hold back all requests for (at least) the given `duration`.
+    fn pause(&self, duration: Duration, throttled: bool) {
+        let mut throttle = self.throttle.lock().unwrap();
+        let until = Instant::now() + duration;
+
+        if throttle.until.is_none_or(|current| current < until) {
+            throttle.until = Some(until);
+        }
+        if throttled {
+            throttle.stats.throttled += 1;
+        }
+    }
+
+    /// Wait until a (possibly) active throttling has passed.
+    async fn wait(&self) {
+        loop {
+            let remaining = self.throttle.lock().unwrap().remaining(Instant::now());
+
+            match remaining {
+                Some(wait) if !wait.is_zero() => tokio::time::sleep(wait).await,
+                _ => return,
+            }
+        }
+    }
+}
+
+/// `PubApi` serving a fixed set of packages without issuing any requests.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MockPubApi {
+    /// Versions by package name (the last one being the latest)
+    packages: HashMap<String, Vec<String>>,
+    /// Publish timestamps by package name and version
+    published: HashMap<(String, String), SystemTime>,
+    /// Dart SDK constraints by package name and version
+    sdk: HashMap<(String, String), String>,
+    scores: HashMap<String, PubScore>,
+    /// Verified publishers by package name
+    publishers: HashMap<String, String>,
+}
+
+#[cfg(test)]
+impl MockPubApi {
+    pub(crate) fn with_package(mut self, name: &str, versions: &[&str]) -> MockPubApi {
+        self.packages.insert(
+            name.to_owned(),
+            versions.iter().map(|version| version.to_string()).collect(),
+        );
+        self
+    }
+
+    /// Record the given `version` of the package `name` was published at
+    /// `timestamp`.
+    pub(crate) fn with_published(
+        mut self,
+        name: &str,
+        version: &str,
+        timestamp: SystemTime,
+    ) -> MockPubApi {
+        self.published
+            .insert((name.to_owned(), version.to_owned()), timestamp);
+        self
+    }
+
+    /// Record the given `version` of the package `name` requires the Dart
+    /// SDK `constraint`.
+    pub(crate) fn with_sdk(mut self, name: &str, version: &str, constraint: &str) -> MockPubApi {
+        self.sdk
+            .insert((name.to_owned(), version.to_owned()), constraint.to_owned());
+        self
+    }
+
+    /// Record the score (pub points and popularity) of the package `name`.
+    pub(crate) fn with_score(
+        mut self,
+        name: &str,
+        points: u32,
+        popularity: Option<u32>,
+    ) -> MockPubApi {
+        let score = PubScore {
+            name: name.to_owned(),
+            points: Some(points),
+            popularity,
+        };
+        self.scores.insert(name.to_owned(), score);
+        self
+    }
+
+    /// Record the package `name` is published by the verified `publisher`.
+    pub(crate) fn with_publisher(mut self, name: &str, publisher: &str) -> MockPubApi {
+        self.publishers
+            .insert(name.to_owned(), publisher.to_owned());
+        self
+    }
+}
+
+#[cfg(test)]
+impl PubApi for MockPubApi {
+    async fn fetch_dep_versions(&self, package_name: &str) -> Result<Option<PubVersions>, FlError> {
+        Ok(self.packages.get(package_name).map(|versions| PubVersions {
+            name: package_name.to_owned(),
+            latest: versions.last().cloned().unwrap_or_default(),
+            versions: versions.clone(),
+            published: versions
+                .iter()
+                .flat_map(|version| {
+                    let key = (package_name.to_owned(), version.clone());
+                    let timestamp = self.published.get(&key)?;
+                    Some((version.clone(), *timestamp))
+                })
+                .collect(),
+            sdk: versions
+                .iter()
+                .flat_map(|version| {
+                    let key = (package_name.to_owned(), version.clone());
+                    Some((version.clone(), self.sdk.get(&key)?.clone()))
+                })
+                .collect(),
+        }))
+    }
+
+    async fn fetch_score(&self, package_name: &str) -> Result<Option<PubScore>, FlError> {
+        Ok(self.scores.get(package_name).cloned())
+    }
+
+    async fn fetch_publisher(&self, package_name: &str) -> Result<Option<PubPublisher>, FlError> {
+        Ok(self
+            .packages
+            .contains_key(package_name)
+            .then(|| PubPublisher {
+                name: package_name.to_owned(),
+                publisher_id: self.publishers.get(package_name).cloned(),
+            }))
+    }
+}
+
+/// Split the given PEM `content` (e.g. a CA bundle) into its certificates.
+fn pem_certificates(content: &str) -> Vec<&str> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut certificates = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find(BEGIN) {
+        let Some(len) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + len + END.len();
+        certificates.push(&rest[start..end]);
+        rest = &rest[end..];
+    }
+
+    certificates
+}
+
+/// Determine the delay before retrying a throttled request: the one
+/// requested via `Retry-After` (failing if exceeding `MAX_RETRY_AFTER`) or
+/// an exponential backoff based on the number of attempts.
+fn backoff(
+    headers: &HeaderMap,
+    attempt: u32,
+    package_name: &str,
+    now: SystemTime,
+) -> Result<Duration, FlError> {
+    match retry_after(headers, now) {
+        Some(delay) if delay > MAX_RETRY_AFTER => Err(RateLimited(
+            package_name.to_owned(),
+            delay.as_secs(),
+            MAX_RETRY_AFTER.as_secs(),
+        )),
+        Some(delay) => Ok(delay),
+        None => Ok(DEFAULT_BACKOFF * 2u32.pow(attempt)),
+    }
+}
+
+/// Determine the delay requested via the `Retry-After` header that
+/// is either given in seconds or as an HTTP date.
+fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Determine the time until the rate limit is reset, if there are no
+/// remaining requests left according to the `X-RateLimit-*` headers. The
+/// reset is given in seconds or as seconds since the epoch and limited to
+/// `MAX_RETRY_AFTER`.
+fn rate_limit_reset(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let header_u64 = |name: &str| -> Option<u64> {
+        headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok()
+    };
+
+    if header_u64("x-ratelimit-remaining")? > 0 {
+        return None;
+    }
+
+    let reset = header_u64("x-ratelimit-reset")?;
+    let delay = if reset > EPOCH_RESET_THRESHOLD {
+        (UNIX_EPOCH + Duration::from_secs(reset))
+            .duration_since(now)
+            .unwrap_or(Duration::ZERO)
+    } else {
+        Duration::from_secs(reset)
+    };
+
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pubdev::backoff;
+    use crate::pubdev::pem_certificates;
+    use crate::pubdev::rate_limit_reset;
+    use crate::pubdev::retry_after;
+    use crate::pubdev::PubDevPublisher;
+    use crate::pubdev::Throttle;
+    use reqwest::header::HeaderMap;
+    use reqwest::header::HeaderValue;
+    use reqwest::header::RETRY_AFTER;
+    use std::time::Duration;
+    use std::time::Instant;
+    use std::time::SystemTime;
+    use std::time::UNIX_EPOCH;
+
+    fn headers(values: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in values {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        let headers = headers(&[(RETRY_AFTER.as_str(), "30")]);
+
+        assert_eq!(
+            retry_after(&headers, SystemTime::now()),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let headers = headers(&[(RETRY_AFTER.as_str(), "Wed, 21 Oct 2015 07:28:00 GMT")]);
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:27:00 GMT").unwrap();
+
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn throttle_wait_accounted_once() {
+        let now = Instant::now();
+        let mut throttle = Throttle {
+            until: Some(now + Duration::from_secs(10)),
+            ..Throttle::default()
+        };
+
+        // concurrent requests wait for the same throttling
+        assert_eq!(throttle.remaining(now), Some(Duration::from_secs(10)));
+        assert_eq!(throttle.remaining(now), Some(Duration::from_secs(10)));
+        assert_eq!(throttle.stats.throttle_wait, Duration::from_secs(10));
+
+        // extending the throttling accounts the additional time only
+        throttle.until = Some(now + Duration::from_secs(15));
+        let later = now + Duration::from_secs(5);
+        assert_eq!(throttle.remaining(later), Some(Duration::from_secs(10)));
+        assert_eq!(throttle.stats.throttle_wait, Duration::from_secs(15));
+
+        assert_eq!(throttle.remaining(now + Duration::from_secs(20)), None);
+        assert_eq!(throttle.stats.throttle_wait, Duration::from_secs(15));
+    }
+
+    #[test]
+    fn bounded_backoff() {
+        let now = SystemTime::now();
+        let backoff = |value: &'static str, attempt| {
+            backoff(
+                &headers(&[(RETRY_AFTER.as_str(), value)]),
+                attempt,
+                "http",
+                now,
+            )
+        };
+
+        assert_eq!(backoff("30", 0).unwrap(), Duration::from_secs(30));
+        assert_eq!(backoff("", 2).unwrap(), Duration::from_secs(8));
+        assert_eq!(
+            backoff("86400", 0).unwrap_err().to_string(),
+            "pub.dev requested to retry 'http' after 86400s (waiting at most 300s)"
+        );
+    }
+
+    #[test]
+    fn retry_after_missing() {
+        assert_eq!(retry_after(&HeaderMap::new(), SystemTime::now()), None);
+    }
+
+    #[test]
+    fn publisher_response() {
+        let verified: PubDevPublisher =
+            serde_json::from_str(r#"{"publisherId": "dart.dev"}"#).unwrap();
+        assert_eq!(verified.publisher_id.as_deref(), Some("dart.dev"));
+
+        // packages of individuals have no publisher
+        let individual: PubDevPublisher = serde_json::from_str(r#"{"publisherId": null}"#).unwrap();
+        assert_eq!(individual.publisher_id, None);
+    }
+
+    #[test]
+    fn pem_bundle() {
+        let bundle =
+            "# corporate CA\n-----BEGIN CERTIFICATE-----\nAAA\n-----END CERTIFICATE-----\n\
+                      -----BEGIN CERTIFICATE-----\nBBB\n-----END CERTIFICATE-----\n";
+
+        assert_eq!(
+            pem_certificates(bundle),
+            vec![
+                "-----BEGIN CERTIFICATE-----\nAAA\n-----END CERTIFICATE-----",
+                "-----BEGIN CERTIFICATE-----\nBBB\n-----END CERTIFICATE-----"
+            ]
+        );
+        assert!(pem_certificates("no certificates").is_empty());
+    }
+
+    #[test]
+    fn rate_limit_exhausted() {
+        let exhausted = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "5")]);
+        let remaining = headers(&[("x-ratelimit-remaining", "3"), ("x-ratelimit-reset", "5")]);
+
+        let now = SystemTime::now();
+
+        assert_eq!(
+            rate_limit_reset(&exhausted, now),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(rate_limit_reset(&remaining, now), None);
+    }
+
+    #[test]
+    fn rate_limit_reset_bounds() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let reset = |value: &'static str| {
+            let headers = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", value)]);
+            rate_limit_reset(&headers, now)
+        };
+
+        // delays are limited to the longest `Retry-After` waited for
+        assert_eq!(reset("3600"), Some(Duration::from_secs(300)));
 
-    Ok(PubVersions {
-        name: package_name.to_owned(),
-        latest: res.latest.version,
-        versions: res.versions.into_iter().map(|v| v.version).collect(),
-    })
+        // implausibly large values are points in time
+        assert_eq!(reset("1700000010"), Some(Duration::from_secs(10)));
+        assert_eq!(reset("1700086400"), Some(Duration::from_secs(300)));
+        assert_eq!(reset("1699999990"), Some(Duration::ZERO));
+    }
 }
@@ -0,0 +1,69 @@
+use crate::workspace::Workspace;
+use crate::FlError;
+use crate::Pubspec;
+
+/// Determine the order the packages of the workspace can be published in.
+///
+/// Packages are grouped into waves: every package depends on packages of
+/// previous waves only, so all packages of one wave can be published in
+/// parallel. Dependencies are either local ones or dependencies referring
+/// to a workspace package by name (e.g. hosted or git dependencies),
+/// dev_dependencies are not relevant for publishing.
+pub fn publish_waves(pubspecs: &[Pubspec]) -> Result<Vec<Vec<String>>, FlError> {
+    let waves = Workspace::new(pubspecs).topo_order()?;
+
+    Ok(waves
+        .into_iter()
+        .map(|wave| {
+            let mut names = wave
+                .into_iter()
+                .map(|idx| pubspecs[idx].name.clone())
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+            names
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::publish::publish_waves;
+    use crate::Pubspec;
+
+    #[test]
+    fn waves() {
+        let all = vec![
+            Pubspec::test("app")
+                .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+                .with_local_deps(&["pkg_ui"]),
+            Pubspec::test("pkg_ui").with_deps(vec![Pubspec::hosted("pkg_core", "^1.0.0")]),
+            Pubspec::test("pkg_utils"),
+            Pubspec::test("pkg_core").with_local_deps(&["pkg_utils"]),
+            Pubspec::test("pkg_net").with_local_deps(&["pkg_utils"]),
+        ];
+
+        assert_eq!(
+            publish_waves(&all).unwrap(),
+            vec![
+                vec!["pkg_utils"],
+                vec!["pkg_core", "pkg_net"],
+                vec!["pkg_ui"],
+                vec!["app"],
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let all = vec![
+            Pubspec::test("app").with_local_deps(&["pkg_a"]),
+            Pubspec::test("pkg_a").with_local_deps(&["pkg_b"]),
+            Pubspec::test("pkg_b").with_local_deps(&["pkg_a"]),
+        ];
+
+        assert_eq!(
+            publish_waves(&all).unwrap_err().to_string(),
+            "cyclic dependency: pkg_a -> pkg_b -> 'pkg_a'"
+        );
+    }
+}
@@ -1,21 +1,86 @@
 extern crate walkdir;
 
+use crate::cache::CacheState;
+use crate::cache::PubspecCache;
 use crate::config::PackageType;
+use crate::config::PublicPackagePolicy;
+use crate::constraint::rule_validations;
 use crate::dependency::Dependency;
+use crate::dependency::DependencyAnnotation;
 use crate::error::FlError;
 use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
 use crate::error::ValidationType;
-use crate::util::load_yaml;
+use crate::util::canonicalize;
+use crate::util::edit_distance;
+use crate::util::normalize_path;
 use crate::util::normalize_path_str;
+use crate::util::relative_dir;
+use crate::util::relative_path;
+use crate::util::yaml_str_list;
+use crate::util::{parse_yaml, parse_yaml_documents};
+use crate::version::parse_version;
+use crate::workspace::describe_cycle;
+use crate::workspace::Workspace;
 use crate::Config;
 use crate::FlError::ConfigValidation;
 
+use log::debug;
+use log::warn;
+use schemars::JsonSchema;
+use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::Instant;
 use walkdir::WalkDir;
 use yaml_rust::Yaml;
 
-#[derive(Debug, Serialize)]
+/// Maximum number of workspace packages suggested for an unknown dependency.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// File names (in lowercase) of the pubspec of a package.
+const PUBSPEC_FILES: [&str; 2] = ["pubspec.yaml", "pubspec.yml"];
+
+/// Platform supported by a flutter plugin package (as declared in
+/// `flutter.plugin.platforms`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct PluginPlatform {
+    pub name: String,
+    /// Whether the platform is implemented via native code (`pluginClass`
+    /// or `ffiPlugin`) rather than dart only (`dartPluginClass`)
+    pub native: bool,
+}
+
+/// Structural problem of a pubspec detected while loading it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SchemaProblem {
+    /// Dependency the problem refers to (if any)
+    pub dependency: Option<String>,
+    pub message: String,
+}
+
+/// Path skipped during package discovery, e.g. a directory that cannot be
+/// read or a pubspec that cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedPath {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Result of the package discovery.
+pub struct Discovery {
+    pub pubspecs: Vec<Pubspec>,
+    pub skipped: Vec<SkippedPath>,
+    /// Pubspecs loaded via the cache (if discovered with a cache)
+    pub cache: Option<CacheState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Pubspec {
     pub name: String,
     pub path: String,
@@ -24,11 +89,163 @@ pub struct Pubspec {
     pub dependencies: Vec<Dependency>,
     pub dev_dependencies: Vec<Dependency>,
     pub is_public: bool,
+    /// Version as declared in the pubspec (if any)
+    pub version: Option<String>,
+    /// Repository the package is published from (if any)
+    pub repository: Option<String>,
+    /// Platforms supported by the package if it is a flutter plugin
+    pub platforms: Vec<PluginPlatform>,
+    /// Asset and font paths (relative to the package directory) as
+    /// declared in the `flutter` section
+    pub assets: Vec<String>,
+    /// Name of the package type as declared via `flcheck: type` (taking
+    /// precedence over the directory prefixes of the package types)
+    pub package_type: Option<String>,
+    /// Team owning the package (`flcheck: owner`)
+    pub owner: Option<String>,
+    /// Arbitrary tags (`flcheck: tags`), e.g. to select packages in rules
+    pub tags: Vec<String>,
+    /// Problems of the pubspec's structure (e.g. malformed dependencies)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schema_problems: Vec<SchemaProblem>,
+    /// Settings of dependencies declared via a nested `flcheck` section
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependency_annotations: Vec<DependencyAnnotation>,
+    /// Number of YAML documents following the first one, which are ignored
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub extra_documents: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+/// Name (and version) of the package along with its pubspec file, e.g.
+/// `pkg_core 1.2.0 [packages/pkg_core/pubspec.yaml]`.
+impl Display for Pubspec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "<unnamed>")?;
+        } else {
+            write!(f, "{}", self.name)?;
+        }
+        if let Some(version) = &self.version {
+            write!(f, " {}", version)?;
+        }
+        write!(f, " [{}]", self.path)
+    }
+}
+
+/// Fixtures of packages shared by all tests.
+#[cfg(test)]
+impl Pubspec {
+    /// Package `name` located in `/tmp/<name>` without any dependencies.
+    pub fn test(name: &str) -> Pubspec {
+        Pubspec::test_at(name, &format!("/tmp/{}", name))
+    }
+
+    /// Package `name` located in the directory `dir_path` without any
+    /// dependencies.
+    pub fn test_at(name: &str, dir_path: &str) -> Pubspec {
+        Pubspec {
+            name: name.to_owned(),
+            path: format!("{}/pubspec.yaml", dir_path),
+            dir_name: name.to_owned(),
+            dir_path: dir_path.to_owned(),
+            dependencies: Vec::new(),
+            dev_dependencies: Vec::new(),
+            is_public: false,
+            version: None,
+            repository: None,
+            platforms: Vec::new(),
+            assets: Vec::new(),
+            package_type: None,
+            owner: None,
+            tags: Vec::new(),
+            schema_problems: Vec::new(),
+            dependency_annotations: Vec::new(),
+            extra_documents: 0,
+        }
+    }
+
+    pub fn with_deps(self, dependencies: Vec<Dependency>) -> Pubspec {
+        Pubspec {
+            dependencies,
+            ..self
+        }
+    }
+
+    pub fn with_dev_deps(self, dev_dependencies: Vec<Dependency>) -> Pubspec {
+        Pubspec {
+            dev_dependencies,
+            ..self
+        }
+    }
+
+    /// Add local dependencies on the sibling packages of the given names.
+    pub fn with_local_deps(mut self, names: &[&str]) -> Pubspec {
+        self.dependencies.extend(Pubspec::local_deps(names));
+        self
+    }
+
+    /// Add local dev dependencies on the sibling packages of the given names.
+    pub fn with_local_dev_deps(mut self, names: &[&str]) -> Pubspec {
+        self.dev_dependencies.extend(Pubspec::local_deps(names));
+        self
+    }
+
+    fn local_deps<'a>(names: &'a [&str]) -> impl Iterator<Item = Dependency> + 'a {
+        names
+            .iter()
+            .map(|name| Pubspec::local(name, &format!("../{}", name)))
+    }
+
+    pub fn with_owner(self, owner: &str) -> Pubspec {
+        Pubspec {
+            owner: Some(owner.to_owned()),
+            ..self
+        }
+    }
+
+    pub fn with_version(self, version: &str) -> Pubspec {
+        Pubspec {
+            version: Some(version.to_owned()),
+            ..self
+        }
+    }
+
+    /// Local dependency `name` on the package at the relative `path`.
+    pub fn local(name: &str, path: &str) -> Dependency {
+        Dependency::Local {
+            name: name.to_owned(),
+            path: path.to_owned(),
+            overridden: Box::new(None),
+        }
+    }
+
+    /// Hosted (pub.dev) dependency `name` with the given version constraint.
+    pub fn hosted(name: &str, version: &str) -> Dependency {
+        Dependency::PubDev {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            overridden: Box::new(None),
+        }
+    }
 }
 
 impl Pubspec {
     pub fn load(path: &str) -> Result<Pubspec, FlError> {
-        let yaml = load_yaml(path)?;
+        Pubspec::parse(path, &std::fs::read_to_string(path)?)
+    }
+
+    /// Parse the given `content` of the pubspec file at `path`.
+    pub fn parse(path: &str, content: &str) -> Result<Pubspec, FlError> {
+        let (yaml, extra_documents) = parse_yaml_documents(content, path)?;
+
+        Pubspec::from_yaml(path, &yaml, extra_documents)
+    }
+
+    fn from_yaml(path: &str, yaml: &Yaml, extra_documents: usize) -> Result<Pubspec, FlError> {
         let name = yaml["name"].as_str().unwrap_or("").to_owned();
 
         pubspec_dir(path)
@@ -37,93 +254,318 @@ impl Pubspec {
                 path
             )))
             .map(|(dir_name, dir_path)| Pubspec {
-                name: name,
+                name,
                 path: path.to_owned(),
-                dir_name: dir_name,
-                dir_path: dir_path,
-                dependencies: get_dependencies(&yaml),
-                dev_dependencies: get_dev_dependencies(&yaml),
-                is_public: is_public_package(&yaml),
+                dir_name,
+                dir_path,
+                dependencies: get_dependencies(yaml),
+                dev_dependencies: get_dev_dependencies(yaml),
+                is_public: is_public_package(yaml),
+                version: yaml["version"].as_str().map(|version| version.to_owned()),
+                repository: yaml["repository"]
+                    .as_str()
+                    .map(|repository| repository.to_owned()),
+                platforms: plugin_platforms(yaml),
+                assets: flutter_assets(yaml),
+                package_type: flcheck_str(yaml, "type"),
+                owner: flcheck_str(yaml, "owner"),
+                schema_problems: schema_problems(yaml),
+                dependency_annotations: dependency_annotations(yaml),
+                extra_documents,
+                tags: yaml["flcheck"]["tags"]
+                    .as_vec()
+                    .map(|tags| {
+                        tags.iter()
+                            .flat_map(|tag| tag.as_str())
+                            .map(|tag| tag.to_owned())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
             })
     }
 
-    pub fn validate(&self, config: &Config, packages: &Vec<Pubspec>) -> Vec<PackageValidation> {
+    /// Settings of the dependency `name` declared via `flcheck` (if any).
+    pub fn annotation(&self, name: &str) -> Option<&DependencyAnnotation> {
+        self.dependency_annotations
+            .iter()
+            .find(|annotation| annotation.dependency == name)
+    }
+
+    /// Whether the given dependency refers to a workspace package, either as
+    /// git dependency on an internal repository or by its `flcheck:
+    /// internal` annotation.
+    pub fn is_internal_dependency(&self, dep: &Dependency, config: &Config) -> bool {
+        dep.is_internal(config)
+            || self
+                .annotation(dep.name())
+                .is_some_and(|annotation| annotation.internal)
+    }
+
+    /// Whether the given finding is ignored via the `flcheck: ignore`
+    /// annotation of the dependency it refers to.
+    pub fn ignores(&self, validation: &PackageValidation) -> bool {
+        validation
+            .dependency
+            .as_deref()
+            .and_then(|name| self.annotation(name))
+            .is_some_and(|annotation| annotation.ignores(&validation.code))
+    }
+
+    /// Platforms of this (plugin) package that contain native code.
+    pub fn native_platforms(&self) -> Vec<&str> {
+        self.platforms
+            .iter()
+            .filter(|platform| platform.native)
+            .map(|platform| platform.name.as_str())
+            .collect()
+    }
+
+    /// Determine the `PackageType` of this package: the one declared via
+    /// `flcheck: type` or the (first) one matching its directory.
+    pub fn package_type<'a>(&self, config: &'a Config) -> Option<&'a PackageType> {
+        match &self.package_type {
+            Some(name) => config
+                .package_types
+                .iter()
+                .find(|pkg_type| &pkg_type.name == name),
+            None => config.package_type(&self.dir_name),
+        }
+    }
+
+    /// All package types of this package: the declared one or all
+    /// matching its directory.
+    fn package_types<'a>(&self, config: &'a Config) -> Vec<&'a PackageType> {
+        match &self.package_type {
+            Some(_) => self.package_type(config).into_iter().collect(),
+            None => config
+                .package_types
+                .iter()
+                .filter(|pkg_type| pkg_type.matches_prefix(&self.dir_name))
+                .collect(),
+        }
+    }
+
+    pub fn validate(&self, config: &Config, workspace: &Workspace) -> Vec<PackageValidation> {
         if config.is_blacklisted(&self.path) {
             return vec![];
         }
 
+        // the (transitive) cycle detection is skipped if disabled anyway
+        let cycles = self.is_enabled(config, &ValidationType::CyclicDependency);
         let all_dependencies = self.dependencies.iter().chain(self.dev_dependencies.iter());
         let all_dependency_validations = all_dependencies.flat_map(|dep| {
-            self.cyclic_dependency(config, dep, packages, vec![self.dir_path.clone()])
+            if let Some(outside) = self.outside_workspace(dep, config) {
+                return Some(
+                    self.validation(
+                        config,
+                        dep,
+                        format!(
+                            "dependency '{}' resolves outside of the workspace ({})",
+                            dep.name(),
+                            outside.display()
+                        ),
+                        ValidationType::OutsideWorkspace,
+                        "local dependencies have to be located in one of the root directories"
+                            .to_owned(),
+                    ),
+                );
+            }
+
+            // dependencies on the package itself are no cycles on their own
+            if self.is_self_dependency(dep, config, workspace) {
+                return Some(self.validation(
+                    config,
+                    dep,
+                    format!("package depends on itself via '{}'", dep.name()),
+                    ValidationType::SelfDependency,
+                    None,
+                ));
+            }
+            if !cycles {
+                return None;
+            }
+            self.cyclic_dependency(config, dep, dep, workspace, vec![self])
         });
 
         let dependency_validations = self.dependencies.iter().flat_map(|dep| {
             vec![
-                self.allowed_dependency(dep, config, packages),
-                self.public_package_git_dependencies_only(config, dep),
+                self.allowed_dependency(dep, config, workspace),
+                self.public_package_dependency(config, dep, workspace),
+                self.compatible_version(config, dep, workspace),
+                self.native_plugin_dependency(config, dep, workspace),
+                self.dev_only_dependency(config, dep),
             ]
             .into_iter()
             .flatten()
         });
 
         let dev_dependency_validations = self.dev_dependencies.iter().flat_map(|dep| {
-            vec![self.git_packages_in_dev_dependencies(config, dep)]
-                .into_iter()
-                .flatten()
+            vec![
+                self.git_packages_in_dev_dependencies(config, dep),
+                self.compatible_version(config, dep, workspace),
+            ]
+            .into_iter()
+            .flatten()
         });
 
-        return dependency_validations
+        dependency_validations
             .chain(all_dependency_validations)
             .chain(dev_dependency_validations)
-            .collect();
+            .chain(self.dependency_depth(config, workspace))
+            .chain(self.duplicate_dependencies(config, workspace))
+            .chain(self.duplicate_package(config, workspace))
+            .chain(self.missing_required_dependencies(config, workspace))
+            .chain(self.constraint_styles(config))
+            .chain(self.file_checks(config))
+            .chain(self.alternative_packages(config, workspace))
+            .chain(self.misplaced_dependencies(config, workspace))
+            .chain(self.schema_validations(config))
+            .chain(rule_validations(self, config, workspace))
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect()
     }
 
-    fn resolve_dependency<'a>(
+    /// Validate the files of this package besides its pubspec only (see
+    /// `ValidationType::reads_files`).
+    pub fn validate_files(&self, config: &Config) -> Vec<PackageValidation> {
+        if config.is_blacklisted(&self.path) {
+            return vec![];
+        }
+
+        self.file_checks(config)
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect()
+    }
+
+    fn file_checks<'a>(
+        &'a self,
+        config: &'a Config,
+    ) -> impl Iterator<Item = PackageValidation> + 'a {
+        self.missing_assets(config)
+            .into_iter()
+            .chain(self.missing_tests(config))
+            .chain(self.missing_structure(config))
+            .chain(self.analysis_options(config))
+            .chain(self.public_changelog(config))
+    }
+
+    /// Whether the given validation is enabled for this package, i.e. its
+    /// level is not `none`.
+    pub(crate) fn is_enabled(&self, config: &Config, code: &ValidationType) -> bool {
+        config.validation_level_at(code, &self.dir_path) != ValidationLevel::None
+    }
+
+    /// Resolve the given (local) dependency to the workspace package
+    /// it refers to. Dependencies whose path does not point to any package
+    /// fall back to the workspace package of the same name (see
+    /// `misplaced_dependency`).
+    pub fn resolve_dependency<'a>(
         &self,
         dep: &Dependency,
-        packages: &'a Vec<Pubspec>,
+        workspace: &Workspace<'a>,
     ) -> Option<&'a Pubspec> {
         match dep.effective() {
-            Dependency::Local { path, .. } => {
-                let full_path = format!("{}/{}", self.dir_path, path);
-                let normalized = normalize_path_str(full_path);
-                let full_str = normalized.to_str()?;
-
-                packages.iter().find(|pubspec| pubspec.dir_path == full_str)
-            }
+            Dependency::Local { path, .. } => self
+                .package_at_path(path, workspace)
+                .or_else(|| self.misplaced_dependency(dep, workspace)),
             _ => None,
         }
     }
 
-    fn cyclic_dependency(
+    /// Workspace package located at the (relative) `path` of a local
+    /// dependency.
+    fn package_at_path<'a>(&self, path: &str, workspace: &Workspace<'a>) -> Option<&'a Pubspec> {
+        let normalized = normalize_path_str(format!("{}/{}", self.dir_path, path));
+        let full_str = normalized.to_str()?;
+
+        workspace.at_dir(full_str)
+    }
+
+    /// Workspace package of the same name as the local dependency `dep`
+    /// whose path does not point to any package at all, e.g. due to a typo
+    /// or a moved package. Paths of packages that are not part of the
+    /// workspace (e.g. excluded ones) are no misplaced dependencies.
+    pub fn misplaced_dependency<'a>(
+        &self,
+        dep: &Dependency,
+        workspace: &Workspace<'a>,
+    ) -> Option<&'a Pubspec> {
+        let Dependency::Local { name, path, .. } = dep.effective() else {
+            return None;
+        };
+        let normalized = normalize_path_str(format!("{}/{}", self.dir_path, path));
+        if self.package_at_path(path, workspace).is_some()
+            || PUBSPEC_FILES
+                .iter()
+                .any(|file| normalized.join(file).exists())
+        {
+            return None;
+        }
+
+        workspace.package(name)
+    }
+
+    /// Resolve the given dependency to the workspace package it refers to,
+    /// including git dependencies on internal repositories (see
+    /// `internal_repositories`) whose path matches the package's directory
+    /// and dependencies annotated as `internal` (resolved by name unless
+    /// given as git dependency with a path).
+    pub fn resolve_internal<'a>(
         &self,
+        dep: &Dependency,
+        config: &Config,
+        workspace: &Workspace<'a>,
+    ) -> Option<&'a Pubspec> {
+        let internal = self.is_internal_dependency(dep, config);
+
+        match dep.effective() {
+            Dependency::PubDev { name, .. } if internal => workspace.package(name),
+            Dependency::Git {
+                name, git, path, ..
+            } if internal || config.is_internal_repo(git) => {
+                let path = path.trim_start_matches("./").trim_matches('/');
+                if path.is_empty() {
+                    return workspace.package(name);
+                }
+
+                let suffix = format!("/{}", path);
+                workspace
+                    .packages
+                    .iter()
+                    .find(|pubspec| pubspec.dir_path.ends_with(&suffix))
+            }
+            _ => self.resolve_dependency(dep, workspace),
+        }
+    }
+
+    /// Find a cycle back to this package via `dep`, which is (transitively)
+    /// reached via the direct dependency `origin`.
+    fn cyclic_dependency<'a>(
+        &'a self,
         config: &Config,
+        origin: &Dependency,
         dep: &Dependency,
-        packages: &Vec<Pubspec>,
-        seen: Vec<String>,
+        workspace: &Workspace<'a>,
+        seen: Vec<&'a Pubspec>,
     ) -> Option<PackageValidation> {
-        match self.resolve_dependency(dep, packages) {
+        match self.resolve_internal(dep, config, workspace) {
             Some(rev_dep) => {
-                if let Some(idx) = seen.iter().position(|d| *d == rev_dep.dir_path) {
+                if let Some(idx) = seen.iter().position(|d| d.dir_path == rev_dep.dir_path) {
                     // we only want to report the cyclic dependency for the involved packages only
                     if self.dir_path != rev_dep.dir_path {
                         return None;
                     }
 
-                    let mut route = seen.clone();
-                    let mut prepared: Vec<_> = route
-                        .drain(idx..)
-                        .flat_map(|path| file_name(&path))
-                        .collect();
+                    // every package of the cycle describes it the same way
+                    let route = seen[idx..].iter().map(|pubspec| pubspec.name.as_str());
 
-                    prepared.push(format!("'{}'", rev_dep.dir_name));
-
-                    return Some(self.validation(
+                    Some(self.validation(
                         config,
-                        format!("cyclic dependency {}", prepared.join(" -> ")),
+                        origin,
+                        format!("cyclic dependency {}", describe_cycle(route.collect())),
                         ValidationType::CyclicDependency,
                         None,
-                    ));
+                    ))
                 } else {
                     let all_dependencies = rev_dep
                         .dependencies
@@ -131,14 +573,15 @@ impl Pubspec {
                         .chain(rev_dep.dev_dependencies.iter());
                     for inner_dep in all_dependencies {
                         let mut dep_path = seen.clone();
-                        dep_path.push(rev_dep.dir_path.clone());
+                        dep_path.push(rev_dep);
 
-                        let cyclic = self.cyclic_dependency(config, inner_dep, packages, dep_path);
+                        let cyclic =
+                            self.cyclic_dependency(config, origin, inner_dep, workspace, dep_path);
                         if cyclic.is_some() {
                             return cyclic;
                         }
                     }
-                    return None;
+                    None
                 }
             }
             None => None,
@@ -153,6 +596,7 @@ impl Pubspec {
         if dep.is_public(config) {
             Some(self.validation(
                 config,
+                dep,
                 format!("git dependency in dev_dependencies {}", dep.name()),
                 ValidationType::GitDevDependency,
                 None,
@@ -162,60 +606,145 @@ impl Pubspec {
         }
     }
 
-    fn public_package_git_dependencies_only(
+    /// Verify local dependencies of a public package against the
+    /// `PublicPackagePolicy` of its package type.
+    fn public_package_dependency(
         &self,
         config: &Config,
         dep: &Dependency,
+        workspace: &Workspace,
     ) -> Option<PackageValidation> {
         if !self.is_public || !dep.is_local() {
-            None
-        } else {
-            Some(self.validation(
+            return None;
+        }
+
+        let policy = config.public_package_policy(self.package_type(config));
+        if policy == PublicPackagePolicy::GitOnly {
+            return Some(self.validation(
                 config,
+                dep,
                 format!("non-git dependency '{}' in public package", dep.name()),
                 ValidationType::NonGitDependencyInPublicPackage,
                 None,
+            ));
+        }
+
+        // unresolvable dependencies are reported as unknown already
+        let resolved = self.resolve_dependency(dep, workspace)?;
+        if !resolved.is_public {
+            Some(self.validation(
+                config,
+                dep,
+                format!(
+                    "dependency on non-public package '{}' in public package",
+                    dep.name()
+                ),
+                ValidationType::PrivateDependencyInPublicPackage,
+                None,
+            ))
+        } else if policy == PublicPackagePolicy::SameRepository
+            && (self.repository.is_none() || self.repository != resolved.repository)
+        {
+            Some(self.validation(
+                config,
+                dep,
+                format!(
+                    "dependency '{}' of another repository in public package",
+                    dep.name()
+                ),
+                ValidationType::ForeignRepositoryDependencyInPublicPackage,
+                Some(format!(
+                    "local dependencies of public packages must be published from the same repository ({})",
+                    self.repository.as_deref().unwrap_or("<none>")
+                )),
             ))
+        } else {
+            None
         }
     }
 
-    fn allowed_dependency(
+    /// Verify the version constraint of a dependency on a workspace package
+    /// allows the version declared in that package's pubspec.
+    fn compatible_version(
         &self,
-        dep: &Dependency,
         config: &Config,
-        packages: &Vec<Pubspec>,
+        dep: &Dependency,
+        workspace: &Workspace,
     ) -> Option<PackageValidation> {
-        // public/external dependencies are allowed/ignored anyways
-        if dep.is_pubdev() {
+        let Dependency::PubDev { name, version, .. } = dep else {
             return None;
+        };
+
+        let declared = workspace.package(name)?.version.as_deref()?;
+        let constraint = dep.constraint()?;
+
+        if constraint.allows(&parse_version(declared)?) {
+            None
+        } else {
+            Some(self.validation(
+                config,
+                dep,
+                format!(
+                    "constraint '{}' on '{}' does not match its version {}",
+                    version, name, declared
+                ),
+                ValidationType::IncompatibleVersion,
+                None,
+            ))
         }
+    }
 
-        // git dependencies are allowed/ignore for now
-        // TODO: we might need a concept to identify "internal" git dependencies
-        if dep.is_git() {
+    fn allowed_dependency(
+        &self,
+        dep: &Dependency,
+        config: &Config,
+        workspace: &Workspace,
+    ) -> Option<PackageValidation> {
+        // public/external and git dependencies are allowed/ignored unless
+        // referring to an internal repository (or annotated as internal)
+        if (dep.is_pubdev() || dep.is_git()) && !self.is_internal_dependency(dep, config) {
             return None;
         }
 
-        let valid_prefixes: Vec<_> = config
-            .package_types
-            .iter()
-            .filter(|pkg_type| pkg_type.matches_prefix(&self.dir_name))
-            .flat_map(|include| valid_include_prefixes(include, config))
-            .collect();
-
-        match self.resolve_dependency(dep, packages) {
+        match self.resolve_internal(dep, config, workspace) {
+            // reported as located outside of the workspace
+            None if self.outside_workspace(dep, config).is_some() => None,
             None => Some(self.validation(
                 config,
+                dep,
                 format!("unable to find dependency '{}'", dep.name()),
                 ValidationType::UnknownDependency,
-                None,
+                self.similar_packages(dep, workspace),
             )),
+            // reported as self dependency
+            Some(dep_pubspec) if dep_pubspec.dir_path == self.dir_path => None,
             Some(dep_pubspec) => {
-                let non_valid = !valid_prefixes
-                    .iter()
-                    .any(|prefix| dep_pubspec.dir_name.starts_with(prefix));
-                if non_valid {
-                    let mut valid_packages = valid_prefixes
+                let non_valid = !self.allows_dependency(dep_pubspec, config);
+                let own_type = self.package_type(config);
+                let same_type = own_type.filter(|own_type| {
+                    dep_pubspec
+                        .package_type(config)
+                        .is_some_and(|dep_type| dep_type.name == own_type.name)
+                });
+
+                if let (true, Some(pkg_type)) = (non_valid, same_type) {
+                    Some(self.validation(
+                        config,
+                        dep,
+                        format!(
+                            "'{}' must not depend on '{}' of the same package type '{}'",
+                            self.dir_name, dep_pubspec.dir_name, pkg_type.name
+                        ),
+                        ValidationType::CrossAppDependency,
+                        format!(
+                            "packages of type '{}' are independent of each other, move the code \
+                             shared by '{}' and '{}' into a package of an included type",
+                            pkg_type.name, self.dir_name, dep_pubspec.dir_name
+                        ),
+                    ))
+                } else if non_valid {
+                    let mut valid_packages = self
+                        .valid_prefixes(config)
                         .iter()
                         .map(|prefix| format!("'{}'", prefix))
                         .collect::<Vec<_>>();
@@ -225,11 +754,13 @@ impl Pubspec {
 
                     Some(self.validation(
                         config,
+                        dep,
                         format!("dependency to '{}' is not allowed", dep.name()),
                         ValidationType::DependencyNotAllowed,
                         format!(
-                            "packages with the following directory prefixes are allowed only: {}",
-                            valid_packages.join(", ")
+                            "packages with the following directory prefixes are allowed only: {}{}",
+                            valid_packages.join(", "),
+                            self.include_hint(dep_pubspec, config)
                         ),
                     ))
                 } else {
@@ -239,466 +770,2804 @@ impl Pubspec {
         }
     }
 
-    /// Create a new `PackageValidation` instance for this `Pubspec`
-    fn validation<T: Into<Option<String>>>(
+    /// Determine the normalized path of the given local dependency if it is
+    /// located outside of all root directories of the workspace.
+    fn outside_workspace(&self, dep: &Dependency, config: &Config) -> Option<PathBuf> {
+        let Dependency::Local { path, .. } = dep.effective() else {
+            return None;
+        };
+
+        let resolved = normalize_path_str(format!("{}/{}", self.dir_path, path));
+        let inside = config.roots.is_empty()
+            || config
+                .roots
+                .iter()
+                .any(|root| resolved.starts_with(normalize_path(Path::new(root))));
+
+        (!inside).then_some(resolved)
+    }
+
+    /// Whether the given dependency resolves to this package itself.
+    fn is_self_dependency(&self, dep: &Dependency, config: &Config, workspace: &Workspace) -> bool {
+        self.resolve_internal(dep, config, workspace)
+            .is_some_and(|resolved| resolved.dir_path == self.dir_path)
+    }
+
+    /// Suggest the workspace packages the unknown dependency `dep` most
+    /// likely refers to: packages whose name (or directory name for local
+    /// dependencies) differs by a few characters only, closest first.
+    fn similar_packages(&self, dep: &Dependency, workspace: &Workspace) -> Option<String> {
+        let name = dep.effective().name();
+        let dir_name = match dep.effective() {
+            Dependency::Local { path, .. } => file_name(path.trim_end_matches('/')),
+            _ => None,
+        };
+        let distance = |typed: &str, actual: &str| {
+            let distance = edit_distance(typed, actual);
+            (distance <= (typed.chars().count() / 3).max(1)).then_some(distance)
+        };
+
+        let mut similar = workspace
+            .packages
+            .iter()
+            .filter(|pubspec| pubspec.dir_path != self.dir_path)
+            .flat_map(|pubspec| {
+                let by_name = distance(name, &pubspec.name);
+                let by_dir = dir_name
+                    .as_ref()
+                    .and_then(|dir_name| distance(dir_name, &pubspec.dir_name));
+                let closest = by_name.into_iter().chain(by_dir).min()?;
+
+                Some((closest, pubspec))
+            })
+            .collect::<Vec<_>>();
+        if similar.is_empty() {
+            return None;
+        }
+        similar.sort_by(|(a, a_pubspec), (b, b_pubspec)| {
+            a.cmp(b).then_with(|| a_pubspec.name.cmp(&b_pubspec.name))
+        });
+
+        let suggestions = similar
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, pubspec)| {
+                let path = relative_dir(Path::new(&self.dir_path), Path::new(&pubspec.dir_path));
+                format!("'{}' at '{}'", pubspec.name, path)
+            })
+            .collect::<Vec<_>>();
+
+        Some(format!("did you mean {}?", suggestions.join(" or ")))
+    }
+
+    /// Find local dependencies (and dev_dependencies) whose path does not
+    /// point to any package while a workspace package of the dependency's
+    /// name exists (see `misplaced_dependency`).
+    fn misplaced_dependencies(
         &self,
         config: &Config,
-        error: String,
-        code: ValidationType,
-        description: T,
-    ) -> PackageValidation {
-        let level = config.validation_level(&code);
-
-        PackageValidation {
-            package_name: self.name.clone(),
-            error: error,
-            code: code,
-            level: level,
-            description: description.into(),
+        workspace: &Workspace,
+    ) -> Vec<PackageValidation> {
+        if !self.is_enabled(config, &ValidationType::DependencyPathMismatch) {
+            return vec![];
         }
+
+        self.dependencies
+            .iter()
+            .chain(self.dev_dependencies.iter())
+            .flat_map(|dep| {
+                let resolved = self.misplaced_dependency(dep, workspace)?;
+                let Dependency::Local { name, path, .. } = dep.effective() else {
+                    return None;
+                };
+                let expected =
+                    relative_dir(Path::new(&self.dir_path), Path::new(&resolved.dir_path));
+
+                Some(self.validation(
+                    config,
+                    dep,
+                    format!(
+                        "path '{}' of dependency '{}' does not exist but package '{}' is at '{}'",
+                        path, name, resolved.name, expected
+                    ),
+                    ValidationType::DependencyPathMismatch,
+                    format!("did you mean path '{}'?", expected),
+                ))
+            })
+            .collect()
     }
-}
 
-fn valid_include_prefixes(pkg_type: &PackageType, config: &Config) -> Vec<String> {
-    let mut prefixes = vec![];
-    config.package_types.iter().for_each(|pkg| {
-        if pkg_type.includes.iter().any(|inc| *inc == pkg.name) {
-            for prefix in pkg.prefixes.iter() {
-                if !prefixes.contains(prefix) {
-                    prefixes.push(prefix.clone());
-
-                    if pkg.name != pkg_type.name {
-                        for iprefix in valid_include_prefixes(pkg, config) {
-                            if !prefixes.contains(&iprefix) {
-                                prefixes.push(iprefix);
-                            }
-                        }
-                    }
-                }
+    /// Find dependency entries (of dependencies and dev_dependencies) with
+    /// different names that resolve to the same workspace package.
+    fn duplicate_dependencies(
+        &self,
+        config: &Config,
+        workspace: &Workspace,
+    ) -> Vec<PackageValidation> {
+        if !self.is_enabled(config, &ValidationType::DuplicateDependency) {
+            return vec![];
+        }
+
+        let mut seen: Vec<(&Dependency, &Pubspec)> = Vec::new();
+        let mut validations = Vec::new();
+
+        for dep in self.dependencies.iter().chain(self.dev_dependencies.iter()) {
+            let Some(resolved) = self.resolve_internal(dep, config, workspace) else {
+                continue;
+            };
+
+            let duplicate = seen.iter().find(|(other, other_resolved)| {
+                other_resolved.dir_path == resolved.dir_path && other.name() != dep.name()
+            });
+
+            match duplicate {
+                Some((other, _)) => validations.push(self.validation(
+                    config,
+                    dep,
+                    format!(
+                        "dependencies '{}' and '{}' refer to the same package '{}'",
+                        other.name(),
+                        dep.name(),
+                        resolved.dir_name
+                    ),
+                    ValidationType::DuplicateDependency,
+                    None,
+                )),
+                None => seen.push((dep, resolved)),
             }
         }
-    });
-    prefixes
-}
 
-pub fn find_pubspecs(root_dir: &str) -> Vec<String> {
-    let mut pubspecs = vec![];
+        validations
+    }
 
-    let walker = WalkDir::new(root_dir)
-        .into_iter()
-        // filter hidden files/directories
-        .filter_entry(|e| {
-            !e.file_name()
-                .to_str()
-                .map(|s| s.starts_with("."))
-                .unwrap_or(false)
-        })
-        // skip errors (e.g. non permission directories)
-        .filter_map(|e| e.ok());
+    /// Verify the longest chain of local dependencies does not exceed the
+    /// `max_depth` of this package's type.
+    fn dependency_depth(
+        &self,
+        config: &Config,
+        workspace: &Workspace,
+    ) -> Option<PackageValidation> {
+        let max_depth = self.package_type(config)?.max_depth?;
+        if !self.is_enabled(config, &ValidationType::DependencyDepth) {
+            return None;
+        }
+        let mut memo = HashMap::new();
 
-    for entry in walker {
-        let filename = entry.file_name().to_str().unwrap_or("").to_lowercase();
-        let is_pubspec = filename == "pubspec.yaml" || filename == "pubspec.yml";
+        let (dep, chain) = self
+            .dependencies
+            .iter()
+            .flat_map(|dep| {
+                let resolved = self.resolve_internal(dep, config, workspace)?;
+                let mut path = vec![self.dir_path.as_str()];
+                Some((
+                    dep,
+                    longest_chain(resolved, config, workspace, &mut path, &mut memo),
+                ))
+            })
+            .max_by_key(|(_, chain)| chain.len())?;
 
-        if is_pubspec {
-            if let Some(path) = entry.path().to_str() {
-                pubspecs.push(path.to_owned());
-            }
+        if chain.len() <= max_depth {
+            return None;
         }
+
+        let route = std::iter::once(self.name.as_str())
+            .chain(chain.iter().map(|pubspec| pubspec.name.as_str()))
+            .collect::<Vec<_>>();
+
+        Some(self.validation(
+            config,
+            dep,
+            format!(
+                "dependency chain of depth {} exceeds the maximum of {}",
+                chain.len(),
+                max_depth
+            ),
+            ValidationType::DependencyDepth,
+            route.join(" -> "),
+        ))
     }
 
-    pubspecs
-}
+    /// Verify all assets and fonts declared in the `flutter` section exist
+    /// (directories are given with a trailing `/`).
+    fn missing_assets(&self, config: &Config) -> Vec<PackageValidation> {
+        let code = ValidationType::MissingAsset;
+        if !self.is_enabled(config, &code) {
+            return vec![];
+        }
 
-fn is_public_package(yaml: &Yaml) -> bool {
-    let is_public_node = &yaml["flcheck"]["is_public"].as_bool();
-    is_public_node.unwrap_or(false)
-}
+        self.assets
+            .iter()
+            .filter(|asset| {
+                let path = Path::new(&self.dir_path).join(asset);
+                if asset.ends_with('/') {
+                    !path.is_dir()
+                } else {
+                    !path.exists()
+                }
+            })
+            .map(|asset| {
+                self.finding(
+                    config,
+                    code.clone(),
+                    format!("asset '{}' does not exist", asset),
+                )
+            })
+            .collect()
+    }
 
-fn get_dependencies(yaml: &Yaml) -> Vec<Dependency> {
-    let dependencies = &yaml["dependencies"];
-    let dependency_overrides = &yaml["dependency_overrides"];
-    let empty = Default::default();
+    /// Verify a package whose type has `require_tests` set contains at least
+    /// one `_test.dart` file in its `test` directory.
+    fn missing_tests(&self, config: &Config) -> Option<PackageValidation> {
+        let code = ValidationType::MissingTests;
+        if !self.is_enabled(config, &code) {
+            return None;
+        }
+        let pkg_type = self
+            .package_type(config)
+            .filter(|pkg_type| pkg_type.require_tests)?;
 
-    let mut deps = vec![];
+        let has_tests = WalkDir::new(Path::new(&self.dir_path).join("test"))
+            .into_iter()
+            .flatten()
+            .any(|entry| {
+                entry.file_type().is_file()
+                    && entry.file_name().to_string_lossy().ends_with("_test.dart")
+            });
+        if has_tests {
+            return None;
+        }
 
-    for (key, value) in dependencies.as_hash().unwrap_or(&empty).iter() {
-        let key = key.as_str().unwrap_or("");
-        if key.is_empty() {
-            continue;
+        Some(PackageValidation {
+            description: Some(format!(
+                "packages of type '{}' have to contain tests",
+                pkg_type.name
+            )),
+            ..self.finding(
+                config,
+                code,
+                "no '_test.dart' file in the test directory".to_owned(),
+            )
+        })
+    }
+
+    /// Verify all files and directories (given with a trailing `/`) the
+    /// `structure` configuration requires for this package's type exist.
+    fn missing_structure(&self, config: &Config) -> Vec<PackageValidation> {
+        let code = ValidationType::MissingStructure;
+        if !self.is_enabled(config, &code) {
+            return vec![];
         }
+        let Some(pkg_type) = self.package_type(config) else {
+            return vec![];
+        };
+        let Some(required) = config.structure.get(&pkg_type.name) else {
+            return vec![];
+        };
 
-        if let Some(dep) = extract_dependency(key, value) {
-            // we found a "normal" dependency
-            // before collecting this one, we have to check if there is
-            // a dependency override set for that same dependency
-            let new_dependency =
-                if let Some(dep_override) = extract_dependency(key, &dependency_overrides[key]) {
-                    dep.with_override(dep_override)
+        required
+            .iter()
+            .filter(|entry| {
+                let path = Path::new(&self.dir_path).join(entry);
+                if entry.ends_with('/') {
+                    !path.is_dir()
                 } else {
-                    dep
+                    !path.is_file()
+                }
+            })
+            .map(|entry| PackageValidation {
+                description: Some(format!(
+                    "packages of type '{}' have to contain {}",
+                    pkg_type.name,
+                    required.join(", ")
+                )),
+                ..self.finding(
+                    config,
+                    code.clone(),
+                    format!(
+                        "required {} '{}' does not exist",
+                        if entry.ends_with('/') {
+                            "directory"
+                        } else {
+                            "file"
+                        },
+                        entry
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// Verify the package's `analysis_options.yaml` includes the shared lint
+    /// configuration (see `analysis_options` of the configuration).
+    fn analysis_options(&self, config: &Config) -> Option<PackageValidation> {
+        let code = ValidationType::AnalysisOptions;
+        let expected = config
+            .analysis_options
+            .as_deref()
+            .filter(|_| self.is_enabled(config, &code))?;
+        let path = Path::new(&self.dir_path).join("analysis_options.yaml");
+
+        let (error, line) = match std::fs::read_to_string(&path) {
+            Err(_) => ("missing analysis_options.yaml".to_owned(), None),
+            Ok(content) => {
+                // `include` may be a list as of dart 3.6
+                let includes = parse_yaml(&content, &path.to_string_lossy())
+                    .map(|yaml| match yaml["include"].as_str() {
+                        Some(include) => vec![include.to_owned()],
+                        None => yaml_str_list(&yaml["include"]),
+                    })
+                    .unwrap_or_default();
+                if includes.iter().any(|include| include == expected) {
+                    return None;
+                }
+
+                let line = content
+                    .lines()
+                    .position(|line| line.starts_with("include:"))
+                    .map(|idx| idx + 1);
+                let error = match includes.first() {
+                    Some(other) => format!(
+                        "analysis_options.yaml includes '{}' instead of '{}'",
+                        other, expected
+                    ),
+                    None => format!("analysis_options.yaml does not include '{}'", expected),
                 };
+                (error, line)
+            }
+        };
 
-            deps.push(new_dependency);
+        Some(PackageValidation {
+            description: Some(format!("add 'include: {}'", expected)),
+            file: path.to_str().map(|file| file.to_owned()),
+            line,
+            ..self.finding(config, code, error)
+        })
+    }
+
+    /// Verify no package of the workspace uses a different package of an
+    /// `alternatives` group this package depends on.
+    fn alternative_packages(
+        &self,
+        config: &Config,
+        workspace: &Workspace,
+    ) -> Vec<PackageValidation> {
+        if !self.is_enabled(config, &ValidationType::AlternativePackages) {
+            return vec![];
+        }
+
+        let uses = |pubspec: &Pubspec, name: &str| {
+            !config.is_blacklisted(&pubspec.path)
+                && pubspec
+                    .dependencies
+                    .iter()
+                    .chain(pubspec.dev_dependencies.iter())
+                    .any(|dep| dep.name() == name)
+        };
+        let mut validations = Vec::new();
+
+        for dep in self.dependencies.iter().chain(self.dev_dependencies.iter()) {
+            let groups = config
+                .alternatives
+                .iter()
+                .filter(|group| group.packages.iter().any(|name| name == dep.name()));
+
+            for group in groups {
+                let others = group
+                    .packages
+                    .iter()
+                    .filter(|name| *name != dep.name())
+                    .flat_map(|name| {
+                        let users = workspace
+                            .packages
+                            .iter()
+                            .filter(|pubspec| uses(pubspec, name))
+                            .map(|pubspec| pubspec.name.as_str())
+                            .collect::<Vec<_>>();
+                        (!users.is_empty()).then(|| format!("{} ({})", name, users.join(", ")))
+                    })
+                    .collect::<Vec<_>>();
+                if others.is_empty() {
+                    continue;
+                }
+
+                validations.push(self.validation(
+                    config,
+                    dep,
+                    format!(
+                        "dependency '{}' has alternatives used in the workspace: {}",
+                        dep.name(),
+                        others.join(", ")
+                    ),
+                    ValidationType::AlternativePackages,
+                    format!(
+                        "use a single package of '{}' ({}) across the workspace",
+                        group.name,
+                        group.packages.join(", ")
+                    ),
+                ));
+            }
         }
+
+        validations
     }
 
-    deps
-}
+    /// Verify no other (non-blacklisted) workspace package shares the name
+    /// of this package, as dependencies are resolved by name.
+    fn duplicate_package(
+        &self,
+        config: &Config,
+        workspace: &Workspace,
+    ) -> Option<PackageValidation> {
+        let code = ValidationType::DuplicatePackage;
+        if !self.is_enabled(config, &code) {
+            return None;
+        }
 
-fn get_dev_dependencies(yaml: &Yaml) -> Vec<Dependency> {
-    let dependencies = &yaml["dev_dependencies"];
-    let empty = Default::default();
+        let paths = workspace
+            .namesakes(self)
+            .filter(|other| !config.is_blacklisted(&other.path))
+            .map(|other| other.path.as_str())
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return None;
+        }
 
-    let mut deps = vec![];
+        Some(self.finding(
+            config,
+            code,
+            format!(
+                "package name '{}' is used by {} as well",
+                self.name,
+                paths.join(", ")
+            ),
+        ))
+    }
 
-    for (key, value) in dependencies.as_hash().unwrap_or(&empty).iter() {
-        let key = key.as_str().unwrap_or("");
-        if key.is_empty() {
-            continue;
+    /// Verify a public package contains a `CHANGELOG.md` with a heading
+    /// mentioning the package's `version` (as required for publishing).
+    fn public_changelog(&self, config: &Config) -> Option<PackageValidation> {
+        let code = ValidationType::PublicChangelog;
+        if !self.is_public || !self.is_enabled(config, &code) {
+            return None;
         }
 
-        if let Some(dep) = extract_dependency(key, value) {
-            deps.push(dep);
+        let path = Path::new(&self.dir_path).join("CHANGELOG.md");
+        let error = match std::fs::read_to_string(&path) {
+            Err(_) => "missing CHANGELOG.md".to_owned(),
+            Ok(content) => {
+                let version = self.version.as_deref()?;
+                if changelog_mentions(&content, version) {
+                    return None;
+                }
+                format!("CHANGELOG.md has no entry of version '{}'", version)
+            }
+        };
+
+        Some(PackageValidation {
+            description: Some(
+                "public packages have to describe the version to publish in their changelog"
+                    .to_owned(),
+            ),
+            file: path.to_str().map(|file| file.to_owned()),
+            ..self.finding(config, code, error)
+        })
+    }
+
+    /// Report the structural problems found while loading the pubspec:
+    /// malformed dependencies and violations of the pubspec format.
+    fn schema_validations(&self, config: &Config) -> Vec<PackageValidation> {
+        self.schema_problems
+            .iter()
+            .map(|problem| {
+                let code = match problem.dependency {
+                    Some(_) => ValidationType::MalformedDependency,
+                    None => ValidationType::InvalidPubspec,
+                };
+
+                let error = match &problem.dependency {
+                    Some(dependency) => {
+                        format!("dependency '{}': {}", dependency, problem.message)
+                    }
+                    None => problem.message.clone(),
+                };
+
+                PackageValidation {
+                    dependency: problem.dependency.clone(),
+                    ..self.finding(config, code, error)
+                }
+            })
+            .collect()
+    }
+
+    /// Verify the given (regular) dependency is no external package that is
+    /// needed during development only (see `Config::is_dev_only_package`).
+    fn dev_only_dependency(&self, config: &Config, dep: &Dependency) -> Option<PackageValidation> {
+        if dep.is_local() || !config.is_dev_only_package(dep.name()) {
+            return None;
         }
+
+        Some(self.validation(
+            config,
+            dep,
+            format!("dev-only package '{}' declared in dependencies", dep.name()),
+            ValidationType::DevOnlyDependency,
+            format!(
+                "'{}' is needed during development only, move it into dev_dependencies",
+                dep.name()
+            ),
+        ))
     }
 
-    deps
-}
+    /// Verify the version constraints of all external dependencies are
+    /// written in the styles of the matching `constraint_styles`.
+    fn constraint_styles(&self, config: &Config) -> Vec<PackageValidation> {
+        let code = ValidationType::ConstraintStyle;
+        if config.constraint_styles.is_empty() || !self.is_enabled(config, &code) {
+            return vec![];
+        }
 
-fn extract_dependency(key: &str, value: &Yaml) -> Option<Dependency> {
-    let path = value["path"].as_str().unwrap_or("");
+        let mut validations = Vec::new();
+
+        for dep in self.dependencies.iter().chain(self.dev_dependencies.iter()) {
+            let (Dependency::PubDev { name, version, .. }, Some(constraint)) =
+                (dep, dep.constraint())
+            else {
+                continue;
+            };
+
+            for policy in config
+                .constraint_styles
+                .iter()
+                .filter(|policy| policy.matches(name))
+            {
+                if let Some(style) = policy.require.filter(|style| *style != constraint.style) {
+                    validations.push(self.validation(
+                        config,
+                        dep,
+                        format!(
+                            "constraint '{}' of dependency '{}' is not of style '{}'",
+                            version, name, style
+                        ),
+                        code.clone(),
+                        format!(
+                            "packages matching '{}' require '{}' constraints",
+                            policy.packages, style
+                        ),
+                    ));
+                }
+                if policy.forbid.contains(&constraint.style) {
+                    validations.push(self.validation(
+                        config,
+                        dep,
+                        format!(
+                            "constraint '{}' of dependency '{}' is of forbidden style '{}'",
+                            version, name, constraint.style
+                        ),
+                        code.clone(),
+                        format!(
+                            "packages matching '{}' forbid '{}' constraints",
+                            policy.packages, constraint.style
+                        ),
+                    ));
+                }
+            }
+        }
 
-    // check local dependency first
-    if !path.is_empty() {
-        return Some(Dependency::Local {
-            name: key.to_owned(),
-            path: path.to_owned(),
-            overridden: Box::new(None),
-        });
+        validations
     }
 
-    // check git dependency
-    let git_node = &value["git"];
-    let git_url = git_node["url"].as_str().unwrap_or("");
-    let git_path = git_node["path"].as_str().unwrap_or("");
+    /// Verify a pure dart package (see `pure_dart` of its package type)
+    /// does not depend on a workspace plugin package with native code.
+    fn native_plugin_dependency(
+        &self,
+        config: &Config,
+        dep: &Dependency,
+        workspace: &Workspace,
+    ) -> Option<PackageValidation> {
+        let pkg_type = self
+            .package_type(config)
+            .filter(|pkg_type| pkg_type.pure_dart)?;
+        let resolved = self.resolve_internal(dep, config, workspace)?;
 
-    if !git_url.is_empty() && !git_path.is_empty() {
-        return Some(Dependency::Git {
-            name: key.to_owned(),
-            git: git_url.to_owned(),
-            path: git_path.to_owned(),
-            overridden: Box::new(None),
-        });
+        let native = resolved.native_platforms();
+        if native.is_empty() {
+            return None;
+        }
+
+        Some(self.validation(
+            config,
+            dep,
+            format!(
+                "pure dart package depends on plugin '{}' with native code",
+                dep.name()
+            ),
+            ValidationType::NativePluginDependency,
+            format!(
+                "packages of type '{}' are pure dart; '{}' implements {}",
+                pkg_type.name,
+                resolved.name,
+                native.join(", ")
+            ),
+        ))
     }
 
-    // try public (external) dependency at last
-    value
-        .as_str()
-        .map(|str| str.to_owned())
-        .or_else(|| value.as_f64().map(|num| format!("{}", num)))
-        .map(|version| Dependency::PubDev {
-            name: key.to_owned(),
-            version: version,
-            overridden: Box::new(None),
-        })
-}
+    /// Verify this package depends on all packages its package type
+    /// `requires` (by the name of the resolved workspace package or the
+    /// dependency's name).
+    fn missing_required_dependencies(
+        &self,
+        config: &Config,
+        workspace: &Workspace,
+    ) -> Vec<PackageValidation> {
+        let code = ValidationType::MissingRequiredDependency;
+        if !self.is_enabled(config, &code) {
+            return vec![];
+        }
+        let Some(pkg_type) = self.package_type(config) else {
+            return vec![];
+        };
 
-fn file_name(path: &str) -> Option<String> {
-    PathBuf::from(path)
-        .file_name()
-        .and_then(|path| path.to_str())
-        .map(|path| path.to_owned())
-}
+        pkg_type
+            .requires
+            .iter()
+            .filter(|required| {
+                !self.dependencies.iter().any(|dep| {
+                    dep.name() == *required
+                        || self
+                            .resolve_internal(dep, config, workspace)
+                            .is_some_and(|resolved| &resolved.name == *required)
+                })
+            })
+            .map(|required| PackageValidation {
+                description: Some(format!(
+                    "packages of type '{}' have to depend on '{}'",
+                    pkg_type.name, required
+                )),
+                dependency: Some(required.clone()),
+                ..self.finding(
+                    config,
+                    code.clone(),
+                    format!("missing required dependency '{}'", required),
+                )
+            })
+            .collect()
+    }
 
-fn pubspec_dir(path: &str) -> Option<(String, String)> {
-    let full_path = std::path::Path::new(path);
-    let dir_name = full_path
-        .parent()
-        .and_then(|d| d.file_name())
-        .and_then(|f| f.to_str())?;
-    let full_dir = full_path.parent().and_then(|f| f.to_str())?;
+    /// Directory prefixes of all packages this package may depend on
+    /// (based on the includes of its package types).
+    fn valid_prefixes(&self, config: &Config) -> Vec<String> {
+        self.package_types(config)
+            .into_iter()
+            .flat_map(|pkg_type| included_types(pkg_type, config))
+            .flat_map(|include| include.prefixes.iter().cloned())
+            .collect()
+    }
 
-    Some((dir_name.to_owned(), full_dir.to_owned()))
+    /// Whether this package may depend on the workspace package `dep`:
+    /// packages declaring their type have to be of an included type,
+    /// all others have to match the prefix of an included type.
+    pub fn allows_dependency(&self, dep: &Pubspec, config: &Config) -> bool {
+        self.package_types(config)
+            .into_iter()
+            .flat_map(|pkg_type| included_types(pkg_type, config))
+            .any(|include| match &dep.package_type {
+                Some(name) => &include.name == name,
+                None => include.matches_prefix(&dep.dir_name),
+            })
+    }
+
+    /// Describe the package type of the not allowed dependency `dep` and
+    /// the include that would allow it.
+    fn include_hint(&self, dep: &Pubspec, config: &Config) -> String {
+        let own_type = self.package_type(config);
+
+        match (own_type, dep.package_type(config)) {
+            (Some(own_type), Some(dep_type)) => format!(
+                "; '{}' is of package type '{}' (include '{}' in package type '{}' to allow it)",
+                dep.dir_name, dep_type.name, dep_type.name, own_type.name
+            ),
+            (None, Some(dep_type)) => {
+                format!(
+                    "; '{}' is of package type '{}'",
+                    dep.dir_name, dep_type.name
+                )
+            }
+            (_, None) => format!("; '{}' matches no package type", dep.dir_name),
+        }
+    }
+
+    /// Create a new `PackageValidation` instance for this `Pubspec`
+    /// concerning the dependency `dep`
+    fn validation<T: Into<Option<String>>>(
+        &self,
+        config: &Config,
+        dep: &Dependency,
+        error: String,
+        code: ValidationType,
+        description: T,
+    ) -> PackageValidation {
+        PackageValidation {
+            description: description.into(),
+            dependency: Some(dep.name().clone()),
+            ..self.finding(config, code, error)
+        }
+    }
+
+    /// Create a new `PackageValidation` instance for this `Pubspec`
+    /// concerning the package as a whole
+    fn finding(&self, config: &Config, code: ValidationType, error: String) -> PackageValidation {
+        let level = config.validation_level_at(&code, &self.dir_path);
+
+        PackageValidation {
+            package_name: self.name.clone(),
+            error,
+            code,
+            level,
+            description: None,
+            dependency: None,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::dependency::Dependency;
-    use crate::error::ValidationType;
-    use crate::pubspec::PackageType;
-    use crate::pubspec::PackageValidation;
-    use crate::Config;
-    use crate::Pubspec;
+/// Longest chain of workspace packages starting with `pubspec` along its
+/// (non-dev) local dependencies. Packages already on the current `path`
+/// are skipped in order to terminate on cyclic dependencies.
+fn longest_chain<'a: 'p, 'p>(
+    pubspec: &'a Pubspec,
+    config: &Config,
+    workspace: &Workspace<'a>,
+    path: &mut Vec<&'p str>,
+    memo: &mut HashMap<&'a str, Vec<&'a Pubspec>>,
+) -> Vec<&'a Pubspec> {
+    if let Some(chain) = memo.get(pubspec.dir_path.as_str()) {
+        return chain.clone();
+    }
 
-    fn empty_config() -> Config {
-        return Config {
-            package_types: Vec::new(),
-            blacklist: Vec::new(),
-            validations: Vec::new(),
-            public_repositories: Vec::new(),
+    path.push(&pubspec.dir_path);
+    let mut longest: Vec<&Pubspec> = Vec::new();
+
+    for dep in pubspec.dependencies.iter() {
+        let Some(resolved) = pubspec.resolve_internal(dep, config, workspace) else {
+            continue;
         };
+        if path.contains(&resolved.dir_path.as_str()) {
+            continue;
+        }
+
+        let chain = longest_chain(resolved, config, workspace, path, memo);
+        if chain.len() > longest.len() {
+            longest = chain;
+        }
     }
+    path.pop();
 
-    fn base_config() -> Config {
-        let empty = empty_config();
-        return Config {
-            package_types: vec![
-                PackageType {
-                    name: "app".to_owned(),
-                    prefixes: vec!["app_".to_owned()],
-                    includes: vec!["shared".to_owned()],
-                },
-                PackageType {
-                    name: "shared".to_owned(),
-                    prefixes: vec!["shared_".to_owned()],
-                    includes: vec!["shared".to_owned(), "package".to_owned()],
-                },
-                PackageType {
-                    name: "package".to_owned(),
-                    prefixes: vec!["pkg_".to_owned()],
+    let mut chain = vec![pubspec];
+    chain.extend(longest);
+    memo.insert(&pubspec.dir_path, chain.clone());
+    chain
+}
+
+/// Determine all package types the given package type (transitively)
+/// includes. Cyclic includes are expanded once only.
+fn included_types<'a>(pkg_type: &PackageType, config: &'a Config) -> Vec<&'a PackageType> {
+    let mut included: Vec<&PackageType> = vec![];
+    let mut expanded: Vec<&str> = vec![];
+    let mut pending = vec![pkg_type];
+
+    while let Some(current) = pending.pop() {
+        if expanded.contains(&current.name.as_str()) {
+            continue;
+        }
+        expanded.push(&current.name);
+
+        for pkg in config.package_types.iter() {
+            if current.includes.contains(&pkg.name) {
+                if !included.iter().any(|include| include.name == pkg.name) {
+                    included.push(pkg);
+                }
+                pending.push(pkg);
+            }
+        }
+    }
+    included
+}
+
+/// Find all pubspec files below `root_dir`, skipping hidden directories
+/// as well as those excluded via the given `Config`. Symbolic links to
+/// directories are descended into if `follow_symlinks` is set only.
+pub fn find_pubspecs(
+    root_dir: &str,
+    config: &Config,
+    follow_symlinks: bool,
+    skipped: &mut Vec<SkippedPath>,
+) -> Vec<String> {
+    let mut pubspecs = vec![];
+    let root = Path::new(root_dir);
+
+    let walker = WalkDir::new(root_dir)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_entry(|e| {
+            // filter hidden files/directories (the root directory itself
+            // may be given as e.g. '.' though)
+            let is_hidden = e.depth() > 0
+                && e.file_name()
+                    .to_str()
+                    .map(|s| s.starts_with("."))
+                    .unwrap_or(false);
+
+            // filter excluded directories
+            let is_excluded = e.file_type().is_dir()
+                && relative_path(root, e.path())
+                    .map(|rel| config.is_excluded_dir(&rel))
+                    .unwrap_or(false);
+
+            if is_excluded {
+                debug!("skipping excluded directory {}", e.path().display());
+            }
+
+            !is_hidden && !is_excluded
+        })
+        // skip errors (e.g. non permission directories or symbolic link
+        // loops, which are detected by walkdir) but keep track of them
+        .filter_map(|e| {
+            e.map_err(|err| {
+                let path = err
+                    .path()
+                    .map_or(root_dir.to_owned(), |path| path.display().to_string());
+                warn!("skipping {}: {}", path, err);
+                skipped.push(SkippedPath {
+                    path,
+                    reason: err.to_string(),
+                });
+            })
+            .ok()
+        });
+
+    for entry in walker {
+        let filename = entry.file_name().to_str().unwrap_or("").to_lowercase();
+        if PUBSPEC_FILES.contains(&filename.as_str()) {
+            let is_included = entry
+                .path()
+                .parent()
+                .and_then(|dir| relative_path(root, dir))
+                .map(|rel| config.is_included_dir(&rel))
+                .unwrap_or(false);
+
+            if !is_included {
+                debug!("skipping {} (not included)", entry.path().display());
+                continue;
+            }
+
+            if let Some(path) = entry.path().to_str() {
+                pubspecs.push(path.to_owned());
+            }
+        }
+    }
+
+    pubspecs
+}
+
+/// Discover and load all pubspecs below the given root directories
+/// (following symbolic links if `follow_symlinks` is set).
+///
+/// Pubspecs reachable from multiple (overlapping) root directories or via
+/// symbolic links are loaded once only, whereas different packages of the
+/// same name in separate root directories are rejected. Directories that
+/// cannot be read and pubspecs that cannot be parsed are skipped (see
+/// `discover_pubspecs`).
+pub fn load_pubspecs(
+    root_dirs: &[String],
+    config: &Config,
+    follow_symlinks: bool,
+) -> Result<Vec<Pubspec>, FlError> {
+    discover_pubspecs(root_dirs, config, follow_symlinks, None).map(|discovery| discovery.pubspecs)
+}
+
+/// Discover and load all pubspecs like `load_pubspecs`, listing all paths
+/// skipped along the way as well. Unchanged pubspecs are taken from the
+/// given `cache` (if any) instead of parsing them again.
+pub fn discover_pubspecs(
+    root_dirs: &[String],
+    config: &Config,
+    follow_symlinks: bool,
+    mut cache: Option<&mut PubspecCache>,
+) -> Result<Discovery, FlError> {
+    let mut pubspecs: Vec<Pubspec> = Vec::new();
+    let mut canonical_paths = HashSet::new();
+    let mut skipped = Vec::new();
+
+    for root_dir in root_dirs {
+        let paths = find_pubspecs(root_dir, config, follow_symlinks, &mut skipped);
+        debug!("found {} pubspec(s) below {}", paths.len(), root_dir);
+
+        for path in paths {
+            let canonical = canonicalize(&path).unwrap_or_else(|| path.clone());
+            if !canonical_paths.insert(canonical) {
+                debug!("skipping {} (already loaded)", path);
+                continue;
+            }
+
+            let started = Instant::now();
+            let loaded = match cache.as_deref_mut() {
+                Some(cache) => cache.load(&path),
+                None => Pubspec::load(&path),
+            };
+            let pubspec = match loaded {
+                Ok(pubspec) => pubspec,
+                Err(err) => {
+                    warn!("skipping {}: {}", path, err);
+                    skipped.push(SkippedPath {
+                        path,
+                        reason: err.to_string(),
+                    });
+                    continue;
+                }
+            };
+            debug!("loaded {} in {:.2?}", pubspec, started.elapsed());
+            pubspecs.push(pubspec);
+        }
+    }
+
+    canonical_order(&mut pubspecs);
+
+    Ok(Discovery {
+        pubspecs,
+        skipped,
+        cache: cache.map(|cache| cache.state()),
+    })
+}
+
+/// Sort the given pubspecs by path and their dependencies by name, so
+/// that all output is independent of the order of the directory entries
+/// (which differs between file systems).
+fn canonical_order(pubspecs: &mut [Pubspec]) {
+    pubspecs.sort_by(|a, b| Path::new(&a.path).cmp(Path::new(&b.path)));
+
+    for pubspec in pubspecs.iter_mut() {
+        pubspec.dependencies.sort_by(|a, b| a.name().cmp(b.name()));
+        pubspec
+            .dev_dependencies
+            .sort_by(|a, b| a.name().cmp(b.name()));
+    }
+}
+
+fn plugin_platforms(yaml: &Yaml) -> Vec<PluginPlatform> {
+    let empty = Default::default();
+
+    yaml["flutter"]["plugin"]["platforms"]
+        .as_hash()
+        .unwrap_or(&empty)
+        .iter()
+        .flat_map(|(key, value)| {
+            // `pluginClass: none` is used by dart-only implementations
+            let plugin_class = value["pluginClass"]
+                .as_str()
+                .filter(|class| *class != "none");
+            let ffi_plugin = value["ffiPlugin"].as_bool().unwrap_or(false);
+
+            key.as_str().map(|name| PluginPlatform {
+                name: name.to_owned(),
+                native: plugin_class.is_some() || ffi_plugin,
+            })
+        })
+        .collect()
+}
+
+/// Extract the paths of all assets (`flutter.assets`) and fonts
+/// (`flutter.fonts`) of the given pubspec `Yaml`.
+///
+/// Assets may be given as plain paths or as mappings with a `path` (along
+/// with `flavors` or `transformers`). Paths of other packages' assets
+/// (`packages/...`) are skipped.
+fn flutter_assets(yaml: &Yaml) -> Vec<String> {
+    let flutter = &yaml["flutter"];
+
+    let assets = flutter["assets"]
+        .as_vec()
+        .into_iter()
+        .flatten()
+        .flat_map(|asset| asset.as_str().or_else(|| asset["path"].as_str()));
+
+    let fonts = flutter["fonts"]
+        .as_vec()
+        .into_iter()
+        .flatten()
+        .flat_map(|family| family["fonts"].as_vec().into_iter().flatten())
+        .flat_map(|font| font["asset"].as_str());
+
+    assets
+        .chain(fonts)
+        .filter(|path| !path.starts_with("packages/"))
+        .map(|path| path.to_owned())
+        .collect()
+}
+
+/// Whether any markdown heading of the given changelog mentions `version`,
+/// e.g. `## 1.2.0`, `## [1.2.0] - 2024-01-31` or `# v1.2.0`.
+fn changelog_mentions(content: &str, version: &str) -> bool {
+    content
+        .lines()
+        .filter(|line| line.trim_start().starts_with('#'))
+        .any(|line| {
+            line.split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '+' | '-')))
+                .any(|word| word == version || word.strip_prefix('v') == Some(version))
+        })
+}
+
+fn is_public_package(yaml: &Yaml) -> bool {
+    let is_public_node = &yaml["flcheck"]["is_public"].as_bool();
+    is_public_node.unwrap_or(false)
+}
+
+/// Non-empty string value of the given key of the `flcheck` section.
+fn flcheck_str(yaml: &Yaml, key: &str) -> Option<String> {
+    yaml["flcheck"][key]
+        .as_str()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_owned())
+}
+
+fn get_dependencies(yaml: &Yaml) -> Vec<Dependency> {
+    let dependencies = &yaml["dependencies"];
+    let dependency_overrides = &yaml["dependency_overrides"];
+    let empty = Default::default();
+
+    let mut deps = vec![];
+
+    for (key, value) in dependencies.as_hash().unwrap_or(&empty).iter() {
+        let key = key.as_str().unwrap_or("");
+        if key.is_empty() {
+            continue;
+        }
+
+        if let Some(dep) = extract_dependency(key, value) {
+            // we found a "normal" dependency
+            // before collecting this one, we have to check if there is
+            // a dependency override set for that same dependency
+            let new_dependency =
+                if let Some(dep_override) = extract_dependency(key, &dependency_overrides[key]) {
+                    dep.with_override(dep_override)
+                } else {
+                    dep
+                };
+
+            deps.push(new_dependency);
+        }
+    }
+
+    deps
+}
+
+fn get_dev_dependencies(yaml: &Yaml) -> Vec<Dependency> {
+    let dependencies = &yaml["dev_dependencies"];
+    let empty = Default::default();
+
+    let mut deps = vec![];
+
+    for (key, value) in dependencies.as_hash().unwrap_or(&empty).iter() {
+        let key = key.as_str().unwrap_or("");
+        if key.is_empty() {
+            continue;
+        }
+
+        if let Some(dep) = extract_dependency(key, value) {
+            deps.push(dep);
+        }
+    }
+
+    deps
+}
+
+/// Structural problems of the given pubspec `yaml`: a missing package name,
+/// a version that is no string and malformed dependencies.
+fn schema_problems(yaml: &Yaml) -> Vec<SchemaProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |dependency: Option<&str>, message: &str| {
+        problems.push(SchemaProblem {
+            dependency: dependency.map(|name| name.to_owned()),
+            message: message.to_owned(),
+        })
+    };
+
+    if yaml["name"].as_str().unwrap_or("").trim().is_empty() {
+        problem(None, "missing package name");
+    }
+
+    if !matches!(yaml["version"], Yaml::String(_) | Yaml::BadValue) {
+        problem(None, "version must be a string (e.g. '1.0.0')");
+    }
+
+    for section in ["dependencies", "dev_dependencies", "dependency_overrides"] {
+        let dependencies = match &yaml[section] {
+            Yaml::Hash(dependencies) => dependencies,
+            Yaml::BadValue | Yaml::Null => continue,
+            _ => {
+                problem(None, &format!("'{}' must be a mapping", section));
+                continue;
+            }
+        };
+
+        for (key, value) in dependencies {
+            match key.as_str().filter(|name| !name.is_empty()) {
+                Some(name) => {
+                    if let Some(message) = malformed_dependency(value) {
+                        problem(Some(name), &message);
+                    }
+                }
+                None => problem(None, &format!("invalid dependency name in '{}'", section)),
+            }
+        }
+    }
+
+    problems
+}
+
+/// Determine why the given dependency declaration is malformed (if it is).
+fn malformed_dependency(value: &Yaml) -> Option<String> {
+    let hash = match value {
+        // version constraint (or any version if empty)
+        Yaml::String(_) | Yaml::Null => return None,
+        Yaml::Hash(hash) => hash,
+        _ => return Some("version constraint must be a string".to_owned()),
+    };
+
+    let has = |key: &str| hash.contains_key(&Yaml::String(key.to_owned()));
+    if !["path", "git", "hosted", "sdk", "version"]
+        .iter()
+        .any(|key| has(key))
+    {
+        return Some(
+            "unknown dependency shape (expected path, git, hosted, sdk or version)".to_owned(),
+        );
+    }
+
+    if has("path") && value["path"].as_str().unwrap_or("").is_empty() {
+        return Some("path must be a non-empty string".to_owned());
+    }
+
+    if has("git") && git_url(&value["git"]).is_none() {
+        return Some("git dependency without url".to_owned());
+    }
+
+    if !matches!(value["version"], Yaml::String(_) | Yaml::BadValue) {
+        return Some("version constraint must be a string".to_owned());
+    }
+
+    None
+}
+
+/// URL of the given git dependency declared either as string or mapping.
+fn git_url(git: &Yaml) -> Option<&str> {
+    git.as_str()
+        .or_else(|| git["url"].as_str())
+        .filter(|url| !url.is_empty())
+}
+
+/// Extract the `flcheck` settings nested under the (dev) dependencies of the
+/// given pubspec `Yaml`.
+fn dependency_annotations(yaml: &Yaml) -> Vec<DependencyAnnotation> {
+    let empty = Default::default();
+
+    ["dependencies", "dev_dependencies"]
+        .iter()
+        .flat_map(|section| yaml[*section].as_hash().unwrap_or(&empty).iter())
+        .flat_map(|(key, value)| {
+            let flcheck = &value["flcheck"];
+            flcheck.as_hash()?;
+
+            Some(DependencyAnnotation {
+                dependency: key.as_str()?.to_owned(),
+                reason: flcheck["reason"]
+                    .as_str()
+                    .map(|reason| reason.trim())
+                    .filter(|reason| !reason.is_empty())
+                    .map(|reason| reason.to_owned()),
+                ignore: yaml_str_list(&flcheck["ignore"]),
+                internal: flcheck["internal"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn extract_dependency(key: &str, value: &Yaml) -> Option<Dependency> {
+    let path = value["path"].as_str().unwrap_or("");
+
+    // check local dependency first
+    if !path.is_empty() {
+        return Some(Dependency::Local {
+            name: key.to_owned(),
+            path: path.to_owned(),
+            overridden: Box::new(None),
+        });
+    }
+
+    // check git dependency
+    // (the path defaults to the repository root)
+    let git_node = &value["git"];
+    if let Some(git_url) = git_url(git_node) {
+        return Some(Dependency::Git {
+            name: key.to_owned(),
+            git: git_url.to_owned(),
+            path: git_node["path"].as_str().unwrap_or("").to_owned(),
+            overridden: Box::new(None),
+        });
+    }
+
+    // try public (external) dependency at last, which may be given as
+    // mapping with a `version` as well (e.g. to attach `flcheck` settings)
+    let pubdev_version = value["version"]
+        .as_str()
+        .filter(|_| value["hosted"].is_badvalue() && value["sdk"].is_badvalue());
+    value
+        .as_str()
+        .or(pubdev_version)
+        .map(|str| str.to_owned())
+        .or_else(|| value.as_f64().map(|num| format!("{}", num)))
+        .map(|version| Dependency::PubDev {
+            name: key.to_owned(),
+            version,
+            overridden: Box::new(None),
+        })
+}
+
+/// Determine the line (1-based) the dependency `name` is declared in within
+/// the `dependencies` or `dev_dependencies` of the given pubspec `content`.
+pub fn dependency_line(content: &str, name: &str) -> Option<usize> {
+    let mut in_dependencies = false;
+    // indentation of the dependencies' keys (as opposed to their details)
+    let mut indent = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let line_indent = line.len() - trimmed.len();
+        if line_indent == 0 {
+            let key = line.split(':').next().unwrap_or("").trim();
+            in_dependencies = key == "dependencies" || key == "dev_dependencies";
+            indent = None;
+            continue;
+        }
+
+        if !in_dependencies || *indent.get_or_insert(line_indent) != line_indent {
+            continue;
+        }
+
+        if trimmed
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        {
+            return Some(idx + 1);
+        }
+    }
+
+    None
+}
+
+fn file_name(path: &str) -> Option<String> {
+    PathBuf::from(path)
+        .file_name()
+        .and_then(|path| path.to_str())
+        .map(|path| path.to_owned())
+}
+
+fn pubspec_dir(path: &str) -> Option<(String, String)> {
+    let full_path = std::path::Path::new(path);
+    let dir_name = full_path
+        .parent()
+        .and_then(|d| d.file_name())
+        .and_then(|f| f.to_str())?;
+    let full_dir = full_path.parent().and_then(|f| f.to_str())?;
+
+    Some((dir_name.to_owned(), full_dir.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::constraint_policies;
+    use crate::config::AlternativeGroup;
+    use crate::config::CheckSettings;
+    use crate::config::HttpSettings;
+    use crate::config::PublicPackagePolicy;
+    use crate::config::ValidationSetting;
+    use crate::dependency::Dependency;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::pubspec::dependency_line;
+    use crate::pubspec::discover_pubspecs;
+    use crate::pubspec::file_name;
+    use crate::pubspec::get_dependencies;
+    use crate::pubspec::load_pubspecs;
+    use crate::pubspec::plugin_platforms;
+    use crate::pubspec::schema_problems;
+    use crate::pubspec::PackageType;
+    use crate::pubspec::PackageValidation;
+    use crate::pubspec::PluginPlatform;
+    use crate::workspace::Workspace;
+    use crate::Config;
+    use crate::Pubspec;
+    use regex::Regex;
+    use std::collections::HashMap;
+    use yaml_rust::YamlLoader;
+
+    fn empty_config() -> Config {
+        Config {
+            package_types: Vec::new(),
+            blacklist: Vec::new(),
+            validations: Vec::new(),
+            public_repositories: Vec::new(),
+            public_package_policy: HashMap::new(),
+            internal_repositories: Vec::new(),
+            private_packages: Vec::new(),
+            dev_only_packages: Vec::new(),
+            structure: HashMap::new(),
+            analysis_options: None,
+            constraint_styles: Vec::new(),
+            alternatives: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            roots: Vec::new(),
+            custom_validations: Vec::new(),
+            rules: Vec::new(),
+            codeowners: None,
+            http: HttpSettings::default(),
+            check: CheckSettings::default(),
+        }
+    }
+
+    fn base_config() -> Config {
+        let empty = empty_config();
+        Config {
+            package_types: vec![
+                PackageType {
+                    name: "app".to_owned(),
+                    prefixes: vec!["app_".to_owned()],
+                    includes: vec!["shared".to_owned()],
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
+                },
+                PackageType {
+                    name: "shared".to_owned(),
+                    prefixes: vec!["shared_".to_owned()],
+                    includes: vec!["shared".to_owned(), "package".to_owned()],
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
+                },
+                PackageType {
+                    name: "package".to_owned(),
+                    prefixes: vec!["pkg_".to_owned()],
                     includes: vec!["package".to_owned()],
+                    max_depth: None,
+                    requires: Vec::new(),
+                    pure_dart: false,
+                    require_tests: false,
                 },
             ],
             ..empty
+        }
+    }
+
+    fn codes(validations: Vec<PackageValidation>) -> Vec<ValidationType> {
+        validations.into_iter().map(|v| v.code).collect()
+    }
+
+    #[test]
+    fn display() {
+        let dep = Dependency::PubDev {
+            name: "pkg_core".to_owned(),
+            version: "^1.0.0".to_owned(),
+            overridden: Box::new(None),
+        }
+        .with_override(Dependency::Local {
+            name: "pkg_core".to_owned(),
+            path: "../pkg_core".to_owned(),
+            overridden: Box::new(None),
+        });
+        assert_eq!(
+            dep.to_string(),
+            "pkg_core [public, ^1.0.0] -> [local, ../pkg_core]"
+        );
+
+        let mut pubspec = Pubspec::test_at("foo", "/tmp/foo");
+        assert_eq!(pubspec.to_string(), "foo [/tmp/foo/pubspec.yaml]");
+        pubspec.version = Some("1.2.0".to_owned());
+        pubspec.name.clear();
+        assert_eq!(
+            pubspec.to_string(),
+            "<unnamed> 1.2.0 [/tmp/foo/pubspec.yaml]"
+        );
+    }
+
+    #[test]
+    fn empty_dependencies() {
+        let config = base_config();
+        let all = vec![Pubspec::test_at("test", "/tmp/test")];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        assert_eq!(errors.len(), 0);
+    }
+
+    #[test]
+    fn multiple_packages() {
+        let config = base_config();
+        let all = vec![
+            Pubspec::test_at("foo", "/tmp/foo"),
+            Pubspec::test_at("bar", "/tmp/bar"),
+            Pubspec::test_at("ham", "/tmp/ham"),
+            Pubspec::test_at("eggs", "/tmp/eggs"),
+        ];
+
+        for pkg in all.iter() {
+            let errors = pkg.validate(&config, &Workspace::new(&all));
+            assert_eq!(errors.len(), 0);
+        }
+    }
+
+    #[test]
+    fn git_dependency() {
+        let config = base_config();
+        let all = vec![Pubspec {
+            dependencies: vec![Dependency::Git {
+                name: "git".to_owned(),
+                git: "git://repo".to_owned(),
+                path: "".to_owned(),
+                overridden: Box::new(None),
+            }],
+            ..Pubspec::test_at("foo", "/tmp/foo")
+        }];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        assert_eq!(errors.len(), 0);
+    }
+
+    #[test]
+    fn unknown_dependency() {
+        let config = base_config();
+        let all = vec![Pubspec {
+            dependencies: vec![Dependency::Local {
+                name: "bar".to_owned(),
+                path: "../bar".to_owned(),
+                overridden: Box::new(None),
+            }],
+            ..Pubspec::test_at("foo", "/tmp/foo")
+        }];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        let error_codes = codes(errors);
+
+        assert_eq!(error_codes, vec![ValidationType::UnknownDependency]);
+    }
+
+    #[test]
+    fn unknown_dependency_suggestions() {
+        let config = base_config();
+        let all = vec![
+            Pubspec::test_at("app_foo", "/tmp/ws/apps/app_foo").with_deps(vec![
+                Pubspec::local("pkg_croe", "../../packages/pkg_croe"),
+                Pubspec::local("ui", "../../packages/shard_ui/"),
+                Pubspec::local("pkg_network", "../../packages/pkg_network"),
+            ]),
+            Pubspec::test_at("pkg_core", "/tmp/ws/packages/pkg_core"),
+            Pubspec::test_at("pkg_cre", "/tmp/ws/legacy/pkg_cre"),
+            Pubspec::test_at("shared_ui", "/tmp/ws/packages/shared_ui"),
+        ];
+
+        let descriptions = all[0]
+            .validate(&config, &Workspace::new(&all))
+            .into_iter()
+            .map(|validation| (validation.code, validation.description))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            descriptions,
+            vec![
+                (
+                    ValidationType::UnknownDependency,
+                    Some(
+                        "did you mean 'pkg_cre' at '../../legacy/pkg_cre' or 'pkg_core' at \
+                         '../../packages/pkg_core'?"
+                            .to_owned()
+                    )
+                ),
+                (
+                    ValidationType::UnknownDependency,
+                    Some("did you mean 'shared_ui' at '../../packages/shared_ui'?".to_owned())
+                ),
+                (ValidationType::UnknownDependency, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn misplaced_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "shared_ui".to_owned(),
+                    path: "../../packages/shard_ui".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_foo", "/tmp/ws/apps/app_foo")
+            },
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "app_foo".to_owned(),
+                    path: "../../apps/app_foo".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("shared_ui", "/tmp/ws/packages/shared_ui")
+            },
+        ];
+
+        // the dependency is resolved by name for all other validations
+        let resolved = all[0].resolve_dependency(&all[0].dependencies[0], &Workspace::new(&all));
+        assert_eq!(
+            resolved.map(|pubspec| pubspec.name.as_str()),
+            Some("shared_ui")
+        );
+        assert!(all[1]
+            .misplaced_dependency(&all[1].dependencies[0], &Workspace::new(&all))
+            .is_none());
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(
+            errors.iter().map(|v| v.code.clone()).collect::<Vec<_>>(),
+            vec![
+                ValidationType::CyclicDependency,
+                ValidationType::DependencyPathMismatch
+            ]
+        );
+        assert_eq!(
+            errors[1].error,
+            "path '../../packages/shard_ui' of dependency 'shared_ui' does not exist but \
+             package 'shared_ui' is at '../../packages/shared_ui'"
+        );
+        assert_eq!(
+            errors[1].description.as_deref(),
+            Some("did you mean path '../../packages/shared_ui'?")
+        );
+    }
+
+    #[test]
+    fn misplaced_dependency_yml() {
+        let root = std::env::temp_dir().join(format!("flcheck-yml-{}", std::process::id()));
+        let other = root.join("packages").join("other_ui");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("pubspec.yml"), "name: other_ui").unwrap();
+
+        let ws = root.to_string_lossy();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "shared_ui".to_owned(),
+                    path: "../../packages/other_ui".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_foo", &format!("{}/apps/app_foo", ws))
+            },
+            Pubspec::test_at("shared_ui", &format!("{}/packages/shared_ui", ws)),
+        ];
+
+        // the path points to an (excluded) package with a 'pubspec.yml'
+        assert!(all[0]
+            .misplaced_dependency(&all[0].dependencies[0], &Workspace::new(&all))
+            .is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn unconfigured_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "bar".to_owned(),
+                    path: "../bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("foo", "/tmp/foo")
+            },
+            Pubspec {
+                dependencies: vec![],
+                ..Pubspec::test_at("bar", "/tmp/bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        let error_codes = codes(errors);
+
+        assert_eq!(error_codes, vec![ValidationType::DependencyNotAllowed]);
+    }
+
+    #[test]
+    fn cyclic_package_type_includes() {
+        let mut config = base_config();
+        config.package_types[2].includes.push("app".to_owned());
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "pkg_bar".to_owned(),
+                    path: "../pkg_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_foo", "/tmp/app_foo")
+            },
+            Pubspec {
+                dependencies: vec![],
+                ..Pubspec::test_at("pkg_bar", "/tmp/pkg_bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        let error_codes = codes(errors);
+
+        assert_eq!(error_codes, Vec::new());
+    }
+
+    #[test]
+    fn basic_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "pkg_bar".to_owned(),
+                    path: "../pkg_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_foo", "/tmp/app_foo")
+            },
+            Pubspec {
+                dependencies: vec![],
+                ..Pubspec::test_at("pkg_bar", "/tmp/pkg_bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        let error_codes = codes(errors);
+
+        assert_eq!(error_codes, Vec::new());
+    }
+
+    #[test]
+    fn unallowed_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "app_bar".to_owned(),
+                    path: "../app_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("pkg_foo", "/tmp/pkg_foo")
+            },
+            Pubspec {
+                dependencies: vec![],
+                ..Pubspec::test_at("app_bar", "/tmp/app_bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(
+            errors[0].description.as_deref(),
+            Some(
+                "packages with the following directory prefixes are allowed only: 'pkg_'; \
+                 'app_bar' is of package type 'app' (include 'app' in package type 'package' \
+                 to allow it)"
+            )
+        );
+        assert_eq!(codes(errors), vec![ValidationType::DependencyNotAllowed]);
+    }
+
+    #[test]
+    fn cross_app_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "app_bar".to_owned(),
+                    path: "../app_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_foo", "/tmp/app_foo")
+            },
+            Pubspec::test_at("app_bar", "/tmp/app_bar"),
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ValidationType::CrossAppDependency);
+        assert_eq!(
+            errors[0].error,
+            "'app_foo' must not depend on 'app_bar' of the same package type 'app'"
+        );
+    }
+
+    #[test]
+    fn declared_package_type() {
+        let config = base_config();
+        let all = vec![
+            Pubspec::test("app_foo").with_local_deps(&["legacy_ui"]),
+            Pubspec::test("pkg_core").with_local_deps(&["legacy_ui"]),
+            Pubspec {
+                package_type: Some("shared".to_owned()),
+                ..Pubspec::test("legacy_ui").with_local_deps(&["pkg_util"])
+            },
+            Pubspec::test_at("pkg_util", "/tmp/pkg_util"),
+        ];
+
+        assert_eq!(all[2].package_type(&config).unwrap().name, "shared");
+        assert!(all[0].validate(&config, &Workspace::new(&all)).is_empty());
+        assert!(all[2].validate(&config, &Workspace::new(&all)).is_empty());
+
+        let errors = all[1].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ValidationType::DependencyNotAllowed);
+        assert!(errors[0]
+            .description
+            .as_ref()
+            .unwrap()
+            .ends_with("'legacy_ui' is of package type 'shared' (include 'shared' in package type 'package' to allow it)"));
+    }
+
+    #[test]
+    fn load_package_metadata() {
+        let dir = std::env::temp_dir().join(format!("flcheck-metadata-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("pubspec.yaml");
+        std::fs::write(
+            &path,
+            "
+name: legacy_ui
+flcheck:
+  type: shared
+  owner: team-payments
+  tags: [legacy, ui]
+",
+        )
+        .unwrap();
+
+        let pubspec = Pubspec::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(pubspec.package_type, Some("shared".to_owned()));
+        assert_eq!(pubspec.owner, Some("team-payments".to_owned()));
+        assert_eq!(pubspec.tags, vec!["legacy", "ui"]);
+        assert!(!pubspec.is_public);
+    }
+
+    #[test]
+    fn cyclic_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "pkg_bar".to_owned(),
+                    path: "../pkg_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("pkg_foo", "/tmp/pkg_foo")
+            },
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "pkg_foo".to_owned(),
+                    path: "../pkg_foo".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("pkg_bar", "/tmp/pkg_bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        assert_eq!(errors[0].dependency.as_deref(), Some("pkg_bar"));
+
+        // both packages describe the cycle the same way
+        let expected = "cyclic dependency pkg_bar -> pkg_foo -> 'pkg_bar'";
+        assert_eq!(errors[0].error, expected);
+        assert_eq!(
+            all[1].validate(&config, &Workspace::new(&all))[0].error,
+            expected
+        );
+
+        let error_codes = codes(errors);
+
+        assert_eq!(error_codes, vec![ValidationType::CyclicDependency]);
+    }
+
+    #[test]
+    fn disabled_cyclic_dependency() {
+        let all = vec![
+            Pubspec::test("pkg_foo").with_local_deps(&["pkg_bar"]),
+            Pubspec::test("pkg_bar").with_local_deps(&["pkg_foo"]),
+        ];
+
+        // disabled validations are neither computed nor reported
+        let disabled = Config {
+            validations: vec![ValidationSetting {
+                validation_type: ValidationType::CyclicDependency,
+                level: ValidationLevel::None,
+                paths: Vec::new(),
+            }],
+            ..base_config()
+        };
+        assert_eq!(
+            codes(all[0].validate(&base_config(), &Workspace::new(&all))),
+            vec![ValidationType::CyclicDependency]
+        );
+        assert!(all[0].validate(&disabled, &Workspace::new(&all)).is_empty());
+    }
+
+    #[test]
+    fn cyclic_and_unallowed_dependency() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "app_bar".to_owned(),
+                    path: "../app_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_foo", "/tmp/app_foo")
+            },
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "app_foo".to_owned(),
+                    path: "../app_foo".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("app_bar", "/tmp/app_bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        let error_codes = codes(errors);
+
+        assert_eq!(
+            error_codes,
+            vec![
+                ValidationType::CrossAppDependency,
+                ValidationType::CyclicDependency
+            ]
+        );
+    }
+
+    #[test]
+    fn dev_only_dependency() {
+        let config = base_config();
+        let all = vec![Pubspec::test("pkg_foo")
+            .with_deps(vec![
+                Pubspec::hosted("http", "^2.0.0"),
+                Pubspec::hosted("build_runner", "^2.0.0"),
+            ])
+            .with_dev_deps(vec![Pubspec::hosted("mockito", "^2.0.0")])];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ValidationType::DevOnlyDependency);
+        assert_eq!(errors[0].level, ValidationLevel::Warning);
+        assert_eq!(errors[0].dependency.as_deref(), Some("build_runner"));
+    }
+
+    #[test]
+    fn dependency_annotations() {
+        let config = base_config();
+        let content = "
+name: pkg_foo
+dependencies:
+  app_bar:
+    version: ^1.0.0
+    flcheck:
+      internal: true
+      reason: ' published from the monorepo '
+  http: ^1.0.0
+  build_runner:
+    version: ^2.0.0
+    flcheck:
+      ignore: [validation:dependency:dev-only]
+";
+        let all = vec![
+            Pubspec::parse("/tmp/pkg_foo/pubspec.yaml", content).unwrap(),
+            Pubspec::test_at("app_bar", "/tmp/app_bar"),
+        ];
+
+        let annotation = all[0].annotation("app_bar").unwrap();
+        assert!(annotation.internal);
+        assert_eq!(
+            annotation.reason.as_deref(),
+            Some("published from the monorepo")
+        );
+        assert_eq!(
+            all[0].annotation("build_runner").unwrap().ignore,
+            vec!["validation:dependency:dev-only"]
+        );
+        assert!(all[0].annotation("http").is_none());
+
+        // the internal (pub.dev) dependency is resolved by name
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].code, ValidationType::DependencyNotAllowed);
+        assert!(!all[0].ignores(&errors[0]));
+        assert_eq!(errors[1].code, ValidationType::DevOnlyDependency);
+        assert!(all[0].ignores(&errors[1]));
+    }
+
+    #[test]
+    fn constraint_styles() {
+        let yaml = YamlLoader::load_from_str(
+            "constraint_styles: [{ packages: '*', forbid: [any] }, { packages: 'firebase_*', require: exact }]",
+        )
+        .unwrap();
+        let config = Config {
+            constraint_styles: constraint_policies(&yaml[0]).unwrap(),
+            ..base_config()
+        };
+        let all = vec![Pubspec::test("pkg_foo")
+            .with_deps(vec![
+                Pubspec::hosted("http", "^1.0.0"),
+                Pubspec::hosted("firebase_core", "^2.0.0"),
+                Pubspec::hosted("firebase_auth", "4.1.0"),
+            ])
+            .with_dev_deps(vec![Pubspec::hosted("mocktail", "any")])];
+
+        let errors = all[0]
+            .validate(&config, &Workspace::new(&all))
+            .into_iter()
+            .map(|validation| (validation.code, validation.error))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    ValidationType::ConstraintStyle,
+                    "constraint '^2.0.0' of dependency 'firebase_core' is not of style 'exact'"
+                        .to_owned()
+                ),
+                (
+                    ValidationType::ConstraintStyle,
+                    "constraint 'any' of dependency 'mocktail' is of forbidden style 'any'"
+                        .to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn cyclic_dev_dependencies() {
+        let config = base_config();
+        let all = vec![
+            Pubspec {
+                dependencies: vec![Dependency::Local {
+                    name: "pkg_bar".to_owned(),
+                    path: "../pkg_bar".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("pkg_foo", "/tmp/pkg_foo")
+            },
+            Pubspec {
+                dev_dependencies: vec![Dependency::Local {
+                    name: "pkg_foo".to_owned(),
+                    path: "../pkg_foo".to_owned(),
+                    overridden: Box::new(None),
+                }],
+                ..Pubspec::test_at("pkg_bar", "/tmp/pkg_bar")
+            },
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        let error_codes = codes(errors);
+
+        assert_eq!(error_codes, vec![ValidationType::CyclicDependency]);
+    }
+
+    #[test]
+    fn incompatible_version() {
+        let config = base_config();
+        let all = vec![
+            Pubspec::test("pkg_foo")
+                .with_deps(vec![Pubspec::hosted("pkg_ui", "^2.0.0")])
+                .with_dev_deps(vec![Pubspec::hosted("pkg_ui", ">=1.0.0 <2.0.0")]),
+            Pubspec::test("pkg_ui").with_version("1.4.0"),
+        ];
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ValidationType::IncompatibleVersion);
+        assert_eq!(
+            errors[0].error,
+            "constraint '^2.0.0' on 'pkg_ui' does not match its version 1.4.0"
+        );
+    }
+
+    #[test]
+    fn public_package_policy() {
+        let public = |name: &str, repository: &str| Pubspec {
+            is_public: true,
+            repository: Some(repository.to_owned()),
+            ..Pubspec::test_at(name, &format!("/tmp/{}", name))
+        };
+        let all = vec![
+            public("pkg_foo", "https://github.com/org/foo")
+                .with_local_deps(&["pkg_core", "pkg_net", "pkg_util"]),
+            public("pkg_core", "https://github.com/org/foo"),
+            public("pkg_net", "https://github.com/org/net"),
+            Pubspec::test_at("pkg_util", "/tmp/pkg_util"),
+        ];
+        let validate = |policy: Option<PublicPackagePolicy>| {
+            let config = Config {
+                public_package_policy: policy
+                    .map(|policy| HashMap::from([("package".to_owned(), policy)]))
+                    .unwrap_or_default(),
+                ..base_config()
+            };
+            // the (non-existent) package directory lacks a CHANGELOG.md
+            let mut validations = all[0].validate(&config, &Workspace::new(&all));
+            validations.retain(|validation| validation.code != ValidationType::PublicChangelog);
+            codes(validations)
+        };
+
+        assert_eq!(
+            validate(None),
+            vec![ValidationType::NonGitDependencyInPublicPackage; 3]
+        );
+        assert_eq!(
+            validate(Some(PublicPackagePolicy::Public)),
+            vec![ValidationType::PrivateDependencyInPublicPackage]
+        );
+        assert_eq!(
+            validate(Some(PublicPackagePolicy::SameRepository)),
+            vec![
+                ValidationType::ForeignRepositoryDependencyInPublicPackage,
+                ValidationType::PrivateDependencyInPublicPackage
+            ]
+        );
+    }
+
+    #[test]
+    fn internal_git_dependency() {
+        let git = |name: &str, repository: &str, path: &str| Dependency::Git {
+            name: name.to_owned(),
+            git: repository.to_owned(),
+            path: path.to_owned(),
+            overridden: Box::new(None),
+        };
+        let all = vec![
+            Pubspec {
+                dependencies: vec![
+                    git("app_bar", "git@github.com:org/ws.git", "packages/app_bar"),
+                    git("ext", "git@github.com:other/ext.git", ""),
+                ],
+                ..Pubspec::test_at("pkg_foo", "/tmp/ws/packages/pkg_foo")
+            },
+            Pubspec::test_at("app_bar", "/tmp/ws/packages/app_bar").with_local_deps(&["pkg_foo"]),
+        ];
+
+        let config = base_config();
+        assert!(all[0].validate(&config, &Workspace::new(&all)).is_empty());
+
+        let config = Config {
+            internal_repositories: vec![Regex::new("github.com:org/ws").unwrap()],
+            ..base_config()
         };
+        assert_eq!(
+            codes(all[0].validate(&config, &Workspace::new(&all))),
+            vec![
+                ValidationType::DependencyNotAllowed,
+                ValidationType::CyclicDependency
+            ]
+        );
+    }
+
+    #[test]
+    fn dependency_depth() {
+        let all = vec![
+            Pubspec::test("app_foo").with_local_deps(&["pkg_a", "shared_ui"]),
+            Pubspec::test("shared_ui").with_local_deps(&["pkg_a"]),
+            Pubspec::test("pkg_a")
+                .with_local_deps(&["pkg_b"])
+                .with_local_dev_deps(&["pkg_c"]),
+            Pubspec::test("pkg_b").with_local_deps(&["pkg_a"]),
+            Pubspec::test("pkg_c"),
+        ];
+
+        let mut config = base_config();
+        config.package_types[0].max_depth = Some(2);
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+        assert_eq!(errors.len(), 1);
+
+        let depth = &errors[0];
+        assert_eq!(depth.code, ValidationType::DependencyDepth);
+        assert_eq!(
+            depth.error,
+            "dependency chain of depth 3 exceeds the maximum of 2"
+        );
+        assert_eq!(
+            depth.description.as_deref(),
+            Some("app_foo -> shared_ui -> pkg_a -> pkg_b")
+        );
+        assert_eq!(depth.dependency.as_deref(), Some("shared_ui"));
+
+        config.package_types[0].max_depth = Some(3);
+        assert!(all[0].validate(&config, &Workspace::new(&all)).is_empty());
+    }
+
+    #[test]
+    fn self_and_duplicate_dependencies() {
+        let all = vec![
+            Pubspec::test("app_foo")
+                .with_deps(vec![
+                    Pubspec::local("app_foo", "../app_foo"),
+                    Pubspec::local("pkg_core", "../pkg_core"),
+                    Pubspec::local("core", "../pkg_core"),
+                ])
+                .with_local_dev_deps(&["pkg_core"]),
+            Pubspec::test("pkg_core"),
+        ];
+        let config = base_config();
+
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(
+            errors[1].error,
+            "dependencies 'pkg_core' and 'core' refer to the same package 'pkg_core'"
+        );
+        assert_eq!(
+            codes(errors),
+            vec![
+                ValidationType::SelfDependency,
+                ValidationType::DuplicateDependency
+            ]
+        );
     }
 
-    fn pkg(name: &str, path: &str) -> Pubspec {
-        return Pubspec {
-            name: name.to_owned(),
-            path: format!("{}/pubspec.yaml", path),
-            dir_name: name.to_owned(),
-            dir_path: path.to_owned(),
-            dependencies: Vec::new(),
-            dev_dependencies: Vec::new(),
-            is_public: false,
+    #[test]
+    fn dependency_outside_workspace() {
+        let all = vec![
+            Pubspec::test_at("app_foo", "/tmp/ws/apps/app_foo")
+                .with_deps(vec![Pubspec::local("pkg_other", "../../other/pkg_other")])
+                .with_dev_deps(vec![Pubspec::local("pkg_core", "../../packages/pkg_core")]),
+            Pubspec::test_at("pkg_core", "/tmp/ws/packages/pkg_core"),
+        ];
+        let config = Config {
+            roots: vec!["/tmp/ws/apps".to_owned(), "/tmp/ws/packages".to_owned()],
+            ..base_config()
         };
-    }
 
-    fn codes(validations: Vec<PackageValidation>) -> Vec<ValidationType> {
-        return validations.into_iter().map(|v| v.code).collect();
+        let errors = all[0].validate(&config, &Workspace::new(&all));
+
+        assert_eq!(codes(errors), vec![ValidationType::OutsideWorkspace]);
+
+        // without any roots every dependency is considered inside
+        let errors = all[0].validate(&base_config(), &Workspace::new(&all));
+        assert_eq!(codes(errors), vec![ValidationType::UnknownDependency]);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn empty_dependencies() {
-        let config = base_config();
-        let all = vec![pkg("test", "/tmp/test")];
+    fn load_symlinked_pubspecs() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join(format!("flcheck-symlinks-{}", std::process::id()));
+        let packages = root.join("packages");
+        let apps = root.join("apps");
+        std::fs::create_dir_all(packages.join("pkg_core")).unwrap();
+        std::fs::create_dir_all(&apps).unwrap();
+        std::fs::write(
+            packages.join("pkg_core").join("pubspec.yaml"),
+            "name: pkg_core\n",
+        )
+        .unwrap();
+
+        // symlinked package and a symbolic link loop
+        symlink(packages.join("pkg_core"), apps.join("pkg_core")).unwrap();
+        symlink(&apps, apps.join("loop")).unwrap();
+
+        // pubspec that cannot be parsed
+        std::fs::create_dir_all(packages.join("pkg_broken")).unwrap();
+        std::fs::write(
+            packages.join("pkg_broken").join("pubspec.yaml"),
+            "name: [pkg_broken\n",
+        )
+        .unwrap();
+
+        let roots = vec![root.to_str().unwrap().to_owned()];
+        let names = |follow: bool| {
+            load_pubspecs(&roots, &Config::default(), follow)
+                .unwrap()
+                .into_iter()
+                .map(|pubspec| pubspec.name)
+                .collect::<Vec<_>>()
+        };
 
-        let errors = all[0].validate(&config, &all);
-        assert_eq!(errors.len(), 0);
+        assert_eq!(names(false), vec!["pkg_core"]);
+        assert_eq!(names(true), vec!["pkg_core"]);
+
+        let skipped = |follow: bool| {
+            let mut paths = discover_pubspecs(&roots, &Config::default(), follow, None)
+                .unwrap()
+                .skipped
+                .into_iter()
+                .map(|skipped| skipped.path.rsplit('/').nth(1).unwrap_or("").to_owned())
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(skipped(false), vec!["pkg_broken"]);
+        assert_eq!(skipped(true), vec!["apps", "pkg_broken"]);
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
     #[test]
-    fn multiple_packages() {
-        let config = base_config();
-        let all = vec![
-            pkg("foo", "/tmp/foo"),
-            pkg("bar", "/tmp/bar"),
-            pkg("ham", "/tmp/ham"),
-            pkg("eggs", "/tmp/eggs"),
-        ];
-
-        for pkg in all.iter() {
-            let errors = pkg.validate(&config, &all);
-            assert_eq!(errors.len(), 0);
+    fn duplicate_package_names() {
+        let root = std::env::temp_dir().join(format!("flcheck-duplicates-{}", std::process::id()));
+        for dir in ["apps/foo", "packages/foo", "packages/foo/example"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("pubspec.yaml"), "name: foo").unwrap();
         }
+
+        let roots = vec![root.to_str().unwrap().to_owned()];
+        let config = Config {
+            blacklist: vec![Regex::new("/example").unwrap()],
+            ..Config::default()
+        };
+        let all = load_pubspecs(&roots, &config, false).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // all packages are loaded, the blacklisted example is no duplicate
+        let path = |dir: &str| format!("{}/{}/pubspec.yaml", root.display(), dir);
+        let errors = |dir: &str| {
+            let pubspec = all
+                .iter()
+                .find(|pubspec| pubspec.path == path(dir))
+                .unwrap();
+            pubspec
+                .validate(&config, &Workspace::new(&all))
+                .into_iter()
+                .filter(|validation| validation.code == ValidationType::DuplicatePackage)
+                .map(|validation| validation.error)
+                .collect::<Vec<_>>()
+        };
+        let used_by = |dir: &str| {
+            vec![format!(
+                "package name 'foo' is used by {} as well",
+                path(dir)
+            )]
+        };
+        assert_eq!(all.len(), 3);
+        assert_eq!(errors("apps/foo"), used_by("packages/foo"));
+        assert_eq!(errors("packages/foo"), used_by("apps/foo"));
+        assert!(errors("packages/foo/example").is_empty());
     }
 
     #[test]
-    fn git_dependency() {
-        let config = base_config();
-        let all = vec![Pubspec {
-            dependencies: vec![Dependency::Git {
-                name: "git".to_owned(),
-                git: "git://repo".to_owned(),
-                path: "".to_owned(),
-                overridden: Box::new(None),
-            }],
-            ..pkg("foo", "/tmp/foo")
-        }];
+    fn hidden_root_directory() {
+        // the default root directory '.' is hidden by name itself
+        let root = std::env::temp_dir().join(format!(".flcheck-hidden-{}", std::process::id()));
+        for dir in ["pkg_a", ".dart_tool/pkg_b"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(
+                root.join(dir).join("pubspec.yaml"),
+                format!("name: {}", file_name(dir).unwrap()),
+            )
+            .unwrap();
+        }
 
-        let errors = all[0].validate(&config, &all);
-        assert_eq!(errors.len(), 0);
+        let roots = vec![root.to_str().unwrap().to_owned()];
+        let pubspecs = load_pubspecs(&roots, &Config::default(), false).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let names = pubspecs
+            .iter()
+            .map(|pubspec| pubspec.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["pkg_a"]);
     }
 
     #[test]
-    fn unknown_dependency() {
-        let config = base_config();
-        let all = vec![Pubspec {
-            dependencies: vec![Dependency::Local {
-                name: "bar".to_owned(),
-                path: "../bar".to_owned(),
-                overridden: Box::new(None),
-            }],
-            ..pkg("foo", "/tmp/foo")
-        }];
+    fn canonical_order() {
+        let root = std::env::temp_dir().join(format!("flcheck-order-{}", std::process::id()));
+        for (dir, content) in [
+            (
+                "pkg_b",
+                "name: pkg_b\ndependencies:\n  pkg_c: ^1.0.0\n  pkg_a: ^1.0.0",
+            ),
+            ("pkg_a-ui", "name: pkg_a_ui"),
+            (
+                "pkg_a",
+                "name: pkg_a\ndev_dependencies:\n  test: any\n  mockito: any",
+            ),
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            std::fs::write(root.join(dir).join("pubspec.yaml"), content).unwrap();
+        }
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
+        let roots = vec![root.to_str().unwrap().to_owned()];
+        let pubspecs = load_pubspecs(&roots, &Config::default(), false).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
 
-        assert_eq!(error_codes, vec![ValidationType::UnknownDependency]);
+        let names = |deps: &[Dependency]| {
+            deps.iter()
+                .map(|dep| dep.name().clone())
+                .collect::<Vec<_>>()
+        };
+        let packages = pubspecs
+            .iter()
+            .map(|pubspec| pubspec.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(packages, vec!["pkg_a", "pkg_a_ui", "pkg_b"]);
+        assert_eq!(
+            names(&pubspecs[0].dev_dependencies),
+            vec!["mockito", "test"]
+        );
+        assert_eq!(names(&pubspecs[2].dependencies), vec!["pkg_a", "pkg_c"]);
     }
 
     #[test]
-    fn unconfigured_dependency() {
-        let config = base_config();
+    fn missing_required_dependency() {
+        let mut config = base_config();
+        config.package_types[0].requires = vec!["shared_logging".to_owned()];
+
         let all = vec![
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "bar".to_owned(),
-                    path: "../bar".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("foo", "/tmp/foo")
-            },
-            Pubspec {
-                dependencies: vec![],
-                ..pkg("bar", "/tmp/bar")
-            },
+            Pubspec::test("app_foo").with_local_deps(&["shared_logging"]),
+            Pubspec::test("app_bar").with_local_dev_deps(&["shared_logging"]),
+            Pubspec::test_at("shared_logging", "/tmp/shared_logging"),
         ];
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
+        assert_eq!(
+            codes(all[0].validate(&config, &Workspace::new(&all))),
+            vec![]
+        );
+        assert_eq!(
+            codes(all[1].validate(&config, &Workspace::new(&all))),
+            vec![ValidationType::MissingRequiredDependency]
+        );
+        assert_eq!(
+            codes(all[2].validate(&config, &Workspace::new(&all))),
+            vec![]
+        );
+    }
 
-        assert_eq!(error_codes, vec![ValidationType::DependencyNotAllowed]);
+    #[test]
+    fn parse_plugin_platforms() {
+        let yaml = YamlLoader::load_from_str(
+            "
+flutter:
+  plugin:
+    platforms:
+      android:
+        package: com.example.camera
+        pluginClass: CameraPlugin
+      ios:
+        ffiPlugin: true
+      web:
+        pluginClass: CameraWeb
+        fileName: camera_web.dart
+      linux:
+        dartPluginClass: CameraLinux
+        pluginClass: none
+",
+        )
+        .unwrap()
+        .remove(0);
+
+        let native = |name: &str, native| PluginPlatform {
+            name: name.to_owned(),
+            native,
+        };
+
+        assert_eq!(
+            plugin_platforms(&yaml),
+            vec![
+                native("android", true),
+                native("ios", true),
+                native("web", true),
+                native("linux", false),
+            ]
+        );
     }
 
     #[test]
-    fn basic_dependency() {
-        let config = base_config();
+    fn native_plugin_dependency() {
+        let mut config = base_config();
+        config.package_types[2].pure_dart = true;
+
+        let platform = |name: &str, native| PluginPlatform {
+            name: name.to_owned(),
+            native,
+        };
         let all = vec![
+            Pubspec::test("pkg_domain").with_local_deps(&["pkg_camera", "pkg_share"]),
             Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "pkg_bar".to_owned(),
-                    path: "../pkg_bar".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("app_foo", "/tmp/app_foo")
+                platforms: vec![platform("android", true), platform("web", false)],
+                assets: Vec::new(),
+                package_type: None,
+                owner: None,
+                tags: Vec::new(),
+                ..Pubspec::test_at("pkg_camera", "/tmp/pkg_camera")
             },
             Pubspec {
-                dependencies: vec![],
-                ..pkg("pkg_bar", "/tmp/pkg_bar")
+                platforms: vec![platform("linux", false)],
+                assets: Vec::new(),
+                package_type: None,
+                owner: None,
+                tags: Vec::new(),
+                ..Pubspec::test_at("pkg_share", "/tmp/pkg_share")
             },
+            Pubspec::test("shared_ui").with_local_deps(&["pkg_camera"]),
         ];
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
-
-        assert_eq!(error_codes, Vec::new());
+        let validations = all[0].validate(&config, &Workspace::new(&all));
+        assert_eq!(
+            codes(validations),
+            vec![ValidationType::NativePluginDependency]
+        );
+        assert_eq!(
+            codes(all[3].validate(&config, &Workspace::new(&all))),
+            vec![]
+        );
     }
 
     #[test]
-    fn unallowed_dependency() {
-        let config = base_config();
-        let all = vec![
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "app_bar".to_owned(),
-                    path: "../app_bar".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("pkg_foo", "/tmp/pkg_foo")
-            },
-            Pubspec {
-                dependencies: vec![],
-                ..pkg("app_bar", "/tmp/app_bar")
-            },
-        ];
+    fn missing_assets() {
+        let root = std::env::temp_dir().join(format!("flcheck-assets-{}", std::process::id()));
+        let pkg_dir = root.join("app_foo");
+        std::fs::create_dir_all(pkg_dir.join("assets").join("images")).unwrap();
+        std::fs::create_dir_all(pkg_dir.join("fonts")).unwrap();
+        std::fs::write(pkg_dir.join("assets").join("logo.png"), "").unwrap();
+        std::fs::write(pkg_dir.join("fonts").join("Roboto.ttf"), "").unwrap();
+        std::fs::write(
+            pkg_dir.join("pubspec.yaml"),
+            "
+name: app_foo
+flutter:
+  assets:
+    - assets/logo.png
+    - assets/images/
+    - assets/missing.png
+    - path: assets/icons/
+      flavors: [staging]
+    - packages/shared_ui/logo.png
+  fonts:
+    - family: Roboto
+      fonts:
+        - asset: fonts/Roboto.ttf
+        - asset: fonts/Roboto-Bold.ttf
+          weight: 700
+",
+        )
+        .unwrap();
+
+        let pubspec = Pubspec::load(pkg_dir.join("pubspec.yaml").to_str().unwrap()).unwrap();
+        let errors = pubspec
+            .validate(&base_config(), &Workspace::new(&[]))
+            .into_iter()
+            .map(|validation| validation.error)
+            .collect::<Vec<_>>();
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
+        assert_eq!(
+            errors,
+            vec![
+                "asset 'assets/missing.png' does not exist",
+                "asset 'assets/icons/' does not exist",
+                "asset 'fonts/Roboto-Bold.ttf' does not exist",
+            ]
+        );
 
-        assert_eq!(error_codes, vec![ValidationType::DependencyNotAllowed]);
+        // disabled validations do not touch the file system at all
+        let disabled = Config {
+            validations: vec![ValidationSetting {
+                validation_type: ValidationType::MissingAsset,
+                level: ValidationLevel::None,
+                paths: Vec::new(),
+            }],
+            ..base_config()
+        };
+        assert!(pubspec.missing_assets(&disabled).is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
     #[test]
-    fn cyclic_dependency() {
-        let config = base_config();
-        let all = vec![
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "pkg_bar".to_owned(),
-                    path: "../pkg_bar".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("pkg_foo", "/tmp/pkg_foo")
-            },
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "pkg_foo".to_owned(),
-                    path: "../pkg_foo".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("pkg_bar", "/tmp/pkg_bar")
-            },
-        ];
+    fn missing_tests() {
+        let mut config = base_config();
+        config.package_types[2].require_tests = true;
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
+        let root = std::env::temp_dir().join(format!("flcheck-tests-{}", std::process::id()));
+        let pkg_dir = root.join("pkg_foo");
+        std::fs::create_dir_all(pkg_dir.join("test").join("src")).unwrap();
+        std::fs::write(pkg_dir.join("test").join("helpers.dart"), "").unwrap();
+        let pubspec = Pubspec::test_at("pkg_foo", pkg_dir.to_str().unwrap());
 
-        assert_eq!(error_codes, vec![ValidationType::CyclicDependency]);
+        let errors = pubspec.validate(&config, &Workspace::new(&[]));
+        assert_eq!(codes(errors), vec![ValidationType::MissingTests]);
+
+        std::fs::write(pkg_dir.join("test").join("src").join("foo_test.dart"), "").unwrap();
+        assert!(pubspec.validate(&config, &Workspace::new(&[])).is_empty());
+
+        // other package types do not require tests
+        let app = Pubspec::test_at("app_foo", root.join("app_foo").to_str().unwrap());
+        assert!(app.validate(&config, &Workspace::new(&[])).is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
     #[test]
-    fn cyclic_and_unallowed_dependency() {
-        let config = base_config();
-        let all = vec![
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "app_bar".to_owned(),
-                    path: "../app_bar".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("app_foo", "/tmp/app_foo")
-            },
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "app_foo".to_owned(),
-                    path: "../app_foo".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("app_bar", "/tmp/app_bar")
-            },
-        ];
+    fn missing_structure() {
+        let mut config = base_config();
+        config.structure.insert(
+            "package".to_owned(),
+            vec!["README.md".to_owned(), "lib/src/".to_owned()],
+        );
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
+        let root = std::env::temp_dir().join(format!("flcheck-structure-{}", std::process::id()));
+        let pkg_dir = root.join("pkg_foo");
+        std::fs::create_dir_all(pkg_dir.join("lib")).unwrap();
+        // a file does not satisfy a required directory
+        std::fs::write(pkg_dir.join("lib").join("src"), "").unwrap();
+        let pubspec = Pubspec::test_at("pkg_foo", pkg_dir.to_str().unwrap());
 
+        let errors = pubspec
+            .validate(&config, &Workspace::new(&[]))
+            .into_iter()
+            .map(|validation| validation.error)
+            .collect::<Vec<_>>();
         assert_eq!(
-            error_codes,
+            errors,
             vec![
-                ValidationType::DependencyNotAllowed,
-                ValidationType::CyclicDependency
+                "required file 'README.md' does not exist",
+                "required directory 'lib/src/' does not exist",
             ]
         );
+
+        std::fs::remove_file(pkg_dir.join("lib").join("src")).unwrap();
+        std::fs::create_dir_all(pkg_dir.join("lib").join("src")).unwrap();
+        std::fs::write(pkg_dir.join("README.md"), "").unwrap();
+        assert!(pubspec.validate(&config, &Workspace::new(&[])).is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
     }
 
     #[test]
-    fn cyclic_dev_dependencies() {
-        let config = base_config();
+    fn analysis_options() {
+        let mut config = base_config();
+        config.analysis_options = Some("package:company_lints/analysis_options.yaml".to_owned());
+
+        let root = std::env::temp_dir().join(format!("flcheck-analysis-{}", std::process::id()));
+        let pkg_dir = root.join("pkg_foo");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        let pubspec = Pubspec::test_at("pkg_foo", pkg_dir.to_str().unwrap());
+        let validate = |content: Option<&str>| {
+            if let Some(content) = content {
+                std::fs::write(pkg_dir.join("analysis_options.yaml"), content).unwrap();
+            }
+            pubspec
+                .validate(&config, &Workspace::new(&[]))
+                .into_iter()
+                .map(|validation| (validation.error, validation.line))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            validate(None),
+            vec![("missing analysis_options.yaml".to_owned(), None)]
+        );
+        assert_eq!(
+            validate(Some("linter:\n  rules: []\ninclude: package:flutter_lints/flutter.yaml\n")),
+            vec![(
+                "analysis_options.yaml includes 'package:flutter_lints/flutter.yaml' instead of 'package:company_lints/analysis_options.yaml'".to_owned(),
+                Some(3)
+            )]
+        );
+        assert!(validate(Some(
+            "include: package:company_lints/analysis_options.yaml\n"
+        ))
+        .is_empty());
+        assert!(validate(Some(
+            "include:\n  - package:company_lints/analysis_options.yaml\n  - extra.yaml\n"
+        ))
+        .is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn public_changelog() {
+        let root = std::env::temp_dir().join(format!("flcheck-changelog-{}", std::process::id()));
+        let pkg_dir = root.join("pkg_foo");
+        std::fs::create_dir_all(&pkg_dir).unwrap();
+        let pubspec = Pubspec {
+            is_public: true,
+            version: Some("1.2.0".to_owned()),
+            ..Pubspec::test_at("pkg_foo", pkg_dir.to_str().unwrap())
+        };
+        let validate = |content: Option<&str>| {
+            if let Some(content) = content {
+                std::fs::write(pkg_dir.join("CHANGELOG.md"), content).unwrap();
+            }
+            pubspec
+                .validate(&base_config(), &Workspace::new(&[]))
+                .into_iter()
+                .map(|validation| validation.error)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(validate(None), vec!["missing CHANGELOG.md"]);
+        assert_eq!(
+            validate(Some(
+                "# Changelog\n\n## 1.2.0-dev\n\n- 1.2.0 soon\n\n## 1.1.0\n"
+            )),
+            vec!["CHANGELOG.md has no entry of version '1.2.0'"]
+        );
+        assert!(validate(Some("## [1.2.0] - 2024-01-31\n")).is_empty());
+        assert!(validate(Some("# v1.2.0\n")).is_empty());
+
+        // private packages need no changelog
+        std::fs::remove_file(pkg_dir.join("CHANGELOG.md")).unwrap();
+        let private = Pubspec {
+            is_public: false,
+            ..pubspec.clone()
+        };
+        assert!(private
+            .validate(&base_config(), &Workspace::new(&[]))
+            .is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn alternative_packages() {
+        let config = Config {
+            alternatives: vec![
+                AlternativeGroup {
+                    name: "http-clients".to_owned(),
+                    packages: vec!["dio".to_owned(), "http".to_owned(), "chopper".to_owned()],
+                },
+                AlternativeGroup {
+                    name: "mocks".to_owned(),
+                    packages: vec!["mockito".to_owned(), "mocktail".to_owned()],
+                },
+            ],
+            ..base_config()
+        };
         let all = vec![
-            Pubspec {
-                dependencies: vec![Dependency::Local {
-                    name: "pkg_bar".to_owned(),
-                    path: "../pkg_bar".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("pkg_foo", "/tmp/pkg_foo")
-            },
-            Pubspec {
-                dev_dependencies: vec![Dependency::Local {
-                    name: "pkg_foo".to_owned(),
-                    path: "../pkg_foo".to_owned(),
-                    overridden: Box::new(None),
-                }],
-                ..pkg("pkg_bar", "/tmp/pkg_bar")
-            },
+            Pubspec::test("app_foo")
+                .with_deps(vec![Pubspec::hosted("http", "any")])
+                .with_dev_deps(vec![Pubspec::hosted("mocktail", "any")]),
+            Pubspec::test("pkg_core").with_deps(vec![Pubspec::hosted("dio", "any")]),
+            Pubspec::test("pkg_ui")
+                .with_deps(vec![Pubspec::hosted("dio", "any")])
+                .with_dev_deps(vec![Pubspec::hosted("mocktail", "any")]),
         ];
+        let findings = |idx: usize| {
+            all[idx]
+                .validate(&config, &Workspace::new(&all))
+                .into_iter()
+                .filter(|validation| validation.code == ValidationType::AlternativePackages)
+                .map(|validation| (validation.level, validation.error))
+                .collect::<Vec<_>>()
+        };
 
-        let errors = all[0].validate(&config, &all);
-        let error_codes = codes(errors);
+        assert_eq!(
+            findings(0),
+            vec![(
+                ValidationLevel::Warning,
+                "dependency 'http' has alternatives used in the workspace: dio (pkg_core, pkg_ui)"
+                    .to_owned()
+            )]
+        );
+        assert_eq!(
+            findings(1),
+            vec![(
+                ValidationLevel::Warning,
+                "dependency 'dio' has alternatives used in the workspace: http (app_foo)"
+                    .to_owned()
+            )]
+        );
+    }
 
-        assert_eq!(error_codes, vec![ValidationType::CyclicDependency]);
+    #[test]
+    fn dependency_lines() {
+        let content = r#"name: app_foo
+dependencies:
+  # local packages
+  pkg_core:
+    path: ../pkg_core
+  path: ^1.8.0
+
+dev_dependencies:
+  test: any
+flutter:
+  assets:
+    - test:
+"#;
+
+        assert_eq!(dependency_line(content, "pkg_core"), Some(4));
+        assert_eq!(dependency_line(content, "path"), Some(6));
+        assert_eq!(dependency_line(content, "test"), Some(9));
+        assert_eq!(dependency_line(content, "pkg"), None);
+        assert_eq!(dependency_line(content, "name"), None);
+    }
+
+    #[test]
+    fn malformed_dependencies() {
+        let yaml = YamlLoader::load_from_str(
+            "
+version: 1.0
+dependencies:
+  flutter:
+    sdk: flutter
+  http: ^1.2.0
+  collection:
+  pkg_core:
+    path: ../pkg_core
+  pkg_shared:
+    git: git@github.com:company/shared.git
+  pkg_utils:
+    git:
+      ref: main
+  intl: 0.18
+  pkg_typo:
+    paht: ../pkg_typo
+dev_dependencies: [lints]
+",
+        )
+        .unwrap()
+        .remove(0);
+
+        let problems = schema_problems(&yaml)
+            .into_iter()
+            .map(|problem| (problem.dependency, problem.message))
+            .collect::<Vec<_>>();
+
+        let problem = |dependency: Option<&str>, message: &str| {
+            (dependency.map(|name| name.to_owned()), message.to_owned())
+        };
+
+        assert_eq!(
+            problems,
+            vec![
+                problem(None, "missing package name"),
+                problem(None, "version must be a string (e.g. '1.0.0')"),
+                problem(Some("pkg_utils"), "git dependency without url"),
+                problem(Some("intl"), "version constraint must be a string"),
+                problem(
+                    Some("pkg_typo"),
+                    "unknown dependency shape (expected path, git, hosted, sdk or version)"
+                ),
+                problem(None, "'dev_dependencies' must be a mapping"),
+            ]
+        );
+
+        // git dependencies default to the repository root
+        assert!(get_dependencies(&yaml).iter().any(|dep| matches!(
+            dep,
+            Dependency::Git { name, path, .. } if name == "pkg_shared" && path.is_empty()
+        )));
     }
 }
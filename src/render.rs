@@ -0,0 +1,1708 @@
+use crate::bump::BumpReport;
+use crate::command::CheckReport;
+use crate::command::ExternalReport;
+use crate::dependency::Dependency;
+use crate::dependency::DependencyAnnotation;
+use crate::diff::FindingsDiff;
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::findings::FindingsFormat;
+use crate::findings::FindingsRenderer;
+use crate::findings::PathStyle;
+use crate::graph::Graph;
+use crate::lint::ConfigProblem;
+use crate::list::PackageEntry;
+use crate::overrides::OverrideEntry;
+use crate::report::GroupCount;
+use crate::report::Report;
+use crate::report::ReportContent;
+use crate::report::ReportFormat;
+use crate::report::Summary;
+use crate::rules::Rule;
+use crate::snapshot::SnapshotDependency;
+use crate::snapshot::SnapshotDrift;
+use crate::stats::DependencyCounts;
+use crate::stats::Stats;
+use crate::stats::UNKNOWN_PACKAGE_TYPE;
+use crate::suggest::IncludeSuggestion;
+use crate::tree::TreeMarker;
+use crate::tree::TreeNode;
+use crate::Pubspec;
+use std::collections::HashSet;
+use std::io;
+use std::io::IsTerminal;
+use std::io::Write;
+
+#[derive(PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Json,
+    Yaml,
+    Toml,
+    Checkstyle,
+    Rdjson,
+}
+
+/// Renderer of the results of all commands.
+///
+/// Library users may implement this trait in order to supply
+/// their own output format.
+pub trait Renderer {
+    /// Render the validation findings of all packages.
+    fn render_validations(&mut self, validations: &[PackageValidation]) -> io::Result<()>;
+
+    /// Render the validation findings grouped by the owners of their
+    /// packages (the findings are sorted by owner already).
+    fn render_validations_by_owner(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        self.render_validations(validations)
+    }
+
+    /// Render the validation findings of several repositories grouped by
+    /// their repository (the findings are sorted by repository already).
+    fn render_validations_by_repo(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        self.render_validations(validations)
+    }
+
+    /// Render the dump of all (loaded) packages.
+    fn render_dump(&mut self, pubspecs: &[Pubspec]) -> io::Result<()>;
+
+    /// Render the external dependency versions report.
+    fn render_check(&mut self, report: &CheckReport) -> io::Result<()>;
+
+    /// Render the external dependencies and the packages using them.
+    fn render_check_by_package(&mut self, report: &ExternalReport) -> io::Result<()>;
+
+    /// Render the package dependency graph.
+    fn render_graph(&mut self, graph: &Graph) -> io::Result<()>;
+
+    /// Render the workspace statistics.
+    fn render_stats(&mut self, stats: &Stats) -> io::Result<()>;
+
+    /// Render the dependency trees of the given root packages.
+    fn render_tree(&mut self, trees: &[TreeNode]) -> io::Result<()>;
+
+    /// Render the list of all validation rules.
+    fn render_rules(&mut self, rules: &[Rule]) -> io::Result<()>;
+
+    /// Render the detailed explanation of a single validation rule.
+    fn render_rule(&mut self, rule: &Rule) -> io::Result<()>;
+
+    /// Render the problems found in the configuration.
+    fn render_config_problems(&mut self, problems: &[ConfigProblem]) -> io::Result<()>;
+
+    /// Render the waves of packages in the order they can be published.
+    fn render_publish_order(&mut self, waves: &[Vec<String>]) -> io::Result<()>;
+
+    /// Render the constraints affected by a version bump.
+    fn render_bump(&mut self, report: &BumpReport) -> io::Result<()>;
+
+    /// Render the includes to add to the package types' configuration.
+    fn render_include_suggestions(&mut self, suggestions: &[IncludeSuggestion]) -> io::Result<()>;
+
+    /// Render the comparison of the findings of two validation runs.
+    fn render_diff(&mut self, diff: &FindingsDiff) -> io::Result<()>;
+
+    /// Render the differences between the snapshot and the workspace.
+    fn render_snapshot_drift(&mut self, drift: &SnapshotDrift) -> io::Result<()>;
+
+    /// Render the (filtered) packages of the workspace.
+    fn render_packages(&mut self, packages: &[PackageEntry]) -> io::Result<()>;
+
+    /// Render the active dependency overrides of the workspace.
+    fn render_overrides(&mut self, overrides: &[OverrideEntry]) -> io::Result<()>;
+}
+
+/// Create the `Renderer` for the given `OutputFormat` writing to stdout.
+///
+/// The `color` flag enables colorized output of the plain renderer, the
+/// `path_style` applies to the checkstyle and rdjson output.
+pub fn renderer(format: &OutputFormat, color: bool, path_style: PathStyle) -> Box<dyn Renderer> {
+    match format {
+        OutputFormat::Plain => Box::new(PlainRenderer::new(io::stdout()).with_color(color)),
+        OutputFormat::Json => Box::new(ReportRenderer::new(io::stdout())),
+        OutputFormat::Yaml => {
+            Box::new(ReportRenderer::new(io::stdout()).with_format(ReportFormat::Yaml))
+        }
+        OutputFormat::Toml => {
+            Box::new(ReportRenderer::new(io::stdout()).with_format(ReportFormat::Toml))
+        }
+        OutputFormat::Checkstyle => Box::new(
+            FindingsRenderer::new(io::stdout(), FindingsFormat::Checkstyle)
+                .with_path_style(path_style),
+        ),
+        OutputFormat::Rdjson => Box::new(
+            FindingsRenderer::new(io::stdout(), FindingsFormat::Rdjson).with_path_style(path_style),
+        ),
+    }
+}
+
+/// Whether colorized output should be used: colors are disabled if
+/// requested explicitly, via the `NO_COLOR` environment variable or
+/// if stdout is not a terminal.
+pub fn use_color(no_color: bool) -> bool {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+    !no_color && !no_color_env && io::stdout().is_terminal()
+}
+
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const GREEN: &str = "1;32";
+
+/// Human readable (plain text) output.
+pub struct PlainRenderer<W: Write> {
+    out: W,
+    color: bool,
+}
+
+impl<W: Write> PlainRenderer<W> {
+    pub fn new(out: W) -> PlainRenderer<W> {
+        PlainRenderer { out, color: false }
+    }
+
+    /// Return a copy with colorized output enabled or disabled.
+    pub fn with_color(self, color: bool) -> Self {
+        PlainRenderer { color, ..self }
+    }
+
+    /// Wrap the given `text` in the ANSI escape sequences of `style`
+    /// (if colors are enabled).
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", style, text)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn level(&self, level: &ValidationLevel) -> String {
+        let padded = format!("{:<5}", level.to_string());
+        match level {
+            ValidationLevel::Error => self.paint(&padded, RED),
+            ValidationLevel::Warning => self.paint(&padded, YELLOW),
+            ValidationLevel::None => padded,
+        }
+    }
+
+    /// Print the given findings one per line (including the package name)
+    /// aligned to the given column widths.
+    fn finding_rows(
+        &mut self,
+        findings: &[&PackageValidation],
+        package_width: usize,
+        width: usize,
+    ) -> io::Result<()> {
+        for finding in findings {
+            writeln!(
+                self.out,
+                "  {}  {:<package_width$}  {:<width$}  {}",
+                self.level(&finding.level),
+                finding.package_name,
+                finding.error,
+                self.paint(&finding.code.to_string(), DIM),
+                package_width = package_width,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Print the number of the given (visible) findings by validation type
+    /// and package type followed by the summary line.
+    fn validation_summary(
+        &mut self,
+        visible: &[&PackageValidation],
+        num_packages: usize,
+    ) -> io::Result<()> {
+        let summary = Summary::of_findings(visible);
+
+        self.group_counts("validation type", &summary.by_code)?;
+        self.group_counts("package type", &summary.by_package_type)?;
+
+        writeln!(
+            self.out,
+            "{}, {} in {}",
+            plural(summary.errors, "error"),
+            plural(summary.warnings, "warning"),
+            plural(num_packages, "package")
+        )
+    }
+
+    /// Print a table of the given finding counts (if any).
+    fn group_counts(&mut self, title: &str, counts: &[GroupCount]) -> io::Result<()> {
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let names = counts.iter().map(|count| count.name.as_str());
+        let width = column_width(names.chain([title]));
+        let header = format!("{:<width$}  errors  warnings", title, width = width);
+        writeln!(self.out, "{}", self.paint(&header, DIM))?;
+        for count in counts {
+            writeln!(
+                self.out,
+                "{:<width$}  {:>6}  {:>8}",
+                count.name,
+                count.errors,
+                count.warnings,
+                width = width
+            )?;
+        }
+        writeln!(self.out)
+    }
+
+    fn dependencies(
+        &mut self,
+        title: &str,
+        deps: &[Dependency],
+        annotations: &[DependencyAnnotation],
+    ) -> io::Result<()> {
+        if deps.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(self.out, "  {}:", title)?;
+
+        let width = column_width(deps.iter().map(|dep| dep.name().as_str()));
+        for dep in deps {
+            writeln!(
+                self.out,
+                "    {:<width$}  {}",
+                dep.name(),
+                dep.details(),
+                width = width
+            )?;
+
+            if let Some(override_dependency) = dep.overridden() {
+                writeln!(
+                    self.out,
+                    "    {:<width$}  {} {}",
+                    "",
+                    self.paint("override:", DIM),
+                    override_dependency.details(),
+                    width = width
+                )?;
+            }
+
+            let reason = annotations
+                .iter()
+                .find(|annotation| annotation.dependency == *dep.name())
+                .and_then(|annotation| annotation.reason.as_ref());
+            if let Some(reason) = reason {
+                writeln!(
+                    self.out,
+                    "    {:<width$}  {} {}",
+                    "",
+                    self.paint("reason:", DIM),
+                    reason,
+                    width = width
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print all children of the tree `node` indented by `prefix`.
+    fn tree_children(&mut self, node: &TreeNode, prefix: &str) -> io::Result<()> {
+        for (idx, child) in node.children.iter().enumerate() {
+            let last = idx + 1 == node.children.len();
+            let branch = if last { "└── " } else { "├── " };
+
+            let mut line = child.name.clone();
+            if let Some(details) = &child.details {
+                line = format!("{} {}", line, self.paint(details, DIM));
+            }
+            let marker = match child.marker {
+                Some(TreeMarker::Duplicate) => Some(("(*)", DIM)),
+                Some(TreeMarker::Cycle) => Some(("(cycle)", YELLOW)),
+                Some(TreeMarker::Unresolved) => Some(("(unresolved)", RED)),
+                None => None,
+            };
+            if let Some((text, style)) = marker {
+                line = format!("{} {}", line, self.paint(text, style));
+            }
+            writeln!(self.out, "{}{}{}", prefix, branch, line)?;
+
+            let indent = if last { "    " } else { "│   " };
+            self.tree_children(child, &format!("{}{}", prefix, indent))?;
+        }
+        Ok(())
+    }
+}
+
+fn dependency_counts(counts: &DependencyCounts) -> String {
+    format!(
+        "local {}, git {}, pub.dev {}",
+        counts.local, counts.git, counts.pubdev
+    )
+}
+
+/// Maximum (character) width of all given `values`.
+fn column_width<'a, I: Iterator<Item = &'a str>>(values: I) -> usize {
+    values.map(|value| value.chars().count()).max().unwrap_or(0)
+}
+
+/// Format `count` along with the (possibly pluralized) `noun`.
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+impl<W: Write> Renderer for PlainRenderer<W> {
+    fn render_validations(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        let visible = validations
+            .iter()
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect::<Vec<_>>();
+        let width = column_width(visible.iter().map(|validation| validation.error.as_str()));
+
+        let mut num_packages = 0;
+        for package in visible.chunk_by(|a, b| a.package_name == b.package_name) {
+            num_packages += 1;
+            match &package[0].owner {
+                Some(owner) => writeln!(
+                    self.out,
+                    "{} {}",
+                    self.paint(&package[0].package_name, BOLD),
+                    self.paint(&format!("({})", owner), DIM)
+                )?,
+                None => writeln!(self.out, "{}", self.paint(&package[0].package_name, BOLD))?,
+            }
+
+            // list errors before warnings of every package
+            for level in [ValidationLevel::Error, ValidationLevel::Warning] {
+                for validation in package.iter().filter(|v| v.level == level) {
+                    writeln!(
+                        self.out,
+                        "  {}  {:<width$}  {}",
+                        self.level(&validation.level),
+                        validation.error,
+                        self.paint(&validation.code.to_string(), DIM),
+                        width = width
+                    )?;
+
+                    if let Some(desc) = &validation.description {
+                        writeln!(self.out, "  {:<5}  {}", "", self.paint(desc, DIM))?;
+                    }
+                }
+            }
+            writeln!(self.out)?;
+        }
+
+        self.validation_summary(&visible, num_packages)
+    }
+
+    fn render_validations_by_owner(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        let visible = validations
+            .iter()
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect::<Vec<_>>();
+        let package_width = column_width(visible.iter().map(|v| v.package_name.as_str()));
+        let width = column_width(visible.iter().map(|validation| validation.error.as_str()));
+
+        for owned in visible.chunk_by(|a, b| a.owner == b.owner) {
+            let owner = owned[0].owner.as_deref().unwrap_or("(unowned)");
+            writeln!(self.out, "{}", self.paint(owner, BOLD))?;
+            self.finding_rows(owned, package_width, width)?;
+            writeln!(self.out)?;
+        }
+
+        let num_packages = visible
+            .iter()
+            .map(|validation| &validation.package_name)
+            .collect::<HashSet<_>>()
+            .len();
+        self.validation_summary(&visible, num_packages)
+    }
+
+    fn render_validations_by_repo(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        let visible = validations
+            .iter()
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect::<Vec<_>>();
+        let package_width = column_width(visible.iter().map(|v| v.package_name.as_str()));
+        let width = column_width(visible.iter().map(|validation| validation.error.as_str()));
+
+        for repo_findings in visible.chunk_by(|a, b| a.repo == b.repo) {
+            let repo = repo_findings[0].repo.as_deref().unwrap_or("(unknown)");
+            writeln!(self.out, "{}", self.paint(repo, BOLD))?;
+            self.finding_rows(repo_findings, package_width, width)?;
+            writeln!(self.out)?;
+        }
+
+        // equally named packages of different repositories are distinct
+        let num_packages = visible
+            .iter()
+            .map(|validation| (&validation.repo, &validation.package_name))
+            .collect::<HashSet<_>>()
+            .len();
+        self.validation_summary(&visible, num_packages)
+    }
+
+    fn render_dump(&mut self, pubspecs: &[Pubspec]) -> io::Result<()> {
+        for pubspec in pubspecs {
+            writeln!(
+                self.out,
+                "{} {}",
+                self.paint(&pubspec.name, BOLD),
+                self.paint(&format!("[{}]", pubspec.path), DIM)
+            )?;
+
+            let annotations = &pubspec.dependency_annotations;
+            self.dependencies("dependencies", &pubspec.dependencies, annotations)?;
+            self.dependencies("dev-dependencies", &pubspec.dev_dependencies, annotations)?;
+
+            if !pubspec.platforms.is_empty() {
+                let platforms = pubspec
+                    .platforms
+                    .iter()
+                    .map(|platform| {
+                        if platform.native {
+                            format!("{} (native)", platform.name)
+                        } else {
+                            platform.name.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(self.out, "  platforms: {}", platforms.join(", "))?;
+            }
+            if let Some(package_type) = &pubspec.package_type {
+                writeln!(self.out, "  type: {}", package_type)?;
+            }
+            if let Some(owner) = &pubspec.owner {
+                writeln!(self.out, "  owner: {}", owner)?;
+            }
+            if !pubspec.tags.is_empty() {
+                writeln!(self.out, "  tags: {}", pubspec.tags.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_check(&mut self, report: &CheckReport) -> io::Result<()> {
+        for package in report.packages.iter() {
+            writeln!(self.out, "{}", package.name)?;
+
+            for dep in package.dependencies.iter() {
+                let pub_version = if dep.private {
+                    "<private>"
+                } else {
+                    dep.latest.as_deref().unwrap_or("<unknown>")
+                };
+                let dev = if dep.dev { " (dev)" } else { "" };
+                writeln!(
+                    self.out,
+                    "  {}: {} [{}]{}",
+                    dep.name, dep.version, pub_version, dev
+                )?;
+
+                for pinned in dep.pinned_by.iter() {
+                    writeln!(
+                        self.out,
+                        "    pinned by {}: {}",
+                        pinned.package, pinned.version
+                    )?;
+                }
+
+                if let Some(stale) = &dep.stale {
+                    let message =
+                        format!("{} published {} days ago", stale.version, stale.age_days);
+                    writeln!(self.out, "    stale: {}", self.paint(&message, YELLOW))?;
+                }
+
+                for problem in dep.problems.iter() {
+                    writeln!(
+                        self.out,
+                        "    {} {}: {}",
+                        self.level(&problem.level),
+                        problem.rule,
+                        problem.message
+                    )?;
+                }
+            }
+        }
+
+        if !report.unknown.is_empty() {
+            writeln!(self.out)?;
+            writeln!(
+                self.out,
+                "not found on pub.dev: {}",
+                report.unknown.join(", ")
+            )?;
+        }
+
+        if let Some(probes) = &report.git {
+            writeln!(self.out)?;
+            let broken = probes
+                .iter()
+                .filter(|probe| probe.problem.is_some())
+                .collect::<Vec<_>>();
+
+            for probe in broken.iter() {
+                let git_ref = probe
+                    .git_ref
+                    .as_ref()
+                    .map(|git_ref| format!(" ({})", git_ref))
+                    .unwrap_or_default();
+                let path = probe
+                    .path
+                    .as_ref()
+                    .map(|path| format!(" {}", path))
+                    .unwrap_or_default();
+                let problem = probe.problem.map(|problem| problem.to_string());
+                let message = probe
+                    .message
+                    .as_ref()
+                    .map(|message| format!(": {}", message))
+                    .unwrap_or_default();
+                writeln!(
+                    self.out,
+                    "{}  {}  {}{}{}  {}{}",
+                    probe.package,
+                    probe.dependency,
+                    probe.url,
+                    git_ref,
+                    path,
+                    self.paint(&problem.unwrap_or_default(), RED),
+                    message
+                )?;
+            }
+            writeln!(
+                self.out,
+                "{} git dependencies probed, {} broken",
+                probes.len(),
+                broken.len()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn render_check_by_package(&mut self, report: &ExternalReport) -> io::Result<()> {
+        for package in report.packages.iter() {
+            let pub_version = if package.private {
+                "<private>"
+            } else {
+                package.latest.as_deref().unwrap_or("<unknown>")
+            };
+            let conflicting = if package.conflicting {
+                format!(" {}", self.paint("conflicting constraints", YELLOW))
+            } else {
+                String::new()
+            };
+            writeln!(
+                self.out,
+                "{} [{}]{}",
+                self.paint(&package.name, BOLD),
+                pub_version,
+                conflicting
+            )?;
+
+            for constraint in package.constraints.iter() {
+                writeln!(
+                    self.out,
+                    "  {}: {}",
+                    constraint.version,
+                    constraint.packages.join(", ")
+                )?;
+            }
+        }
+
+        if !report.unknown.is_empty() {
+            writeln!(self.out)?;
+            writeln!(
+                self.out,
+                "not found on pub.dev: {}",
+                report.unknown.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    fn render_graph(&mut self, graph: &Graph) -> io::Result<()> {
+        writeln!(self.out, "//")?;
+        writeln!(
+            self.out,
+            "// automatically generated by flcheck <https://github.com/kongo2002/flcheck>"
+        )?;
+        writeln!(self.out, "//")?;
+        writeln!(self.out, "digraph dependencies {{")?;
+        writeln!(self.out, "  ranksep =\"2.0 equally\";")?;
+
+        for node in graph.nodes.iter() {
+            writeln!(self.out, "  // {}", node)?;
+            if graph.violations.contains(node) {
+                writeln!(self.out, "  {} [color=red, fontcolor=red]", node)?;
+            } else {
+                writeln!(self.out, "  {} []", node)?;
+            }
+
+            for edge in graph.edges_from(node) {
+                let mut attributes = Vec::new();
+                if edge.dev {
+                    attributes.push("style=dotted");
+                }
+                if edge.violation {
+                    attributes.push("color=red");
+                } else if edge.overridden {
+                    attributes.push("color=blue, fontcolor=blue");
+                }
+                if edge.overridden {
+                    attributes.push("label=\"override\"");
+                }
+
+                if attributes.is_empty() {
+                    writeln!(self.out, "  {} -> {};", edge.from, edge.to)?;
+                } else {
+                    writeln!(
+                        self.out,
+                        "  {} -> {} [{}];",
+                        edge.from,
+                        edge.to,
+                        attributes.join(", ")
+                    )?;
+                }
+            }
+        }
+
+        for external in graph.externals.iter() {
+            writeln!(self.out, "  // {} (external)", external)?;
+            writeln!(self.out, "  {} [shape=box, style=dashed]", external)?;
+        }
+
+        writeln!(self.out, "}}")
+    }
+
+    fn render_stats(&mut self, stats: &Stats) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "{} {}",
+            self.paint("packages:", BOLD),
+            stats.packages
+        )?;
+
+        let width = column_width(stats.package_types.keys().map(|name| name.as_str()));
+        writeln!(self.out, "{}", self.paint("package types:", BOLD))?;
+        for (name, count) in stats.package_types.iter() {
+            writeln!(self.out, "  {:<width$}  {}", name, count, width = width)?;
+        }
+
+        writeln!(
+            self.out,
+            "{} {}",
+            self.paint("dependencies:", BOLD),
+            dependency_counts(&stats.dependencies)
+        )?;
+        writeln!(
+            self.out,
+            "{} {}",
+            self.paint("dev-dependencies:", BOLD),
+            dependency_counts(&stats.dev_dependencies)
+        )?;
+        writeln!(
+            self.out,
+            "{} {}",
+            self.paint("external packages:", BOLD),
+            stats.external_packages
+        )?;
+        writeln!(
+            self.out,
+            "{} average {:.2}, max {}",
+            self.paint("dependency depth:", BOLD),
+            stats.average_depth,
+            stats.max_depth
+        )?;
+
+        let platforms = stats
+            .platforms
+            .iter()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect::<Vec<_>>();
+        writeln!(
+            self.out,
+            "{} {}{}",
+            self.paint("plugins:", BOLD),
+            stats.plugins,
+            if platforms.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", platforms.join(", "))
+            }
+        )?;
+
+        if !stats.owners.is_empty() {
+            let width = column_width(stats.owners.keys().map(|name| name.as_str()));
+            writeln!(self.out, "{}", self.paint("owners:", BOLD))?;
+            for (name, count) in stats.owners.iter() {
+                writeln!(self.out, "  {:<width$}  {}", name, count, width = width)?;
+            }
+        }
+
+        if !stats.tags.is_empty() {
+            let tags = stats
+                .tags
+                .iter()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .collect::<Vec<_>>();
+            writeln!(
+                self.out,
+                "{} {}",
+                self.paint("tags:", BOLD),
+                tags.join(", ")
+            )?;
+        }
+
+        if !stats.most_depended_upon.is_empty() {
+            let width = column_width(
+                stats
+                    .most_depended_upon
+                    .iter()
+                    .map(|dependents| dependents.name.as_str()),
+            );
+            writeln!(self.out, "{}", self.paint("most depended upon:", BOLD))?;
+            for dependents in stats.most_depended_upon.iter() {
+                writeln!(
+                    self.out,
+                    "  {:<width$}  {}",
+                    dependents.name,
+                    dependents.dependents,
+                    width = width
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_tree(&mut self, trees: &[TreeNode]) -> io::Result<()> {
+        for (idx, tree) in trees.iter().enumerate() {
+            if idx > 0 {
+                writeln!(self.out)?;
+            }
+            writeln!(self.out, "{}", self.paint(&tree.name, BOLD))?;
+            self.tree_children(tree, "")?;
+        }
+        Ok(())
+    }
+
+    fn render_rules(&mut self, rules: &[Rule]) -> io::Result<()> {
+        let width = column_width(rules.iter().map(|rule| rule.code.as_str()));
+        for rule in rules {
+            writeln!(
+                self.out,
+                "{}  {}  {}",
+                self.paint(
+                    &format!("{:<width$}", rule.code.to_string(), width = width),
+                    BOLD
+                ),
+                self.level(&rule.level),
+                rule.summary
+            )?;
+        }
+        Ok(())
+    }
+
+    fn render_rule(&mut self, rule: &Rule) -> io::Result<()> {
+        writeln!(self.out, "{}", self.paint(&rule.code.to_string(), BOLD))?;
+        writeln!(self.out, "{}", rule.summary)?;
+        writeln!(self.out)?;
+        writeln!(
+            self.out,
+            "{} {} (default: {})",
+            self.paint("level:", BOLD),
+            rule.level,
+            rule.default_level
+        )?;
+        writeln!(self.out)?;
+        writeln!(self.out, "{}", rule.explanation)
+    }
+
+    fn render_config_problems(&mut self, problems: &[ConfigProblem]) -> io::Result<()> {
+        let width = column_width(problems.iter().map(|problem| problem.key.as_str()));
+        for problem in problems {
+            writeln!(
+                self.out,
+                "{}  {:<width$}  {}",
+                self.level(&problem.level),
+                problem.key,
+                problem.message,
+                width = width
+            )?;
+        }
+
+        if !problems.is_empty() {
+            writeln!(self.out)?;
+        }
+
+        let count = |level: ValidationLevel| problems.iter().filter(|p| p.level == level).count();
+        writeln!(
+            self.out,
+            "{}, {}",
+            plural(count(ValidationLevel::Error), "error"),
+            plural(count(ValidationLevel::Warning), "warning")
+        )
+    }
+
+    fn render_publish_order(&mut self, waves: &[Vec<String>]) -> io::Result<()> {
+        for (idx, wave) in waves.iter().enumerate() {
+            if idx > 0 {
+                writeln!(self.out)?;
+            }
+            writeln!(
+                self.out,
+                "{}",
+                self.paint(&format!("wave {}", idx + 1), BOLD)
+            )?;
+            for package in wave {
+                writeln!(self.out, "  {}", package)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_bump(&mut self, report: &BumpReport) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "{} {} -> {}",
+            self.paint(&report.package, BOLD),
+            report.version,
+            report.new_version
+        )?;
+
+        if report.outdated.is_empty() {
+            return writeln!(self.out, "all constraints match {}", report.new_version);
+        }
+
+        writeln!(self.out)?;
+        let width = column_width(report.outdated.iter().map(|c| c.package.as_str()));
+        let constraint_width = column_width(report.outdated.iter().map(|c| c.constraint.as_str()));
+        for outdated in report.outdated.iter() {
+            writeln!(
+                self.out,
+                "  {:<width$}  {:<constraint_width$}  {}{}",
+                outdated.package,
+                outdated.constraint,
+                outdated.path,
+                if outdated.dev { " (dev)" } else { "" },
+                width = width,
+                constraint_width = constraint_width
+            )?;
+        }
+
+        writeln!(self.out)?;
+        if report.written {
+            writeln!(
+                self.out,
+                "updated {}",
+                plural(report.outdated.len(), "constraint")
+            )
+        } else {
+            writeln!(
+                self.out,
+                "{} not matching {} (use --write to update)",
+                plural(report.outdated.len(), "constraint"),
+                report.new_version
+            )
+        }
+    }
+
+    fn render_include_suggestions(&mut self, suggestions: &[IncludeSuggestion]) -> io::Result<()> {
+        if suggestions.is_empty() {
+            return writeln!(self.out, "# no configuration changes required");
+        }
+
+        writeln!(self.out, "package_types:")?;
+        for suggestion in suggestions.iter() {
+            writeln!(self.out, "  {}:", suggestion.package_type)?;
+            writeln!(self.out, "    includes:")?;
+            for include in suggestion.includes.iter() {
+                writeln!(self.out, "      - {}", include)?;
+            }
+            for include in suggestion.added.iter() {
+                writeln!(
+                    self.out,
+                    "      - {}  {}",
+                    include,
+                    self.paint("# added", YELLOW)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn render_diff(&mut self, diff: &FindingsDiff) -> io::Result<()> {
+        let sections = [
+            ("new", &diff.new, RED),
+            ("fixed", &diff.fixed, BOLD),
+            ("persisting", &diff.persisting, BOLD),
+        ];
+        let all = || sections.iter().flat_map(|(_, findings, _)| findings.iter());
+        let package_width = column_width(all().map(|finding| finding.package_name.as_str()));
+        let width = column_width(all().map(|finding| finding.error.as_str()));
+
+        for (title, findings, style) in sections {
+            if findings.is_empty() {
+                continue;
+            }
+
+            writeln!(self.out, "{}", self.paint(title, style))?;
+            let findings = findings.iter().collect::<Vec<_>>();
+            self.finding_rows(&findings, package_width, width)?;
+            writeln!(self.out)?;
+        }
+
+        writeln!(
+            self.out,
+            "new {}, fixed {}, persisting {}",
+            diff.new.len(),
+            diff.fixed.len(),
+            diff.persisting.len()
+        )
+    }
+
+    fn render_snapshot_drift(&mut self, drift: &SnapshotDrift) -> io::Result<()> {
+        if drift.is_empty() {
+            return writeln!(self.out, "workspace matches the snapshot");
+        }
+
+        let dependency = |dep: &SnapshotDependency| {
+            let dev = if dep.dev { " (dev)" } else { "" };
+            format!("{} -> {}{}", dep.from, dep.to, dev)
+        };
+        let sections = [
+            ("added packages", "+", drift.added_packages.clone(), GREEN),
+            ("removed packages", "-", drift.removed_packages.clone(), RED),
+            (
+                "added dependencies",
+                "+",
+                drift.added_dependencies.iter().map(dependency).collect(),
+                GREEN,
+            ),
+            (
+                "removed dependencies",
+                "-",
+                drift.removed_dependencies.iter().map(dependency).collect(),
+                RED,
+            ),
+        ];
+
+        for (title, marker, entries, style) in sections {
+            if entries.is_empty() {
+                continue;
+            }
+
+            writeln!(self.out, "{}", self.paint(title, BOLD))?;
+            for entry in entries {
+                writeln!(self.out, "  {} {}", self.paint(marker, style), entry)?;
+            }
+            writeln!(self.out)?;
+        }
+
+        writeln!(self.out, "{}", plural(drift.len(), "difference"))
+    }
+
+    fn render_packages(&mut self, packages: &[PackageEntry]) -> io::Result<()> {
+        if packages.is_empty() {
+            return writeln!(self.out, "no matching packages");
+        }
+
+        let package_type = |entry: &PackageEntry| {
+            entry
+                .package_type
+                .as_deref()
+                .unwrap_or(UNKNOWN_PACKAGE_TYPE)
+                .to_owned()
+        };
+        let types = packages.iter().map(package_type).collect::<Vec<_>>();
+        let name_width = column_width(
+            packages
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .chain(["name"]),
+        );
+        let type_width = column_width(types.iter().map(|name| name.as_str()).chain(["type"]));
+
+        writeln!(
+            self.out,
+            "{}",
+            self.paint(
+                &format!(
+                    "{:<name_width$}  {:<type_width$}  public  deps  path",
+                    "name", "type"
+                ),
+                BOLD
+            )
+        )?;
+        for (entry, package_type) in packages.iter().zip(types) {
+            writeln!(
+                self.out,
+                "{:<name_width$}  {:<type_width$}  {:<6}  {:>4}  {}",
+                entry.name,
+                package_type,
+                if entry.public { "yes" } else { "no" },
+                entry.dependencies,
+                entry.path
+            )?;
+        }
+        Ok(())
+    }
+
+    fn render_overrides(&mut self, overrides: &[OverrideEntry]) -> io::Result<()> {
+        if overrides.is_empty() {
+            return writeln!(self.out, "no active overrides");
+        }
+
+        let package_width = column_width(overrides.iter().map(|entry| entry.package.as_str()));
+        let dependency_width =
+            column_width(overrides.iter().map(|entry| entry.dependency.as_str()));
+
+        for entry in overrides {
+            let mut line = format!(
+                "{:<package_width$}  {:<dependency_width$}  {} -> {}  {}",
+                entry.package,
+                entry.dependency,
+                entry.original.as_deref().unwrap_or("[transitive]"),
+                entry.effective,
+                self.paint(&format!("({})", entry.file), DIM)
+            );
+            if let Some(problem) = &entry.problem {
+                line = format!("{}  {}", line, self.paint(&problem.to_string(), YELLOW));
+            }
+            writeln!(self.out, "{}", line)?;
+        }
+
+        let problems = overrides
+            .iter()
+            .filter(|entry| entry.problem.is_some())
+            .count();
+        writeln!(
+            self.out,
+            "{}, {}",
+            plural(overrides.len(), "override"),
+            plural(problems, "problem")
+        )
+    }
+}
+
+/// Machine readable output (JSON unless configured otherwise).
+pub struct ReportRenderer<W: Write> {
+    out: W,
+    format: ReportFormat,
+}
+
+/// Machine readable JSON output.
+pub type JsonRenderer<W> = ReportRenderer<W>;
+
+impl<W: Write> ReportRenderer<W> {
+    pub fn new(out: W) -> ReportRenderer<W> {
+        ReportRenderer {
+            out,
+            format: ReportFormat::Json,
+        }
+    }
+
+    /// Return a copy writing the given `ReportFormat`.
+    pub fn with_format(self, format: ReportFormat) -> Self {
+        ReportRenderer { format, ..self }
+    }
+
+    fn report(&mut self, content: ReportContent) -> io::Result<()> {
+        self.format.write(&mut self.out, &Report::new(content))
+    }
+}
+
+impl<W: Write> Renderer for ReportRenderer<W> {
+    fn render_validations(&mut self, validations: &[PackageValidation]) -> io::Result<()> {
+        let findings = validations
+            .iter()
+            .filter(|validation| validation.level != ValidationLevel::None)
+            .collect::<Vec<_>>();
+        let summary = Summary::of_findings(&findings);
+
+        self.report(ReportContent::Validations { findings, summary })
+    }
+
+    fn render_dump(&mut self, pubspecs: &[Pubspec]) -> io::Result<()> {
+        self.report(ReportContent::Dump { pubspecs })
+    }
+
+    fn render_check(&mut self, report: &CheckReport) -> io::Result<()> {
+        self.report(ReportContent::Check(report))
+    }
+
+    fn render_check_by_package(&mut self, report: &ExternalReport) -> io::Result<()> {
+        self.report(ReportContent::CheckByPackage(report))
+    }
+
+    fn render_graph(&mut self, graph: &Graph) -> io::Result<()> {
+        self.report(ReportContent::Graph(graph))
+    }
+
+    fn render_stats(&mut self, stats: &Stats) -> io::Result<()> {
+        self.report(ReportContent::Stats(stats))
+    }
+
+    fn render_tree(&mut self, trees: &[TreeNode]) -> io::Result<()> {
+        self.report(ReportContent::Tree { trees })
+    }
+
+    fn render_rules(&mut self, rules: &[Rule]) -> io::Result<()> {
+        self.report(ReportContent::Rules { rules })
+    }
+
+    fn render_rule(&mut self, rule: &Rule) -> io::Result<()> {
+        self.report(ReportContent::Rule(rule))
+    }
+
+    fn render_config_problems(&mut self, problems: &[ConfigProblem]) -> io::Result<()> {
+        self.report(ReportContent::ConfigProblems {
+            findings: problems,
+            summary: summary(problems.iter().map(|problem| &problem.level)),
+        })
+    }
+
+    fn render_publish_order(&mut self, waves: &[Vec<String>]) -> io::Result<()> {
+        self.report(ReportContent::PublishOrder { waves })
+    }
+
+    fn render_bump(&mut self, report: &BumpReport) -> io::Result<()> {
+        self.report(ReportContent::Bump(report))
+    }
+
+    fn render_include_suggestions(&mut self, suggestions: &[IncludeSuggestion]) -> io::Result<()> {
+        self.report(ReportContent::IncludeSuggestions {
+            package_types: suggestions,
+        })
+    }
+
+    fn render_diff(&mut self, diff: &FindingsDiff) -> io::Result<()> {
+        self.report(ReportContent::Diff(diff))
+    }
+
+    fn render_snapshot_drift(&mut self, drift: &SnapshotDrift) -> io::Result<()> {
+        self.report(ReportContent::SnapshotDrift(drift))
+    }
+
+    fn render_packages(&mut self, packages: &[PackageEntry]) -> io::Result<()> {
+        self.report(ReportContent::Packages { packages })
+    }
+
+    fn render_overrides(&mut self, overrides: &[OverrideEntry]) -> io::Result<()> {
+        self.report(ReportContent::Overrides { overrides })
+    }
+}
+
+/// Count the given levels of findings.
+fn summary<'a, I: Iterator<Item = &'a ValidationLevel>>(levels: I) -> Summary {
+    let mut summary = Summary {
+        errors: 0,
+        warnings: 0,
+        packages: None,
+        by_code: Vec::new(),
+        by_package_type: Vec::new(),
+    };
+    for level in levels {
+        match level {
+            ValidationLevel::Error => summary.errors += 1,
+            ValidationLevel::Warning => summary.warnings += 1,
+            ValidationLevel::None => {}
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::CheckProblem;
+    use crate::command::CheckReport;
+    use crate::command::CheckRule;
+    use crate::command::CheckedDependency;
+    use crate::command::CheckedPackage;
+    use crate::command::PinnedConstraint;
+    use crate::command::StaleVersion;
+    use crate::dependency::Dependency;
+    use crate::diff::FindingsDiff;
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::graph::Edge;
+    use crate::graph::Graph;
+    use crate::probe::GitProbe;
+    use crate::probe::GitProblem;
+    use crate::render::JsonRenderer;
+    use crate::render::PlainRenderer;
+    use crate::render::ReportRenderer;
+    use crate::report::ReportFormat;
+    use crate::tree::TreeMarker;
+    use crate::tree::TreeNode;
+    use crate::Pubspec;
+    use crate::Renderer;
+
+    fn validation(package: &str, level: ValidationLevel) -> PackageValidation {
+        PackageValidation {
+            package_name: package.to_owned(),
+            error: "some error".to_owned(),
+            description: None,
+            code: ValidationType::UnknownDependency,
+            level,
+            dependency: None,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: None,
+        }
+    }
+
+    fn render<F: FnOnce(&mut dyn Renderer)>(json: bool, f: F) -> String {
+        let mut out = Vec::new();
+        if json {
+            f(&mut JsonRenderer::new(&mut out));
+        } else {
+            f(&mut PlainRenderer::new(&mut out));
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn plain_validations() {
+        let validations = vec![
+            validation("foo", ValidationLevel::Warning),
+            PackageValidation {
+                code: ValidationType::CyclicDependency,
+                package_type: Some("app".to_owned()),
+                ..validation("foo", ValidationLevel::Error)
+            },
+            validation("bar", ValidationLevel::None),
+        ];
+
+        let output = render(false, |r| r.render_validations(&validations).unwrap());
+
+        assert_eq!(
+            output,
+            "foo\n  \
+             error  some error  validation:dependency:cyclic\n  \
+             warn   some error  validation:dependency:unknown\n\n\
+             validation type                errors  warnings\n\
+             validation:dependency:cyclic        1         0\n\
+             validation:dependency:unknown       0         1\n\n\
+             package type  errors  warnings\n\
+             <none>             0         1\n\
+             app                1         0\n\n\
+             1 error, 1 warning in 1 package\n"
+        );
+    }
+
+    #[test]
+    fn plain_validations_by_owner() {
+        let owned = |package: &str, owner: Option<&str>| PackageValidation {
+            owner: owner.map(|owner| owner.to_owned()),
+            ..validation(package, ValidationLevel::Error)
+        };
+        let validations = vec![
+            owned("foo", Some("@org/core")),
+            owned("foobar", Some("@org/core")),
+            owned("bar", None),
+        ];
+
+        let output = render(false, |r| r.render_validations(&validations).unwrap());
+        assert!(output.starts_with("foo (@org/core)\n"));
+
+        let output = render(false, |r| {
+            r.render_validations_by_owner(&validations).unwrap()
+        });
+
+        assert_eq!(
+            output,
+            "@org/core\n  \
+             error  foo     some error  validation:dependency:unknown\n  \
+             error  foobar  some error  validation:dependency:unknown\n\n\
+             (unowned)\n  \
+             error  bar     some error  validation:dependency:unknown\n\n\
+             validation type                errors  warnings\n\
+             validation:dependency:unknown       3         0\n\n\
+             package type  errors  warnings\n\
+             <none>             3         0\n\n\
+             3 errors, 0 warnings in 3 packages\n"
+        );
+    }
+
+    #[test]
+    fn plain_validations_by_repo() {
+        let in_repo = |package: &str, repo: &str| PackageValidation {
+            repo: Some(repo.to_owned()),
+            ..validation(package, ValidationLevel::Warning)
+        };
+        let validations = vec![
+            in_repo("foo", "shop"),
+            in_repo("foo", "payments"),
+            in_repo("bar", "payments"),
+        ];
+
+        let output = render(false, |r| {
+            r.render_validations_by_repo(&validations).unwrap()
+        });
+
+        assert_eq!(
+            output,
+            "shop\n  \
+             warn   foo  some error  validation:dependency:unknown\n\n\
+             payments\n  \
+             warn   foo  some error  validation:dependency:unknown\n  \
+             warn   bar  some error  validation:dependency:unknown\n\n\
+             validation type                errors  warnings\n\
+             validation:dependency:unknown       0         3\n\n\
+             package type  errors  warnings\n\
+             <none>             0         3\n\n\
+             0 errors, 3 warnings in 3 packages\n"
+        );
+    }
+
+    #[test]
+    fn plain_diff() {
+        let diff = FindingsDiff {
+            new: vec![validation("foo", ValidationLevel::Error)],
+            fixed: Vec::new(),
+            persisting: vec![validation("foobar", ValidationLevel::Warning)],
+        };
+
+        let output = render(false, |r| r.render_diff(&diff).unwrap());
+
+        assert_eq!(
+            output,
+            "new\n  \
+             error  foo     some error  validation:dependency:unknown\n\n\
+             persisting\n  \
+             warn   foobar  some error  validation:dependency:unknown\n\n\
+             new 1, fixed 0, persisting 1\n"
+        );
+    }
+
+    #[test]
+    fn plain_validations_color() {
+        let validations = vec![validation("foo", ValidationLevel::Error)];
+
+        let mut out = Vec::new();
+        PlainRenderer::new(&mut out)
+            .with_color(true)
+            .render_validations(&validations)
+            .unwrap();
+        let output = String::from_utf8(out).unwrap();
+
+        assert!(output.contains("\x1b[1;31merror\x1b[0m"));
+    }
+
+    #[test]
+    fn json_validations() {
+        let validations = vec![
+            validation("foo", ValidationLevel::Warning),
+            validation("bar", ValidationLevel::None),
+        ];
+
+        let output = render(true, |r| r.render_validations(&validations).unwrap());
+
+        assert_eq!(
+            output,
+            "{\"version\":1,\"kind\":\"validations\",\"findings\":[{\"package_name\":\"foo\",\
+             \"error\":\"some error\",\"description\":null,\"code\":\"validation:dependency:unknown\",\
+             \"level\":\"warn\",\"dependency\":null,\"owner\":null,\"file\":null,\"line\":null,\"fix\":null}],\"summary\":{\"errors\":0,\"warnings\":1,\"packages\":1,\
+             \"by_code\":[{\"name\":\"validation:dependency:unknown\",\"errors\":0,\"warnings\":1}],\
+             \"by_package_type\":[{\"name\":\"<none>\",\"errors\":0,\"warnings\":1}]}}\n"
+        );
+    }
+
+    #[test]
+    fn toml_validations() {
+        let validations = vec![validation("foo", ValidationLevel::Error)];
+
+        let mut out = Vec::new();
+        ReportRenderer::new(&mut out)
+            .with_format(ReportFormat::Toml)
+            .render_validations(&validations)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "version = 1\nkind = \"validations\"\n\n[[findings]]\npackage_name = \"foo\"\n\
+             error = \"some error\"\ncode = \"validation:dependency:unknown\"\nlevel = \"error\"\n\n\
+             [summary]\nerrors = 1\nwarnings = 0\npackages = 1\n\n\
+             [[summary.by_code]]\nname = \"validation:dependency:unknown\"\nerrors = 1\nwarnings = 0\n\n\
+             [[summary.by_package_type]]\nname = \"<none>\"\nerrors = 1\nwarnings = 0\n"
+        );
+    }
+
+    #[test]
+    fn json_dump() {
+        let pubspec = Pubspec::test("foo")
+            .with_deps(vec![Dependency::PubDev {
+                name: "bar".to_owned(),
+                version: "^1.0.0".to_owned(),
+                overridden: Box::new(None),
+            }
+            .with_override(Dependency::Local {
+                name: "bar".to_owned(),
+                path: "../bar".to_owned(),
+                overridden: Box::new(None),
+            })])
+            .with_version("1.0.0");
+
+        let output = render(true, |r| r.render_dump(&[pubspec]).unwrap());
+
+        assert!(output.contains(
+            "\"dependencies\":[{\"type\":\"pubdev\",\"name\":\"bar\",\"version\":\"^1.0.0\",\
+             \"overridden\":{\"type\":\"local\",\"name\":\"bar\",\"path\":\"../bar\",\"overridden\":null}}]"
+        ));
+    }
+
+    #[test]
+    fn plain_graph() {
+        let graph = Graph {
+            nodes: vec!["foo".to_owned(), "bar".to_owned()],
+            externals: Vec::new(),
+            edges: vec![Edge {
+                from: "foo".to_owned(),
+                to: "bar".to_owned(),
+                dev: false,
+                overridden: false,
+                violation: false,
+            }],
+            violations: Vec::new(),
+        };
+
+        let output = render(false, |r| r.render_graph(&graph).unwrap());
+
+        assert!(output.contains("  foo []\n  foo -> bar;\n  // bar\n  bar []\n}\n"));
+    }
+
+    #[test]
+    fn plain_graph_externals() {
+        let graph = Graph {
+            nodes: vec!["foo".to_owned()],
+            externals: vec!["http".to_owned()],
+            edges: vec![Edge {
+                from: "foo".to_owned(),
+                to: "http".to_owned(),
+                dev: false,
+                overridden: false,
+                violation: false,
+            }],
+            violations: Vec::new(),
+        };
+
+        let output = render(false, |r| r.render_graph(&graph).unwrap());
+
+        assert!(output.contains(
+            "  foo -> http;\n  // http (external)\n  http [shape=box, style=dashed]\n}\n"
+        ));
+    }
+
+    #[test]
+    fn plain_graph_edge_styles() {
+        let edge = |to: &str, dev, overridden| Edge {
+            from: "foo".to_owned(),
+            to: to.to_owned(),
+            dev,
+            overridden,
+            violation: false,
+        };
+        let graph = Graph {
+            nodes: vec!["foo".to_owned()],
+            externals: Vec::new(),
+            edges: vec![edge("bar", true, false), edge("baz", true, true)],
+            violations: Vec::new(),
+        };
+
+        let output = render(false, |r| r.render_graph(&graph).unwrap());
+
+        assert!(output.contains("  foo -> bar [style=dotted];\n"));
+        assert!(output.contains(
+            "  foo -> baz [style=dotted, color=blue, fontcolor=blue, label=\"override\"];\n"
+        ));
+    }
+
+    #[test]
+    fn plain_graph_violations() {
+        let graph = Graph {
+            nodes: vec!["foo".to_owned(), "bar".to_owned()],
+            externals: Vec::new(),
+            edges: vec![Edge {
+                from: "foo".to_owned(),
+                to: "bar".to_owned(),
+                dev: false,
+                overridden: true,
+                violation: true,
+            }],
+            violations: vec!["bar".to_owned(), "foo".to_owned()],
+        };
+
+        let output = render(false, |r| r.render_graph(&graph).unwrap());
+
+        assert!(output.contains(
+            "  foo [color=red, fontcolor=red]\n  foo -> bar [color=red, label=\"override\"];\n"
+        ));
+        assert!(output.contains("  bar [color=red, fontcolor=red]\n"));
+    }
+
+    #[test]
+    fn plain_check() {
+        let report = CheckReport {
+            packages: vec![CheckedPackage {
+                name: "app".to_owned(),
+                dependencies: vec![
+                    CheckedDependency {
+                        name: "http".to_owned(),
+                        version: "^1.0.0".to_owned(),
+                        latest: Some("1.2.0".to_owned()),
+                        private: false,
+                        dev: false,
+                        pinned_by: vec![PinnedConstraint {
+                            package: "pkg_core".to_owned(),
+                            version: "^1.1.0".to_owned(),
+                        }],
+                        stale: None,
+                        problems: vec![CheckProblem {
+                            rule: CheckRule::Quality,
+                            level: ValidationLevel::Error,
+                            message: "90 pub points (minimum 120)".to_owned(),
+                        }],
+                    },
+                    CheckedDependency {
+                        name: "lints".to_owned(),
+                        version: "^3.0.0".to_owned(),
+                        latest: None,
+                        private: false,
+                        dev: true,
+                        pinned_by: Vec::new(),
+                        stale: Some(StaleVersion {
+                            version: "3.0.0".to_owned(),
+                            age_days: 412,
+                        }),
+                        problems: Vec::new(),
+                    },
+                ],
+            }],
+            unknown: vec!["lints".to_owned()],
+            git: None,
+        };
+
+        let output = render(false, |r| r.render_check(&report).unwrap());
+
+        assert_eq!(
+            output,
+            "app
+  \
+             http: ^1.0.0 [1.2.0]
+    \
+             pinned by pkg_core: ^1.1.0
+    \
+             error quality: 90 pub points (minimum 120)
+  \
+             lints: ^3.0.0 [<unknown>] (dev)
+    \
+             stale: 3.0.0 published 412 days ago
+
+\
+             not found on pub.dev: lints
+"
+        );
+
+        let probe = |dependency: &str, problem| GitProbe {
+            package: "app".to_owned(),
+            dependency: dependency.to_owned(),
+            url: format!("git@github.com:org/{}.git", dependency),
+            git_ref: Some("v1.0.0".to_owned()),
+            path: Some(dependency.to_owned()).filter(|_| problem == Some(GitProblem::MissingPath)),
+            dev: false,
+            problem,
+            message: None,
+        };
+        let report = CheckReport {
+            packages: Vec::new(),
+            unknown: Vec::new(),
+            git: Some(vec![
+                probe("pkg_core", None),
+                probe("pkg_ui", Some(GitProblem::MissingRef)),
+                probe("pkg_auth", Some(GitProblem::MissingPath)),
+            ]),
+        };
+
+        assert_eq!(
+            render(false, |r| r.render_check(&report).unwrap()),
+            "
+app  pkg_ui  git@github.com:org/pkg_ui.git (v1.0.0)  ref does not exist
+app  pkg_auth  git@github.com:org/pkg_auth.git (v1.0.0) pkg_auth  no pubspec.yaml at path
+3 git dependencies probed, 2 broken
+"
+        );
+    }
+
+    #[test]
+    fn plain_check_by_package() {
+        let dependency = |name: &str, version: &str| CheckedDependency {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            latest: Some("1.2.0".to_owned()),
+            private: false,
+            dev: false,
+            pinned_by: Vec::new(),
+            stale: None,
+            problems: Vec::new(),
+        };
+        let package = |name: &str, dependencies| CheckedPackage {
+            name: name.to_owned(),
+            dependencies,
+        };
+        let report = CheckReport {
+            packages: vec![
+                package(
+                    "app",
+                    vec![dependency("http", "^1.0.0"), dependency("path", "^1.8.0")],
+                ),
+                package("pkg_core", vec![dependency("http", "^0.13.0")]),
+                package("pkg_ui", vec![dependency("http", "^1.0.0")]),
+            ],
+            unknown: Vec::new(),
+            git: None,
+        };
+
+        let output = render(false, |r| {
+            r.render_check_by_package(&report.by_package()).unwrap()
+        });
+
+        assert_eq!(
+            output,
+            "http [1.2.0] conflicting constraints\n  \
+             ^1.0.0: app, pkg_ui\n  \
+             ^0.13.0: pkg_core\n\
+             path [1.2.0]\n  \
+             ^1.8.0: app\n"
+        );
+    }
+
+    #[test]
+    fn plain_tree() {
+        let node = |name: &str, marker, children| TreeNode {
+            name: name.to_owned(),
+            details: None,
+            marker,
+            children,
+        };
+        let tree = node(
+            "app",
+            None,
+            vec![
+                node("pkg_a", None, vec![node("pkg_b", None, vec![])]),
+                node("pkg_b", Some(TreeMarker::Duplicate), vec![]),
+            ],
+        );
+
+        let output = render(false, |r| r.render_tree(&[tree]).unwrap());
+
+        assert_eq!(output, "app\n├── pkg_a\n│   └── pkg_b\n└── pkg_b (*)\n");
+    }
+}
@@ -0,0 +1,344 @@
+use crate::bump::BumpReport;
+use crate::command::CheckReport;
+use crate::command::ExternalReport;
+use crate::diff::FindingsDiff;
+use crate::error::PackageValidation;
+use crate::error::ValidationLevel;
+use crate::graph::Graph;
+use crate::lint::ConfigProblem;
+use crate::list::PackageEntry;
+use crate::overrides::OverrideEntry;
+use crate::rules::Rule;
+use crate::snapshot::SnapshotDrift;
+use crate::stats::Stats;
+use crate::stats::UNKNOWN_PACKAGE_TYPE;
+use crate::suggest::IncludeSuggestion;
+use crate::tree::TreeNode;
+use crate::Pubspec;
+use schemars::schema::RootSchema;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+
+/// Version of the machine readable output, incremented on incompatible
+/// changes only.
+pub const REPORT_VERSION: u32 = 1;
+
+/// Envelope of all machine readable output (see `flcheck schema`).
+#[derive(Serialize, JsonSchema)]
+pub struct Report<'a> {
+    /// Version of the output format
+    pub version: u32,
+    #[serde(flatten)]
+    pub content: ReportContent<'a>,
+}
+
+/// Command specific content of a report, tagged by its `kind`.
+#[derive(Serialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ReportContent<'a> {
+    Validations {
+        findings: Vec<&'a PackageValidation>,
+        summary: Summary,
+    },
+    Dump {
+        pubspecs: &'a [Pubspec],
+    },
+    Check(&'a CheckReport),
+    CheckByPackage(&'a ExternalReport),
+    Graph(&'a Graph),
+    Stats(&'a Stats),
+    Tree {
+        trees: &'a [TreeNode],
+    },
+    Rules {
+        rules: &'a [Rule],
+    },
+    Rule(&'a Rule),
+    ConfigProblems {
+        findings: &'a [ConfigProblem],
+        summary: Summary,
+    },
+    PublishOrder {
+        waves: &'a [Vec<String>],
+    },
+    Bump(&'a BumpReport),
+    IncludeSuggestions {
+        package_types: &'a [IncludeSuggestion],
+    },
+    Diff(&'a FindingsDiff),
+    SnapshotDrift(&'a SnapshotDrift),
+    Packages {
+        packages: &'a [PackageEntry],
+    },
+    Overrides {
+        overrides: &'a [OverrideEntry],
+    },
+}
+
+/// Number of findings by level.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct Summary {
+    pub errors: usize,
+    pub warnings: usize,
+    /// Number of packages with findings (validations only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packages: Option<usize>,
+    /// Number of findings by validation type (validations only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_code: Vec<GroupCount>,
+    /// Number of findings by the type of their packages (validations only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub by_package_type: Vec<GroupCount>,
+}
+
+/// Number of findings by level of a group of findings.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct GroupCount {
+    pub name: String,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl Summary {
+    /// Count the given (visible) validation findings by level, validation
+    /// type and package type (sorted by name).
+    pub fn of_findings(findings: &[&PackageValidation]) -> Summary {
+        let mut by_code: BTreeMap<String, GroupCount> = BTreeMap::new();
+        let mut by_package_type: BTreeMap<String, GroupCount> = BTreeMap::new();
+        let mut packages = HashSet::new();
+        let (mut errors, mut warnings) = (0, 0);
+
+        for finding in findings {
+            let package_type = finding
+                .package_type
+                .as_deref()
+                .unwrap_or(UNKNOWN_PACKAGE_TYPE);
+            let groups = [
+                by_code.entry(finding.code.to_string()),
+                by_package_type.entry(package_type.to_owned()),
+            ];
+            for group in groups {
+                let count = group.or_insert_with_key(|name| GroupCount {
+                    name: name.clone(),
+                    errors: 0,
+                    warnings: 0,
+                });
+                match finding.level {
+                    ValidationLevel::Error => count.errors += 1,
+                    ValidationLevel::Warning => count.warnings += 1,
+                    ValidationLevel::None => {}
+                }
+            }
+            match finding.level {
+                ValidationLevel::Error => errors += 1,
+                ValidationLevel::Warning => warnings += 1,
+                ValidationLevel::None => {}
+            }
+            packages.insert(&finding.package_name);
+        }
+
+        Summary {
+            errors,
+            warnings,
+            packages: Some(packages.len()),
+            by_code: by_code.into_values().collect(),
+            by_package_type: by_package_type.into_values().collect(),
+        }
+    }
+}
+
+impl<'a> Report<'a> {
+    pub fn new(content: ReportContent<'a>) -> Report<'a> {
+        Report {
+            version: REPORT_VERSION,
+            content,
+        }
+    }
+
+    /// JSON schema of all reports.
+    pub fn schema() -> RootSchema {
+        schemars::schema_for!(Report<'static>)
+    }
+}
+
+/// Formats machine readable reports can be serialized in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ReportFormat {
+    /// Serialize the report `value` in this format into `out`.
+    pub fn write<W: Write, T: Serialize>(&self, out: &mut W, value: &T) -> io::Result<()> {
+        match self {
+            ReportFormat::Json => {
+                serde_json::to_writer(&mut *out, value)?;
+                writeln!(out)
+            }
+            ReportFormat::Yaml => serde_yaml::to_writer(&mut *out, value).map_err(io::Error::other),
+            ReportFormat::Toml => {
+                let toml = toml::to_string(value).map_err(io::Error::other)?;
+                out.write_all(toml.as_bytes())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::PackageValidation;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::report::GroupCount;
+    use crate::report::Report;
+    use crate::report::ReportContent;
+    use crate::report::ReportFormat;
+    use crate::report::Summary;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Sample {
+        name: &'static str,
+        description: Option<&'static str>,
+        values: Vec<u32>,
+    }
+
+    fn write(format: ReportFormat) -> String {
+        let report = Sample {
+            name: "foo",
+            description: None,
+            values: vec![1, 2],
+        };
+
+        let mut out = Vec::new();
+        format.write(&mut out, &report).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            write(ReportFormat::Json),
+            "{\"name\":\"foo\",\"description\":null,\"values\":[1,2]}\n"
+        );
+        assert_eq!(
+            write(ReportFormat::Yaml),
+            "name: foo\ndescription: null\nvalues:\n- 1\n- 2\n"
+        );
+        assert_eq!(
+            write(ReportFormat::Toml),
+            "name = \"foo\"\nvalues = [1, 2]\n"
+        );
+    }
+
+    fn finding(
+        package: &str,
+        package_type: Option<&str>,
+        code: ValidationType,
+        level: ValidationLevel,
+    ) -> PackageValidation {
+        PackageValidation {
+            package_name: package.to_owned(),
+            error: "some error".to_owned(),
+            description: None,
+            code,
+            level,
+            dependency: None,
+            owner: None,
+            file: None,
+            relative_file: None,
+            line: None,
+            fix: None,
+            repo: None,
+            package_type: package_type.map(|name| name.to_owned()),
+        }
+    }
+
+    fn group(name: &str, errors: usize, warnings: usize) -> GroupCount {
+        GroupCount {
+            name: name.to_owned(),
+            errors,
+            warnings,
+        }
+    }
+
+    #[test]
+    fn summary_of_findings() {
+        let findings = [
+            finding(
+                "app_foo",
+                Some("app"),
+                ValidationType::CyclicDependency,
+                ValidationLevel::Error,
+            ),
+            finding(
+                "app_foo",
+                Some("app"),
+                ValidationType::UnknownDependency,
+                ValidationLevel::Warning,
+            ),
+            finding(
+                "pkg_bar",
+                None,
+                ValidationType::UnknownDependency,
+                ValidationLevel::Error,
+            ),
+        ];
+        let summary = Summary::of_findings(&findings.iter().collect::<Vec<_>>());
+
+        assert_eq!(summary.errors, 2);
+        assert_eq!(summary.warnings, 1);
+        assert_eq!(summary.packages, Some(2));
+        assert_eq!(
+            summary.by_code,
+            vec![
+                group("validation:dependency:cyclic", 1, 0),
+                group("validation:dependency:unknown", 1, 1),
+            ]
+        );
+        assert_eq!(
+            summary.by_package_type,
+            vec![group("<none>", 1, 0), group("app", 1, 1)]
+        );
+
+        // groups are omitted if there are no findings
+        let empty = serde_json::to_string(&Summary::of_findings(&[])).unwrap();
+        assert_eq!(empty, "{\"errors\":0,\"warnings\":0,\"packages\":0}");
+    }
+
+    #[test]
+    fn envelope() {
+        let waves = vec![vec!["pkg_core".to_owned()], vec!["app_foo".to_owned()]];
+        let report = Report::new(ReportContent::PublishOrder { waves: &waves });
+
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            "{\"version\":1,\"kind\":\"publish-order\",\"waves\":[[\"pkg_core\"],[\"app_foo\"]]}"
+        );
+    }
+
+    #[test]
+    fn schema() {
+        let schema = serde_json::to_value(Report::schema()).unwrap();
+
+        assert_eq!(schema["required"], serde_json::json!(["version"]));
+
+        let kinds = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["kind"]["enum"][0].as_str().unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(kinds.contains(&"validations"));
+        assert!(kinds.contains(&"config-problems"));
+        assert!(kinds.contains(&"check-by-package"));
+        assert_eq!(kinds.len(), 17);
+    }
+}
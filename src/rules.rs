@@ -0,0 +1,376 @@
+use crate::error::ValidationLevel;
+use crate::error::ValidationType;
+use crate::Config;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Description of a single validation rule along with its configured level.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Rule {
+    pub code: ValidationType,
+    pub default_level: ValidationLevel,
+    /// Level as configured in the `validations` configuration
+    pub level: ValidationLevel,
+    pub summary: &'static str,
+    /// Longer explanation including suggestions how to fix findings
+    pub explanation: &'static str,
+}
+
+impl Rule {
+    pub fn new(code: &ValidationType, config: &Config) -> Rule {
+        let (summary, explanation) = describe(code);
+
+        Rule {
+            code: code.clone(),
+            default_level: code.default_level(),
+            level: config.validation_level(code),
+            summary,
+            explanation,
+        }
+    }
+
+    /// List of all known validation rules.
+    pub fn all(config: &Config) -> Vec<Rule> {
+        ValidationType::values()
+            .map(|code| Rule::new(code, config))
+            .collect()
+    }
+}
+
+fn describe(code: &ValidationType) -> (&'static str, &'static str) {
+    match code {
+        ValidationType::GitDevDependency => (
+            "git dependency of a public repository in dev_dependencies",
+            "A package lists a dependency on a public git repository (see \
+             `public_repositories`) in its dev_dependencies. These are usually \
+             leftovers of local development that make builds depend on the state \
+             of a foreign repository.\n\n\
+             Fix: depend on a released version on pub.dev instead or move the \
+             dependency into a private repository.",
+        ),
+        ValidationType::UnknownDependency => (
+            "local dependency that cannot be resolved",
+            "A package has a local (path) dependency that does not point to any \
+             package found in the workspace. Either the path is wrong, the package \
+             was moved or removed or its directory is excluded from discovery.\n\n\
+             Fix: correct the dependency's path, check the `include` and `exclude` \
+             configuration or pass the missing root directory via `-d`.",
+        ),
+        ValidationType::DependencyNotAllowed => (
+            "dependency not allowed by the package type rules",
+            "A package depends on a local package whose directory prefix is not \
+             listed in the `includes` of the depending package's type (see \
+             `package_types`). This usually means the dependency hierarchy (e.g. \
+             app -> package) is violated.\n\n\
+             Fix: move the shared code into a package that may be depended upon, \
+             invert the dependency or extend the package type's `includes`.",
+        ),
+        ValidationType::CyclicDependency => (
+            "cyclic dependency between local packages",
+            "Two or more local packages depend on each other (directly or \
+             transitively, including dev_dependencies). Cycles make packages \
+             impossible to build, test and release independently.\n\n\
+             Fix: extract the commonly used code into a separate package both \
+             packages depend on. Use `flcheck tree` to inspect the dependencies.",
+        ),
+        ValidationType::NonGitDependencyInPublicPackage => (
+            "local dependency in a public package",
+            "A package marked as public (`flcheck: is_public: true` in its \
+             pubspec.yaml) depends on a local (path) package. Consumers of the \
+             published package cannot resolve local paths.\n\n\
+             Fix: depend on the package via git or a released version on pub.dev \
+             instead or relax the `public_package_policy` of its package type.",
+        ),
+        ValidationType::PrivateDependencyInPublicPackage => (
+            "local dependency on a non-public package in a public package",
+            "A package marked as public depends on a local package that is not \
+             public itself, although its package type's `public_package_policy` \
+             allows local dependencies on public packages. The non-public package \
+             is not published along with it.\n\n\
+             Fix: mark the dependency as public as well or depend on it via git \
+             or pub.dev instead.",
+        ),
+        ValidationType::ForeignRepositoryDependencyInPublicPackage => (
+            "local dependency of another repository in a public package",
+            "A package marked as public depends on a local public package that \
+             declares a different `repository` in its pubspec.yaml, whereas its \
+             package type's `public_package_policy` (`same_repository`) allows \
+             local dependencies within the same published repository only.\n\n\
+             Fix: depend on the package via git or pub.dev instead or align the \
+             `repository` of both packages.",
+        ),
+        ValidationType::DependencyDepth => (
+            "chain of local dependencies exceeding the package type's max_depth",
+            "The longest chain of local dependencies (excluding dev_dependencies) \
+             rooted at a package is longer than the `max_depth` configured for its \
+             package type. Deep dependency chains make packages slow to build and \
+             changes ripple through many layers.\n\n\
+             Fix: flatten the hierarchy, e.g. by merging thin intermediate \
+             packages or depending on the lower layers directly. Use `flcheck \
+             tree` to inspect the chain.",
+        ),
+        ValidationType::CrossAppDependency => (
+            "dependency between two packages of the same package type",
+            "A package depends on another package of its own package type (e.g. \
+             one app on another app), although the type does not include itself. \
+             Packages of such types (usually apps) are meant to be independent of \
+             each other.\n\n\
+             Fix: move the code both packages need into a package of a type that \
+             is included by theirs (e.g. a shared package).",
+        ),
+        ValidationType::SelfDependency => (
+            "package depending on itself",
+            "A dependency of a package resolves to the package's own directory, \
+             e.g. a leftover `path: ../` or `path: .` entry. `pub get` fails on \
+             such dependencies with confusing error messages.\n\n\
+             Fix: remove the dependency.",
+        ),
+        ValidationType::DuplicateDependency => (
+            "multiple dependencies referring to the same package",
+            "Two dependency entries with different names (in dependencies or \
+             dev_dependencies) resolve to the same local package. At most one of \
+             them matches the package's actual name, which makes `pub get` fail.\n\n\
+             Fix: remove the dependency whose name does not match the package's \
+             name.",
+        ),
+        ValidationType::OutsideWorkspace => (
+            "local dependency located outside of the workspace",
+            "A local (path) dependency resolves to a directory outside of all root \
+             directories of the workspace (e.g. `path: ../../other-repo/pkg`). \
+             Such dependencies only work with a matching directory layout on the \
+             local machine and break fresh clones and CI builds.\n\n\
+             Fix: depend on the package via git or pub.dev instead or add its \
+             directory to the workspace's `roots`.",
+        ),
+        ValidationType::MissingRequiredDependency => (
+            "package missing a dependency its package type requires",
+            "A package does not depend on a package listed in the `requires` of \
+             its package type (see `package_types`), e.g. every app has to depend \
+             on the shared logging package. Only regular dependencies are \
+             considered.\n\n\
+             Fix: add the required dependency to the package's pubspec.yaml.",
+        ),
+        ValidationType::NativePluginDependency => (
+            "pure dart package depending on a plugin with native code",
+            "A package of a type marked as `pure_dart` (see `package_types`) \
+             depends on a workspace plugin package whose `flutter.plugin.platforms` \
+             implement native code (`pluginClass` or `ffiPlugin`). Pure dart \
+             layers (e.g. the domain layer) are meant to be platform independent \
+             and testable without flutter.\n\n\
+             Fix: depend on an abstraction instead and inject the plugin based \
+             implementation from an app or platform package.",
+        ),
+        ValidationType::MissingAsset => (
+            "asset or font declared in the pubspec.yaml does not exist",
+            "A package lists an asset (`flutter.assets`) or font \
+             (`flutter.fonts`) whose file or directory (given with a trailing \
+             `/`) does not exist relative to the package directory. The build \
+             fails once the asset is bundled.\n\n\
+             Fix: correct the path, add the missing file or remove the entry.",
+        ),
+        ValidationType::MalformedDependency => (
+            "dependency declaration of an unknown shape",
+            "A dependency in the pubspec.yaml could not be interpreted, e.g. a \
+             version constraint that is no string (`http: 1.0`), a git \
+             dependency without `url` or a mapping without any of `path`, \
+             `git`, `hosted`, `sdk` or `version`. Such dependencies are not \
+             considered by any other validation.\n\n\
+             Fix: correct the dependency according to the pubspec format.",
+        ),
+        ValidationType::InvalidPubspec => (
+            "pubspec.yaml not matching the pubspec format",
+            "The pubspec.yaml lacks a package `name`, declares a `version` \
+             that is no string or `dependencies`, `dev_dependencies` or \
+             `dependency_overrides` that are no mapping.\n\n\
+             Fix: correct the pubspec.yaml according to the pubspec format.",
+        ),
+        ValidationType::DeniedByRule => (
+            "dependency denied by a `deny` rule",
+            "A package depends on a package (local or external) although a \
+             `deny` constraint of the `rules` configuration forbids dependencies \
+             of packages matching `from` on packages matching `to`.\n\n\
+             Fix: remove the dependency or move the required code into a \
+             package that may be depended upon.",
+        ),
+        ValidationType::RequiredByRule => (
+            "dependency missing according to a `require` rule",
+            "A package lacks a dependency that a `require` constraint of the \
+             `rules` configuration demands for all packages matching `package`, \
+             e.g. every app has to depend on the shared analytics package.\n\n\
+             Fix: add a dependency on a package matching `depends_on`.",
+        ),
+        ValidationType::DevOnlyDependency => (
+            "development tool declared as regular dependency",
+            "A package declares a package that is needed during development \
+             only (e.g. `build_runner`, `mockito` or `flutter_lints`, extended \
+             by the `dev_only_packages` configuration) in `dependencies`. \
+             Regular dependencies are resolved for and may be bundled into \
+             release builds and constrain the versions available to all \
+             dependents.\n\n\
+             Fix: move the dependency into `dev_dependencies`.",
+        ),
+        ValidationType::UndeclaredImport => (
+            "dart file importing an undeclared package (via --imports)",
+            "A dart file in `lib` or `test` imports a package (`package:...`) \
+             that is not declared in the pubspec.yaml, or a file in `lib` \
+             imports a package declared in `dev_dependencies` only. Such imports \
+             work only as long as some other dependency pulls in the package \
+             transitively.\n\n\
+             Fix: declare the package in `dependencies` (or `dev_dependencies` \
+             if imported by tests only).",
+        ),
+        ValidationType::UnusedDependency => (
+            "dependency never imported by any dart file (via --imports)",
+            "A package declared in `dependencies` is not imported by any dart \
+             file in `lib` or `test`. Unused dependencies slow down dependency \
+             resolution and builds. Packages used without being imported (e.g. \
+             fonts or plugins registering themselves) are reported as well.\n\n\
+             Fix: remove the dependency or lower the validation's level for \
+             the affected packages.",
+        ),
+        ValidationType::MissingTests => (
+            "package without tests although its package type requires them",
+            "A package of a type marked with `require_tests` (see \
+             `package_types`) lacks a `test` directory containing at least one \
+             `_test.dart` file. Shared code without tests tends to break its \
+             dependents unnoticed.\n\n\
+             Fix: add tests to the package's `test` directory.",
+        ),
+        ValidationType::MissingStructure => (
+            "file or directory required by the package type's structure missing",
+            "A package lacks a file or directory (given with a trailing `/`) \
+             that the `structure` configuration requires for all packages of \
+             its package type, e.g. `analysis_options.yaml`, `README.md` or \
+             `lib/src/`. Scaffolds of packages drift apart over time.\n\n\
+             Fix: add the missing file or directory.",
+        ),
+        ValidationType::AnalysisOptions => (
+            "analysis_options.yaml not including the shared lint configuration",
+            "A package's `analysis_options.yaml` is missing or does not \
+             `include` the shared lint configuration given by \
+             `analysis_options.include` (e.g. \
+             `package:company_lints/analysis_options.yaml`), so the package is \
+             analyzed with different lints than the rest of the workspace.\n\n\
+             Fix: include the shared configuration in the package's \
+             `analysis_options.yaml`.",
+        ),
+        ValidationType::ConstraintStyle => (
+            "version constraint not written in the configured style",
+            "The version constraint of an external (pub.dev) dependency is not \
+             written in the style a matching entry of the `constraint_styles` \
+             configuration requires (`require`) or is written in a style it \
+             forbids (`forbid`), e.g. `any` or exact versions. The styles are \
+             `any`, `caret` (`^1.2.3`), `exact` (`1.2.3`) and `range` \
+             (`>=1.2.3 <2.0.0`).\n\n\
+             Fix: rewrite the constraint in the required style.",
+        ),
+        ValidationType::PublicChangelog => (
+            "public package without changelog entry of its version",
+            "A public package (`flcheck: is_public: true`) lacks a \
+             `CHANGELOG.md` or its changelog contains no heading mentioning the \
+             `version` of the pubspec.yaml (e.g. `## 1.2.0`). pub.dev expects \
+             a changelog describing the published version, which is only \
+             noticed when publishing.\n\n\
+             Fix: add a section of the package's version to the `CHANGELOG.md`.",
+        ),
+        ValidationType::AlternativePackages => (
+            "several alternatives of a package group used in the workspace",
+            "The package depends on a package of a group configured in \
+             `alternatives` (e.g. HTTP clients like `dio`, `http` and \
+             `chopper`), while other packages of the workspace use different \
+             packages of the same group. Using several packages for the same \
+             purpose increases the dependency footprint and maintenance effort \
+             of the workspace.\n\n\
+             Fix: settle on a single package of the group across the workspace.",
+        ),
+        ValidationType::DependencyPathMismatch => (
+            "local dependency path not pointing to the package of that name",
+            "The path of a local dependency does not point to any package, while \
+             a package of the dependency's name exists elsewhere in the \
+             workspace, e.g. `path: ../shard_ui` instead of `../shared_ui`. \
+             Usually the path contains a typo or the package was moved. The \
+             dependency is resolved to the package of that name for all other \
+             validations.\n\n\
+             Fix: correct the dependency's path as suggested by the finding.",
+        ),
+        ValidationType::DuplicatePackage => (
+            "multiple packages of the same name",
+            "Several packages of the workspace (possibly below different root \
+             directories) share the same name. Dependencies on that name are \
+             resolved to the first of these packages (by path) only, so the \
+             other ones are not validated as intended. Blacklisted packages \
+             (e.g. `/example`) are not considered.\n\n\
+             Fix: rename one of the packages or exclude it from the workspace \
+             via `blacklist` or `exclude`.",
+        ),
+        ValidationType::Custom(_) => (
+            "finding of a custom validation command",
+            "An external command configured in `custom_validations` reported a \
+             finding for the package. The command receives the workspace dump \
+             as JSON on stdin and implements an organization specific rule.\n\n\
+             Fix: see the finding's description or the command's documentation.",
+        ),
+        ValidationType::IncompatibleVersion => (
+            "version constraint not matching the workspace package's version",
+            "A package depends on another package of the workspace via a version \
+             constraint (e.g. `shared_ui: ^2.0.0`) that does not allow the \
+             version declared in the dependency's pubspec.yaml (e.g. `version: \
+             1.4.0`). Either the constraint is outdated or the dependency's \
+             version was not bumped on release.\n\n\
+             Fix: update the constraint or the dependency's version, see \
+             `flcheck bump`.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::CheckSettings;
+    use crate::config::HttpSettings;
+    use crate::config::ValidationSetting;
+    use crate::error::ValidationLevel;
+    use crate::error::ValidationType;
+    use crate::rules::Rule;
+    use crate::Config;
+    use std::collections::HashMap;
+
+    #[test]
+    fn configured_levels() {
+        let config = Config {
+            package_types: Vec::new(),
+            blacklist: Vec::new(),
+            validations: vec![ValidationSetting {
+                validation_type: ValidationType::CyclicDependency,
+                level: ValidationLevel::Warning,
+                paths: Vec::new(),
+            }],
+            public_repositories: Vec::new(),
+            public_package_policy: HashMap::new(),
+            internal_repositories: Vec::new(),
+            private_packages: Vec::new(),
+            dev_only_packages: Vec::new(),
+            structure: HashMap::new(),
+            analysis_options: None,
+            constraint_styles: Vec::new(),
+            alternatives: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            roots: Vec::new(),
+            custom_validations: Vec::new(),
+            rules: Vec::new(),
+            codeowners: None,
+            http: HttpSettings::default(),
+            check: CheckSettings::default(),
+        };
+
+        let rules = Rule::all(&config);
+        let cyclic = rules
+            .iter()
+            .find(|rule| rule.code == ValidationType::CyclicDependency)
+            .unwrap();
+
+        assert_eq!(rules.len(), ValidationType::values().len());
+        assert_eq!(cyclic.default_level, ValidationLevel::Error);
+        assert_eq!(cyclic.level, ValidationLevel::Warning);
+    }
+}
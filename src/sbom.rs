@@ -0,0 +1,673 @@
+use crate::dependency::Dependency;
+use crate::util::format_timestamp;
+use crate::util::parse_yaml;
+use crate::Config;
+use crate::FlError;
+use crate::Pubspec;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Lockfile of the resolved dependencies next to a pubspec.yaml.
+pub const LOCK_FILE: &str = "pubspec.lock";
+
+/// Formats of the Software Bill of Materials written by `sbom`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 (JSON)
+    CycloneDx,
+    /// SPDX 2.3 (JSON)
+    Spdx,
+}
+
+impl SbomFormat {
+    pub fn parse(value: &str) -> Option<SbomFormat> {
+        match value {
+            "cyclonedx" => Some(SbomFormat::CycloneDx),
+            "spdx" => Some(SbomFormat::Spdx),
+            _ => None,
+        }
+    }
+}
+
+/// External dependency of the workspace (declared by a package or resolved
+/// in one of the lockfiles).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SbomComponent {
+    pub name: String,
+    /// Version resolved in a `pubspec.lock` (if any)
+    pub version: Option<String>,
+    /// Distinct constraints declared by the workspace packages
+    pub constraints: Vec<String>,
+    /// pub.dev page, hosted URL or git repository of the package
+    pub source: String,
+    /// Package is a git dependency
+    pub git: bool,
+    /// SPDX identifier of the license (if known)
+    pub license: Option<String>,
+}
+
+/// Collect the external dependencies of all `pubspecs`: every package
+/// resolved in their lockfiles (including transitive ones) along with the
+/// declared dependencies that are not resolved in any lockfile. Licenses
+/// are detected from the packages in the local pub cache (if any).
+pub fn sbom_components(config: &Config, pubspecs: &[Pubspec]) -> Vec<SbomComponent> {
+    let is_external = |name: &str| !pubspecs.iter().any(|pubspec| pubspec.name == name);
+    let mut constraints: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut declared: BTreeMap<&str, (String, bool)> = BTreeMap::new();
+
+    for pubspec in pubspecs {
+        for dep in pubspec
+            .dependencies
+            .iter()
+            .chain(pubspec.dev_dependencies.iter())
+            .map(|dep| dep.effective())
+        {
+            let (name, source, git) = match dep {
+                Dependency::PubDev { name, version, .. } => {
+                    let versions = constraints.entry(name).or_default();
+                    if !versions.contains(version) {
+                        versions.push(version.clone());
+                    }
+                    (name, pub_dev_page(name), false)
+                }
+                Dependency::Git { name, git, .. } if !config.is_internal_repo(git) => {
+                    (name, git.clone(), true)
+                }
+                _ => continue,
+            };
+            if is_external(name) {
+                declared.entry(name).or_insert((source, git));
+            }
+        }
+    }
+
+    let mut components = pubspecs
+        .iter()
+        .flat_map(|pubspec| locked_packages(&Path::new(&pubspec.dir_path).join(LOCK_FILE)))
+        .filter(|locked| is_external(&locked.name))
+        .filter(|locked| !(locked.git && config.is_internal_repo(&locked.source)))
+        .collect::<Vec<_>>();
+
+    for (name, (source, git)) in declared {
+        if !components.iter().any(|component| component.name == name) {
+            components.push(SbomComponent {
+                name: name.to_owned(),
+                version: None,
+                constraints: Vec::new(),
+                source,
+                git,
+                license: None,
+            });
+        }
+    }
+
+    components.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    components.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+
+    let pub_cache = pub_cache_dir();
+    for component in components.iter_mut() {
+        component.constraints = constraints
+            .get(component.name.as_str())
+            .cloned()
+            .unwrap_or_default();
+        component.license = pub_cache
+            .as_deref()
+            .and_then(|cache| cached_license(cache, component));
+    }
+
+    components
+}
+
+/// Render the SBOM of the given `components` in `format`, created at
+/// `created`.
+pub fn render_sbom(
+    format: SbomFormat,
+    components: &[SbomComponent],
+    created: SystemTime,
+) -> Result<String, FlError> {
+    let timestamp = format_timestamp(created);
+    let json = match format {
+        SbomFormat::CycloneDx => serde_json::to_string_pretty(&cyclonedx(components, timestamp)),
+        SbomFormat::Spdx => serde_json::to_string_pretty(&spdx(components, timestamp)),
+    };
+
+    json.map_err(|err| FlError::ConfigValidation(format!("cannot serialize SBOM: {}", err)))
+}
+
+fn pub_dev_page(name: &str) -> String {
+    format!("https://pub.dev/packages/{}", name)
+}
+
+/// Read the hosted and git packages resolved in the given lockfile (none if
+/// it does not exist).
+fn locked_packages(file: &Path) -> Vec<SbomComponent> {
+    let Ok(content) = std::fs::read_to_string(file) else {
+        return Vec::new();
+    };
+    let Ok(yaml) = parse_yaml(&content, &file.to_string_lossy()) else {
+        return Vec::new();
+    };
+    let empty = Default::default();
+
+    yaml["packages"]
+        .as_hash()
+        .unwrap_or(&empty)
+        .iter()
+        .flat_map(|(name, package)| {
+            let name = name.as_str()?;
+            let description = &package["description"];
+            let (source, git) = match package["source"].as_str()? {
+                "hosted" => {
+                    let source = match description["url"].as_str() {
+                        Some(url) if !is_pub_dev(url) => {
+                            format!("{}/packages/{}", url.trim_end_matches('/'), name)
+                        }
+                        _ => pub_dev_page(name),
+                    };
+                    (source, false)
+                }
+                "git" => (description["url"].as_str()?.to_owned(), true),
+                _ => return None,
+            };
+
+            Some(SbomComponent {
+                name: name.to_owned(),
+                version: package["version"]
+                    .as_str()
+                    .map(|version| version.to_owned()),
+                constraints: Vec::new(),
+                source,
+                git,
+                license: None,
+            })
+        })
+        .collect()
+}
+
+fn is_pub_dev(url: &str) -> bool {
+    let host = url.trim_start_matches("https://").trim_end_matches('/');
+    host == "pub.dev" || host == "pub.dartlang.org"
+}
+
+/// Directory of the local pub cache (`PUB_CACHE` or `~/.pub-cache`).
+fn pub_cache_dir() -> Option<PathBuf> {
+    std::env::var_os("PUB_CACHE")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".pub-cache")))
+}
+
+/// Detect the license of the given (hosted) package from its `LICENSE` file
+/// in the pub cache.
+fn cached_license(cache: &Path, component: &SbomComponent) -> Option<String> {
+    let version = component.version.as_ref().filter(|_| !component.git)?;
+    let dir_name = format!("{}-{}", component.name, version);
+
+    ["pub.dev", "pub.dartlang.org"]
+        .iter()
+        .map(|host| {
+            cache
+                .join("hosted")
+                .join(host)
+                .join(&dir_name)
+                .join("LICENSE")
+        })
+        .find_map(|file| std::fs::read_to_string(file).ok())
+        .and_then(|text| detect_license(&text))
+        .map(|license| license.to_owned())
+}
+
+/// Determine the SPDX identifier of common license texts.
+fn detect_license(text: &str) -> Option<&'static str> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.contains("Apache License") && text.contains("Version 2.0") {
+        Some("Apache-2.0")
+    } else if text.contains("Permission is hereby granted, free of charge") {
+        Some("MIT")
+    } else if text.contains("Redistribution and use in source and binary forms") {
+        if text.contains("Neither the name") {
+            Some("BSD-3-Clause")
+        } else {
+            Some("BSD-2-Clause")
+        }
+    } else if text.contains("Mozilla Public License Version 2.0") {
+        Some("MPL-2.0")
+    } else {
+        None
+    }
+}
+
+/// Package URL of a hosted package (see https://github.com/package-url/purl-spec).
+fn purl(component: &SbomComponent) -> Option<String> {
+    if component.git {
+        return None;
+    }
+    Some(match &component.version {
+        Some(version) => format!("pkg:pub/{}@{}", component.name, version),
+        None => format!("pkg:pub/{}", component.name),
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDx {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: CycloneDxMetadata,
+    components: Vec<CycloneDxComponent>,
+}
+
+#[derive(Serialize)]
+struct CycloneDxMetadata {
+    timestamp: String,
+    tools: Vec<CycloneDxTool>,
+}
+
+#[derive(Serialize)]
+struct CycloneDxTool {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxComponent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    purl: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<CycloneDxLicense>,
+    external_references: Vec<CycloneDxReference>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    properties: Vec<CycloneDxProperty>,
+}
+
+#[derive(Serialize)]
+struct CycloneDxLicense {
+    license: CycloneDxLicenseId,
+}
+
+#[derive(Serialize)]
+struct CycloneDxLicenseId {
+    id: String,
+}
+
+#[derive(Serialize)]
+struct CycloneDxReference {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct CycloneDxProperty {
+    name: &'static str,
+    value: String,
+}
+
+fn cyclonedx(components: &[SbomComponent], timestamp: String) -> CycloneDx {
+    CycloneDx {
+        bom_format: "CycloneDX",
+        spec_version: "1.5",
+        version: 1,
+        metadata: CycloneDxMetadata {
+            timestamp,
+            tools: vec![CycloneDxTool {
+                name: "flcheck",
+                version: env!("CARGO_PKG_VERSION"),
+            }],
+        },
+        components: components
+            .iter()
+            .map(|component| CycloneDxComponent {
+                kind: "library",
+                bom_ref: match &component.version {
+                    Some(version) => format!("{}@{}", component.name, version),
+                    None => component.name.clone(),
+                },
+                name: component.name.clone(),
+                version: component.version.clone(),
+                purl: purl(component),
+                licenses: component
+                    .license
+                    .iter()
+                    .map(|id| CycloneDxLicense {
+                        license: CycloneDxLicenseId { id: id.clone() },
+                    })
+                    .collect(),
+                external_references: vec![CycloneDxReference {
+                    kind: if component.git { "vcs" } else { "distribution" },
+                    url: component.source.clone(),
+                }],
+                properties: component
+                    .constraints
+                    .iter()
+                    .map(|constraint| CycloneDxProperty {
+                        name: "flcheck:constraint",
+                        value: constraint.clone(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Spdx {
+    spdx_version: &'static str,
+    data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+    name: &'static str,
+    document_namespace: String,
+    creation_info: SpdxCreationInfo,
+    packages: Vec<SpdxPackage>,
+}
+
+#[derive(Serialize)]
+struct SpdxCreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    name: String,
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_info: Option<String>,
+    download_location: String,
+    files_analyzed: bool,
+    license_concluded: &'static str,
+    license_declared: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_refs: Vec<SpdxReference>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxReference {
+    reference_category: &'static str,
+    reference_type: &'static str,
+    reference_locator: String,
+}
+
+fn spdx(components: &[SbomComponent], created: String) -> Spdx {
+    // identifiers may contain letters, digits, `.` and `-` only
+    let spdx_id = |component: &SbomComponent| {
+        let id = match &component.version {
+            Some(version) => format!("{}-{}", component.name, version),
+            None => component.name.clone(),
+        };
+        let id = id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+        format!("SPDXRef-Package-{}", id)
+    };
+
+    Spdx {
+        spdx_version: "SPDX-2.3",
+        data_license: "CC0-1.0",
+        spdx_id: "SPDXRef-DOCUMENT",
+        name: "flcheck-sbom",
+        document_namespace: format!("https://spdx.org/spdxdocs/flcheck-sbom-{}", created),
+        creation_info: SpdxCreationInfo {
+            created,
+            creators: vec![format!("Tool: flcheck-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        packages: components
+            .iter()
+            .map(|component| SpdxPackage {
+                name: component.name.clone(),
+                spdx_id: spdx_id(component),
+                version_info: component.version.clone(),
+                download_location: if component.git {
+                    format!("git+{}", component.source)
+                } else {
+                    component.source.clone()
+                },
+                files_analyzed: false,
+                license_concluded: "NOASSERTION",
+                license_declared: component
+                    .license
+                    .clone()
+                    .unwrap_or_else(|| "NOASSERTION".to_owned()),
+                comment: Some(component.constraints.join(", "))
+                    .filter(|constraints| !constraints.is_empty())
+                    .map(|constraints| format!("declared constraints: {}", constraints)),
+                external_refs: purl(component)
+                    .into_iter()
+                    .map(|purl| SpdxReference {
+                        reference_category: "PACKAGE-MANAGER",
+                        reference_type: "purl",
+                        reference_locator: purl,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::dependency::Dependency;
+    use crate::sbom::detect_license;
+    use crate::sbom::render_sbom;
+    use crate::sbom::sbom_components;
+    use crate::sbom::SbomComponent;
+    use crate::sbom::SbomFormat;
+    use crate::Pubspec;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    #[test]
+    fn components() {
+        let root = std::env::temp_dir().join(format!("flcheck-sbom-{}", std::process::id()));
+        let app_dir = root.join("app_foo");
+        std::fs::create_dir_all(&app_dir).unwrap();
+        std::fs::write(
+            app_dir.join("pubspec.lock"),
+            r#"
+packages:
+  http:
+    dependency: "direct main"
+    description:
+      name: http
+      url: "https://pub.dev"
+    source: hosted
+    version: "1.2.0"
+  http_parser:
+    dependency: transitive
+    description:
+      name: http_parser
+      url: "https://pub.dev"
+    source: hosted
+    version: "4.0.2"
+  company_lints:
+    dependency: "direct dev"
+    description:
+      name: company_lints
+      url: "https://dart.company.com/"
+    source: hosted
+    version: "2.0.0"
+  pkg_core:
+    dependency: "direct main"
+    description:
+      path: "../pkg_core"
+      relative: true
+    source: path
+    version: "1.0.0"
+  flutter:
+    dependency: "direct main"
+    description: flutter
+    source: sdk
+    version: "0.0.0"
+"#,
+        )
+        .unwrap();
+
+        let pubdev = |name: &str, version: &str| Dependency::PubDev {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            overridden: Box::new(None),
+        };
+        let app = Pubspec {
+            dependencies: vec![
+                pubdev("http", "^1.0.0"),
+                Dependency::Local {
+                    name: "pkg_core".to_owned(),
+                    path: "../pkg_core".to_owned(),
+                    overridden: Box::new(None),
+                },
+            ],
+            ..Pubspec::parse(
+                &app_dir.join("pubspec.yaml").to_string_lossy(),
+                "name: app_foo",
+            )
+            .unwrap()
+        };
+        let core = Pubspec {
+            dependencies: vec![
+                pubdev("http", "^1.1.0"),
+                Dependency::Git {
+                    name: "charts".to_owned(),
+                    git: "https://github.com/org/charts.git".to_owned(),
+                    path: String::new(),
+                    overridden: Box::new(None),
+                },
+            ],
+            ..Pubspec::parse(
+                &root.join("pkg_core/pubspec.yaml").to_string_lossy(),
+                "name: pkg_core",
+            )
+            .unwrap()
+        };
+
+        let components = sbom_components(&Config::default(), &[app, core]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let summary = components
+            .iter()
+            .map(|component| {
+                (
+                    component.name.as_str(),
+                    component.version.as_deref(),
+                    component.constraints.join(" "),
+                    component.source.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "charts",
+                    None,
+                    String::new(),
+                    "https://github.com/org/charts.git"
+                ),
+                (
+                    "company_lints",
+                    Some("2.0.0"),
+                    String::new(),
+                    "https://dart.company.com/packages/company_lints"
+                ),
+                (
+                    "http",
+                    Some("1.2.0"),
+                    "^1.0.0 ^1.1.0".to_owned(),
+                    "https://pub.dev/packages/http"
+                ),
+                (
+                    "http_parser",
+                    Some("4.0.2"),
+                    String::new(),
+                    "https://pub.dev/packages/http_parser"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let components = vec![SbomComponent {
+            name: "http".to_owned(),
+            version: Some("1.2.0".to_owned()),
+            constraints: vec!["^1.0.0".to_owned()],
+            source: "https://pub.dev/packages/http".to_owned(),
+            git: false,
+            license: Some("BSD-3-Clause".to_owned()),
+        }];
+        let created = SystemTime::UNIX_EPOCH + Duration::from_secs(1706702400);
+
+        let cyclonedx: serde_json::Value = serde_json::from_str(
+            &render_sbom(SbomFormat::CycloneDx, &components, created).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cyclonedx["bomFormat"], "CycloneDX");
+        assert_eq!(cyclonedx["metadata"]["timestamp"], "2024-01-31T12:00:00Z");
+        assert_eq!(cyclonedx["components"][0]["purl"], "pkg:pub/http@1.2.0");
+        assert_eq!(
+            cyclonedx["components"][0]["licenses"][0]["license"]["id"],
+            "BSD-3-Clause"
+        );
+        assert_eq!(
+            cyclonedx["components"][0]["properties"][0]["value"],
+            "^1.0.0"
+        );
+
+        let spdx: serde_json::Value =
+            serde_json::from_str(&render_sbom(SbomFormat::Spdx, &components, created).unwrap())
+                .unwrap();
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(spdx["packages"][0]["SPDXID"], "SPDXRef-Package-http-1.2.0");
+        assert_eq!(spdx["packages"][0]["licenseDeclared"], "BSD-3-Clause");
+        assert_eq!(
+            spdx["packages"][0]["externalRefs"][0]["referenceLocator"],
+            "pkg:pub/http@1.2.0"
+        );
+    }
+
+    #[test]
+    fn licenses() {
+        assert_eq!(
+            detect_license("Permission is hereby granted, free\nof charge, to any person"),
+            Some("MIT")
+        );
+        assert_eq!(
+            detect_license(
+                "Redistribution and use in source and binary forms ... Neither the name of Google"
+            ),
+            Some("BSD-3-Clause")
+        );
+        assert_eq!(
+            detect_license("Apache License\n   Version 2.0, January 2004"),
+            Some("Apache-2.0")
+        );
+        assert_eq!(detect_license("All rights reserved."), None);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::command::validation_findings;
+use crate::error::FlError;
+use crate::graph::Graph;
+use crate::graph::GraphOptions;
+use crate::render::ReportRenderer;
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::Pubspec;
+use crate::Renderer;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+use std::io;
+use std::time::Duration;
+
+/// Interval the pubspecs of the served workspace are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// error codes defined by JSON-RPC 2.0
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// Configuration and packages of the workspace kept in memory by the
+/// `serve` command.
+pub struct ServerState {
+    pub config: Config,
+    pub pubspecs: Vec<Pubspec>,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    /// Requests without `id` are notifications that are not answered
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Parameters of the `validate`, `dependents` and `graph` methods.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Params {
+    /// Packages to validate (all if empty)
+    packages: Vec<String>,
+    package: Option<String>,
+    /// Include external dependencies in the graph
+    externals: bool,
+}
+
+/// Error of a request along with its JSON-RPC error code.
+struct RequestError(i64, String);
+
+impl From<FlError> for RequestError {
+    fn from(err: FlError) -> Self {
+        RequestError(SERVER_ERROR, err.to_string())
+    }
+}
+
+impl From<io::Error> for RequestError {
+    fn from(err: io::Error) -> Self {
+        RequestError(SERVER_ERROR, err.to_string())
+    }
+}
+
+/// Whether the given request `line` asks the server to stop.
+pub fn is_shutdown(line: &str) -> bool {
+    serde_json::from_str::<Request>(line).is_ok_and(|request| request.method == "shutdown")
+}
+
+/// Handle the JSON-RPC 2.0 request `line` against the served `state` and
+/// return the response (if any).
+///
+/// Supported methods are `validate` (findings of all or the given
+/// `packages`), `dependents` (packages transitively depending on the given
+/// `package`), `graph` (internal dependency graph, including external
+/// dependencies with `externals`) and `shutdown`. Results are the reports of
+/// the JSON output of the respective commands.
+pub fn handle_request(line: &str, state: &ServerState) -> Option<Value> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(value) => match serde_json::from_value::<Request>(value) {
+            Ok(request) => request,
+            Err(err) => return Some(error_response(Value::Null, INVALID_REQUEST, err)),
+        },
+        Err(err) => return Some(error_response(Value::Null, PARSE_ERROR, err)),
+    };
+
+    let result = serde_json::from_value::<Params>(match request.params {
+        Value::Null => json!({}),
+        params => params,
+    })
+    .map_err(|err| RequestError(INVALID_PARAMS, err.to_string()))
+    .and_then(|params| dispatch(&request.method, &params, state));
+
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RequestError(code, message)) => error_response(id, code, message),
+    })
+}
+
+fn dispatch(method: &str, params: &Params, state: &ServerState) -> Result<Value, RequestError> {
+    let pubspecs = &state.pubspecs;
+
+    match method {
+        "validate" => {
+            let selected = pubspecs
+                .iter()
+                .filter(|pubspec| {
+                    params.packages.is_empty() || params.packages.contains(&pubspec.name)
+                })
+                .collect::<Vec<_>>();
+            if let Some(unknown) = params
+                .packages
+                .iter()
+                .find(|name| !selected.iter().any(|pubspec| &pubspec.name == *name))
+            {
+                return Err(unknown_package(unknown));
+            }
+
+            let validations =
+                validation_findings(&state.config, &selected, &Workspace::new(pubspecs))?;
+            report(|renderer| renderer.render_validations(&validations))
+        }
+        "dependents" => {
+            let name = params.package.as_deref().ok_or_else(|| {
+                RequestError(INVALID_PARAMS, "missing parameter 'package'".to_owned())
+            })?;
+            let workspace = Workspace::new(pubspecs);
+            let package = *workspace
+                .by_name
+                .get(name)
+                .ok_or_else(|| unknown_package(name))?;
+
+            let dependents = workspace
+                .affected(&[package])
+                .into_iter()
+                .skip(1)
+                .map(|idx| pubspecs[idx].name.as_str())
+                .collect::<Vec<_>>();
+            Ok(json!({ "package": name, "dependents": dependents }))
+        }
+        "graph" => {
+            let options = GraphOptions {
+                externals: params.externals,
+                external_filter: None,
+            };
+            let graph = Graph::from_workspace(&Workspace::new(pubspecs), &options);
+            report(|renderer| renderer.render_graph(&graph))
+        }
+        "shutdown" => Ok(Value::Null),
+        _ => Err(RequestError(
+            METHOD_NOT_FOUND,
+            format!("unknown method '{}'", method),
+        )),
+    }
+}
+
+/// Render a report via the JSON renderer.
+fn report<F>(render: F) -> Result<Value, RequestError>
+where
+    F: FnOnce(&mut dyn Renderer) -> io::Result<()>,
+{
+    let mut out = Vec::new();
+    render(&mut ReportRenderer::new(&mut out))?;
+
+    serde_json::from_slice(&out).map_err(|err| RequestError(SERVER_ERROR, err.to_string()))
+}
+
+fn unknown_package(name: &str) -> RequestError {
+    RequestError(INVALID_PARAMS, format!("unknown package '{}'", name))
+}
+
+fn error_response(id: Value, code: i64, message: impl ToString) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.to_string() }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serve::handle_request;
+    use crate::serve::is_shutdown;
+    use crate::serve::ServerState;
+    use crate::Config;
+    use crate::Pubspec;
+
+    #[test]
+    fn requests() {
+        let state = ServerState {
+            config: Config::default(),
+            pubspecs: vec![
+                Pubspec::test_at("pkg_core", "/ws/pkg_core"),
+                Pubspec::test_at("pkg_ui", "/ws/pkg_ui").with_local_deps(&["pkg_core"]),
+                Pubspec::test_at("app_foo", "/ws/app_foo").with_local_deps(&["pkg_ui"]),
+            ],
+        };
+        let request = |line: &str| handle_request(line, &state).unwrap();
+
+        let response = request(
+            r#"{"jsonrpc":"2.0","id":1,"method":"dependents","params":{"package":"pkg_core"}}"#,
+        );
+        assert_eq!(
+            response["result"],
+            serde_json::json!({ "package": "pkg_core", "dependents": ["pkg_ui", "app_foo"] })
+        );
+
+        let response = request(r#"{"jsonrpc":"2.0","id":2,"method":"graph"}"#);
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["result"]["kind"], "graph");
+        assert_eq!(response["result"]["nodes"].as_array().unwrap().len(), 3);
+
+        let response = request(
+            r#"{"jsonrpc":"2.0","id":3,"method":"validate","params":{"packages":["app_foo"]}}"#,
+        );
+        assert_eq!(response["result"]["kind"], "validations");
+
+        let response = request(
+            r#"{"jsonrpc":"2.0","id":4,"method":"validate","params":{"packages":["unknown"]}}"#,
+        );
+        assert_eq!(response["error"]["code"], -32602);
+
+        let response = request(r#"{"jsonrpc":"2.0","id":5,"method":"publish"}"#);
+        assert_eq!(response["error"]["code"], -32601);
+        assert_eq!(request("{")["error"]["code"], -32700);
+
+        // notifications are not answered
+        assert!(handle_request(r#"{"jsonrpc":"2.0","method":"graph"}"#, &state).is_none());
+        assert!(is_shutdown(
+            r#"{"jsonrpc":"2.0","id":6,"method":"shutdown"}"#
+        ));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::error::FlError;
+use crate::error::FlError::InvalidSnapshot;
+use crate::graph::Graph;
+use crate::graph::GraphOptions;
+use crate::workspace::Workspace;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Version of the snapshot file format.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Snapshot file written by `snapshot write` unless given otherwise.
+pub const SNAPSHOT_FILE: &str = "workspace.lock.json";
+
+/// Internal dependency graph of the workspace as recorded in the snapshot
+/// file. Packages and dependencies are sorted to keep changes reviewable.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Snapshot {
+    pub version: u32,
+    pub packages: Vec<String>,
+    pub dependencies: Vec<SnapshotDependency>,
+}
+
+/// Dependency between two packages of the workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SnapshotDependency {
+    pub from: String,
+    pub to: String,
+    /// Dependency is part of the dev_dependencies
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dev: bool,
+}
+
+/// Differences between the snapshot and the actual workspace.
+#[derive(Debug, Default, Serialize, JsonSchema, PartialEq)]
+pub struct SnapshotDrift {
+    pub added_packages: Vec<String>,
+    pub removed_packages: Vec<String>,
+    pub added_dependencies: Vec<SnapshotDependency>,
+    pub removed_dependencies: Vec<SnapshotDependency>,
+}
+
+impl SnapshotDrift {
+    /// Number of differences in total.
+    pub fn len(&self) -> usize {
+        self.added_packages.len()
+            + self.removed_packages.len()
+            + self.added_dependencies.len()
+            + self.removed_dependencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Snapshot {
+    /// Capture the (effective) local and git dependencies between the
+    /// packages of the workspace.
+    pub fn from_pubspecs(pubspecs: &[Pubspec]) -> Snapshot {
+        let graph = Graph::from_workspace(&Workspace::new(pubspecs), &GraphOptions::default());
+
+        let mut packages = graph.nodes.clone();
+        packages.sort();
+        packages.dedup();
+
+        let mut dependencies = graph
+            .edges
+            .iter()
+            .filter(|edge| packages.contains(&edge.to))
+            .map(|edge| SnapshotDependency {
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                dev: edge.dev,
+            })
+            .collect::<Vec<_>>();
+        dependencies.sort();
+        dependencies.dedup();
+
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            packages,
+            dependencies,
+        }
+    }
+
+    /// Load the snapshot from the given `file`.
+    pub fn load(file: &str) -> Result<Snapshot, FlError> {
+        let content = std::fs::read_to_string(file)?;
+        let snapshot: Snapshot = serde_json::from_str(&content)
+            .map_err(|err| InvalidSnapshot(file.to_owned(), err.to_string()))?;
+
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(InvalidSnapshot(
+                file.to_owned(),
+                format!(
+                    "unsupported snapshot version {} (supported: {})",
+                    snapshot.version, SNAPSHOT_VERSION
+                ),
+            ));
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Serialize the snapshot as (pretty printed) JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+
+    /// Determine the differences of the `actual` workspace compared to
+    /// this snapshot.
+    pub fn drift(&self, actual: &Snapshot) -> SnapshotDrift {
+        let missing = |a: &[String], b: &[String]| {
+            a.iter()
+                .filter(|name| !b.contains(name))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let missing_deps = |a: &[SnapshotDependency], b: &[SnapshotDependency]| {
+            a.iter()
+                .filter(|dep| !b.contains(dep))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        SnapshotDrift {
+            added_packages: missing(&actual.packages, &self.packages),
+            removed_packages: missing(&self.packages, &actual.packages),
+            added_dependencies: missing_deps(&actual.dependencies, &self.dependencies),
+            removed_dependencies: missing_deps(&self.dependencies, &actual.dependencies),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::Snapshot;
+    use crate::snapshot::SnapshotDependency;
+    use crate::Pubspec;
+
+    fn dep(from: &str, to: &str, dev: bool) -> SnapshotDependency {
+        SnapshotDependency {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            dev,
+        }
+    }
+
+    #[test]
+    fn snapshot_and_drift() {
+        let snapshot = Snapshot::from_pubspecs(&[
+            Pubspec::test("pkg_core").with_local_dev_deps(&["pkg_test"]),
+            Pubspec::test("app_foo").with_local_deps(&["pkg_core", "outside"]),
+            Pubspec::test("pkg_test"),
+        ]);
+
+        assert_eq!(snapshot.packages, vec!["app_foo", "pkg_core", "pkg_test"]);
+        assert_eq!(
+            snapshot.dependencies,
+            vec![
+                dep("app_foo", "pkg_core", false),
+                dep("pkg_core", "pkg_test", true)
+            ]
+        );
+        assert_eq!(
+            serde_json::from_str::<Snapshot>(&snapshot.to_json()).unwrap(),
+            snapshot
+        );
+
+        let actual = Snapshot::from_pubspecs(&[
+            Pubspec::test("pkg_core").with_local_dev_deps(&["pkg_test"]),
+            Pubspec::test("app_foo").with_local_deps(&["pkg_core", "pkg_ui"]),
+            Pubspec::test("pkg_ui"),
+        ]);
+        let drift = snapshot.drift(&actual);
+
+        assert_eq!(drift.added_packages, vec!["pkg_ui"]);
+        assert_eq!(drift.removed_packages, vec!["pkg_test"]);
+        assert_eq!(
+            drift.added_dependencies,
+            vec![dep("app_foo", "pkg_ui", false)]
+        );
+        assert_eq!(
+            drift.removed_dependencies,
+            vec![dep("pkg_core", "pkg_test", true)]
+        );
+        assert_eq!(drift.len(), 4);
+        assert!(snapshot.drift(&snapshot).is_empty());
+    }
+}
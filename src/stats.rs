@@ -0,0 +1,289 @@
+use crate::dependency::Dependency;
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// Number of packages listed as "most depended upon".
+const MAX_DEPENDED_UPON: usize = 10;
+
+/// Name used for packages that do not match any configured package type.
+pub const UNKNOWN_PACKAGE_TYPE: &str = "<none>";
+
+/// Number of dependencies by source.
+#[derive(Debug, Default, Serialize, JsonSchema, PartialEq)]
+pub struct DependencyCounts {
+    pub local: usize,
+    pub git: usize,
+    pub pubdev: usize,
+}
+
+impl DependencyCounts {
+    fn count(deps: &[Dependency]) -> DependencyCounts {
+        let mut counts = DependencyCounts::default();
+        for dep in deps {
+            match dep.effective() {
+                Dependency::Local { .. } => counts.local += 1,
+                Dependency::Git { .. } => counts.git += 1,
+                Dependency::PubDev { .. } => counts.pubdev += 1,
+            }
+        }
+        counts
+    }
+
+    fn add(&mut self, other: DependencyCounts) {
+        self.local += other.local;
+        self.git += other.git;
+        self.pubdev += other.pubdev;
+    }
+}
+
+/// Internal package along with the number of packages depending on it.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct Dependents {
+    pub name: String,
+    pub dependents: usize,
+}
+
+/// Workspace metrics as printed by the `stats` command.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Stats {
+    pub packages: usize,
+    pub package_types: BTreeMap<String, usize>,
+    pub dependencies: DependencyCounts,
+    pub dev_dependencies: DependencyCounts,
+    /// Number of distinct external (pub.dev) packages
+    pub external_packages: usize,
+    pub most_depended_upon: Vec<Dependents>,
+    pub average_depth: f64,
+    pub max_depth: usize,
+    /// Number of flutter plugin packages
+    pub plugins: usize,
+    /// Number of plugin packages supporting each platform
+    pub platforms: BTreeMap<String, usize>,
+    /// Number of packages per owner (see `flcheck: owner`)
+    pub owners: BTreeMap<String, usize>,
+    /// Number of packages per tag (see `flcheck: tags`)
+    pub tags: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    /// Collect the statistics of all given pubspecs.
+    pub fn collect(config: &Config, pubspecs: &[Pubspec]) -> Stats {
+        let mut package_types = BTreeMap::new();
+        let mut dependencies = DependencyCounts::default();
+        let mut dev_dependencies = DependencyCounts::default();
+
+        for pubspec in pubspecs {
+            let package_type = pubspec
+                .package_type(config)
+                .map_or(UNKNOWN_PACKAGE_TYPE, |pkg_type| pkg_type.name.as_str());
+            *package_types.entry(package_type.to_owned()).or_insert(0) += 1;
+
+            dependencies.add(DependencyCounts::count(&pubspec.dependencies));
+            dev_dependencies.add(DependencyCounts::count(&pubspec.dev_dependencies));
+        }
+
+        let mut platforms = BTreeMap::new();
+        for platform in pubspecs.iter().flat_map(|pubspec| pubspec.platforms.iter()) {
+            *platforms.entry(platform.name.clone()).or_insert(0) += 1;
+        }
+
+        let mut owners = BTreeMap::new();
+        for owner in pubspecs.iter().flat_map(|pubspec| pubspec.owner.as_ref()) {
+            *owners.entry(owner.clone()).or_insert(0) += 1;
+        }
+
+        let mut tags = BTreeMap::new();
+        for tag in pubspecs.iter().flat_map(|pubspec| pubspec.tags.iter()) {
+            *tags.entry(tag.clone()).or_insert(0) += 1;
+        }
+
+        let external_packages = pubspecs
+            .iter()
+            .flat_map(|pubspec| pubspec.dependencies.iter())
+            .filter(|dep| dep.effective().is_pubdev())
+            .map(|dep| dep.name())
+            .collect::<HashSet<_>>()
+            .len();
+
+        let edges = internal_edges(&Workspace::new(pubspecs));
+
+        let mut dependents = vec![0usize; pubspecs.len()];
+        for targets in edges.iter() {
+            for target in targets {
+                dependents[*target] += 1;
+            }
+        }
+
+        let mut most_depended_upon = dependents
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(idx, count)| Dependents {
+                name: pubspecs[idx].name.clone(),
+                dependents: *count,
+            })
+            .collect::<Vec<_>>();
+        most_depended_upon
+            .sort_by(|a, b| b.dependents.cmp(&a.dependents).then(a.name.cmp(&b.name)));
+        most_depended_upon.truncate(MAX_DEPENDED_UPON);
+
+        let mut memo = HashMap::new();
+        let depths = (0..pubspecs.len())
+            .map(|idx| depth(idx, &edges, &mut memo, &mut Vec::new()).0)
+            .collect::<Vec<_>>();
+
+        let max_depth = depths.iter().copied().max().unwrap_or(0);
+        let average_depth = if depths.is_empty() {
+            0.0
+        } else {
+            depths.iter().sum::<usize>() as f64 / depths.len() as f64
+        };
+
+        Stats {
+            packages: pubspecs.len(),
+            package_types,
+            dependencies,
+            dev_dependencies,
+            external_packages,
+            most_depended_upon,
+            average_depth,
+            max_depth,
+            plugins: pubspecs
+                .iter()
+                .filter(|pubspec| !pubspec.platforms.is_empty())
+                .count(),
+            platforms,
+            owners,
+            tags,
+        }
+    }
+}
+
+/// Determine the indices of all internal packages every package
+/// depends on (regular dependencies only).
+fn internal_edges(workspace: &Workspace) -> Vec<Vec<usize>> {
+    (0..workspace.packages.len())
+        .map(|idx| workspace.dependencies(idx, false).collect())
+        .collect()
+}
+
+/// Length of the longest internal dependency chain starting at package `idx`.
+///
+/// Cyclic dependencies are not followed, which is why the result is flagged
+/// as truncated (and not memoized) whenever a cycle was encountered.
+fn depth(
+    idx: usize,
+    edges: &[Vec<usize>],
+    memo: &mut HashMap<usize, usize>,
+    path: &mut Vec<usize>,
+) -> (usize, bool) {
+    if let Some(known) = memo.get(&idx) {
+        return (*known, false);
+    }
+
+    path.push(idx);
+    let mut max_child = 0;
+    let mut truncated = false;
+    for target in edges[idx].iter() {
+        if path.contains(target) {
+            truncated = true;
+        } else {
+            let (child, child_truncated) = depth(*target, edges, memo, path);
+            max_child = max_child.max(child + 1);
+            truncated |= child_truncated;
+        }
+    }
+    path.pop();
+
+    if !truncated {
+        memo.insert(idx, max_child);
+    }
+    (max_child, truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PackageType;
+    use crate::stats::DependencyCounts;
+    use crate::stats::Dependents;
+    use crate::stats::Stats;
+    use crate::Config;
+    use crate::Pubspec;
+
+    fn config() -> Config {
+        Config {
+            package_types: vec![PackageType {
+                name: "package".to_owned(),
+                prefixes: vec!["pkg_".to_owned()],
+                includes: vec!["package".to_owned()],
+                max_depth: None,
+                requires: Vec::new(),
+                pure_dart: false,
+                require_tests: false,
+            }],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn collect_stats() {
+        let all = vec![
+            Pubspec::test("app")
+                .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+                .with_local_deps(&["pkg_a", "pkg_b"]),
+            Pubspec::test("pkg_a")
+                .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+                .with_local_deps(&["pkg_b"]),
+            Pubspec::test("pkg_b").with_deps(vec![Pubspec::hosted("path", "^1.0.0")]),
+        ];
+
+        let stats = Stats::collect(&config(), &all);
+
+        assert_eq!(stats.packages, 3);
+        assert_eq!(stats.package_types.get("package"), Some(&2));
+        assert_eq!(stats.package_types.get("<none>"), Some(&1));
+        assert_eq!(
+            stats.dependencies,
+            DependencyCounts {
+                local: 3,
+                git: 0,
+                pubdev: 3
+            }
+        );
+        assert_eq!(stats.external_packages, 2);
+        assert_eq!(
+            stats.most_depended_upon,
+            vec![
+                Dependents {
+                    name: "pkg_b".to_owned(),
+                    dependents: 2
+                },
+                Dependents {
+                    name: "pkg_a".to_owned(),
+                    dependents: 1
+                }
+            ]
+        );
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.average_depth, 1.0);
+    }
+
+    #[test]
+    fn cyclic_depth() {
+        let all = vec![
+            Pubspec::test("pkg_a").with_local_deps(&["pkg_b"]),
+            Pubspec::test("pkg_b").with_local_deps(&["pkg_a"]),
+        ];
+
+        let stats = Stats::collect(&config(), &all);
+
+        assert_eq!(stats.max_depth, 1);
+        assert_eq!(stats.average_depth, 1.0);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::workspace::Workspace;
+use crate::Config;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Includes to add to a package type in order to allow all (currently not
+/// allowed) dependencies of its packages.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct IncludeSuggestion {
+    pub package_type: String,
+    /// Currently configured includes
+    pub includes: Vec<String>,
+    /// Package types to include additionally
+    pub added: Vec<String>,
+}
+
+/// Determine the minimal changes of the package types' includes that
+/// allow all dependencies of the workspace.
+///
+/// Dependencies on packages that match no package type cannot be allowed
+/// by any include and are skipped.
+pub fn suggest_includes(config: &Config, pubspecs: &[Pubspec]) -> Vec<IncludeSuggestion> {
+    let mut suggestions: Vec<IncludeSuggestion> = Vec::new();
+
+    let workspace = Workspace::new(pubspecs);
+
+    for pubspec in pubspecs {
+        if config.is_blacklisted(&pubspec.path) {
+            continue;
+        }
+
+        let Some(pkg_type) = pubspec.package_type(config) else {
+            continue;
+        };
+
+        for dep in pubspec.dependencies.iter() {
+            if (dep.is_pubdev() || dep.is_git()) && !pubspec.is_internal_dependency(dep, config) {
+                continue;
+            }
+
+            let dep_type = pubspec
+                .resolve_internal(dep, config, &workspace)
+                .filter(|resolved| !pubspec.allows_dependency(resolved, config))
+                .and_then(|resolved| resolved.package_type(config));
+
+            let Some(dep_type) = dep_type else {
+                continue;
+            };
+
+            let idx = match suggestions
+                .iter()
+                .position(|suggestion| suggestion.package_type == pkg_type.name)
+            {
+                Some(idx) => idx,
+                None => {
+                    suggestions.push(IncludeSuggestion {
+                        package_type: pkg_type.name.clone(),
+                        includes: pkg_type.includes.clone(),
+                        added: Vec::new(),
+                    });
+                    suggestions.len() - 1
+                }
+            };
+
+            let suggestion = &mut suggestions[idx];
+            if !suggestion.added.contains(&dep_type.name) {
+                suggestion.added.push(dep_type.name.clone());
+            }
+        }
+    }
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::PackageType;
+    use crate::suggest::suggest_includes;
+    use crate::suggest::IncludeSuggestion;
+    use crate::Config;
+    use crate::Pubspec;
+
+    fn pkg_type(name: &str, prefix: &str, includes: &[&str]) -> PackageType {
+        PackageType {
+            name: name.to_owned(),
+            prefixes: vec![prefix.to_owned()],
+            includes: includes.iter().map(|include| include.to_string()).collect(),
+            max_depth: None,
+            requires: Vec::new(),
+            pure_dart: false,
+            require_tests: false,
+        }
+    }
+
+    #[test]
+    fn suggestions() {
+        let config = Config {
+            package_types: vec![
+                pkg_type("app", "app_", &["package"]),
+                pkg_type("shared", "shared_", &[]),
+                pkg_type("package", "pkg_", &[]),
+            ],
+            ..Default::default()
+        };
+        let all = vec![
+            Pubspec::test("app_foo").with_local_deps(&[
+                "pkg_core",
+                "shared_ui",
+                "app_bar",
+                "other",
+            ]),
+            Pubspec::test("app_bar").with_local_deps(&["shared_ui"]),
+            Pubspec::test("pkg_core"),
+            Pubspec::test("shared_ui"),
+            Pubspec::test("other"),
+        ];
+
+        assert_eq!(
+            suggest_includes(&config, &all),
+            vec![IncludeSuggestion {
+                package_type: "app".to_owned(),
+                includes: vec!["package".to_owned()],
+                added: vec!["shared".to_owned(), "app".to_owned()],
+            }]
+        );
+    }
+}
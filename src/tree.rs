@@ -0,0 +1,180 @@
+use crate::dependency::Dependency;
+use crate::workspace::Workspace;
+use crate::Pubspec;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Special conditions of a node in the dependency tree.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeMarker {
+    /// The package was already expanded before
+    Duplicate,
+    /// The package is part of a cyclic dependency
+    Cycle,
+    /// The (local) dependency could not be resolved
+    Unresolved,
+}
+
+/// Single node of a dependency tree.
+#[derive(Debug, Serialize, JsonSchema, PartialEq)]
+pub struct TreeNode {
+    pub name: String,
+    /// Dependency details of external packages
+    pub details: Option<String>,
+    pub marker: Option<TreeMarker>,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn leaf(name: &str, details: Option<String>, marker: Option<TreeMarker>) -> TreeNode {
+        TreeNode {
+            name: name.to_owned(),
+            details,
+            marker,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Build the dependency tree of the given `root` package.
+///
+/// Packages that were expanded already are marked as duplicates, cyclic
+/// dependencies are marked as such. External dependencies are included
+/// only if `externals` is set.
+pub fn dependency_tree<'a>(
+    root: &'a Pubspec,
+    workspace: &Workspace<'a>,
+    externals: bool,
+) -> TreeNode {
+    let mut expanded = HashSet::new();
+    build(root, workspace, externals, &mut vec![], &mut expanded)
+}
+
+/// Determine the root packages of the workspace, meaning all packages
+/// no other package depends on (or all packages if there are none).
+pub fn root_packages<'a>(workspace: &Workspace<'a>) -> Vec<&'a Pubspec> {
+    let pubspecs = workspace.packages;
+    let dependencies = (0..pubspecs.len())
+        .flat_map(|idx| workspace.dependencies(idx, false))
+        .collect::<HashSet<_>>();
+
+    let roots = pubspecs
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !dependencies.contains(idx))
+        .map(|(_, pubspec)| pubspec)
+        .collect::<Vec<_>>();
+
+    if roots.is_empty() {
+        pubspecs.iter().collect()
+    } else {
+        roots
+    }
+}
+
+fn build<'a>(
+    pubspec: &'a Pubspec,
+    workspace: &Workspace<'a>,
+    externals: bool,
+    path: &mut Vec<&'a str>,
+    expanded: &mut HashSet<&'a str>,
+) -> TreeNode {
+    path.push(pubspec.dir_path.as_str());
+    expanded.insert(pubspec.dir_path.as_str());
+
+    let mut children = Vec::new();
+    for dep in pubspec.dependencies.iter() {
+        match (dep.effective(), pubspec.resolve_dependency(dep, workspace)) {
+            (_, Some(resolved)) => {
+                let dir = resolved.dir_path.as_str();
+                let child = if path.contains(&dir) {
+                    TreeNode::leaf(&resolved.name, None, Some(TreeMarker::Cycle))
+                } else if expanded.contains(dir) {
+                    TreeNode::leaf(&resolved.name, None, Some(TreeMarker::Duplicate))
+                } else {
+                    build(resolved, workspace, externals, path, expanded)
+                };
+                children.push(child);
+            }
+            (Dependency::Local { .. }, None) => {
+                children.push(TreeNode::leaf(
+                    dep.name(),
+                    None,
+                    Some(TreeMarker::Unresolved),
+                ));
+            }
+            (effective, None) => {
+                if externals {
+                    children.push(TreeNode::leaf(dep.name(), Some(effective.details()), None));
+                }
+            }
+        }
+    }
+
+    path.pop();
+
+    TreeNode {
+        name: pubspec.name.clone(),
+        details: None,
+        marker: None,
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::dependency_tree;
+    use crate::tree::root_packages;
+    use crate::tree::TreeMarker;
+    use crate::workspace::Workspace;
+    use crate::Pubspec;
+
+    #[test]
+    fn duplicate_dependencies() {
+        let all = vec![
+            Pubspec::test("app")
+                .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+                .with_local_deps(&["pkg_a", "pkg_b"]),
+            Pubspec::test("pkg_a").with_local_deps(&["pkg_b"]),
+            Pubspec::test("pkg_b"),
+        ];
+
+        let tree = dependency_tree(&all[0], &Workspace::new(&all), false);
+
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.children[0].children[0].name, "pkg_b");
+        assert_eq!(tree.children[0].children[0].marker, None);
+        assert_eq!(tree.children[1].marker, Some(TreeMarker::Duplicate));
+    }
+
+    #[test]
+    fn cyclic_dependencies() {
+        let all = vec![
+            Pubspec::test("pkg_a").with_local_deps(&["pkg_b"]),
+            Pubspec::test("pkg_b").with_local_deps(&["pkg_a"]),
+        ];
+
+        let tree = dependency_tree(&all[0], &Workspace::new(&all), false);
+
+        assert_eq!(tree.children[0].children[0].marker, Some(TreeMarker::Cycle));
+        assert_eq!(root_packages(&Workspace::new(&all)).len(), 2);
+    }
+
+    #[test]
+    fn external_dependencies() {
+        let all = vec![Pubspec::test("app")
+            .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+            .with_local_deps(&["missing"])];
+
+        let tree = dependency_tree(&all[0], &Workspace::new(&all), true);
+
+        assert_eq!(
+            tree.children[0].details,
+            Some("[public, ^1.0.0]".to_owned())
+        );
+        assert_eq!(tree.children[1].marker, Some(TreeMarker::Unresolved));
+        assert_eq!(root_packages(&Workspace::new(&all)).len(), 1);
+    }
+}
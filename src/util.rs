@@ -1,8 +1,12 @@
 use crate::error::FlError;
 use crate::FlError::NoConfigFound;
+use log::warn;
 use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use yaml_rust::yaml::Hash;
 use yaml_rust::Yaml;
 use yaml_rust::YamlLoader;
 
@@ -13,13 +17,74 @@ pub fn load_yaml(config_file: &str) -> Result<Yaml, FlError> {
     }
 
     let config_content = std::fs::read_to_string(config_file)?;
-    let mut docs = YamlLoader::load_from_str(&config_content)?;
+
+    parse_yaml(&config_content, config_file)
+}
+
+/// Try to parse the YAML `content` (loaded from `source`) into a `Yaml` structure
+/// with all merge keys resolved (see `resolve_merge_keys`).
+///
+/// Only the first document is used, any further documents are ignored with
+/// a warning.
+pub fn parse_yaml(content: &str, source: &str) -> Result<Yaml, FlError> {
+    parse_yaml_documents(content, source).map(|(yaml, _)| yaml)
+}
+
+/// Parse the first YAML document of `content` along with the number of
+/// additional documents, which are ignored.
+pub fn parse_yaml_documents(content: &str, source: &str) -> Result<(Yaml, usize), FlError> {
+    let mut docs = YamlLoader::load_from_str(content)?;
 
     if docs.is_empty() {
-        Err(NoConfigFound(config_file.to_owned()))
-    } else {
-        // we are only interested in the first parsed "file"
-        Ok(docs.remove(0))
+        return Err(NoConfigFound(source.to_owned()));
+    }
+    if docs.len() > 1 {
+        warn!(
+            "{}: ignoring {} additional YAML document(s)",
+            source,
+            docs.len() - 1
+        );
+    }
+
+    // we are only interested in the first parsed "file"
+    let extra = docs.len() - 1;
+    Ok((resolve_merge_keys(docs.remove(0)), extra))
+}
+
+/// Resolve the merge keys (`<<`) of all mappings of the given `yaml`: the
+/// entries of the merged mapping (or list of mappings) are added unless
+/// given explicitly, earlier mappings of a list take precedence.
+pub fn resolve_merge_keys(yaml: Yaml) -> Yaml {
+    match yaml {
+        Yaml::Array(items) => Yaml::Array(items.into_iter().map(resolve_merge_keys).collect()),
+        Yaml::Hash(hash) => {
+            let merge_key = Yaml::String("<<".to_owned());
+            let entries = hash.into_iter().collect::<Vec<_>>();
+            let explicit = |key: &Yaml| entries.iter().any(|(entry, _)| entry == key);
+            let mut resolved = Hash::new();
+
+            for (key, value) in entries.iter().cloned() {
+                if key != merge_key {
+                    resolved.insert(key, resolve_merge_keys(value));
+                    continue;
+                }
+
+                let merged = match value {
+                    Yaml::Array(items) => items,
+                    value => vec![value],
+                };
+                for mapping in merged.into_iter().map(resolve_merge_keys) {
+                    for (key, value) in mapping.into_hash().unwrap_or_default() {
+                        if !explicit(&key) && !resolved.contains_key(&key) {
+                            resolved.insert(key, value);
+                        }
+                    }
+                }
+            }
+
+            Yaml::Hash(resolved)
+        }
+        yaml => yaml,
     }
 }
 
@@ -30,12 +95,20 @@ pub fn yaml_str_list(yaml: &Yaml) -> Vec<String> {
 
     yaml.as_vec()
         .unwrap_or(&empty_list)
-        .into_iter()
+        .iter()
         .flat_map(|entry| entry.as_str().map(|x| x.to_owned()))
         .filter(|value| !value.is_empty())
         .collect()
 }
 
+/// Return the canonical, absolute form of the given `path` (if existing).
+pub fn canonicalize(path: &str) -> Option<String> {
+    let canonicalized = std::fs::canonicalize(path).ok()?;
+    let canonical_str = canonicalized.to_str()?;
+
+    Some(canonical_str.to_owned())
+}
+
 /// Helper function that normalizes (or canonicalizes) the given `path_str`. This function does not
 /// care if the actual directories exist or not.
 ///
@@ -43,7 +116,7 @@ pub fn yaml_str_list(yaml: &Yaml) -> Vec<String> {
 /// function in the first place.
 pub fn normalize_path_str(path_str: String) -> PathBuf {
     let path = Path::new(path_str.as_str());
-    return normalize_path(path);
+    normalize_path(path)
 }
 
 /// Helper function that normalizes (or canonicalizes) the given `path`. This function does not
@@ -77,3 +150,279 @@ pub fn normalize_path(path: &Path) -> PathBuf {
     }
     ret
 }
+
+/// Convert the given glob `pattern` into an (anchored) regular expression string that
+/// is matched against `/`-separated paths relative to the root directory.
+///
+/// Supported are `*` (any characters except `/`), `**` (any characters including `/`)
+/// and `?` (any single character except `/`). Patterns without a `/` match a
+/// directory name at any depth, similar to `.gitignore` rules.
+pub fn glob_to_regex(pattern: &str) -> String {
+    let trimmed = pattern
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .trim_end_matches('/');
+
+    let mut regex = String::from("^");
+    if !trimmed.contains('/') {
+        regex.push_str("(?:.*/)?");
+    }
+
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+/// Root of the repository containing `dir`, i.e. the first ancestor of
+/// `dir` (or `dir` itself) that contains `.git`.
+pub fn repository_root(dir: &Path) -> Option<&Path> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+}
+
+/// Build the `/`-separated representation of `path` relative to `root`.
+pub fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let components: Option<Vec<&str>> = relative
+        .components()
+        .map(|component| component.as_os_str().to_str())
+        .collect();
+
+    Some(components?.join("/"))
+}
+
+/// Build the `/`-separated path of the directory `to` relative to the
+/// directory `from`, e.g. `../shared_ui` for the sibling `shared_ui`. Both
+/// paths are expected to be absolute (or relative to the same directory).
+pub fn relative_dir(from: &Path, to: &Path) -> String {
+    let from = normalize_path(from);
+    let to = normalize_path(to);
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let parents = from.components().skip(common).map(|_| "..".to_owned());
+    let children = to
+        .components()
+        .skip(common)
+        .map(|component| component.as_os_str().to_string_lossy().into_owned());
+    let components = parents.chain(children).collect::<Vec<_>>();
+
+    if components.is_empty() {
+        ".".to_owned()
+    } else {
+        components.join("/")
+    }
+}
+
+/// Levenshtein distance of the given strings, i.e. the number of single
+/// character insertions, deletions and substitutions turning `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// 64-bit FNV-1a hash of the given `content` (stable across builds as
+/// opposed to the hasher of the standard library).
+pub fn content_hash(content: &str) -> u64 {
+    content.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Format the given point in time as RFC 3339 timestamp in UTC (seconds
+/// precision), e.g. `2024-01-31T12:00:00Z`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let (days, seconds) = ((seconds / 86400) as i64, seconds % 86400);
+
+    // civil date of the days since the epoch
+    // (see http://howardhinnant.github.io/date_algorithms.html)
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Parse an RFC 3339 timestamp in UTC (the inverse of `format_timestamp`)
+/// as returned by pub.dev, e.g. `2023-05-10T12:34:56.789Z` (fractional
+/// seconds are ignored).
+pub fn parse_timestamp(value: &str) -> Option<SystemTime> {
+    let (date, time) = value.trim().split_once('T')?;
+    let time = time.strip_suffix('Z')?;
+    let time = time.split_once('.').map_or(time, |(time, _)| time);
+
+    let number = |value: &str| value.parse::<i64>().ok();
+    let mut date = date.splitn(3, '-').map(number);
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let mut time = time.splitn(3, ':').map(number);
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // days since the epoch of the proleptic gregorian calendar
+    // (see http://howardhinnant.github.io/date_algorithms.html)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let seconds = days * 86400 + hour * 3600 + minute * 60 + second;
+    let seconds = u64::try_from(seconds).ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::util::edit_distance;
+    use crate::util::format_timestamp;
+    use crate::util::parse_timestamp;
+    use crate::util::parse_yaml;
+    use crate::util::relative_dir;
+    use std::path::Path;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    #[test]
+    fn merge_keys() {
+        let yaml = parse_yaml(
+            "
+base: &base
+  dir_prefix: pkg_
+  includes: [shared]
+extra: &extra
+  includes: [other]
+  max_depth: 3
+package_types:
+  package:
+    <<: *base
+    includes: []
+  app:
+    <<: [*extra, *base]
+---
+ignored: true
+",
+            "test.yaml",
+        )
+        .unwrap();
+        let types = &yaml["package_types"];
+
+        assert_eq!(types["package"]["dir_prefix"].as_str(), Some("pkg_"));
+        assert_eq!(types["package"]["includes"].as_vec().unwrap().len(), 0);
+        assert!(types["package"]["<<"].is_badvalue());
+        assert_eq!(types["app"]["includes"][0].as_str(), Some("other"));
+        assert_eq!(types["app"]["max_depth"].as_i64(), Some(3));
+        assert_eq!(types["app"]["dir_prefix"].as_str(), Some("pkg_"));
+        assert!(yaml["ignored"].is_badvalue());
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("shared_ui", "shared_ui"), 0);
+        assert_eq!(edit_distance("shard_ui", "shared_ui"), 1);
+        assert_eq!(edit_distance("pkg_croe", "pkg_core"), 2);
+        assert_eq!(edit_distance("", "pkg"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn relative_dirs() {
+        let relative = |from: &str, to: &str| relative_dir(Path::new(from), Path::new(to));
+
+        assert_eq!(
+            relative("/ws/packages/pkg_a", "/ws/packages/pkg_b"),
+            "../pkg_b"
+        );
+        assert_eq!(
+            relative("/ws/apps/app_foo", "/ws/packages/shared_ui"),
+            "../../packages/shared_ui"
+        );
+        assert_eq!(relative("/ws", "/ws/packages/pkg_b/"), "packages/pkg_b");
+        assert_eq!(
+            relative("/ws/apps/../apps/app_foo", "/ws/apps/app_foo"),
+            "."
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        let format =
+            |seconds: u64| format_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
+
+        let parse = |value: &str| {
+            parse_timestamp(value)
+                .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+        };
+
+        for (seconds, timestamp) in [
+            (0, "1970-01-01T00:00:00Z"),
+            (1709164800, "2024-02-29T00:00:00Z"),
+            (1683722096, "2023-05-10T12:34:56Z"),
+        ] {
+            assert_eq!(format(seconds), timestamp);
+            assert_eq!(parse(timestamp), Some(seconds));
+        }
+
+        assert_eq!(parse("2023-05-10T12:34:56.789123Z"), Some(1683722096));
+        assert_eq!(parse("2023-05-10"), None);
+        assert_eq!(parse("2023-13-10T12:34:56Z"), None);
+    }
+}
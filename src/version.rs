@@ -0,0 +1,244 @@
+use semver::Version;
+use std::fmt;
+use std::fmt::Display;
+
+/// Parse a (possibly incomplete) version like `1.2` or `v1.2.3`, missing
+/// minor/patch components default to zero.
+pub fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    let core_len = version.find(['-', '+']).unwrap_or(version.len());
+    let missing = 3usize.saturating_sub(version[..core_len].split('.').count());
+
+    let padded = format!(
+        "{}{}{}",
+        &version[..core_len],
+        ".0".repeat(missing),
+        &version[core_len..]
+    );
+    Version::parse(&padded).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// Syntax a `VersionConstraint` is written in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstraintStyle {
+    /// `any` version
+    Any,
+    /// Caret syntax, e.g. `^1.2.3`
+    Caret,
+    /// Exact (pinned) version, e.g. `1.2.3`
+    Exact,
+    /// Explicit bounds, e.g. `>=1.0.0 <2.0.0`
+    Range,
+}
+
+impl ConstraintStyle {
+    pub fn parse(value: &str) -> Option<ConstraintStyle> {
+        match value {
+            "any" => Some(ConstraintStyle::Any),
+            "caret" => Some(ConstraintStyle::Caret),
+            "exact" => Some(ConstraintStyle::Exact),
+            "range" => Some(ConstraintStyle::Range),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ConstraintStyle::Any => "any",
+            ConstraintStyle::Caret => "caret",
+            ConstraintStyle::Exact => "exact",
+            ConstraintStyle::Range => "range",
+        }
+    }
+}
+
+impl Display for ConstraintStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Version constraint of a dependency in dart's pubspec syntax, e.g.
+/// `^1.2.0`, `>=1.0.0 <2.0.0`, `1.2.3` or `any`.
+#[derive(Debug, PartialEq)]
+pub struct VersionConstraint {
+    pub style: ConstraintStyle,
+    /// All bounds that have to be satisfied (none for `any`)
+    bounds: Vec<(Op, Version)>,
+}
+
+impl VersionConstraint {
+    pub fn parse(constraint: &str) -> Option<VersionConstraint> {
+        let constraint = constraint.trim();
+
+        if constraint == "any" {
+            return Some(VersionConstraint {
+                style: ConstraintStyle::Any,
+                bounds: Vec::new(),
+            });
+        }
+
+        if let Some(caret) = constraint.strip_prefix('^') {
+            let version = parse_version(caret)?;
+            let upper = next_breaking(&version);
+            return Some(VersionConstraint {
+                style: ConstraintStyle::Caret,
+                bounds: vec![(Op::Ge, version), (Op::Lt, upper)],
+            });
+        }
+
+        let mut bounds = Vec::new();
+        let mut rest = constraint;
+
+        while !rest.is_empty() {
+            let (op, after_op) = [(">=", Op::Ge), ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt)]
+                .iter()
+                .find_map(|(prefix, op)| rest.strip_prefix(prefix).map(|after| (*op, after)))
+                .unwrap_or((Op::Eq, rest));
+
+            let after_op = after_op.trim_start();
+            let end = after_op
+                .find(|c: char| c.is_whitespace() || c == '<' || c == '>')
+                .unwrap_or(after_op.len());
+
+            bounds.push((op, parse_version(&after_op[..end])?));
+            rest = after_op[end..].trim_start();
+        }
+
+        // an exact version cannot be combined with other bounds
+        let exact = bounds.iter().any(|(op, _)| *op == Op::Eq);
+        if bounds.is_empty() || (exact && bounds.len() > 1) {
+            return None;
+        }
+
+        let style = if exact {
+            ConstraintStyle::Exact
+        } else {
+            ConstraintStyle::Range
+        };
+        Some(VersionConstraint { style, bounds })
+    }
+
+    /// Parse the Dart SDK constraint of a pubspec's `environment`. As of
+    /// Dart 3, an upper bound of `<3.0.0` is treated as `<4.0.0` if the
+    /// lower bound is at least `2.12.0` (i.e. the package is null safe).
+    pub fn parse_sdk(constraint: &str) -> Option<VersionConstraint> {
+        let mut constraint = VersionConstraint::parse(constraint)?;
+        let null_safe = constraint.bounds.iter().any(|(op, version)| {
+            matches!(op, Op::Ge | Op::Gt) && *version >= Version::new(2, 12, 0)
+        });
+
+        if null_safe {
+            for (op, version) in constraint.bounds.iter_mut() {
+                if *op == Op::Lt && *version == Version::new(3, 0, 0) {
+                    *version = Version::new(4, 0, 0);
+                }
+            }
+        }
+        Some(constraint)
+    }
+
+    /// Whether the given `version` satisfies this constraint.
+    pub fn allows(&self, version: &Version) -> bool {
+        self.bounds.iter().all(|(op, bound)| match op {
+            Op::Eq => version == bound,
+            Op::Gt => version > bound,
+            Op::Ge => version >= bound,
+            Op::Lt => version < bound,
+            Op::Le => version <= bound,
+        })
+    }
+}
+
+/// First version considered incompatible with `version` by a caret
+/// constraint (the next minor version for `0.x` versions).
+fn next_breaking(version: &Version) -> Version {
+    if version.major == 0 {
+        Version::new(0, version.minor + 1, 0)
+    } else {
+        Version::new(version.major + 1, 0, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::version::parse_version;
+    use crate::version::ConstraintStyle;
+    use crate::version::VersionConstraint;
+
+    fn allows(constraint: &str, version: &str) -> bool {
+        VersionConstraint::parse(constraint)
+            .unwrap()
+            .allows(&parse_version(version).unwrap())
+    }
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(parse_version("1").unwrap().to_string(), "1.0.0");
+        assert_eq!(parse_version("v1.2").unwrap().to_string(), "1.2.0");
+        assert_eq!(
+            parse_version("1.2.3-dev+4").unwrap().to_string(),
+            "1.2.3-dev+4"
+        );
+        assert_eq!(parse_version("foo"), None);
+    }
+
+    #[test]
+    fn constraints() {
+        assert!(allows("any", "17.0.0"));
+        assert!(allows("^1.4.0", "1.9.2"));
+        assert!(!allows("^1.4.0", "2.0.0"));
+        assert!(!allows("^1.4.0", "1.3.9"));
+        assert!(allows("^0.2.1", "0.2.5"));
+        assert!(!allows("^0.2.1", "0.3.0"));
+        assert!(allows(">=1.0.0 <2.0.0", "1.5.0"));
+        assert!(allows(">=1.0.0<2.0.0", "1.5.0"));
+        assert!(!allows(">1.0.0 <=2.0.0", "1.0.0"));
+        assert!(allows(">1.0.0 <=2.0.0", "2.0.0"));
+        assert!(allows("1.2.3", "1.2.3"));
+        assert!(!allows("1.2.3", "1.2.4"));
+    }
+
+    #[test]
+    fn styles() {
+        let style = |constraint: &str| VersionConstraint::parse(constraint).unwrap().style;
+
+        assert_eq!(style("any"), ConstraintStyle::Any);
+        assert_eq!(style("^1.4.0"), ConstraintStyle::Caret);
+        assert_eq!(style("1.2.3"), ConstraintStyle::Exact);
+        assert_eq!(style(">=1.0.0 <2.0.0"), ConstraintStyle::Range);
+        assert_eq!(style(">=1.0.0"), ConstraintStyle::Range);
+    }
+
+    #[test]
+    fn sdk_constraints() {
+        let allows = |constraint: &str, version: &str| {
+            VersionConstraint::parse_sdk(constraint)
+                .unwrap()
+                .allows(&parse_version(version).unwrap())
+        };
+
+        assert!(allows(">=2.12.0 <3.0.0", "3.2.0"));
+        assert!(!allows(">=2.12.0 <3.0.0", "4.0.0"));
+        assert!(!allows(">=2.7.0 <3.0.0", "3.0.0"));
+        assert!(allows("^3.0.0", "3.5.1"));
+        assert!(!allows(">=3.3.0 <4.0.0", "3.0.0"));
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        assert_eq!(VersionConstraint::parse(""), None);
+        assert_eq!(VersionConstraint::parse("^foo"), None);
+        assert_eq!(VersionConstraint::parse("1.0.0 2.0.0"), None);
+        assert_eq!(VersionConstraint::parse(">=1.0.0 <"), None);
+    }
+}
@@ -0,0 +1,444 @@
+use crate::cache::PubspecCache;
+use crate::dependency::Dependency;
+use crate::pubspec::discover_pubspecs;
+use crate::pubspec::Discovery;
+use crate::Config;
+use crate::FlError;
+use crate::FlError::NoInputFiles;
+use crate::Pubspec;
+use log::info;
+use log::warn;
+use std::collections::HashMap;
+
+/// Dependency of a workspace package on another one (see `Workspace`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DependencyEdge {
+    /// Index of the package depended upon
+    pub to: usize,
+    /// Dependency is one of the dev_dependencies
+    pub dev: bool,
+}
+
+/// Packages of the workspace indexed by name and directory along with the
+/// graph of their internal dependencies.
+///
+/// Dependencies are resolved by their path (local dependencies) or by the
+/// name of a workspace package (e.g. hosted or git dependencies on
+/// packages of the workspace).
+pub struct Workspace<'a> {
+    pub packages: &'a [Pubspec],
+    pub by_name: HashMap<&'a str, usize>,
+    pub by_dir: HashMap<&'a str, usize>,
+    /// Names shared by multiple packages along with all of these packages
+    /// (by index), whereas `by_name` resolves to the first one only
+    duplicates: HashMap<&'a str, Vec<usize>>,
+    /// Internal dependencies of every package (by index)
+    pub graph: Vec<Vec<DependencyEdge>>,
+    /// Packages directly depending on every package (by index, including
+    /// dev dependencies)
+    reverse: Vec<Vec<usize>>,
+}
+
+impl<'a> Workspace<'a> {
+    pub fn new(packages: &'a [Pubspec]) -> Workspace<'a> {
+        let mut by_name = HashMap::new();
+        let mut by_dir = HashMap::new();
+        let mut duplicates = HashMap::new();
+        for (idx, pubspec) in packages.iter().enumerate() {
+            if !pubspec.name.is_empty() {
+                let first = *by_name.entry(pubspec.name.as_str()).or_insert(idx);
+                if first != idx {
+                    duplicates
+                        .entry(pubspec.name.as_str())
+                        .or_insert_with(|| vec![first])
+                        .push(idx);
+                }
+            }
+            by_dir.entry(pubspec.dir_path.as_str()).or_insert(idx);
+        }
+
+        let mut workspace = Workspace {
+            packages,
+            by_name,
+            by_dir,
+            duplicates,
+            graph: Vec::new(),
+            reverse: vec![Vec::new(); packages.len()],
+        };
+        workspace.graph = packages
+            .iter()
+            .map(|pubspec| {
+                let deps = pubspec.dependencies.iter().map(|dep| (dep, false));
+                let dev_deps = pubspec.dev_dependencies.iter().map(|dep| (dep, true));
+
+                deps.chain(dev_deps)
+                    .flat_map(|(dep, dev)| {
+                        let to = workspace.resolve(pubspec, dep)?;
+                        Some(DependencyEdge { to, dev })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (idx, edges) in workspace.graph.iter().enumerate() {
+            for edge in edges {
+                let dependents = &mut workspace.reverse[edge.to];
+                if dependents.last() != Some(&idx) {
+                    dependents.push(idx);
+                }
+            }
+        }
+
+        workspace
+    }
+
+    /// Workspace package of the given name (if any).
+    pub fn package(&self, name: &str) -> Option<&'a Pubspec> {
+        self.by_name.get(name).map(|&idx| &self.packages[idx])
+    }
+
+    /// Workspace package located in the directory `dir` (if any).
+    pub fn at_dir(&self, dir: &str) -> Option<&'a Pubspec> {
+        self.by_dir.get(dir).map(|&idx| &self.packages[idx])
+    }
+
+    /// Other workspace packages of the same name as `pubspec`.
+    pub fn namesakes(&self, pubspec: &Pubspec) -> impl Iterator<Item = &'a Pubspec> + '_ {
+        let own = self.index(pubspec);
+        self.duplicates
+            .get(pubspec.name.as_str())
+            .into_iter()
+            .flatten()
+            .filter(move |&&idx| Some(idx) != own)
+            .map(|&idx| &self.packages[idx])
+    }
+
+    /// Index of the given workspace package (if any).
+    pub fn index(&self, pubspec: &Pubspec) -> Option<usize> {
+        self.by_dir.get(pubspec.dir_path.as_str()).copied()
+    }
+
+    /// Resolve the dependency `dep` of `pubspec` to the index of the
+    /// workspace package it refers to (if any).
+    pub fn resolve(&self, pubspec: &Pubspec, dep: &Dependency) -> Option<usize> {
+        match pubspec.resolve_dependency(dep, self) {
+            Some(resolved) => self.by_dir.get(resolved.dir_path.as_str()).copied(),
+            None => self.by_name.get(dep.effective().name().as_str()).copied(),
+        }
+    }
+
+    /// Indices of the packages `idx` depends on (including the dev
+    /// dependencies if `dev` is set).
+    pub fn dependencies(&self, idx: usize, dev: bool) -> impl Iterator<Item = usize> + '_ {
+        self.graph[idx]
+            .iter()
+            .filter(move |edge| dev || !edge.dev)
+            .map(|edge| edge.to)
+    }
+
+    /// Indices of the packages directly depending on package `idx`
+    /// (including dev dependencies), in the order of the packages.
+    pub fn dependents(&self, idx: usize) -> &[usize] {
+        &self.reverse[idx]
+    }
+
+    /// Indices of all packages affected by changes of the `changed`
+    /// packages, meaning these packages themselves as well as all packages
+    /// (transitively) depending on them (including dev dependencies).
+    pub fn affected(&self, changed: &[usize]) -> Vec<usize> {
+        let mut seen = vec![false; self.packages.len()];
+        let mut affected = Vec::new();
+        for &idx in changed {
+            if !seen[idx] {
+                seen[idx] = true;
+                affected.push(idx);
+            }
+        }
+
+        let mut pos = 0;
+        while pos < affected.len() {
+            for &dependent in self.dependents(affected[pos]) {
+                if !seen[dependent] {
+                    seen[dependent] = true;
+                    affected.push(dependent);
+                }
+            }
+            pos += 1;
+        }
+
+        affected
+    }
+
+    /// Indices of all packages package `idx` (transitively) depends on via
+    /// its regular dependencies, excluding the package itself.
+    pub fn closure(&self, idx: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.packages.len()];
+        let mut closure: Vec<usize> = Vec::new();
+        let mut pending = vec![idx];
+        seen[idx] = true;
+
+        while let Some(current) = pending.pop() {
+            for dep in self.dependencies(current, false) {
+                if !seen[dep] {
+                    seen[dep] = true;
+                    closure.push(dep);
+                    pending.push(dep);
+                }
+            }
+        }
+
+        closure
+    }
+
+    /// Group all packages into waves in topological order of their regular
+    /// dependencies: every package depends on packages of previous waves
+    /// only. Fails on cyclic dependencies.
+    pub fn topo_order(&self) -> Result<Vec<Vec<usize>>, FlError> {
+        let mut done = vec![false; self.packages.len()];
+        let mut waves = Vec::new();
+
+        while done.iter().any(|done| !done) {
+            let ready = (0..self.packages.len())
+                .filter(|&idx| !done[idx] && self.dependencies(idx, false).all(|dep| done[dep]))
+                .collect::<Vec<_>>();
+
+            if ready.is_empty() {
+                let cycle = self
+                    .find_cycle(&done)
+                    .into_iter()
+                    .map(|idx| self.packages[idx].name.as_str())
+                    .collect();
+
+                return Err(FlError::DependencyCycle(describe_cycle(cycle)));
+            }
+
+            for &idx in ready.iter() {
+                done[idx] = true;
+            }
+            waves.push(ready);
+        }
+
+        Ok(waves)
+    }
+
+    /// Find a cycle among the packages that are not `done` yet. Every one
+    /// of them depends on another pending package, so following these
+    /// dependencies has to end up in a cycle eventually.
+    fn find_cycle(&self, done: &[bool]) -> Vec<usize> {
+        let mut path: Vec<usize> = Vec::new();
+        let mut current = done.iter().position(|done| !done);
+
+        while let Some(idx) = current {
+            if let Some(start) = path.iter().position(|&visited| visited == idx) {
+                return path.split_off(start);
+            }
+
+            path.push(idx);
+            current = self.dependencies(idx, false).find(|&dep| !done[dep]);
+        }
+
+        path
+    }
+}
+
+/// Describe the dependency cycle of the given packages (in dependency
+/// order) starting with its smallest package name, so that the cycle is
+/// described the same way no matter where it was detected.
+pub fn describe_cycle(mut names: Vec<&str>) -> String {
+    let start = names
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, name)| **name)
+        .map(|(idx, _)| idx)
+        .unwrap_or_default();
+    names.rotate_left(start);
+
+    match names.first() {
+        Some(first) => format!("{} -> '{}'", names.join(" -> "), first),
+        None => String::new(),
+    }
+}
+
+/// Discover and load all packages below the given root directories (see
+/// `discover_pubspecs`), updating the pubspec `cache` (if any). Fails if no
+/// package was found at all.
+pub fn discover(
+    root_dirs: &[String],
+    config: &Config,
+    follow_symlinks: bool,
+    mut cache: Option<PubspecCache>,
+) -> Result<Discovery, FlError> {
+    let discovery = discover_pubspecs(root_dirs, config, follow_symlinks, cache.as_mut())?;
+    // failing to write the cache only slows down the next run
+    if let Some(Err(err)) = cache.map(|cache| cache.save()) {
+        warn!("cannot write pubspec cache: {}", err);
+    }
+
+    info!(
+        "discovered {} package(s) in {}",
+        discovery.pubspecs.len(),
+        root_dirs.join(", ")
+    );
+    if discovery.pubspecs.is_empty() {
+        return Err(NoInputFiles(root_dirs.join(", ")));
+    }
+
+    Ok(discovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dependency::Dependency;
+    use crate::error::ValidationType;
+    use crate::workspace::DependencyEdge;
+    use crate::workspace::Workspace;
+    use crate::Config;
+    use crate::Pubspec;
+
+    fn names<'a>(workspace: &Workspace<'a>, indices: Vec<usize>) -> Vec<&'a str> {
+        indices
+            .into_iter()
+            .map(|idx| workspace.packages[idx].name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn index_and_graph() {
+        let all = vec![
+            Pubspec::test("app_foo")
+                .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+                .with_local_deps(&["shared_ui"]),
+            Pubspec::test("shared_ui")
+                .with_deps(vec![Pubspec::hosted("pkg_core", "^1.0.0")])
+                .with_local_dev_deps(&["pkg_test"]),
+            Pubspec::test("pkg_core").with_local_deps(&["missing"]),
+            Pubspec::test("pkg_test"),
+        ];
+        let workspace = Workspace::new(&all);
+
+        assert_eq!(
+            workspace.package("pkg_core").map(|p| p.dir_path.as_str()),
+            Some("/tmp/pkg_core")
+        );
+        assert!(workspace.package("http").is_none());
+        assert_eq!(workspace.by_dir.get("/tmp/shared_ui"), Some(&1));
+        assert_eq!(
+            workspace.graph[0],
+            vec![DependencyEdge { to: 1, dev: false }]
+        );
+        assert_eq!(
+            workspace.graph[1],
+            vec![
+                DependencyEdge { to: 2, dev: false },
+                DependencyEdge { to: 3, dev: true }
+            ]
+        );
+        assert!(workspace.graph[2].is_empty());
+        assert_eq!(
+            names(&workspace, workspace.dependents(3).to_vec()),
+            vec!["shared_ui"]
+        );
+    }
+
+    #[test]
+    fn affected_by_changes() {
+        let all = vec![
+            Pubspec::test("app_foo").with_local_deps(&["shared_ui"]),
+            Pubspec::test("shared_ui").with_local_dev_deps(&["pkg_core"]),
+            Pubspec::test("pkg_core"),
+            Pubspec::test("pkg_other"),
+        ];
+        let workspace = Workspace::new(&all);
+
+        assert_eq!(
+            names(&workspace, workspace.affected(&[2])),
+            vec!["pkg_core", "shared_ui", "app_foo"]
+        );
+        assert_eq!(
+            names(&workspace, workspace.affected(&[0, 3])),
+            vec!["app_foo", "pkg_other"]
+        );
+        assert!(workspace.affected(&[]).is_empty());
+    }
+
+    #[test]
+    fn transitive_dependencies() {
+        let all = vec![
+            Pubspec::test("app_foo").with_local_deps(&["shared_ui", "pkg_core"]),
+            Pubspec::test("shared_ui")
+                .with_local_deps(&["pkg_core"])
+                .with_local_dev_deps(&["pkg_test"]),
+            Pubspec::test("pkg_core").with_local_deps(&["app_foo"]),
+            Pubspec::test("pkg_test"),
+        ];
+        let workspace = Workspace::new(&all);
+
+        assert_eq!(
+            names(&workspace, workspace.closure(0)),
+            vec!["shared_ui", "pkg_core"]
+        );
+        assert!(workspace.closure(3).is_empty());
+    }
+
+    #[test]
+    fn topo_order() {
+        let all = vec![
+            Pubspec::test("app")
+                .with_deps(vec![Pubspec::hosted("http", "^1.0.0")])
+                .with_local_deps(&["pkg_ui"]),
+            Pubspec::test("pkg_ui")
+                .with_deps(vec![Pubspec::hosted("pkg_core", "^1.0.0")])
+                .with_local_dev_deps(&["app"]),
+            Pubspec::test("pkg_utils"),
+            Pubspec::test("pkg_core").with_local_deps(&["pkg_utils"]),
+        ];
+        let workspace = Workspace::new(&all);
+
+        assert_eq!(
+            workspace.topo_order().unwrap(),
+            vec![vec![2], vec![3], vec![1], vec![0]]
+        );
+
+        let cyclic = vec![
+            Pubspec::test("app").with_local_deps(&["pkg_a"]),
+            Pubspec::test("pkg_a").with_local_deps(&["pkg_b"]),
+            Pubspec::test("pkg_b").with_local_deps(&["pkg_a"]),
+        ];
+        assert_eq!(
+            Workspace::new(&cyclic)
+                .topo_order()
+                .unwrap_err()
+                .to_string(),
+            "cyclic dependency: pkg_a -> pkg_b -> 'pkg_a'"
+        );
+    }
+
+    #[test]
+    fn cycle_description() {
+        // the directory names sort the other way round
+        let dep = |name: &str, dir: &str| Dependency::Local {
+            name: name.to_owned(),
+            path: format!("../{}", dir),
+            overridden: Box::new(None),
+        };
+        let all = vec![
+            Pubspec::test_at("pkg_b", "/tmp/dir_a").with_deps(vec![dep("pkg_a", "dir_b")]),
+            Pubspec::test_at("pkg_a", "/tmp/dir_b").with_deps(vec![dep("pkg_b", "dir_a")]),
+        ];
+        let cycle = "pkg_a -> pkg_b -> 'pkg_a'";
+
+        assert_eq!(
+            Workspace::new(&all).topo_order().unwrap_err().to_string(),
+            format!("cyclic dependency: {}", cycle)
+        );
+        for pubspec in all.iter() {
+            let errors = pubspec
+                .validate(&Config::default(), &Workspace::new(&all))
+                .into_iter()
+                .filter(|validation| validation.code == ValidationType::CyclicDependency)
+                .map(|validation| validation.error)
+                .collect::<Vec<_>>();
+            assert_eq!(errors, vec![format!("cyclic dependency {}", cycle)]);
+        }
+    }
+}